@@ -1,12 +1,36 @@
 use std::any::Any;
-use std::collections::HashSet;
-
-use crossterm::event::KeyCode;
-use tui::layout::Direction;
-
-use crate::autocomplete::{AutoCompleter, PanelAutoCompleter};
-use crate::commands::{ctrl_alt_key, Manager};
-use crate::panels::{PanelFactory, NULL_PANEL_TYPE_ID, INPUT_PANEL_TYPE_ID};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashSet, VecDeque};
+use std::env;
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, MouseButton, MouseEvent, MouseEventKind};
+use tui::layout::{Direction, Rect};
+
+use crate::autocomplete::{AutoCompleter, CommandAutoCompleter, ConfirmAutoCompleter, PanelAutoCompleter};
+use crate::commands::{code, ctrl_alt_key, CommandKeyId, Manager};
+use crate::panels::{
+    CommandOutput, PanelFactory, EDIT_PANEL_TYPE_ID, NULL_PANEL_TYPE_ID, INPUT_PANEL_TYPE_ID, MESSAGE_PANEL_TYPE_ID,
+    OUTPUT_PANEL_TYPE_ID, HELP_PANEL_TYPE_ID, WELCOME_PANEL_TYPE_ID,
+};
+use crate::autosave::{save_modified_files, Autosave, FocusSave};
+use crate::idle::IdleWork;
+use crate::lock::{IdleLock, LockState};
+use crate::panels::commands::format_key;
+use crate::config::{Config, LayoutSpec};
+use crate::history::InputHistory;
+use crate::keymap::{export_keymap, import_keymap};
+use crate::locale::{Catalog, Text};
+use crate::logger::Logger;
+use crate::panels::PanelState;
+use crate::positions::CursorPositions;
+use crate::recent::RecentFiles;
+use crate::share::ShareSession;
+use crate::splits::SplitSize;
+use crate::swap::SwapWriter;
+use crate::theme::Theme;
+use crate::tasks::Tasks;
+use crate::watch::FileWatcher;
 use crate::{
     catch_all, ctrl_key, key, CommandDetails, Commands, PanelSplit, Panels, TextPanel, UserSplits,
 };
@@ -14,7 +38,6 @@ use crate::{
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub enum MessageChannel {
     ERROR,
-    #[allow(dead_code)]
     WARNING,
     INFO,
 }
@@ -22,21 +45,28 @@ pub enum MessageChannel {
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub struct Message {
     channel: MessageChannel,
-    text: String,
+    text: Text,
 }
 
 impl Message {
-    pub fn error<T: ToString>(text: T) -> Message {
+    pub fn error<T: Into<Text>>(text: T) -> Message {
         Message {
             channel: MessageChannel::ERROR,
-            text: text.to_string(),
+            text: text.into(),
+        }
+    }
+
+    pub fn warning<T: Into<Text>>(text: T) -> Message {
+        Message {
+            channel: MessageChannel::WARNING,
+            text: text.into(),
         }
     }
 
-    pub fn info<T: ToString>(text: T) -> Message {
+    pub fn info<T: Into<Text>>(text: T) -> Message {
         Message {
             channel: MessageChannel::INFO,
-            text: text.to_string(),
+            text: text.into(),
         }
     }
 
@@ -44,67 +74,187 @@ impl Message {
         self.channel
     }
 
-    pub fn text(&self) -> &String {
+    pub fn text(&self) -> &Text {
         &self.text
     }
 }
 
+impl MessageChannel {
+    // so severity doesn't rely on color alone
+    pub fn indicator(&self) -> Text {
+        match self {
+            MessageChannel::ERROR => Text::new("message.error", "error: "),
+            MessageChannel::WARNING => Text::new("message.warning", "warning: "),
+            MessageChannel::INFO => Text::default(),
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
 enum State {
     Normal,
     WaitingPanelType(usize),
+    WaitingDeleteConfirmation(usize),
+    WaitingCloseOthersConfirmation(usize),
+    WaitingQuitConfirmation(usize),
+    WaitingCommand(usize),
+    WaitingShellCommand(usize),
+    WaitingKeymapExport(usize),
+    WaitingKeymapImport(usize),
+}
+
+impl State {
+    // panel that was active when state was entered
+    fn for_panel(&self) -> Option<usize> {
+        match self {
+            State::Normal => None,
+            State::WaitingPanelType(panel)
+            | State::WaitingDeleteConfirmation(panel)
+            | State::WaitingCloseOthersConfirmation(panel)
+            | State::WaitingQuitConfirmation(panel)
+            | State::WaitingCommand(panel)
+            | State::WaitingShellCommand(panel)
+            | State::WaitingKeymapExport(panel)
+            | State::WaitingKeymapImport(panel) => Some(*panel),
+        }
+    }
+}
+
+// lets panels define changes without a variant here
+// returned requests are handled after the change is applied
+pub trait StateChange {
+    fn apply(self: Box<Self>, state: &mut AppState, panels: &mut Panels, commands: &mut Manager) -> Vec<StateChangeRequest>;
 }
 
 pub enum StateChangeRequest {
-    // String - prompt to display for input
-    Input(String, Option<Box<dyn AutoCompleter>>),
+    // Text - prompt to display for input
+    Input(Text, Option<Box<dyn AutoCompleter>>),
     InputComplete(String),
+    InputCanceled,
+    // yes or no question, answer is given as input
+    Confirm(Text),
+    // input is checked before being given to requestor
+    ValidatedInput(Text, Option<Box<dyn AutoCompleter>>, InputValidator),
+    // prompts asked in order, answers given to requestor together
+    InputSequence(Vec<Text>),
     Message(Message),
+    Custom(Box<dyn StateChange>),
 }
 
 impl StateChangeRequest {
-    pub fn input_request_with_completer(
-        prompt: String,
+    pub fn input_request_with_completer<T: Into<Text>>(
+        prompt: T,
         completer: Box<dyn AutoCompleter>,
     ) -> StateChangeRequest {
-        StateChangeRequest::Input(prompt, Some(completer))
+        StateChangeRequest::Input(prompt.into(), Some(completer))
+    }
+
+    pub fn validated_input<T: Into<Text>>(prompt: T, validator: InputValidator) -> StateChangeRequest {
+        StateChangeRequest::ValidatedInput(prompt.into(), None, validator)
+    }
+
+    pub fn confirm<T: Into<Text>>(prompt: T) -> StateChangeRequest {
+        StateChangeRequest::Confirm(prompt.into())
     }
 
     pub fn input_complete(text: String) -> StateChangeRequest {
         StateChangeRequest::InputComplete(text)
     }
 
-    pub fn info<T: ToString>(message: T) -> StateChangeRequest {
+    pub fn info<T: Into<Text>>(message: T) -> StateChangeRequest {
         StateChangeRequest::Message(Message::info(message))
     }
 
-    pub fn error<T: ToString>(message: T) -> StateChangeRequest {
+    pub fn error<T: Into<Text>>(message: T) -> StateChangeRequest {
         StateChangeRequest::Message(Message::error(message))
     }
+
+    pub fn custom<T: StateChange + 'static>(change: T) -> StateChangeRequest {
+        StateChangeRequest::Custom(Box::new(change))
+    }
 }
 
 const TOP_REQUESTOR_ID: usize = usize::MAX;
 
+// guards against panels endlessly responding to each other
+const MAX_CHANGES_PER_EVENT: usize = 1000;
+
+// error is shown and prompt stays open when input is rejected
+pub type InputValidator = fn(&str) -> Result<(), String>;
+
 pub struct InputRequest {
-    prompt: String,
+    prompt: Text,
     auto_completer: Option<Box<dyn AutoCompleter>>,
     requestor_id: usize,
+    sequence: Option<InputSequence>,
+    validator: Option<InputValidator>,
+}
+
+pub struct InputSequence {
+    remaining: Vec<Text>,
+    answers: Vec<String>,
+    total: usize,
 }
 
 impl InputRequest {
-    pub fn prompt(&self) -> &String {
+    pub fn prompt(&self) -> &Text {
         &self.prompt
     }
 
     pub fn completer(&self) -> Option<&Box<dyn AutoCompleter>> {
         self.auto_completer.as_ref()
     }
+
+    // 1 based step and total count, when part of a sequence
+    pub fn step(&self) -> Option<(usize, usize)> {
+        self.sequence
+            .as_ref()
+            .map(|s| (s.answers.len() + 1, s.total))
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MessagesPlacement {
+    // shares space evenly like any other panel
+    Split,
+    // short fixed height strip
+    Strip,
+    // not shown until there is an error to see
+    HiddenUntilError,
+    // no panel, latest message shown in status bar
+    StatusBar,
+}
+
+impl MessagesPlacement {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "split" => Some(MessagesPlacement::Split),
+            "strip" => Some(MessagesPlacement::Strip),
+            "auto" => Some(MessagesPlacement::HiddenUntilError),
+            "status" => Some(MessagesPlacement::StatusBar),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FocusDirection {
+    Left,
+    Right,
+    Up,
+    Down,
 }
 
 pub struct LayoutPanel {
     split_index: usize,
     id: char,
     panel_index: usize,
+    // length within its split, shares space evenly when None
+    size: Option<SplitSize>,
+    // area from last render, set during rendering so interior mutability
+    rect: Cell<Rect>,
+    // title from last render, listed while selecting a panel
+    title: RefCell<String>,
 }
 
 impl LayoutPanel {
@@ -113,9 +263,28 @@ impl LayoutPanel {
             split_index,
             id,
             panel_index,
+            size: None,
+            rect: Cell::new(Rect::default()),
+            title: RefCell::new(String::new()),
         }
     }
 
+    pub fn rect(&self) -> Rect {
+        self.rect.get()
+    }
+
+    pub fn set_rect(&self, rect: Rect) {
+        self.rect.set(rect);
+    }
+
+    pub fn title(&self) -> String {
+        self.title.borrow().clone()
+    }
+
+    pub fn set_title(&self, title: String) {
+        self.title.replace(title);
+    }
+
     pub fn panel_index(&self) -> usize {
         self.panel_index
     }
@@ -131,6 +300,14 @@ impl LayoutPanel {
     pub fn set_split(&mut self, split: usize) {
         self.split_index = split;
     }
+
+    pub fn size(&self) -> Option<SplitSize> {
+        self.size
+    }
+
+    pub fn set_size(&mut self, size: Option<SplitSize>) {
+        self.size = size;
+    }
 }
 
 pub struct AppState {
@@ -142,6 +319,41 @@ pub struct AppState {
     messages: Vec<Message>,
     input_request: Option<InputRequest>,
     state: State,
+    clipboard: Vec<String>,
+    quit: bool,
+    // off leaves only Quit command to close editor
+    escape_quits: bool,
+    tasks: Tasks,
+    autosave: Option<Autosave>,
+    focus_save: Option<FocusSave>,
+    file_watcher: Option<FileWatcher>,
+    // terminal has focus, assumed until told otherwise
+    focused: bool,
+    dim_unfocused: bool,
+    screen: Rect,
+    idle_lock: Option<IdleLock>,
+    idle_work: IdleWork,
+    share_session: Option<ShareSession>,
+    // panel index and line where a drag in line numbers started
+    gutter_drag: Option<(usize, usize)>,
+    swap: SwapWriter,
+    messages_placement: MessagesPlacement,
+    // count of messages when messages panel was last looked at
+    seen_messages: usize,
+    messages_return_panel: Option<usize>,
+    logger: Logger,
+    ticks: u64,
+    cursor_positions: Option<CursorPositions>,
+    recent_files: RecentFiles,
+    user_config: Config,
+    // user config with project config from working directory on top
+    config: Config,
+    config_dir: Option<PathBuf>,
+    // project config isn't trusted either
+    safe_mode: bool,
+    catalog: Catalog,
+    theme: Theme,
+    input_history: InputHistory,
 }
 
 const PROMPT_PANEL_ID: char = '$';
@@ -157,6 +369,35 @@ impl AppState {
             messages: vec![],
             input_request: None,
             state: State::Normal,
+            clipboard: vec![],
+            quit: false,
+            escape_quits: true,
+            tasks: Tasks::new(),
+            autosave: None,
+            focus_save: None,
+            file_watcher: None,
+            focused: true,
+            dim_unfocused: false,
+            screen: Rect::default(),
+            idle_lock: None,
+            idle_work: IdleWork::default(),
+            share_session: None,
+            gutter_drag: None,
+            swap: SwapWriter::default(),
+            messages_placement: MessagesPlacement::Split,
+            seen_messages: 0,
+            messages_return_panel: None,
+            logger: Logger::default(),
+            ticks: 0,
+            cursor_positions: None,
+            recent_files: RecentFiles::default(),
+            user_config: Config::default(),
+            config: Config::default(),
+            config_dir: None,
+            safe_mode: false,
+            catalog: Catalog::default(),
+            theme: Theme::default(),
+            input_history: InputHistory::default(),
         }
     }
 
@@ -171,12 +412,137 @@ impl AppState {
         }
     }
 
-    pub fn add_error<T: ToString>(&mut self, message: T) {
-        self.messages.push(Message::error(message));
+    // replaces default layout, input panel is always added to top split
+    // top split stacks so input panel spans the width, side by side layouts go in a split below it
+    pub fn init_with_layout(&mut self, layout: &LayoutSpec, panels: &mut Panels, commands: &mut Manager) -> Result<(), String> {
+        let children = match layout {
+            LayoutSpec::Split(Direction::Vertical, children) => children.clone(),
+            other => vec![other.clone()],
+        };
+
+        let input_index = panels.push(PanelFactory::input());
+
+        self.splits = vec![PanelSplit::new(Direction::Vertical, vec![UserSplits::Panel(input_index)])];
+        self.panels = vec![LayoutPanel::new(0, PROMPT_PANEL_ID, input_index)];
+        self.selecting_panel = false;
+        self.static_panels = vec![PROMPT_PANEL_ID];
+        self.state = State::Normal;
+        self.input_request = None;
+
+        let mut opened = vec![];
+        self.build_layout(0, &children, None, panels, &mut opened)?;
+
+        if self.panels.len() < 2 {
+            return Err("Layout has no panels.".to_string());
+        }
+
+        self.active_panel = 1;
+        match self.get_active_panel().and_then(|lp| panels.get(lp.panel_index)) {
+            None => (),
+            Some(panel) => commands.push_commands_for_panel(panel.panel_type()),
+        }
+
+        // handled as if each file was opened through prompt in its panel
+        for (index, changes) in opened {
+            self.active_panel = index;
+            self.handle_changes(changes, panels, commands);
+        }
+
+        // prompt asked for while opening, e.g. to recover unsaved changes, is left open
+        self.active_panel = match self.input_request {
+            Some(_) => 0,
+            None => 1,
+        };
+
+        Ok(())
+    }
+
+    // changes from opening files are collected with their panel's index, panel needs to be active to handle them
+    fn build_layout(
+        &mut self,
+        split: usize,
+        children: &Vec<LayoutSpec>,
+        size: Option<SplitSize>,
+        panels: &mut Panels,
+        opened: &mut Vec<(usize, Vec<StateChangeRequest>)>,
+    ) -> Result<(), String> {
+        for child in children {
+            match child {
+                LayoutSpec::Sized(child_size, child) => {
+                    self.build_layout(split, &vec![*child.clone()], Some(*child_size), panels, opened)?;
+                }
+                LayoutSpec::Panel(panel_type, file) => {
+                    let mut panel = match PanelFactory::panel(panel_type) {
+                        None => return Err(format!("No panel of type: {:?}", panel_type)),
+                        Some(panel) => panel,
+                    };
+
+                    let changes = match file {
+                        None => vec![],
+                        Some(file) => {
+                            // same path as opening through prompt
+                            panel.set_state(PanelState::WaitingToOpen);
+                            let changes = panel.receive_input(file.clone());
+                            if panel.state() == PanelState::WaitingToOpen {
+                                panel.set_state(PanelState::Normal);
+                            }
+
+                            self.file_opened(&mut panel);
+                            changes
+                        }
+                    };
+
+                    let id = self.first_available_id();
+                    let index = panels.push(panel);
+                    let mut layout_panel = LayoutPanel::new(split, id, index);
+                    layout_panel.set_size(size);
+                    self.panels.push(layout_panel);
+                    self.splits[split].panels.push(UserSplits::Panel(index));
+
+                    if !changes.is_empty() {
+                        opened.push((index, changes));
+                    }
+                }
+                LayoutSpec::Split(direction, children) => {
+                    let new_split = self.splits.len();
+                    let mut panel_split = PanelSplit::new(direction.clone(), vec![]);
+                    panel_split.size = size;
+                    self.splits.push(panel_split);
+                    self.splits[split].panels.push(UserSplits::Split(new_split));
+
+                    self.build_layout(new_split, children, None, panels, opened)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // all messages go through here so they reach the log
+    pub fn add_message(&mut self, message: Message) {
+        self.logger.log(&message);
+        self.messages.push(message);
+    }
+
+    // messages added before logger was set are written immediately
+    pub fn set_logger(&mut self, mut logger: Logger) {
+        for message in self.messages.iter() {
+            logger.log(message);
+        }
+
+        self.logger = logger;
+    }
+
+    pub fn add_error<T: Into<Text>>(&mut self, message: T) {
+        self.add_message(Message::error(message));
+    }
+
+    pub fn add_warning<T: Into<Text>>(&mut self, message: T) {
+        self.add_message(Message::warning(message));
     }
 
-    pub fn add_info<T: ToString>(&mut self, message: T) {
-        self.messages.push(Message::info(message));
+    pub fn add_info<T: Into<Text>>(&mut self, message: T) {
+        self.add_message(Message::info(message));
     }
 
     pub fn reset(&mut self, panels: &mut Panels) {
@@ -211,6 +577,7 @@ impl AppState {
         self.static_panels = vec![PROMPT_PANEL_ID];
         self.state = State::Normal;
         self.input_request = None;
+        self.messages_return_panel = None;
     }
 
     pub fn static_panels(&self) -> &Vec<char> {
@@ -253,6 +620,10 @@ impl AppState {
         self.panels.get(index)
     }
 
+    pub fn layout_panels(&self) -> &Vec<LayoutPanel> {
+        &self.panels
+    }
+
     pub fn get_panel_mut(&mut self, index: usize) -> Option<&mut LayoutPanel> {
         self.panels.get_mut(index)
     }
@@ -269,10 +640,108 @@ impl AppState {
         &self.messages
     }
 
+    pub fn clear_messages(&mut self) {
+        self.messages.clear();
+        self.seen_messages = 0;
+    }
+
+    pub fn input_history_mut(&mut self) -> &mut InputHistory {
+        &mut self.input_history
+    }
+
     pub fn input_request(&self) -> Option<&InputRequest> {
         self.input_request.as_ref()
     }
 
+    pub fn clipboard(&self) -> &Vec<String> {
+        &self.clipboard
+    }
+
+    pub fn set_clipboard(&mut self, lines: Vec<String>) {
+        self.clipboard = lines;
+    }
+
+    pub fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    pub fn escape_quits(&self) -> bool {
+        self.escape_quits
+    }
+
+    pub fn set_escape_quits(&mut self, escape_quits: bool) {
+        self.escape_quits = escape_quits;
+    }
+
+    fn abandon_key_sequence(&mut self, keys: Vec<CommandKeyId>) {
+        // panel list goes away with the sequence that opened it
+        self.selecting_panel = false;
+        self.add_info(
+            Text::new("app.abandoned_key_sequence", "Abandoned key sequence {0}.")
+                .arg(keys.iter().map(format_key).collect::<Vec<String>>().join(" -> ")),
+        )
+    }
+
+    // returns true if there was a sequence in progress
+    pub fn cancel_key_sequence(&mut self, commands: &mut Manager) -> bool {
+        match commands.cancel_progress() {
+            None => false,
+            Some(keys) => {
+                self.abandon_key_sequence(keys);
+                true
+            }
+        }
+    }
+
+    // asks about unsaved changes first, shutdown happens once answered
+    pub fn quit(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
+        if self.request_quit(panels, commands) {
+            self.shutdown(panels);
+        }
+    }
+
+    // gives each panel a chance to clean up, then main loop exits
+    fn shutdown(&mut self, panels: &mut Panels) {
+        for index in 0..panels.len() {
+            match panels.get_mut(index) {
+                None => (),
+                Some(panel) => panel.shutdown(),
+            }
+        }
+
+        if let Some(autosave) = &self.autosave {
+            autosave.remove_recovery_files();
+        }
+
+        self.quit = true;
+    }
+
+    pub fn has_unsaved_changes(&self, panels: &Panels) -> bool {
+        self.panels.iter().any(|lp| {
+            panels
+                .get(lp.panel_index)
+                .map(|panel| panel.any_modified())
+                .unwrap_or(false)
+        })
+    }
+
+    // returns true if quitting can happen immediately
+    // otherwise confirmation is requested through input panel
+    pub fn request_quit(&mut self, panels: &mut Panels, commands: &mut Manager) -> bool {
+        if let State::WaitingQuitConfirmation(_) = self.state {
+            return false;
+        }
+
+        if !self.has_unsaved_changes(panels) {
+            return true;
+        }
+
+        self.state = State::WaitingQuitConfirmation(self.active_panel);
+        self.request_top_confirmation(Text::new("prompt.quit_unsaved", "Unsaved changes. Quit?"), panels, commands);
+
+        false
+    }
+
     pub fn first_available_id(&mut self) -> char {
         let mut current = HashSet::new();
 
@@ -293,591 +762,2663 @@ impl AppState {
         id
     }
 
-    pub fn update(&mut self) {
-        // let mut changes = vec![];
-        // for lp in self.panels.iter_mut().filter(|lp| lp.visible()) {
-        //     changes.extend(lp.panel.update());
-        // }
-        //
-        // self.handle_changes(changes);
+    // count of updates, for panels that animate
+    #[allow(dead_code)]
+    pub fn ticks(&self) -> u64 {
+        self.ticks
     }
 
-    pub fn handle_changes(&mut self, changes: Vec<StateChangeRequest>, panels: &mut Panels, commands: &mut Manager) {
-        let active_panel_id = match self.get_active_panel() {
-            Some(lp) => lp.id,
-            None => {
-                self.messages
-                    .push(Message::error("No active panel for change request."));
-                return;
-            }
-        };
-
-        for change in changes {
-            let additional_changes = match change {
-                StateChangeRequest::Input(prompt, completer) => {
-                    // only one input request at a time, override existing
-                    if self.static_panels.contains(&active_panel_id) {
-                        self.messages
-                            .push(Message::error("Input panel cannot make input request."));
-                        return;
-                    }
+    #[allow(dead_code)]
+    pub fn tasks(&self) -> &Tasks {
+        &self.tasks
+    }
 
-                    self.input_request = Some(InputRequest {
-                        prompt: prompt.clone(),
-                        auto_completer: completer,
-                        requestor_id: self.active_panel,
-                    });
+    #[allow(dead_code)]
+    pub fn tasks_mut(&mut self) -> &mut Tasks {
+        &mut self.tasks
+    }
 
-                    self.active_panel = 0;
-                    commands.replace_top_with_panel(INPUT_PANEL_TYPE_ID);
+    // called every tick of main loop, whether or not a key was pressed
+    pub fn update(&mut self, panels: &mut Panels, commands: &mut Manager) {
+        self.ticks = self.ticks.wrapping_add(1);
 
-                    match self.get_panel(0) {
-                        Some(lp) => match panels.get_mut(lp.panel_index) {
-                            Some(panel) => panel.show(),
-                            None => unimplemented!(),
-                        },
-                        None => unimplemented!(),
-                    }
+        for completion in self.tasks.take_completed() {
+            completion(self, panels);
+        }
 
-                    vec![]
-                }
-                StateChangeRequest::InputComplete(input) => {
-                    let index = match &self.input_request {
-                        Some(request) => request.requestor_id,
-                        None => {
-                            self.messages
-                                .push(Message::error("No active input request."));
-                            return;
-                        }
-                    };
+        let mut changes = vec![];
+        for index in 0..panels.len() {
+            match panels.get_mut(index) {
+                None => (),
+                Some(panel) => changes.extend(panel.update()),
+            }
+        }
 
-                    self.input_request = None;
+        self.handle_changes(changes, panels, commands);
 
-                    let changes = if index == TOP_REQUESTOR_ID {
-                        match self.state {
-                            State::WaitingPanelType(for_panel) => {
-                                match self.get_panel(for_panel) {
-                                    None => unimplemented!(),
-                                    Some(lp) => match panels.get_mut(lp.panel_index) {
-                                        Some(panel) => {
-                                            match PanelFactory::panel(input.as_str()) {
-                                                Some(new_panel) => {
-                                                    commands.replace_top_with_panel(new_panel.panel_type());
-                                                    *panel = new_panel;
-                                                }
-                                                None => {
-                                                    self.add_error(format!("No panel of type: {:?}", input))
-                                                }
-                                            }
-                                        },
-                                        None => unimplemented!(),
-                                    },
-                                }
+        // taken out while running so it can report to state
+        match self.autosave.take() {
+            None => (),
+            Some(mut autosave) => {
+                autosave.tick(self, panels);
+                self.autosave = Some(autosave);
+            }
+        }
 
-                                self.active_panel = for_panel;
-                                self.state = State::Normal;
-                            }
-                            State::Normal => unimplemented!(),
-                        }
+        match self.focus_save.take() {
+            None => (),
+            Some(mut focus_save) => {
+                focus_save.tick(self, panels);
+                self.focus_save = Some(focus_save);
+            }
+        }
 
-                        vec![]
-                    } else {
-                        let changes = match self.get_panel(index) {
-                            Some(lp) => match panels.get_mut(lp.panel_index) {
-                                Some(panel) => {
-                                    commands.replace_top_with_panel(panel.panel_type());
-                                    panel.receive_input(input)
-                                },
-                                None => unimplemented!(),
-                            },
-                            None => {
-                                self.messages
-                                    .push(Message::error("Requesting panel doesn't exist."));
-                                return;
-                            }
-                        };
+        match self.file_watcher.take() {
+            None => (),
+            Some(mut file_watcher) => {
+                let changes = file_watcher.tick(self, panels);
+                self.file_watcher = Some(file_watcher);
+                self.handle_changes(changes, panels, commands);
+            }
+        }
 
-                        self.active_panel = index;
+        match self.idle_lock.as_mut() {
+            None => (),
+            Some(lock) => lock.tick(),
+        }
 
-                        changes
-                    };
+        // partly loaded files would be scanned again once done, wait for them
+        let loading = (0..panels.len()).any(|index| panels.get(index).is_some_and(|panel| panel.loading()));
+        if !loading && self.idle_work.ready() {
+            for index in 0..panels.len() {
+                match panels.get_mut(index) {
+                    Some(panel) if panel.panel_type() == EDIT_PANEL_TYPE_ID => panel.refresh_analysis(),
+                    _ => (),
+                }
+            }
+        }
 
-                    match self.get_panel(0) {
-                        Some(lp) => match panels.get_mut(lp.panel_index) {
-                            Some(panel) => panel.hide(),
-                            None => unimplemented!(),
-                        },
-                        None => unimplemented!(),
-                    }
+        match commands.expire_progress() {
+            None => (),
+            Some(keys) => self.abandon_key_sequence(keys),
+        }
 
-                    changes
-                }
-                StateChangeRequest::Message(message) => {
-                    self.messages.push(message);
-                    vec![]
+        // taken out so host's messages can be handled
+        match self.share_session.take() {
+            None => (),
+            Some(ShareSession::Host(mut host)) => match host.tick(panels) {
+                Ok(changes) => {
+                    self.share_session = Some(ShareSession::Host(host));
+                    self.handle_changes(changes, panels, commands);
                 }
-            };
+                Err(err) => self.add_info(err),
+            },
+            Some(ShareSession::Client(mut client)) => match client.tick(panels) {
+                Ok(()) => self.share_session = Some(ShareSession::Client(client)),
+                Err(err) => self.add_info(err),
+            },
+        }
+
+        let mut swap = std::mem::take(&mut self.swap);
+        swap.tick(self, panels);
+        self.swap = swap;
 
-            self.handle_changes(additional_changes, panels, commands);
+        if self.messages_panel_focused(panels) {
+            self.seen_messages = self.messages.len();
         }
     }
 
-    //
-    // Command Actions
-    //
+    pub fn messages_placement(&self) -> MessagesPlacement {
+        self.messages_placement
+    }
 
-    pub fn start_selecting_panel(&mut self, _code: KeyCode, _panels: &mut Panels, _commands: &mut Manager) {
-        self.selecting_panel = true;
+    pub fn set_messages_placement(&mut self, placement: MessagesPlacement) {
+        self.messages_placement = placement;
     }
 
-    pub fn select_panel(&mut self, code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
-        self.selecting_panel = false;
-        match code {
-            KeyCode::Char(c) => match self.panels.iter().enumerate().find(|(_, lp)| lp.id == c) {
-                None => {
-                    self.messages
-                        .push(Message::info(format!("No panel with ID '{}'", c)));
-                }
-                Some((index, _)) => {
-                    self.set_active_panel(index);
-                    match self.get_active_panel().and_then(|layout| panels.get(layout.panel_index)) {
-                        None => unimplemented!(),
-                        Some(panel) => commands.replace_top_with_panel(panel.panel_type())
-                    }
-
-                    if self.input_request.is_some() {
-                        self.input_request = None;
-                        self.messages.push(Message::info(
-                            "Canceled input request due to panel selection.",
-                        ))
-                    }
-                }
-            },
-            _ => {
-                self.messages.push(Message::info(
-                    "Invalid key for panel id. Options are letters a-z, lower or capital.",
-                ));
-            }
+    pub fn messages_panel_visible(&self) -> bool {
+        match self.messages_placement {
+            MessagesPlacement::Split | MessagesPlacement::Strip => true,
+            MessagesPlacement::StatusBar => false,
+            MessagesPlacement::HiddenUntilError => self
+                .messages
+                .iter()
+                .any(|m| m.channel == MessageChannel::ERROR),
         }
     }
 
-    pub fn split_current_panel_horizontal(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
-        // opposite direction, because visual like will be vertical for horizontal layout
-        self.split(Direction::Vertical, panels, commands)
+    fn messages_panel_index(&self, panels: &Panels) -> Option<usize> {
+        self.panels
+            .iter()
+            .position(|lp| match panels.get(lp.panel_index) {
+                Some(panel) => panel.panel_type() == MESSAGE_PANEL_TYPE_ID,
+                None => false,
+            })
     }
 
-    pub fn split_current_panel_vertical(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
-        // opposite direction, because visual like will be horizontal for vertical layout
-        self.split(Direction::Horizontal, panels, commands)
+    fn messages_panel_focused(&self, panels: &Panels) -> bool {
+        self.messages_panel_visible() && self.messages_panel_index(panels) == Some(self.active_panel)
     }
 
-    pub fn add_panel_to_active_split(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
-        let active_split = match self.get_active_panel() {
-            Some(lp) => lp.split_index,
-            None => {
-                self.add_error("No active panel. Setting to be last panel.");
-                self.active_panel = 1;
-                return;
-            }
-        };
+    pub fn unseen_error_count(&self, panels: &Panels) -> usize {
+        if self.messages_panel_focused(panels) {
+            return 0;
+        }
 
-        let new_panel_index = self.add_panel(active_split, panels, commands);
+        self.messages
+            .iter()
+            .skip(self.seen_messages)
+            .filter(|m| m.channel == MessageChannel::ERROR)
+            .count()
+    }
 
-        match self.splits.get_mut(active_split) {
-            Some(s) => s.panels.push(UserSplits::Panel(new_panel_index)),
-            None => {
-                self.add_error("Active panel's split not found. Resetting state.");
-                self.reset(panels);
-                return;
+    pub fn jump_to_messages(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
+        if self.messages_panel_focused(panels) {
+            match self.messages_return_panel.take() {
+                Some(index) if index < self.panels.len() => {
+                    self.active_panel = index;
+                    self.replace_commands_for_active_panel(panels, commands);
+                }
+                _ => (),
+            }
+            return;
+        }
+
+        match self.messages_panel_index(panels) {
+            None => self.add_message(Message::info(Text::new("app.no_messages_panel", "No messages panel in layout."))),
+            Some(_) if !self.messages_panel_visible() => {
+                self.add_message(Message::info(
+                    Text::new("app.messages_panel_hidden", "Messages panel is hidden by current placement."),
+                ))
+            }
+            Some(index) => {
+                self.messages_return_panel = Some(self.active_panel);
+                self.active_panel = index;
+                self.seen_messages = self.messages.len();
+                self.replace_commands_for_active_panel(panels, commands);
             }
         }
     }
 
-    pub(crate) fn add_panel(&mut self, split: usize, panels: &mut Panels, _commands: &mut Manager) -> usize {
-        let new_id = self.first_available_id();
-        let new_index = panels.push(PanelFactory::edit());
+    pub fn set_catalog(&mut self, catalog: Catalog) {
+        self.catalog = catalog;
+    }
 
-        self.panels.push(LayoutPanel::new(split, new_id, new_index));
+    pub fn catalog(&self) -> &Catalog {
+        &self.catalog
+    }
 
-        new_index
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
     }
 
-    pub fn delete_active_panel(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
-        let (next_active_panel, active_split, active_panel_id, active_panel_index) =
-            match (self.next_panel_index(panels), self.get_active_panel()) {
-                (Err(e), None) | (Err(e), _) => {
-                    self.reset(panels);
-                    self.messages.push(e);
-                    return;
-                }
-                (_, None) => {
-                    self.active_panel = 1;
-                    self.messages
-                        .push(Message::error("No active panel. Setting to be last panel."));
-                    return;
-                }
-                (Ok(next), Some(lp)) => (next, lp.split_index, lp.id, lp.panel_index),
-            };
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
 
-        if self.static_panels().contains(&active_panel_id) {
-            self.messages
-                .push(Message::info(format!("Cannot delete static panel.")));
-            return;
-        }
+    // text in the selected locale, for anything shown to the user
+    pub fn text(&self, text: &Text) -> String {
+        self.catalog.text(text)
+    }
 
-        // find active's index in split
-        let local_current_panel = self.active_panel();
+    pub fn set_safe_mode(&mut self, safe_mode: bool) {
+        self.safe_mode = safe_mode;
+    }
 
-        let remove_split = match self.splits.get_mut(active_split) {
-            None => {
-                self.messages.push(Message::error(
-                    "Active panels split doesn't exist. Resetting state.",
-                ));
-                self.reset(panels);
-                return;
-            }
-            Some(split) => {
-                let index = match split.panels.iter().enumerate().find(|(_, s)| match s {
-                    UserSplits::Panel(index) => *index == local_current_panel,
-                    UserSplits::Split(..) => false,
-                }) {
-                    Some(i) => i.0,
-                    None => {
-                        self.messages.push(Message::error(
-                            "Active panel's split doesn't contain active panel. Resetting state.",
-                        ));
-                        self.reset(panels);
-                        return;
-                    }
-                };
+    pub fn set_config(&mut self, config: Config) {
+        self.user_config = config;
+        self.config_dir = None;
+        self.refresh_local_config(env::current_dir().ok());
+    }
 
-                split.panels.remove(index);
+    // project config is found from given directory upwards, reload only if it changed
+    fn refresh_local_config(&mut self, dir: Option<PathBuf>) {
+        if self.safe_mode || dir == self.config_dir {
+            return;
+        }
 
-                split.panels.is_empty()
-            }
-        };
+        let mut config = self.user_config.clone();
+        match dir.as_ref().map(|dir| Config::load_local(dir)) {
+            Some(Ok(Some(local))) => config.merge(&local),
+            Some(Err(err)) => self.add_error(err),
+            _ => (),
+        }
 
-        if remove_split {
-            self.splits.remove(active_split);
+        self.config = config;
+        self.config_dir = dir;
+    }
 
-            // should always get set
-            // if they remain zero, it would remove static prompt panel
-            // error below
-            let mut parent_index = 0;
-            let mut child_index = 0;
-            'outer: for (i, s) in self.splits.iter().enumerate() {
-                for (j, p) in s.panels.iter().enumerate() {
-                    match p {
-                        UserSplits::Panel(_) => (), // skip panels
-                        UserSplits::Split(index) => {
-                            if *index == active_split {
-                                parent_index = i;
-                                child_index = j;
-                                break 'outer;
-                            }
-                        }
-                    }
-                }
-            }
+    fn apply_config(&mut self, panel: &mut TextPanel) {
+        if let Err(err) = self.config.apply_to_panel(panel) {
+            self.add_error(err);
+        }
+    }
 
-            if parent_index == 0 && child_index == 0 {
-                self.messages.push(Message::error(
-                    "Split not found in parent when removing due to being empty. Resetting state.",
-                ));
-                self.reset(panels);
-                return;
-            }
+    // panel switched to a different file
+    fn file_opened(&mut self, panel: &mut TextPanel) {
+        // project of the file, not of wherever the editor was started
+        let dir = panel
+            .file_path()
+            .and_then(|path| env::current_dir().ok().map(|cwd| cwd.join(path)))
+            .and_then(|path| path.parent().map(Path::to_path_buf));
+        if dir.is_some() {
+            self.refresh_local_config(dir);
+        }
 
-            match self.get_split_mut(parent_index) {
-                Some(p) => {
-                    p.panels.remove(child_index);
-                }
-                None => {
-                    // should be unreachable
-                    // indexes used were gotten by enumerate
-                    // so they should exist
+        self.apply_config(panel);
+        if let Some(positions) = self.cursor_positions.as_ref() {
+            positions.restore(panel);
+        }
 
-                    self.messages.push(Message::error(
-                        "Invalid split index after enumeration. Resetting state.",
-                    ));
-                    self.reset(panels);
-                    return;
-                }
+        if let Some(path) = panel.file_path() {
+            self.recent_files.add(path);
+            if let Err(err) = self.recent_files.save() {
+                self.add_error(err);
             }
         }
+    }
 
-        // verified that it exists from first check getting active panel
-        // self.panels.remove(local_current_panel);
-        panels.remove(active_panel_index);
+    pub fn set_recent_files(&mut self, recent_files: RecentFiles) {
+        self.recent_files = recent_files;
+    }
 
-        let active_count = self
-            .panels
-            .iter()
-            .filter(|lp| {
-                panels
-                    .get(lp.panel_index)
-                    .map(|panel| panel.panel_type() != NULL_PANEL_TYPE_ID)
-                    .unwrap_or(false)
-            })
-            .count();
+    pub fn recent_files(&self) -> &RecentFiles {
+        &self.recent_files
+    }
 
-        // if this is last panel besides static panels
-        // we will replace it
-        if active_count <= self.static_panels.len() {
-            // use last split that we have for new panel's split
-            let last = self.splits_len() - 1;
-            let index = self.add_panel(last, panels, commands);
-            match self.get_split_mut(last) {
-                Some(s) => s.panels.push(UserSplits::Panel(index)),
-                None => {
-                    // should be unreachable
-                    // getting here means splits is empty
-                    // which should only be possible if we had removed the prompt panel
-                    // causing the removal of top split
-                    // this is caught above during the split removal
+    pub fn set_cursor_positions(&mut self, positions: Option<CursorPositions>) {
+        self.cursor_positions = positions;
+    }
 
-                    self.messages
-                        .push(Message::error("No splits remaining. Resetting state."));
-                    self.reset(panels);
-                    return;
-                }
+    // keep positions of open files for next session
+    pub fn save_cursor_positions(&mut self, panels: &Panels) -> Result<(), String> {
+        match self.cursor_positions.as_mut() {
+            None => Ok(()),
+            Some(positions) => {
+                positions.record_all(panels);
+                positions.save()
             }
-
-            self.active_panel = index;
-        } else {
-            self.active_panel = next_active_panel;
         }
     }
 
-    pub fn activate_next_panel(&mut self, _code: KeyCode, panels: &mut Panels, _commands: &mut Manager) {
-        self.resolve_panel_change(self.next_panel_index(panels));
+    pub fn set_autosave(&mut self, autosave: Option<Autosave>) {
+        self.autosave = autosave;
     }
 
-    pub fn activate_previous_panel(&mut self, _code: KeyCode, panels: &mut Panels, _commands: &mut Manager) {
-        self.resolve_panel_change(self.previous_panel_index(panels));
+    pub fn set_focus_save(&mut self, focus_save: Option<FocusSave>) {
+        self.focus_save = focus_save;
     }
 
-    pub fn change_active_panel_type(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
-        self.state = State::WaitingPanelType(self.active_panel);
-        self.active_panel = 0;
-        self.input_request = Some(InputRequest {
-            prompt: "Panel Type".to_string(),
-            requestor_id: TOP_REQUESTOR_ID,
-            auto_completer: Some(Box::new(PanelAutoCompleter::new())),
-        });
-        match self.get_panel(0) {
-            Some(lp) => match panels.get_mut(lp.panel_index) {
-                Some(panel) => {
-                    panel.show();
-                    commands.replace_top_with_panel(panel.panel_type());
-                },
-                None => unimplemented!(),
-            },
-            None => unimplemented!(),
-        }
+    pub fn set_file_watcher(&mut self, file_watcher: Option<FileWatcher>) {
+        self.file_watcher = file_watcher;
     }
 
-    fn resolve_panel_change(&mut self, r: Result<usize, Message>) {
-        match r {
-            Ok(next) => self.active_panel = next,
-            Err(e) => {
-                self.active_panel = 1;
-                self.messages.push(e);
-            }
-        }
+    pub fn focused(&self) -> bool {
+        self.focused
     }
 
-    fn next_panel_index(&self, panels: &Panels) -> Result<usize, Message> {
-        self.active_panel_index(panels, |index, order| {
-            if index + 1 >= order.len() {
-                0
-            } else {
-                index + 1
-            }
-        })
+    // switching away from terminal counts as leaving panel for focus save
+    pub fn set_focused(&mut self, focused: bool, panels: &mut Panels) {
+        if self.focused && !focused && self.focus_save.is_some() {
+            save_modified_files(self, panels);
+        }
+
+        self.focused = focused;
     }
 
-    fn previous_panel_index(&self, panels: &Panels) -> Result<usize, Message> {
-        self.active_panel_index(panels, |index, order| {
-            if index == 0 {
-                order.len() - 1
-            } else {
-                index - 1
-            }
-        })
+    #[allow(dead_code)]
+    pub fn screen(&self) -> Rect {
+        self.screen
     }
 
-    fn active_panel_index<F: FnOnce(usize, &Vec<usize>) -> usize>(
-        &self,
-        panels: &Panels,
-        f: F,
-    ) -> Result<usize, Message> {
-        let order = self.build_order(panels)?;
-        let mut active_panel_index = None;
-        for (i, panel_index) in order.iter().enumerate() {
-            if *panel_index == self.active_panel {
-                active_panel_index = Some(i);
+    // terminal changed size, keep cursors inside views that shrank
+    pub fn resize(&mut self, width: u16, height: u16, panels: &mut Panels) {
+        self.screen = Rect::new(0, 0, width, height);
+
+        for index in 0..panels.len() {
+            match panels.get_mut(index) {
+                Some(panel) if panel.panel_type() == EDIT_PANEL_TYPE_ID => panel.fit_to_screen(self.screen),
+                _ => (),
             }
         }
+    }
 
-        match active_panel_index {
-            None => Err(Message::error("Active panel not found after ordering.")),
-            Some(index) => Ok(order[f(index, &order)]),
-        }
+    pub fn set_dim_unfocused(&mut self, dim_unfocused: bool) {
+        self.dim_unfocused = dim_unfocused;
     }
 
-    fn build_order(&self, panels: &Panels) -> Result<Vec<usize>, Message> {
-        let mut order = vec![];
-        self.push_panels(0, &mut order, panels)?;
-        Ok(order)
+    pub fn dimmed(&self) -> bool {
+        self.dim_unfocused && !self.focused
     }
 
-    fn push_panels(
-        &self,
-        split: usize,
-        order: &mut Vec<usize>,
-        panels: &Panels,
-    ) -> Result<(), Message> {
-        match self.splits.get(split) {
-            None => return Err(Message::error("Child split not found in splits.")),
-            Some(split) => {
-                for child in split.panels.iter() {
-                    match child {
-                        UserSplits::Panel(panel_index) => match self.panels.get(*panel_index) {
-                            Some(lp) => match panels.get(lp.panel_index) {
-                                Some(panel) => match panel.panel_type() == NULL_PANEL_TYPE_ID {
-                                    true => (),
-                                    false => order.push(*panel_index),
-                                },
-                                None => unimplemented!(),
-                            },
-                            None => return Err(Message::error("Child panel not found in panels.")),
-                        },
-                        UserSplits::Split(split_index) => {
-                            self.push_panels(*split_index, order, panels)?
-                        }
+    pub fn set_idle_lock(&mut self, idle_lock: Option<IdleLock>) {
+        self.idle_lock = idle_lock;
+    }
+
+    pub fn set_idle_work(&mut self, idle_work: IdleWork) {
+        self.idle_work = idle_work;
+    }
+
+    // any key or mouse event, delays idle analysis
+    pub fn note_input(&mut self) {
+        self.idle_work.input();
+    }
+
+    // only one at a time, replacing ends the previous one
+    pub fn set_share_session(&mut self, share_session: Option<ShareSession>) {
+        self.share_session = share_session;
+    }
+
+    pub fn share_session(&self) -> Option<&ShareSession> {
+        self.share_session.as_ref()
+    }
+
+    pub fn lock_state(&self) -> LockState {
+        match self.idle_lock.as_ref() {
+            None => LockState::Unlocked,
+            Some(lock) => lock.state(),
+        }
+    }
+
+    // true when key was used to resume and shouldn't be handled as a command
+    pub fn handle_lock_key(&mut self, code: KeyCode) -> bool {
+        match self.idle_lock.as_mut() {
+            None => false,
+            Some(lock) => lock.handle_key(code),
+        }
+    }
+
+    // escape clears panel search before it backs out of anything else
+    pub fn cancel_search(&mut self, panels: &mut Panels) -> bool {
+        match panels.get_mut(self.active_panel) {
+            None => false,
+            Some(panel) => panel.cancel_search(),
+        }
+    }
+
+    pub fn leave_column_select(&mut self, panels: &mut Panels) -> bool {
+        match panels.get_mut(self.active_panel) {
+            None => false,
+            Some(panel) => panel.leave_column_select(),
+        }
+    }
+
+    // escape leaves insert mode before it backs out of anything else
+    pub fn leave_insert_mode(&mut self, panels: &mut Panels) -> bool {
+        match panels.get_mut(self.active_panel) {
+            None => false,
+            Some(panel) => panel.leave_insert_mode(),
+        }
+    }
+
+    // clicking a line number selects that line, dragging selects the lines between
+    pub fn handle_mouse(&mut self, event: MouseEvent, panels: &mut Panels, commands: &mut Manager) {
+        if self.lock_state() != LockState::Unlocked {
+            return;
+        }
+
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.gutter_drag = None;
+
+                let (layout_index, panel_index, line) = match self.gutter_line_at(event.column, event.row, panels) {
+                    None => return,
+                    Some(hit) => hit,
+                };
+
+                if layout_index != self.active_panel {
+                    self.set_active_panel(layout_index);
+                    commands.replace_top_with_panel(EDIT_PANEL_TYPE_ID);
+                }
+
+                match panels.get_mut(panel_index) {
+                    None => (),
+                    Some(panel) => {
+                        panel.select_lines(line, line);
+                        self.gutter_drag = Some((panel_index, line));
+                    }
+                }
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                let (panel_index, anchor) = match self.gutter_drag {
+                    None => return,
+                    Some(drag) => drag,
+                };
+
+                match panels.get_mut(panel_index) {
+                    None => (),
+                    Some(panel) => {
+                        // dragging out of panel keeps to its nearest row, rows past the text mean last line
+                        let viewport = panel.viewport();
+                        let row = event.row.clamp(viewport.y, viewport.bottom().saturating_sub(1).max(viewport.y));
+                        let line = panel
+                            .line_at_row(row)
+                            .unwrap_or(panel.lines().len().saturating_sub(1));
+
+                        panel.select_lines(anchor, line);
                     }
                 }
             }
+            MouseEventKind::Up(MouseButton::Left) => self.gutter_drag = None,
+            _ => (),
         }
+    }
 
-        Ok(())
+    // layout index, panel index and line of edit panel line number at screen position
+    fn gutter_line_at(&self, column: u16, row: u16, panels: &Panels) -> Option<(usize, usize, usize)> {
+        self.panels.iter().enumerate().find_map(|(layout_index, lp)| {
+            let panel = panels.get(lp.panel_index)?;
+            let viewport = panel.viewport();
+
+            let in_gutter = panel.panel_type() == EDIT_PANEL_TYPE_ID
+                && panel.visible()
+                && column >= viewport.x
+                && column < viewport.x.saturating_add(panel.gutter_width()).min(viewport.right());
+
+            match in_gutter {
+                true => panel.line_at_row(row).map(|line| (layout_index, lp.panel_index, line)),
+                false => None,
+            }
+        })
     }
-}
 
-type GlobalAction = fn(&mut AppState, KeyCode, &mut Panels, &mut Manager);
+    pub fn handle_changes(&mut self, changes: Vec<StateChangeRequest>, panels: &mut Panels, commands: &mut Manager) {
+        let mut queue = VecDeque::from(changes);
+        let mut handled = 0;
+
+        while let Some(change) = queue.pop_front() {
+            if handled >= MAX_CHANGES_PER_EVENT {
+                self.add_error(
+                    Text::new(
+                        "app.change_loop_stopped",
+                        "Stopped after {0} state changes in one event, {1} dropped. Panels may be responding to each other in a loop.",
+                    )
+                    .arg(handled)
+                    .arg(queue.len() + 1),
+                );
+                return;
+            }
 
-pub fn global_commands() -> Result<Commands<GlobalAction>, String> {
-    let mut commands = Commands::<GlobalAction>::new();
+            handled += 1;
 
-    //
-    // Panel creation/deletion
-    //
+            let active_panel_id = match self.get_active_panel() {
+                Some(lp) => lp.id,
+                None => {
+                    self.add_message(Message::error(Text::new("app.no_panel_for_change", "No active panel for change request.")));
+                    return;
+                }
+            };
 
-    commands.insert(|b| {
-        b.node(ctrl_key('p')).node(key('h')).action(
-            CommandDetails::split_horizontal(),
-            AppState::split_current_panel_horizontal,
-        )
-    })?;
+            let additional_changes = match change {
+                StateChangeRequest::Input(prompt, completer) => {
+                    if !self.open_input_request(prompt, completer, None, active_panel_id, panels, commands) {
+                        return;
+                    }
 
-    commands.insert(|b| {
-        b.node(ctrl_key('p')).node(key('v')).action(
-            CommandDetails::split_vertical(),
-            AppState::split_current_panel_vertical,
-        )
-    })?;
+                    vec![]
+                }
+                StateChangeRequest::Confirm(prompt) => {
+                    if !self.open_input_request(
+                        confirmation_prompt(&self.text(&prompt)),
+                        Some(Box::new(ConfirmAutoCompleter::new())),
+                        Some(validate_confirmation),
+                        active_panel_id,
+                        panels,
+                        commands,
+                    ) {
+                        return;
+                    }
 
-    commands.insert(|b| {
-        b.node(ctrl_key('p')).node(key('n')).action(
-            CommandDetails::add_panel(),
-            AppState::add_panel_to_active_split,
-        )
-    })?;
+                    vec![]
+                }
+                StateChangeRequest::ValidatedInput(prompt, completer, validator) => {
+                    if !self.open_input_request(prompt, completer, Some(validator), active_panel_id, panels, commands) {
+                        return;
+                    }
 
-    commands.insert(|b| {
-        b.node(ctrl_key('p')).node(key('d')).action(
-            CommandDetails::remove_panel(),
-            AppState::delete_active_panel,
-        )
-    })?;
+                    vec![]
+                }
+                StateChangeRequest::InputSequence(mut prompts) => {
+                    if self.static_panels.contains(&active_panel_id) {
+                        self.add_message(Message::error(
+                            Text::new("app.input_panel_cannot_request", "Input panel cannot make input request."),
+                        ));
+                        return;
+                    }
 
-    commands.insert(|b| {
-        b.node(ctrl_key('p')).node(key('t')).action(
-            CommandDetails::change_panel_type(),
-            AppState::change_active_panel_type,
-        )
-    })?;
+                    if prompts.is_empty() {
+                        self.add_message(Message::error(Text::new("app.input_sequence_empty", "Input sequence has no prompts.")));
+                        return;
+                    }
 
-    //
-    // Panel Navigation
-    //
-    commands.insert(|b| {
-        b.node(ctrl_alt_key('l')).action(
-            CommandDetails::activate_next_panel(),
-            AppState::activate_next_panel,
-        )
-    })?;
+                    let total = prompts.len();
+                    let first = prompts.remove(0);
+                    self.input_request = Some(InputRequest {
+                        prompt: first,
+                        auto_completer: None,
+                        requestor_id: self.active_panel,
+                        sequence: Some(InputSequence {
+                            remaining: prompts,
+                            answers: vec![],
+                            total,
+                        }),
+                        validator: None,
+                    });
+                    self.show_input_panel(panels, commands);
 
-    commands.insert(|b| {
-        b.node(ctrl_alt_key('j')).action(
-            CommandDetails::activate_previous_panel(),
-            AppState::activate_previous_panel,
-        )
-    })?;
+                    vec![]
+                }
+                StateChangeRequest::InputComplete(input) => {
+                    let mut request = match self.input_request.take() {
+                        Some(request) => request,
+                        None => {
+                            self.add_message(Message::error(
+                                Text::new("app.no_active_input_request", "No active input request."),
+                            ));
+                            return;
+                        }
+                    };
 
-    //
-    // Panel Selection
-    //
+                    // put text back so it can be corrected
+                    if let Some(Err(err)) = request.validator.map(|validate| validate(&input)) {
+                        self.add_error(err);
+                        match self.get_panel(0).and_then(|lp| panels.get_mut(lp.panel_index)) {
+                            None => (),
+                            Some(panel) => {
+                                panel.set_cursor_index(input.len());
+                                panel.set_text(input);
+                            }
+                        }
 
-    commands.insert(|b| {
-        b.node(ctrl_key('a').action(AppState::start_selecting_panel))
-            .node(catch_all())
-            .action(CommandDetails::select_panel(), AppState::select_panel)
-    })?;
+                        self.input_request = Some(request);
+                        continue;
+                    }
 
-    Ok(commands)
-}
+                    let index = request.requestor_id;
+                    self.input_history.push(&request.prompt.to_string(), input.clone());
 
-#[cfg(test)]
-mod tests {
-    use crossterm::event::KeyCode;
+                    // ask next question, input panel stays active
+                    let answers = match request.sequence.as_mut() {
+                        None => None,
+                        Some(sequence) if sequence.remaining.is_empty() => {
+                            sequence.answers.push(input.clone());
+                            Some(std::mem::take(&mut sequence.answers))
+                        }
+                        Some(sequence) => {
+                            sequence.answers.push(input);
+                            request.prompt = sequence.remaining.remove(0);
+                            self.input_request = Some(request);
+                            continue;
+                        }
+                    };
 
-    use crate::app::{InputRequest, LayoutPanel, Message, MessageChannel, State, TOP_REQUESTOR_ID};
-    use crate::commands::Manager;
-    use crate::panels::{PanelFactory, NULL_PANEL_TYPE_ID};
-    use crate::{AppState, Panels, UserSplits};
+                    let changes = if index == TOP_REQUESTOR_ID {
+                        match self.state {
+                            State::WaitingPanelType(for_panel) => {
+                                match self.get_panel(for_panel) {
+                                    None => unimplemented!(),
+                                    Some(lp) => match panels.get_mut(lp.panel_index) {
+                                        Some(panel) => {
+                                            match PanelFactory::panel(input.as_str()) {
+                                                Some(new_panel) => {
+                                                    commands.replace_top_with_panel(new_panel.panel_type());
+                                                    *panel = new_panel;
+                                                }
+                                                None => {
+                                                    self.add_error(
+                                                        Text::new("app.no_panel_type", "No panel of type: {0}")
+                                                            .arg(format!("{:?}", input)),
+                                                    )
+                                                }
+                                            }
+                                        },
+                                        None => unimplemented!(),
+                                    },
+                                }
 
-    fn assert_is_default(app: &AppState) {
-        assert_eq!(app.panels.len(), 3, "Panels not set");
-        assert_eq!(app.splits.len(), 1, "Splits not set");
-        assert_eq!(app.active_panel, 1, "Active panel not set");
-        assert_eq!(app.selecting_panel, false, "Selecting panel not set");
-        assert_eq!(app.static_panels, vec!['$'], "Static panels not set");
-        assert_eq!(app.state, State::Normal);
-        assert!(app.input_request.is_none());
+                                self.active_panel = for_panel;
+                                self.state = State::Normal;
+                            }
+                            State::WaitingDeleteConfirmation(for_panel) => {
+                                self.active_panel = for_panel;
+                                self.state = State::Normal;
+
+                                if is_confirmation(&input) {
+                                    self.remove_active_panel(panels, commands);
+                                } else {
+                                    self.add_info(Text::new("app.panel_not_deleted", "Panel not deleted."));
+                                }
+
+                                self.replace_commands_for_active_panel(panels, commands);
+                            }
+                            State::WaitingCloseOthersConfirmation(for_panel) => {
+                                self.active_panel = for_panel;
+                                self.state = State::Normal;
+
+                                if is_confirmation(&input) {
+                                    self.remove_other_panels(panels, commands);
+                                } else {
+                                    self.add_info(Text::new("app.panels_not_closed", "Panels not closed."));
+                                }
+
+                                self.replace_commands_for_active_panel(panels, commands);
+                            }
+                            State::WaitingQuitConfirmation(for_panel) => {
+                                self.active_panel = for_panel;
+                                self.state = State::Normal;
+
+                                if is_confirmation(&input) {
+                                    self.shutdown(panels);
+                                } else {
+                                    self.add_info(Text::new("app.quit_canceled", "Quit canceled."));
+                                }
+
+                                self.replace_commands_for_active_panel(panels, commands);
+                            }
+                            State::WaitingCommand(for_panel) => {
+                                self.active_panel = for_panel;
+                                self.state = State::Normal;
+                                self.replace_commands_for_active_panel(panels, commands);
+
+                                // fall back to best fuzzy match when name isn't exact
+                                let names = self.palette_command_names(commands);
+                                let completer =
+                                    CommandAutoCompleter::new(names.iter().map(|(shown, _)| shown.clone()).collect());
+                                let shown = match completer.ranked_options(&input).first() {
+                                    Some(completion) if !input.is_empty() => completion.option().clone(),
+                                    _ => input.clone(),
+                                };
+                                let name = names
+                                    .into_iter()
+                                    .find(|(option, _)| *option == shown)
+                                    .map_or(shown, |(_, name)| name);
+
+                                if !commands.execute_by_name(&name, self, panels) {
+                                    self.add_error(
+                                        Text::new("palette.no_command", "No command named: {0}").arg(format!("{:?}", input)),
+                                    );
+                                }
+                            }
+                            State::WaitingShellCommand(for_panel) => {
+                                self.active_panel = for_panel;
+                                self.state = State::Normal;
+                                self.replace_commands_for_active_panel(panels, commands);
+
+                                self.show_command_output(input, panels, commands);
+                            }
+                            State::WaitingKeymapExport(for_panel) => {
+                                self.active_panel = for_panel;
+                                self.state = State::Normal;
+                                self.replace_commands_for_active_panel(panels, commands);
+
+                                match input.trim() {
+                                    "" => self.add_info(Text::new("app.export_canceled", "Export canceled.")),
+                                    path => match export_keymap(path, commands) {
+                                        Err(err) => self.add_error(err),
+                                        Ok(count) => self.add_info(
+                                            Text::new("app.keymap_exported", "Exported {0} key binding(s) to {1}.")
+                                                .arg(count)
+                                                .arg(format!("{:?}", path)),
+                                        ),
+                                    },
+                                }
+                            }
+                            State::WaitingKeymapImport(for_panel) => {
+                                self.active_panel = for_panel;
+                                self.state = State::Normal;
+                                self.replace_commands_for_active_panel(panels, commands);
+
+                                match input.trim() {
+                                    "" => self.add_info(Text::new("app.import_canceled", "Import canceled.")),
+                                    path => match import_keymap(path, commands) {
+                                        Err(err) => self.add_error(err),
+                                        Ok(import) => {
+                                            for conflict in import.conflicts() {
+                                                self.add_warning(conflict.clone());
+                                            }
+
+                                            self.add_info(
+                                                Text::new(
+                                                    "app.keymap_imported",
+                                                    "Imported {0} key binding(s) from {1}, {2} conflict(s).",
+                                                )
+                                                .arg(import.added())
+                                                .arg(format!("{:?}", path))
+                                                .arg(import.conflicts().len()),
+                                            );
+                                        }
+                                    },
+                                }
+                            }
+                            State::Normal => unimplemented!(),
+                        }
+
+                        vec![]
+                    } else {
+                        let changes = match self.get_panel(index).map(|lp| lp.panel_index) {
+                            Some(panel_index) => match panels.get_mut(panel_index) {
+                                Some(panel) => {
+                                    commands.replace_top_with_panel(panel.panel_type());
+
+                                    // input may open a different file
+                                    // switching buffers brings its own cursor and settings along
+                                    let previous_path = panel.file_path().cloned();
+                                    let switching_buffer = panel.state() == PanelState::WaitingToSwitchBuffer;
+                                    if let Some(positions) = self.cursor_positions.as_mut() {
+                                        positions.record(panel);
+                                    }
+
+                                    let changes = match answers {
+                                        Some(answers) => panel.receive_answers(answers),
+                                        None => panel.receive_input(input),
+                                    };
+
+                                    if panel.file_path() != previous_path.as_ref() && !switching_buffer {
+                                        self.file_opened(panel);
+                                    }
+
+                                    changes
+                                },
+                                None => unimplemented!(),
+                            },
+                            None => {
+                                self.add_message(Message::error(
+                                    Text::new("app.requesting_panel_missing", "Requesting panel doesn't exist."),
+                                ));
+                                return;
+                            }
+                        };
+
+                        self.active_panel = index;
+
+                        changes
+                    };
+
+                    match self.get_panel(0) {
+                        Some(lp) => match panels.get_mut(lp.panel_index) {
+                            Some(panel) => panel.hide(),
+                            None => unimplemented!(),
+                        },
+                        None => unimplemented!(),
+                    }
+
+                    changes
+                }
+                StateChangeRequest::InputCanceled => {
+                    match self.cancel_input_request(panels) {
+                        None => self.add_message(Message::error(
+                            Text::new("app.no_active_input_request", "No active input request."),
+                        )),
+                        Some(restore) => {
+                            self.active_panel = restore;
+                            self.replace_commands_for_active_panel(panels, commands);
+                            self.add_info(Text::new("app.input_canceled", "Input canceled."));
+                        }
+                    }
+
+                    vec![]
+                }
+                StateChangeRequest::Message(message) => {
+                    self.add_message(message);
+                    vec![]
+                }
+                StateChangeRequest::Custom(change) => change.apply(self, panels, commands),
+            };
+
+            // resulting changes are handled before remaining ones
+            for change in additional_changes.into_iter().rev() {
+                queue.push_front(change);
+            }
+        }
+    }
+
+    //
+    // Command Actions
+    //
+
+    pub fn start_selecting_panel(&mut self, _code: KeyCode, _panels: &mut Panels, _commands: &mut Manager) {
+        self.selecting_panel = true;
+    }
+
+    pub fn select_panel(&mut self, code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
+        self.selecting_panel = false;
+        match code {
+            KeyCode::Char(c) => match self.panels.iter().enumerate().find(|(_, lp)| lp.id == c) {
+                None => {
+                    self.add_message(Message::info(Text::new("app.no_panel_id", "No panel with ID '{0}'").arg(c)));
+                }
+                Some((index, _)) => {
+                    self.set_active_panel(index);
+                    match self.get_active_panel().and_then(|layout| panels.get(layout.panel_index)) {
+                        None => unimplemented!(),
+                        Some(panel) => commands.replace_top_with_panel(panel.panel_type())
+                    }
+
+                    if self.cancel_input_request(panels).is_some() {
+                        self.add_message(Message::info(
+                            Text::new("app.input_canceled_by_panel_selection", "Canceled input request due to panel selection."),
+                        ))
+                    }
+                }
+            },
+            KeyCode::Esc => self.add_info(Text::new("app.panel_selection_canceled", "Panel selection canceled.")),
+            _ => {
+                self.add_message(Message::info(
+                    Text::new("app.invalid_panel_id_key", "Invalid key for panel id. Options are letters a-z, lower or capital."),
+                ));
+            }
+        }
+    }
+
+    pub fn split_current_panel_horizontal(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
+        // opposite direction, because visual like will be vertical for horizontal layout
+        self.split(Direction::Vertical, panels, commands)
+    }
+
+    pub fn split_current_panel_vertical(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
+        // opposite direction, because visual like will be horizontal for vertical layout
+        self.split(Direction::Horizontal, panels, commands)
+    }
+
+    pub fn split_current_panel_view(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
+        let source = match self.get_active_panel().and_then(|lp| panels.get(lp.panel_index).map(|p| (lp.panel_index, p))) {
+            Some((index, panel)) if panel.panel_type() == EDIT_PANEL_TYPE_ID => index,
+            Some(_) => {
+                self.add_info(Text::new("app.only_edit_panels_viewed", "Only edit panels can be viewed in a split."));
+                return;
+            }
+            None => {
+                self.add_error(Text::new("app.no_active_panel", "No active panel."));
+                return;
+            }
+        };
+
+        let target = match self.split_active_panel(panels, commands) {
+            Some(index) => index,
+            None => return,
+        };
+
+        match panels.share_view(source, target) {
+            Ok(()) => (),
+            Err(e) => self.add_error(e),
+        }
+    }
+
+    // index of edit panel added beside active one
+    // None when split failed, split logs its own messages
+    pub fn split_active_panel(&mut self, panels: &mut Panels, commands: &mut Manager) -> Option<usize> {
+        let splits_before = self.splits_len();
+        self.split(Direction::Vertical, panels, commands);
+
+        if self.splits_len() <= splits_before {
+            return None;
+        }
+
+        self.panels.last().map(|lp| lp.panel_index)
+    }
+
+    pub fn panel_index_by_id(&self, id: char) -> Option<usize> {
+        self.panels.iter().find(|lp| lp.id == id).map(|lp| lp.panel_index)
+    }
+
+    pub fn add_panel_to_active_split(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
+        let active_split = match self.get_active_panel() {
+            Some(lp) => lp.split_index,
+            None => {
+                self.add_error(Text::new("app.no_active_panel_reset", "No active panel. Setting to be last panel."));
+                self.active_panel = 1;
+                return;
+            }
+        };
+
+        let new_panel_index = self.add_panel(active_split, panels, commands);
+
+        match self.splits.get_mut(active_split) {
+            Some(s) => s.panels.push(UserSplits::Panel(new_panel_index)),
+            None => {
+                self.add_error(Text::new("app.split_not_found_reset", "Active panel's split not found. Resetting state."));
+                self.reset(panels);
+                return;
+            }
+        }
+    }
+
+    pub(crate) fn add_panel(&mut self, split: usize, panels: &mut Panels, _commands: &mut Manager) -> usize {
+        let new_id = self.first_available_id();
+        let new_index = panels.push(PanelFactory::edit());
+
+        self.panels.push(LayoutPanel::new(split, new_id, new_index));
+
+        new_index
+    }
+
+    pub fn swap_panel_with_next(&mut self, _code: KeyCode, _panels: &mut Panels, _commands: &mut Manager) {
+        self.swap_active_panel(|index, len| if index + 1 < len { Some(index + 1) } else { None });
+    }
+
+    pub fn swap_panel_with_previous(&mut self, _code: KeyCode, _panels: &mut Panels, _commands: &mut Manager) {
+        self.swap_active_panel(|index, _| index.checked_sub(1));
+    }
+
+    pub fn move_panel_to_next_split(&mut self, _code: KeyCode, _panels: &mut Panels, _commands: &mut Manager) {
+        self.move_active_panel_to_split(false);
+    }
+
+    pub fn move_panel_to_previous_split(&mut self, _code: KeyCode, _panels: &mut Panels, _commands: &mut Manager) {
+        self.move_active_panel_to_split(true);
+    }
+
+    // split and position within split of active panel
+    fn active_panel_position(&mut self) -> Result<(usize, usize), Message> {
+        let active_panel_index = self.active_panel;
+        let (active_split, active_id) = match self.get_active_panel() {
+            None => return Err(Message::error(Text::new("app.no_active_panel", "No active panel."))),
+            Some(lp) => (lp.split_index, lp.id),
+        };
+
+        if self.static_panels.contains(&active_id) {
+            return Err(Message::info(Text::new("app.cannot_move_static_panel", "Cannot move static panel.")));
+        }
+
+        match self.splits.get(active_split) {
+            None => Err(Message::error(Text::new("app.split_not_found", "Active panel's split not found."))),
+            Some(split) => match split
+                .panels
+                .iter()
+                .position(|s| *s == UserSplits::Panel(active_panel_index))
+            {
+                None => Err(Message::error(
+                    Text::new("app.split_missing_panel", "Active panel's split doesn't contain active panel."),
+                )),
+                Some(i) => Ok((active_split, i)),
+            },
+        }
+    }
+
+    fn is_static_child(&self, child: &UserSplits) -> bool {
+        match child {
+            UserSplits::Split(_) => false,
+            UserSplits::Panel(index) => self
+                .panels
+                .get(*index)
+                .map(|lp| self.static_panels.contains(&lp.id))
+                .unwrap_or(false),
+        }
+    }
+
+    fn swap_active_panel<F: FnOnce(usize, usize) -> Option<usize>>(&mut self, f: F) {
+        let (split_index, child_index) = match self.active_panel_position() {
+            Ok(position) => position,
+            Err(e) => {
+                self.add_message(e);
+                return;
+            }
+        };
+
+        let len = self.splits[split_index].panels.len();
+        let other = match f(child_index, len) {
+            None => return, // already at the edge
+            Some(other) => other,
+        };
+
+        if self.is_static_child(&self.splits[split_index].panels[other]) {
+            self.add_info(Text::new("app.cannot_swap_static_panel", "Cannot swap with static panel."));
+            return;
+        }
+
+        self.splits[split_index].panels.swap(child_index, other);
+    }
+
+    // moves into nearest sibling split in the given direction
+    // panel is placed on the side closest to where it came from
+    fn move_active_panel_to_split(&mut self, backwards: bool) {
+        let (split_index, child_index) = match self.active_panel_position() {
+            Ok(position) => position,
+            Err(e) => {
+                self.add_message(e);
+                return;
+            }
+        };
+
+        let siblings = &self.splits[split_index].panels;
+        let target = if backwards {
+            siblings[..child_index].iter().rev().find_map(|s| match s {
+                UserSplits::Split(i) => Some(*i),
+                UserSplits::Panel(_) => None,
+            })
+        } else {
+            siblings[child_index + 1..].iter().find_map(|s| match s {
+                UserSplits::Split(i) => Some(*i),
+                UserSplits::Panel(_) => None,
+            })
+        };
+
+        let target = match target {
+            None => {
+                self.add_info(Text::new("app.no_adjacent_split", "No adjacent split to move panel to."));
+                return;
+            }
+            Some(target) => target,
+        };
+
+        if self.splits.get(target).is_none() {
+            self.add_error(Text::new("app.adjacent_split_not_found", "Adjacent split not found."));
+            return;
+        }
+
+        let active_panel_index = self.active_panel;
+        let child = self.splits[split_index].panels.remove(child_index);
+
+        match self.splits.get_mut(target) {
+            None => unreachable!(),
+            Some(split) => match backwards {
+                true => split.panels.push(child),
+                false => split.panels.insert(0, child),
+            },
+        }
+
+        match self.get_panel_mut(active_panel_index) {
+            None => (),
+            Some(lp) => lp.set_split(target),
+        }
+    }
+
+    pub fn delete_active_panel(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
+        let modified = match self.get_active_panel() {
+            Some(lp) => panels
+                .get(lp.panel_index)
+                .map(|panel| panel.any_modified())
+                .unwrap_or(false),
+            None => false,
+        };
+
+        if modified {
+            self.state = State::WaitingDeleteConfirmation(self.active_panel);
+            self.request_top_confirmation(
+                Text::new("prompt.delete_panel_unsaved", "Unsaved changes. Delete panel?")
+            , panels, commands);
+            return;
+        }
+
+        self.remove_active_panel(panels, commands);
+    }
+
+    // static panels stay, splits left empty are removed along the way
+    pub fn close_other_panels(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
+        let others = self.other_panels();
+        if others.is_empty() {
+            self.add_info(Text::new("app.no_other_panels", "No other panels to close."));
+            return;
+        }
+
+        let modified = others.iter().any(|index| {
+            self.get_panel(*index)
+                .and_then(|lp| panels.get(lp.panel_index))
+                .map(|panel| panel.any_modified())
+                .unwrap_or(false)
+        });
+
+        if modified {
+            self.state = State::WaitingCloseOthersConfirmation(self.active_panel);
+            self.request_top_confirmation(
+                Text::new("prompt.close_others_unsaved", "Unsaved changes in other panels. Close them?"),
+                panels,
+                commands,
+            );
+            return;
+        }
+
+        self.remove_other_panels(panels, commands);
+    }
+
+    // shown panels besides active and static ones
+    fn other_panels(&self) -> Vec<usize> {
+        self.splits
+            .iter()
+            .flat_map(|split| split.panels.iter())
+            .filter_map(|item| match item {
+                UserSplits::Panel(index) => Some(*index),
+                UserSplits::Split(_) => None,
+            })
+            .filter(|index| {
+                *index != self.active_panel
+                    && self
+                        .get_panel(*index)
+                        .map(|lp| !self.static_panels.contains(&lp.id))
+                        .unwrap_or(false)
+            })
+            .collect()
+    }
+
+    fn remove_other_panels(&mut self, panels: &mut Panels, commands: &mut Manager) {
+        let keep = self.active_panel;
+        let others = self.other_panels();
+
+        for index in others.iter() {
+            self.active_panel = *index;
+            self.remove_active_panel(panels, commands);
+        }
+
+        self.active_panel = keep;
+        self.replace_commands_for_active_panel(panels, commands);
+        self.add_info(Text::new("app.closed_panels", "Closed {0} panel(s).").arg(others.len()));
+    }
+
+    // buffers that need a path or have changed on disk are left for their own save
+    pub fn save_all(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
+        let mut saved = 0;
+        let mut skipped = vec![];
+        let mut changes = vec![];
+
+        for index in 0..panels.len() {
+            match panels.get_mut(index) {
+                Some(panel) if panel.panel_type() == EDIT_PANEL_TYPE_ID => {
+                    let (count, names, panel_changes) = panel.save_all_buffers();
+                    saved += count;
+                    skipped.extend(names);
+                    changes.extend(panel_changes);
+
+                    // other views of a saved file aren't modified anymore
+                    panels.propagate_edits(index);
+                }
+                _ => (),
+            }
+        }
+
+        self.handle_changes(changes, panels, commands);
+        self.add_info(Text::new("app.saved_buffers", "Saved {0} buffer(s).").arg(saved));
+
+        if !skipped.is_empty() {
+            self.add_warning(Text::new("app.not_saved", "Not saved, save each on its own: {0}").arg(skipped.join(", ")));
+        }
+    }
+
+    fn remove_active_panel(&mut self, panels: &mut Panels, commands: &mut Manager) {
+        let (next_active_panel, active_split, active_panel_id, active_panel_index) =
+            match (self.next_panel_index(panels), self.get_active_panel()) {
+                (Err(e), None) | (Err(e), _) => {
+                    self.reset(panels);
+                    self.add_message(e);
+                    return;
+                }
+                (_, None) => {
+                    self.active_panel = 1;
+                    self.add_message(Message::error(
+                        Text::new("app.no_active_panel_reset", "No active panel. Setting to be last panel."),
+                    ));
+                    return;
+                }
+                (Ok(next), Some(lp)) => (next, lp.split_index, lp.id, lp.panel_index),
+            };
+
+        if self.static_panels().contains(&active_panel_id) {
+            self.add_message(Message::info(Text::new("app.cannot_delete_static_panel", "Cannot delete static panel.")));
+            return;
+        }
+
+        // find active's index in split
+        let local_current_panel = self.active_panel();
+
+        let remove_split = match self.splits.get_mut(active_split) {
+            None => {
+                self.add_message(Message::error(
+                    Text::new("app.split_missing_reset", "Active panels split doesn't exist. Resetting state."),
+                ));
+                self.reset(panels);
+                return;
+            }
+            Some(split) => {
+                let index = match split.panels.iter().enumerate().find(|(_, s)| match s {
+                    UserSplits::Panel(index) => *index == local_current_panel,
+                    UserSplits::Split(..) => false,
+                }) {
+                    Some(i) => i.0,
+                    None => {
+                        self.add_message(Message::error(
+                            Text::new("app.split_missing_panel_reset", "Active panel's split doesn't contain active panel. Resetting state."),
+                        ));
+                        self.reset(panels);
+                        return;
+                    }
+                };
+
+                split.panels.remove(index);
+
+                split.panels.is_empty()
+            }
+        };
+
+        if remove_split {
+            self.splits.remove(active_split);
+
+            // should always get set
+            // if they remain zero, it would remove static prompt panel
+            // error below
+            let mut parent_index = 0;
+            let mut child_index = 0;
+            'outer: for (i, s) in self.splits.iter().enumerate() {
+                for (j, p) in s.panels.iter().enumerate() {
+                    match p {
+                        UserSplits::Panel(_) => (), // skip panels
+                        UserSplits::Split(index) => {
+                            if *index == active_split {
+                                parent_index = i;
+                                child_index = j;
+                                break 'outer;
+                            }
+                        }
+                    }
+                }
+            }
+
+            if parent_index == 0 && child_index == 0 {
+                self.add_message(Message::error(
+                    Text::new("app.split_not_in_parent_reset", "Split not found in parent when removing due to being empty. Resetting state."),
+                ));
+                self.reset(panels);
+                return;
+            }
+
+            match self.get_split_mut(parent_index) {
+                Some(p) => {
+                    p.panels.remove(child_index);
+                }
+                None => {
+                    // should be unreachable
+                    // indexes used were gotten by enumerate
+                    // so they should exist
+
+                    self.add_message(Message::error(
+                        Text::new("app.invalid_split_index_reset", "Invalid split index after enumeration. Resetting state."),
+                    ));
+                    self.reset(panels);
+                    return;
+                }
+            }
+        }
+
+        // verified that it exists from first check getting active panel
+        // self.panels.remove(local_current_panel);
+        panels.remove(active_panel_index);
+
+        let active_count = self
+            .panels
+            .iter()
+            .filter(|lp| {
+                panels
+                    .get(lp.panel_index)
+                    .map(|panel| panel.panel_type() != NULL_PANEL_TYPE_ID)
+                    .unwrap_or(false)
+            })
+            .count();
+
+        // if this is last panel besides static panels
+        // we will replace it
+        if active_count <= self.static_panels.len() {
+            // use last split that we have for new panel's split
+            let last = self.splits_len() - 1;
+            let index = self.add_panel(last, panels, commands);
+            match self.get_split_mut(last) {
+                Some(s) => s.panels.push(UserSplits::Panel(index)),
+                None => {
+                    // should be unreachable
+                    // getting here means splits is empty
+                    // which should only be possible if we had removed the prompt panel
+                    // causing the removal of top split
+                    // this is caught above during the split removal
+
+                    self.add_message(Message::error(Text::new("app.no_splits_reset", "No splits remaining. Resetting state.")));
+                    self.reset(panels);
+                    return;
+                }
+            }
+
+            self.active_panel = index;
+        } else {
+            self.active_panel = next_active_panel;
+        }
+    }
+
+    pub fn activate_next_panel(&mut self, _code: KeyCode, panels: &mut Panels, _commands: &mut Manager) {
+        self.resolve_panel_change(self.next_panel_index(panels));
+    }
+
+    pub fn activate_previous_panel(&mut self, _code: KeyCode, panels: &mut Panels, _commands: &mut Manager) {
+        self.resolve_panel_change(self.previous_panel_index(panels));
+    }
+
+    pub fn focus_panel_left(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
+        self.focus_panel_in_direction(FocusDirection::Left, panels, commands);
+    }
+
+    pub fn focus_panel_right(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
+        self.focus_panel_in_direction(FocusDirection::Right, panels, commands);
+    }
+
+    pub fn focus_panel_up(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
+        self.focus_panel_in_direction(FocusDirection::Up, panels, commands);
+    }
+
+    pub fn focus_panel_down(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
+        self.focus_panel_in_direction(FocusDirection::Down, panels, commands);
+    }
+
+    pub fn focus_panel_in_direction(&mut self, direction: FocusDirection, panels: &mut Panels, commands: &mut Manager) {
+        match self.panel_in_direction(direction) {
+            None => (),
+            Some(index) => {
+                self.active_panel = index;
+                self.replace_commands_for_active_panel(panels, commands);
+            }
+        }
+    }
+
+    // forget areas so panels not drawn this frame can't be navigated to
+    pub fn clear_panel_rects(&self) {
+        for lp in self.panels.iter() {
+            lp.set_rect(Rect::default());
+        }
+    }
+
+    fn panel_in_direction(&self, direction: FocusDirection) -> Option<usize> {
+        let active = self.get_panel(self.active_panel)?.rect();
+        if active.area() == 0 {
+            return None;
+        }
+
+        let center = |r: Rect| (r.x as i32 * 2 + r.width as i32, r.y as i32 * 2 + r.height as i32);
+        let (active_x, active_y) = center(active);
+
+        self.panels
+            .iter()
+            .enumerate()
+            .filter(|(i, lp)| *i != self.active_panel && lp.rect().area() > 0)
+            .filter_map(|(i, lp)| {
+                let r = lp.rect();
+                let (x, y) = center(r);
+
+                // gap along direction of travel and offset across it
+                let (gap, offset) = match direction {
+                    FocusDirection::Left if r.right() <= active.x => (active.x - r.right(), y - active_y),
+                    FocusDirection::Right if r.x >= active.right() => (r.x - active.right(), y - active_y),
+                    FocusDirection::Up if r.bottom() <= active.y => (active.y - r.bottom(), x - active_x),
+                    FocusDirection::Down if r.y >= active.bottom() => (r.y - active.bottom(), x - active_x),
+                    _ => return None,
+                };
+
+                Some(((gap, offset.abs()), i))
+            })
+            .min_by_key(|(score, _)| *score)
+            .map(|(_, i)| i)
+    }
+
+    pub fn change_active_panel_type(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
+        self.state = State::WaitingPanelType(self.active_panel);
+        self.request_top_input(
+            Text::new("prompt.panel_type", "Panel Type"),
+            Some(Box::new(PanelAutoCompleter::fuzzy())),
+            Some(validate_panel_type),
+            panels,
+            commands,
+        );
+    }
+
+    // shown name and command name, a translation shared by more than one command also shows the english name
+    fn palette_command_names(&self, commands: &Manager) -> Vec<(String, String)> {
+        let names = commands.command_names();
+        let shown: Vec<&str> = names.iter().map(|name| self.catalog.command_name(name)).collect();
+
+        names
+            .iter()
+            .zip(shown.iter())
+            .map(|(name, translated)| match shown.iter().filter(|other| *other == translated).count() {
+                1 => (translated.to_string(), name.clone()),
+                _ => (format!("{} ({})", translated, name), name.clone()),
+            })
+            .collect()
+    }
+
+    pub fn run_shell_command(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
+        self.state = State::WaitingShellCommand(self.active_panel);
+        self.request_top_input(Text::new("prompt.shell_command", "Shell Command"), None, None, panels, commands);
+    }
+
+    pub fn export_keymap(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
+        self.state = State::WaitingKeymapExport(self.active_panel);
+        self.request_top_input(Text::new("prompt.export_keymap", "Export Keymap To"), None, None, panels, commands);
+    }
+
+    pub fn import_keymap(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
+        self.state = State::WaitingKeymapImport(self.active_panel);
+        self.request_top_input(Text::new("prompt.import_keymap", "Import Keymap From"), None, None, panels, commands);
+    }
+
+    // reuses first help panel, otherwise one is split from active panel
+    pub fn show_help(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
+        let existing = self
+            .panels
+            .iter()
+            .map(|lp| lp.panel_index)
+            .find(|index| panels.get(*index).map(|panel| panel.panel_type()) == Some(HELP_PANEL_TYPE_ID));
+
+        let index = match existing.or_else(|| self.split_active_panel(panels, commands)) {
+            None => {
+                self.add_error(Text::new("app.no_room_help_panel", "No room for help panel."));
+                return;
+            }
+            Some(index) => index,
+        };
+
+        let panel = match panels.get_mut(index) {
+            None => return,
+            Some(panel) => panel,
+        };
+
+        if panel.panel_type() != HELP_PANEL_TYPE_ID {
+            *panel = PanelFactory::help();
+        }
+
+        if let Some(layout_index) = self.panels.iter().position(|lp| lp.panel_index == index) {
+            self.active_panel = layout_index;
+            commands.replace_top_with_panel(HELP_PANEL_TYPE_ID);
+        }
+    }
+
+    // takes over active panel when it's an empty edit panel, so nothing is lost when it turns back into one
+    // otherwise one is split from active panel
+    pub fn show_welcome(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
+        let active = self.get_panel(self.active_panel).map(|lp| lp.panel_index);
+        let blank = active
+            .and_then(|index| panels.get(index))
+            .map(|panel| {
+                panel.panel_type() == EDIT_PANEL_TYPE_ID
+                    && panel.buffer_count() == 1
+                    && panel.file_path().is_none()
+                    && !panel.modified()
+                    && panel.text().is_empty()
+            })
+            .unwrap_or(false);
+
+        let index = match blank {
+            true => active,
+            false => self.split_active_panel(panels, commands),
+        };
+
+        let index = match index {
+            None => {
+                self.add_error(Text::new("app.no_room_welcome_panel", "No room for welcome panel."));
+                return;
+            }
+            Some(index) => index,
+        };
+
+        if let Some(panel) = panels.get_mut(index) {
+            *panel = PanelFactory::welcome();
+        }
+
+        if let Some(layout_index) = self.panels.iter().position(|lp| lp.panel_index == index) {
+            self.active_panel = layout_index;
+            commands.replace_top_with_panel(WELCOME_PANEL_TYPE_ID);
+        }
+    }
+
+    // any key press goes back to editing, key is then handled as usual
+    pub fn dismiss_welcome(&mut self, panels: &mut Panels) {
+        for lp in self.panels.iter() {
+            match panels.get_mut(lp.panel_index) {
+                Some(panel) if panel.panel_type() == WELCOME_PANEL_TYPE_ID => *panel = PanelFactory::edit(),
+                _ => (),
+            }
+        }
+    }
+
+    // reuses first output panel, otherwise one is split from active panel
+    pub(crate) fn show_command_output(&mut self, command: String, panels: &mut Panels, commands: &mut Manager) {
+        if command.trim().is_empty() {
+            return;
+        }
+
+        let output = match CommandOutput::start(command) {
+            Err(err) => {
+                self.add_error(err);
+                return;
+            }
+            Ok(output) => output,
+        };
+
+        let existing = self
+            .panels
+            .iter()
+            .map(|lp| lp.panel_index)
+            .find(|index| panels.get(*index).map(|panel| panel.panel_type()) == Some(OUTPUT_PANEL_TYPE_ID));
+
+        let index = match existing.or_else(|| self.split_active_panel(panels, commands)) {
+            None => {
+                self.add_error(Text::new("app.no_room_output_panel", "No room for output panel."));
+                return;
+            }
+            Some(index) => index,
+        };
+
+        match panels.get_mut(index) {
+            None => (),
+            Some(panel) => {
+                if panel.panel_type() != OUTPUT_PANEL_TYPE_ID {
+                    *panel = PanelFactory::output();
+                }
+
+                panel.set_command_output(Some(output));
+                panel.set_scroll_y(0);
+            }
+        }
+    }
+
+    pub fn open_command_palette(&mut self, _code: KeyCode, panels: &mut Panels, commands: &mut Manager) {
+        self.state = State::WaitingCommand(self.active_panel);
+        let names = self.palette_command_names(commands).into_iter().map(|(shown, _)| shown).collect();
+        self.request_top_input(
+            Text::new("prompt.command", "Command"),
+            Some(Box::new(CommandAutoCompleter::new(names))),
+            None,
+            panels,
+            commands,
+        );
+    }
+
+    // requestor is reset out of its waiting state
+    // gives panel that should become active
+    fn cancel_input_request(&mut self, panels: &mut Panels) -> Option<usize> {
+        let request = self.input_request.take()?;
+
+        let restore = if request.requestor_id == TOP_REQUESTOR_ID {
+            let for_panel = self.state.for_panel().unwrap_or(1);
+            self.state = State::Normal;
+            for_panel
+        } else {
+            match self.get_panel(request.requestor_id).and_then(|lp| panels.get_mut(lp.panel_index)) {
+                None => (),
+                Some(panel) => panel.set_state(PanelState::Normal),
+            }
+            request.requestor_id
+        };
+
+        match self.get_panel(0).and_then(|lp| panels.get_mut(lp.panel_index)) {
+            None => (),
+            Some(panel) => {
+                panel.set_text("");
+                panel.set_cursor_index(0);
+                panel.set_selection(0);
+                panel.hide();
+            }
+        }
+
+        Some(restore)
+    }
+
+    // false if request can't be made
+    fn open_input_request(
+        &mut self,
+        prompt: Text,
+        completer: Option<Box<dyn AutoCompleter>>,
+        validator: Option<InputValidator>,
+        active_panel_id: char,
+        panels: &mut Panels,
+        commands: &mut Manager,
+    ) -> bool {
+        // only one input request at a time, override existing
+        if self.static_panels.contains(&active_panel_id) {
+            self.add_message(Message::error(
+                Text::new("app.input_panel_cannot_request", "Input panel cannot make input request."),
+            ));
+            return false;
+        }
+
+        self.input_request = Some(InputRequest {
+            prompt,
+            auto_completer: completer,
+            requestor_id: self.active_panel,
+            sequence: None,
+            validator,
+        });
+        self.show_input_panel(panels, commands);
+
+        true
+    }
+
+    fn show_input_panel(&mut self, panels: &mut Panels, commands: &mut Manager) {
+        self.input_history.reset_position();
+        self.active_panel = 0;
+        commands.replace_top_with_panel(INPUT_PANEL_TYPE_ID);
+
+        match self.get_panel(0) {
+            Some(lp) => match panels.get_mut(lp.panel_index) {
+                Some(panel) => panel.show(),
+                None => unimplemented!(),
+            },
+            None => unimplemented!(),
+        }
+    }
+
+    fn request_top_confirmation(&mut self, prompt: Text, panels: &mut Panels, commands: &mut Manager) {
+        self.request_top_input(
+            confirmation_prompt(&self.text(&prompt)),
+            Some(Box::new(ConfirmAutoCompleter::new())),
+            Some(validate_confirmation),
+            panels,
+            commands,
+        );
+    }
+
+    // input request made by app state itself
+    // result is handled according to current state
+    fn request_top_input(
+        &mut self,
+        prompt: Text,
+        completer: Option<Box<dyn AutoCompleter>>,
+        validator: Option<InputValidator>,
+        panels: &mut Panels,
+        commands: &mut Manager,
+    ) {
+        self.active_panel = 0;
+        self.input_request = Some(InputRequest {
+            prompt,
+            requestor_id: TOP_REQUESTOR_ID,
+            auto_completer: completer,
+            sequence: None,
+            validator,
+        });
+        self.input_history.reset_position();
+        match self.get_panel(0) {
+            Some(lp) => match panels.get_mut(lp.panel_index) {
+                Some(panel) => {
+                    panel.show();
+                    commands.replace_top_with_panel(panel.panel_type());
+                },
+                None => unimplemented!(),
+            },
+            None => unimplemented!(),
+        }
+    }
+
+    fn replace_commands_for_active_panel(&mut self, panels: &Panels, commands: &mut Manager) {
+        match self.get_active_panel().and_then(|lp| panels.get(lp.panel_index)) {
+            None => (),
+            Some(panel) => commands.replace_top_with_panel(panel.panel_type()),
+        }
+    }
+
+    fn resolve_panel_change(&mut self, r: Result<usize, Message>) {
+        match r {
+            Ok(next) => self.active_panel = next,
+            Err(e) => {
+                self.active_panel = 1;
+                self.add_message(e);
+            }
+        }
+    }
+
+    fn next_panel_index(&self, panels: &Panels) -> Result<usize, Message> {
+        self.active_panel_index(panels, |index, order| {
+            if index + 1 >= order.len() {
+                0
+            } else {
+                index + 1
+            }
+        })
+    }
+
+    fn previous_panel_index(&self, panels: &Panels) -> Result<usize, Message> {
+        self.active_panel_index(panels, |index, order| {
+            if index == 0 {
+                order.len() - 1
+            } else {
+                index - 1
+            }
+        })
+    }
+
+    fn active_panel_index<F: FnOnce(usize, &Vec<usize>) -> usize>(
+        &self,
+        panels: &Panels,
+        f: F,
+    ) -> Result<usize, Message> {
+        let order = self.build_order(panels)?;
+        let mut active_panel_index = None;
+        for (i, panel_index) in order.iter().enumerate() {
+            if *panel_index == self.active_panel {
+                active_panel_index = Some(i);
+            }
+        }
+
+        match active_panel_index {
+            None => Err(Message::error(Text::new("app.active_panel_not_ordered", "Active panel not found after ordering."))),
+            Some(index) => Ok(order[f(index, &order)]),
+        }
+    }
+
+    fn build_order(&self, panels: &Panels) -> Result<Vec<usize>, Message> {
+        let mut order = vec![];
+        self.push_panels(0, &mut order, panels)?;
+        Ok(order)
+    }
+
+    fn push_panels(
+        &self,
+        split: usize,
+        order: &mut Vec<usize>,
+        panels: &Panels,
+    ) -> Result<(), Message> {
+        match self.splits.get(split) {
+            None => return Err(Message::error(Text::new("app.child_split_not_found", "Child split not found in splits."))),
+            Some(split) => {
+                for child in split.panels.iter() {
+                    match child {
+                        UserSplits::Panel(panel_index) => match self.panels.get(*panel_index) {
+                            Some(lp) => match panels.get(lp.panel_index) {
+                                Some(panel) => match panel.panel_type() {
+                                    NULL_PANEL_TYPE_ID => (),
+                                    MESSAGE_PANEL_TYPE_ID if !self.messages_panel_visible() => (),
+                                    _ => order.push(*panel_index),
+                                },
+                                None => unimplemented!(),
+                            },
+                            None => return Err(Message::error(
+                                Text::new("app.child_panel_not_found", "Child panel not found in panels."),
+                            )),
+                        },
+                        UserSplits::Split(split_index) => {
+                            self.push_panels(*split_index, order, panels)?
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) fn is_confirmation(input: &str) -> bool {
+    matches!(input.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+fn confirmation_prompt(prompt: &str) -> Text {
+    Text::new("prompt.confirm", "{0} (y/n)").arg(prompt)
+}
+
+fn validate_confirmation(input: &str) -> Result<(), String> {
+    match input.trim().to_lowercase().as_str() {
+        "y" | "yes" | "n" | "no" => Ok(()),
+        _ => Err(format!("Expected yes or no, got {:?}.", input)),
+    }
+}
+
+fn validate_panel_type(input: &str) -> Result<(), String> {
+    match PanelFactory::panel(input) {
+        Some(_) => Ok(()),
+        None => Err(format!("No panel of type: {:?}", input)),
+    }
+}
+
+type GlobalAction = fn(&mut AppState, KeyCode, &mut Panels, &mut Manager);
+
+pub fn global_commands() -> Result<Commands<GlobalAction>, String> {
+    let mut commands = Commands::<GlobalAction>::new();
+
+    //
+    // Panel creation/deletion
+    //
+
+    commands.insert(|b| {
+        b.node(ctrl_key('p')).node(key('h')).action(
+            CommandDetails::split_horizontal(),
+            AppState::split_current_panel_horizontal,
+        )
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('p')).node(key('v')).action(
+            CommandDetails::split_vertical(),
+            AppState::split_current_panel_vertical,
+        )
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('p')).node(key('s')).action(
+            CommandDetails::split_view(),
+            AppState::split_current_panel_view,
+        )
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('p')).node(key('n')).action(
+            CommandDetails::add_panel(),
+            AppState::add_panel_to_active_split,
+        )
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('p')).node(key('d')).action(
+            CommandDetails::remove_panel(),
+            AppState::delete_active_panel,
+        )
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('p')).node(key('o')).action(
+            CommandDetails::close_other_panels(),
+            AppState::close_other_panels,
+        )
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('p')).node(key('t')).action(
+            CommandDetails::change_panel_type(),
+            AppState::change_active_panel_type,
+        )
+    })?;
+
+    //
+    // Panel Navigation
+    //
+    commands.insert(|b| {
+        b.node(ctrl_alt_key('l')).action(
+            CommandDetails::activate_next_panel(),
+            AppState::activate_next_panel,
+        )
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_alt_key('j')).action(
+            CommandDetails::activate_previous_panel(),
+            AppState::activate_previous_panel,
+        )
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('p')).node(code(KeyCode::Left)).action(
+            CommandDetails::focus_panel_left(),
+            AppState::focus_panel_left,
+        )
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('p')).node(code(KeyCode::Right)).action(
+            CommandDetails::focus_panel_right(),
+            AppState::focus_panel_right,
+        )
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('p')).node(code(KeyCode::Up)).action(
+            CommandDetails::focus_panel_up(),
+            AppState::focus_panel_up,
+        )
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('p')).node(code(KeyCode::Down)).action(
+            CommandDetails::focus_panel_down(),
+            AppState::focus_panel_down,
+        )
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('p')).node(key('.')).action(
+            CommandDetails::swap_panel_next(),
+            AppState::swap_panel_with_next,
+        )
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('p')).node(key(',')).action(
+            CommandDetails::swap_panel_previous(),
+            AppState::swap_panel_with_previous,
+        )
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('p')).node(key('>')).action(
+            CommandDetails::move_panel_next_split(),
+            AppState::move_panel_to_next_split,
+        )
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('p')).node(key('<')).action(
+            CommandDetails::move_panel_previous_split(),
+            AppState::move_panel_to_previous_split,
+        )
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('p')).node(key('m')).action(
+            CommandDetails::jump_to_messages(),
+            AppState::jump_to_messages,
+        )
+    })?;
+
+    commands.insert(|b| b.node(ctrl_alt_key('s')).action(CommandDetails::save_all(), AppState::save_all))?;
+
+    commands.insert(|b| b.node(ctrl_key('q')).action(CommandDetails::quit(), AppState::quit))?;
+
+    //
+    // Command Palette
+    //
+
+    commands.insert(|b| {
+        b.node(ctrl_alt_key('p')).action(
+            CommandDetails::command_palette(),
+            AppState::open_command_palette,
+        )
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_alt_key('h')).action(
+            CommandDetails::show_help(),
+            AppState::show_help,
+        )
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_alt_key('w')).action(
+            CommandDetails::show_welcome(),
+            AppState::show_welcome,
+        )
+    })?;
+
+    //
+    // External Commands
+    //
+
+    commands.insert(|b| {
+        b.node(ctrl_alt_key('r')).action(
+            CommandDetails::run_shell_command(),
+            AppState::run_shell_command,
+        )
+    })?;
+
+    //
+    // Keymaps
+    //
+
+    commands.insert(|b| {
+        b.node(ctrl_alt_key('b')).node(key('e')).action(
+            CommandDetails::export_keymap(),
+            AppState::export_keymap,
+        )
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_alt_key('b')).node(key('i')).action(
+            CommandDetails::import_keymap(),
+            AppState::import_keymap,
+        )
+    })?;
+
+    //
+    // Panel Selection
+    //
+
+    commands.insert(|b| {
+        b.node(ctrl_key('a').action(AppState::start_selecting_panel))
+            .node(catch_all())
+            .action(CommandDetails::select_panel(), AppState::select_panel)
+    })?;
+
+    Ok(commands)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter;
+    use std::time::Duration;
+
+    use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+    use tui::layout::{Direction, Rect};
+
+    use crate::app::{
+        InputRequest, LayoutPanel, Message, MessageChannel, MessagesPlacement, State, StateChange,
+        StateChangeRequest, TOP_REQUESTOR_ID,
+    };
+    use crate::autosave::FocusSave;
+    use crate::idle::IdleWork;
+    use crate::commands::Manager;
+    use crate::config::{parse_layout, LOCAL_CONFIG_NAME};
+    use crate::locale::Text;
+    use crate::panels::{EditMode, PanelState, HELP_PANEL_TYPE_ID, MESSAGE_PANEL_TYPE_ID, OUTPUT_PANEL_TYPE_ID, WELCOME_PANEL_TYPE_ID};
+    use crate::positions::CursorPositions;
+    use crate::panels::{
+        PanelFactory, COMMANDS_PANEL_TYPE_ID, EDIT_PANEL_TYPE_ID, INPUT_PANEL_TYPE_ID, NULL_PANEL_TYPE_ID,
+    };
+    use crate::splits::SplitSize;
+    use crate::{AppState, CommandKeyId, Panels, TextPanel, UserSplits};
+
+    fn assert_is_default(app: &AppState) {
+        assert_eq!(app.panels.len(), 3, "Panels not set");
+        assert_eq!(app.splits.len(), 1, "Splits not set");
+        assert_eq!(app.active_panel, 1, "Active panel not set");
+        assert_eq!(app.selecting_panel, false, "Selecting panel not set");
+        assert_eq!(app.static_panels, vec!['$'], "Static panels not set");
+        assert_eq!(app.state, State::Normal);
+        assert!(app.input_request.is_none());
+    }
+
+    #[test]
+    fn set_default() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        app.split_current_panel_horizontal(KeyCode::Null, &mut panels, &mut commands);
+        app.split_current_panel_horizontal(KeyCode::Null, &mut panels, &mut commands);
+        app.split_current_panel_horizontal(KeyCode::Null, &mut panels, &mut commands);
+        app.input_request = Some(InputRequest {
+            prompt: "Prompt".into(),
+            requestor_id: TOP_REQUESTOR_ID,
+            auto_completer: None,
+            sequence: None,
+            validator: None,
+        });
+        app.state = State::WaitingPanelType(1);
+        app.set_selecting_panel(true);
+
+        app.reset(&mut panels);
+
+        assert_is_default(&app);
+    }
+
+    #[test]
+    fn init_with_layout() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+
+        let layout = parse_layout("h(Edit, v(Messages, Commands))").unwrap();
+        app.init_with_layout(&layout, &mut panels, &mut commands).unwrap();
+
+        assert_eq!(app.splits.len(), 3);
+        assert_eq!(app.splits[0].direction, Direction::Vertical);
+        assert_eq!(app.splits[0].panels, vec![UserSplits::Panel(0), UserSplits::Split(1)]);
+        assert_eq!(app.splits[1].direction, Direction::Horizontal);
+        assert_eq!(app.splits[1].panels, vec![UserSplits::Panel(1), UserSplits::Split(2)]);
+        assert_eq!(app.splits[2].panels, vec![UserSplits::Panel(2), UserSplits::Panel(3)]);
+        assert_eq!(app.panels[3].split_index, 2);
+        assert_eq!(panels.get(0).unwrap().panel_type(), INPUT_PANEL_TYPE_ID);
+        assert_eq!(panels.get(3).unwrap().panel_type(), COMMANDS_PANEL_TYPE_ID);
+        assert_eq!(app.active_panel, 1);
+        assert_eq!(commands.current_panel().unwrap().0, EDIT_PANEL_TYPE_ID);
+    }
+
+    #[test]
+    fn init_with_layout_sizes() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+
+        let layout = parse_layout("h(Edit@25%, v(Edit, Commands)@30, Edit)").unwrap();
+        app.init_with_layout(&layout, &mut panels, &mut commands).unwrap();
+
+        assert_eq!(app.get_panel(1).unwrap().size(), Some(SplitSize::Percent(25)));
+        assert_eq!(app.splits[2].size, Some(SplitSize::Cells(30)));
+        assert_eq!(app.get_panel(2).unwrap().size(), None);
+        assert_eq!(app.get_panel(4).unwrap().size(), None);
+    }
+
+    #[test]
+    fn init_with_layout_keeps_prompt_from_opening_file() {
+        let path = std::env::temp_dir().join(format!("edish_layout_swap_{}.txt", std::process::id()));
+        let swap = crate::swap::swap_path(&path).unwrap();
+        std::fs::write(&path, "saved").unwrap();
+        std::fs::write(&swap, "unsaved").unwrap();
+
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+
+        let layout = parse_layout(&format!("v(Edit, Edit:{})", path.to_string_lossy())).unwrap();
+        app.init_with_layout(&layout, &mut panels, &mut commands).unwrap();
+
+        assert_eq!(panels.get(2).unwrap().state(), PanelState::WaitingToRecover);
+        assert!(app.input_request().is_some());
+        assert_eq!(app.active_panel, 0);
+
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&swap).unwrap();
+    }
+
+    #[test]
+    fn init_with_layout_unknown_panel() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+
+        let layout = parse_layout("v(Edit, Unknown)").unwrap();
+
+        assert!(app.init_with_layout(&layout, &mut panels, &mut commands).is_err());
+    }
+
+    struct SetClipboard(Vec<String>);
+
+    impl StateChange for SetClipboard {
+        fn apply(self: Box<Self>, state: &mut AppState, _: &mut Panels, _: &mut Manager) -> Vec<StateChangeRequest> {
+            state.set_clipboard(self.0);
+            vec![StateChangeRequest::info("Clipboard set")]
+        }
+    }
+
+    #[test]
+    fn custom_change_applied() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        app.handle_changes(
+            vec![StateChangeRequest::custom(SetClipboard(vec!["text".to_string()]))],
+            &mut panels,
+            &mut commands,
+        );
+
+        assert_eq!(app.clipboard(), &vec!["text".to_string()]);
+        assert_eq!(app.get_messages().last().unwrap(), &Message::info("Clipboard set"));
+    }
+
+    struct Echo;
+
+    impl StateChange for Echo {
+        fn apply(self: Box<Self>, _: &mut AppState, _: &mut Panels, _: &mut Manager) -> Vec<StateChangeRequest> {
+            vec![StateChangeRequest::custom(Echo)]
+        }
+    }
+
+    #[test]
+    fn endless_changes_stopped() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        app.handle_changes(vec![StateChangeRequest::custom(Echo)], &mut panels, &mut commands);
+
+        let last = app.get_messages().last().unwrap();
+        assert_eq!(last.channel(), MessageChannel::ERROR);
+        assert!(last.text().to_string().starts_with("Stopped after 1000 state changes"));
+    }
+
+    #[test]
+    fn resulting_changes_handled_first() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        app.handle_changes(
+            vec![
+                StateChangeRequest::custom(SetClipboard(vec![])),
+                StateChangeRequest::info("After"),
+            ],
+            &mut panels,
+            &mut commands,
+        );
+
+        assert_eq!(
+            app.get_messages(),
+            &vec![Message::info("Clipboard set"), Message::info("After")]
+        );
+    }
+
+    #[test]
+    fn input_sequence_gives_all_answers() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        panels.get_mut(1).unwrap().set_text("one two");
+
+        let (_, changes) = panels
+            .get_mut(1)
+            .unwrap()
+            .start_replace_all(KeyCode::Null, &mut app, &mut commands);
+        app.handle_changes(changes, &mut panels, &mut commands);
+
+        assert_eq!(app.active_panel(), 0);
+        assert_eq!(app.input_request().unwrap().prompt(), "Find");
+        assert_eq!(app.input_request().unwrap().step(), Some((1, 2)));
+
+        app.handle_changes(vec![StateChangeRequest::input_complete("one".to_string())], &mut panels, &mut commands);
+
+        assert_eq!(app.active_panel(), 0);
+        assert_eq!(app.input_request().unwrap().prompt(), "Replace With");
+        assert_eq!(app.input_request().unwrap().step(), Some((2, 2)));
+        assert_eq!(panels.get(1).unwrap().text(), "one two");
+
+        app.handle_changes(vec![StateChangeRequest::input_complete("three".to_string())], &mut panels, &mut commands);
+
+        assert!(app.input_request().is_none());
+        assert_eq!(app.active_panel(), 1);
+        assert_eq!(panels.get(1).unwrap().text(), "three two");
+    }
+
+    #[test]
+    fn empty_input_sequence() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        app.handle_changes(vec![StateChangeRequest::InputSequence(vec![])], &mut panels, &mut commands);
+
+        assert!(app.input_request().is_none());
+        assert_eq!(app.get_messages().last().unwrap(), &Message::error(Text::new("app.input_sequence_empty", "Input sequence has no prompts.")));
+    }
+
+    #[test]
+    fn update_counts_ticks() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        app.update(&mut panels, &mut commands);
+        app.update(&mut panels, &mut commands);
+
+        assert_eq!(app.ticks(), 2);
+    }
+
+    #[test]
+    fn idle_update_scans_buffers_for_todos() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        app.set_idle_work(IdleWork::new(Duration::from_secs(60)));
+        let index = app.active_panel();
+        panels.get_mut(index).unwrap().set_text("a\n// TODO b\nc");
+        app.note_input();
+        app.update(&mut panels, &mut commands);
+
+        // still typing
+        assert!(panels.get(index).unwrap().todo_lines().is_empty());
+
+        app.set_idle_work(IdleWork::new(Duration::ZERO));
+        app.update(&mut panels, &mut commands);
+
+        assert_eq!(panels.get(index).unwrap().todo_lines(), &vec![1]);
+
+        let panel = panels.get_mut(index).unwrap();
+        let (_, changes) = panel.next_todo(KeyCode::Null, &mut app, &mut commands);
+        assert!(changes.is_empty());
+        assert_eq!(panel.current_line(), 1);
+    }
+
+    #[test]
+    fn losing_focus_dims_and_saves() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        let path = std::env::temp_dir().join(format!("edish_focus_lost_{}", std::process::id()));
+        let panel = panels.get_mut(1).unwrap();
+        panel.set_file_path(path.clone());
+        panel.set_text("text");
+        panel.set_modified(true);
+
+        app.set_dim_unfocused(true);
+        app.set_focus_save(Some(FocusSave::default()));
+        assert!(!app.dimmed());
+
+        app.set_focused(false, &mut panels);
+
+        assert!(app.dimmed());
+        assert!(!panels.get(1).unwrap().modified());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "text");
+
+        app.set_focused(true, &mut panels);
+        assert!(!app.dimmed());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn shell_command_output_panel_reused() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        let panel_count = app.panels.len();
+
+        app.show_command_output("echo one".to_string(), &mut panels, &mut commands);
+
+        assert_eq!(app.panels.len(), panel_count + 1);
+        let index = app.panels.last().unwrap().panel_index;
+        assert_eq!(panels.get(index).unwrap().panel_type(), OUTPUT_PANEL_TYPE_ID);
+        assert_eq!(panels.get(index).unwrap().command_output().unwrap().command(), "echo one");
+
+        app.show_command_output("echo two".to_string(), &mut panels, &mut commands);
+
+        assert_eq!(app.panels.len(), panel_count + 1);
+        assert_eq!(panels.get(index).unwrap().command_output().unwrap().command(), "echo two");
+
+        // nothing to run
+        app.show_command_output("  ".to_string(), &mut panels, &mut commands);
+        assert_eq!(panels.get(index).unwrap().command_output().unwrap().command(), "echo two");
+    }
+
+    fn report_update(_: &mut TextPanel) -> Vec<StateChangeRequest> {
+        vec![StateChangeRequest::info("Updated")]
+    }
+
+    #[test]
+    fn resize_keeps_cursor_in_view() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        let panel = panels.get_mut(1).unwrap();
+        panel.set_text((0..100).map(|i| i.to_string()).collect::<Vec<String>>().join("\n"));
+        panel.set_viewport(Rect::new(0, 0, 80, 40));
+        panel.set_current_line(35);
+
+        app.resize(80, 20, &mut panels);
+
+        let panel = panels.get(1).unwrap();
+        assert_eq!(app.screen(), Rect::new(0, 0, 80, 20));
+        assert_eq!(panel.viewport(), Rect::new(0, 0, 80, 20));
+        assert!(panel.scroll_y() <= 35 && 35 < panel.scroll_y() + 20);
+    }
+
+    #[test]
+    fn resize_clamps_horizontal_scroll() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        let panel = panels.get_mut(1).unwrap();
+        panel.set_text("x".repeat(200));
+        panel.set_wrap(false);
+        panel.set_viewport(Rect::new(0, 0, 120, 10));
+        panel.set_cursor_index(110);
+
+        app.resize(50, 10, &mut panels);
+
+        let panel = panels.get(1).unwrap();
+        let width = 50 - panel.gutter_width() as usize;
+        assert_eq!(panel.scroll_x() as usize, 110 - width);
+    }
+
+    #[test]
+    fn resize_clamps_horizontal_scroll_in_characters() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        // two bytes per character, cursor is after 110 characters
+        let panel = panels.get_mut(1).unwrap();
+        panel.set_text("é".repeat(200));
+        panel.set_wrap(false);
+        panel.set_viewport(Rect::new(0, 0, 120, 10));
+        panel.set_cursor_index(220);
+
+        app.resize(50, 10, &mut panels);
+
+        let panel = panels.get(1).unwrap();
+        let width = 50 - panel.gutter_width() as usize;
+        assert_eq!(panel.scroll_x() as usize, 110 - width);
+    }
+
+    #[test]
+    fn update_only_visible_panels() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        panels.get_mut(1).unwrap().update_handler = report_update;
+        panels.get_mut(2).unwrap().update_handler = report_update;
+        panels.get_mut(2).unwrap().hide();
+
+        app.update(&mut panels, &mut commands);
+
+        assert_eq!(app.get_messages(), &vec![Message::info("Updated")]);
+    }
+
+    #[test]
+    fn invalid_input_keeps_prompt_open() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        let (_, changes) = panels
+            .get_mut(1)
+            .unwrap()
+            .set_ruler_column(KeyCode::Null, &mut app, &mut commands);
+        app.handle_changes(changes, &mut panels, &mut commands);
+
+        app.handle_changes(vec![StateChangeRequest::input_complete("abc".to_string())], &mut panels, &mut commands);
+
+        assert_eq!(app.active_panel(), 0);
+        assert_eq!(app.input_request().unwrap().prompt(), "Ruler Column (0 to hide)");
+        assert_eq!(panels.get(0).unwrap().text(), "abc");
+        assert_eq!(app.get_messages().last().unwrap().channel(), MessageChannel::ERROR);
+
+        app.handle_changes(vec![StateChangeRequest::input_complete("80".to_string())], &mut panels, &mut commands);
+
+        assert!(app.input_request().is_none());
+        assert_eq!(app.active_panel(), 1);
+        assert_eq!(panels.get(1).unwrap().ruler(), Some(80));
+    }
+
+    #[test]
+    fn invalid_panel_type_rejected() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        app.change_active_panel_type(KeyCode::Null, &mut panels, &mut commands);
+        app.handle_changes(vec![StateChangeRequest::input_complete("Bogus".to_string())], &mut panels, &mut commands);
+
+        assert!(app.input_request().is_some());
+        assert_eq!(app.get_messages().last().unwrap(), &Message::error("No panel of type: \"Bogus\""));
+    }
+
+    #[test]
+    fn cancel_panel_input_request() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        let (_, changes) = panels
+            .get_mut(1)
+            .unwrap()
+            .set_ruler_column(KeyCode::Null, &mut app, &mut commands);
+        app.handle_changes(changes, &mut panels, &mut commands);
+        panels.get_mut(0).unwrap().set_text("12");
+
+        app.handle_changes(vec![StateChangeRequest::InputCanceled], &mut panels, &mut commands);
+
+        assert!(app.input_request().is_none());
+        assert_eq!(app.active_panel(), 1);
+        assert_eq!(panels.get(1).unwrap().state(), PanelState::Normal);
+        assert_eq!(panels.get(0).unwrap().text(), "");
+        assert!(!panels.get(0).unwrap().visible());
+    }
+
+    #[test]
+    fn cancel_top_input_request() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        app.set_active_panel(2);
+
+        app.change_active_panel_type(KeyCode::Null, &mut panels, &mut commands);
+        app.handle_changes(vec![StateChangeRequest::InputCanceled], &mut panels, &mut commands);
+
+        assert!(app.input_request().is_none());
+        assert_eq!(app.state, State::Normal);
+        assert_eq!(app.active_panel(), 2);
+    }
+
+    #[test]
+    fn cancel_without_input_request() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        app.handle_changes(vec![StateChangeRequest::InputCanceled], &mut panels, &mut commands);
+
+        assert_eq!(app.active_panel(), 1);
+        assert_eq!(app.get_messages().last().unwrap(), &Message::error(Text::new("app.no_active_input_request", "No active input request.")));
+    }
+
+    #[test]
+    fn opening_file_restores_cursor() {
+        let dir = std::env::temp_dir().join(format!("edish-restore-cursor-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("file.txt");
+        std::fs::write(&file, "one\ntwo\nthree\n").unwrap();
+
+        let mut positions = CursorPositions::load(dir.join("positions")).unwrap();
+        positions.set(&file, 2, 3);
+
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        app.set_cursor_positions(Some(positions));
+
+        panels.get_mut(1).unwrap().set_state(PanelState::WaitingToOpen);
+        app.handle_changes(
+            vec![
+                StateChangeRequest::Input("Open".into(), None),
+                StateChangeRequest::input_complete(file.to_string_lossy().to_string()),
+            ],
+            &mut panels,
+            &mut commands,
+        );
+
+        assert_eq!(panels.get(1).unwrap().current_line(), 2);
+        assert_eq!(panels.get(1).unwrap().cursor_index_in_line(), 3);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn opening_file_uses_project_config_of_file() {
+        let dir = std::env::temp_dir().join(format!("edish-file-project-{}", std::process::id()));
+        let nested = dir.join("src");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(dir.join(LOCAL_CONFIG_NAME), "tab_width = 2").unwrap();
+        let file = nested.join("file.txt");
+        std::fs::write(&file, "one\n").unwrap();
+
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        panels.get_mut(1).unwrap().set_state(PanelState::WaitingToOpen);
+        app.handle_changes(
+            vec![
+                StateChangeRequest::Input("Open".into(), None),
+                StateChangeRequest::input_complete(file.to_string_lossy().to_string()),
+            ],
+            &mut panels,
+            &mut commands,
+        );
+
+        assert_eq!(panels.get(1).unwrap().tab_width(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn confirmation_request_constrained() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        app.handle_changes(vec![StateChangeRequest::confirm("Overwrite?")], &mut panels, &mut commands);
+
+        assert_eq!(app.input_request().unwrap().prompt(), "Overwrite? (y/n)");
+        assert!(app.input_request().unwrap().completer().is_some());
+
+        app.handle_changes(vec![StateChangeRequest::input_complete("sure".to_string())], &mut panels, &mut commands);
+        assert!(app.input_request().is_some());
+
+        app.handle_changes(vec![StateChangeRequest::input_complete("No".to_string())], &mut panels, &mut commands);
+        assert!(app.input_request().is_none());
+    }
+
+    #[test]
+    fn messages_hidden_until_error() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        app.set_messages_placement(MessagesPlacement::HiddenUntilError);
+
+        app.add_info("Info");
+        assert!(!app.messages_panel_visible());
+        assert_eq!(app.build_order(&panels).unwrap(), vec![0, 1]);
+
+        app.add_error("Error");
+        assert!(app.messages_panel_visible());
+        assert_eq!(app.build_order(&panels).unwrap(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn jump_to_messages_and_back() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        app.add_error("Error");
+        assert_eq!(app.unseen_error_count(&panels), 1);
+
+        app.jump_to_messages(KeyCode::Null, &mut panels, &mut commands);
+        assert_eq!(app.active_panel(), 2);
+        assert_eq!(app.unseen_error_count(&panels), 0);
+
+        app.jump_to_messages(KeyCode::Null, &mut panels, &mut commands);
+        assert_eq!(app.active_panel(), 1);
+        assert_eq!(app.unseen_error_count(&panels), 0);
+
+        app.add_error("Error");
+        app.add_info("Info");
+        assert_eq!(app.unseen_error_count(&panels), 1);
     }
 
     #[test]
-    fn set_default() {
+    fn jump_to_hidden_messages_panel() {
         let mut panels = Panels::new();
         let mut app = AppState::new();
         let mut commands = Manager::default();
         app.init(&mut panels, &mut commands);
-        app.split_current_panel_horizontal(KeyCode::Null, &mut panels, &mut commands);
-        app.split_current_panel_horizontal(KeyCode::Null, &mut panels, &mut commands);
-        app.split_current_panel_horizontal(KeyCode::Null, &mut panels, &mut commands);
-        app.input_request = Some(InputRequest {
-            prompt: "Prompt".to_string(),
-            requestor_id: TOP_REQUESTOR_ID,
-            auto_completer: None,
-        });
-        app.state = State::WaitingPanelType(1);
-        app.set_selecting_panel(true);
+        app.set_messages_placement(MessagesPlacement::StatusBar);
 
-        app.reset(&mut panels);
+        app.jump_to_messages(KeyCode::Null, &mut panels, &mut commands);
+        assert_eq!(app.active_panel(), 1);
+    }
 
-        assert_is_default(&app);
+    #[test]
+    fn messages_placement_names() {
+        assert_eq!(MessagesPlacement::from_name("Strip"), Some(MessagesPlacement::Strip));
+        assert_eq!(MessagesPlacement::from_name("auto"), Some(MessagesPlacement::HiddenUntilError));
+        assert_eq!(MessagesPlacement::from_name("status"), Some(MessagesPlacement::StatusBar));
+        assert_eq!(MessagesPlacement::from_name("top"), None);
     }
 
     #[test]
@@ -934,9 +3475,11 @@ mod tests {
         app.selecting_panel = true;
         app.add_panel_to_active_split(KeyCode::Null, &mut panels, &mut commands);
         app.input_request = Some(InputRequest {
-            prompt: "Test".to_string(),
+            prompt: "Test".into(),
             requestor_id: TOP_REQUESTOR_ID,
             auto_completer: None,
+            sequence: None,
+            validator: None,
         });
 
         app.select_panel(KeyCode::Char('b'), &mut panels, &mut commands);
@@ -1001,6 +3544,130 @@ mod tests {
         assert_eq!(app.messages[0].channel, MessageChannel::ERROR)
     }
 
+    #[test]
+    fn swap_panel_with_next() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        app.swap_panel_with_next(KeyCode::Null, &mut panels, &mut commands);
+
+        assert_eq!(
+            app.splits[0].panels,
+            vec![UserSplits::Panel(0), UserSplits::Panel(2), UserSplits::Panel(1)]
+        );
+
+        // already last
+        app.swap_panel_with_next(KeyCode::Null, &mut panels, &mut commands);
+
+        assert_eq!(
+            app.splits[0].panels,
+            vec![UserSplits::Panel(0), UserSplits::Panel(2), UserSplits::Panel(1)]
+        );
+    }
+
+    #[test]
+    fn swap_panel_with_static_panel() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        app.swap_panel_with_previous(KeyCode::Null, &mut panels, &mut commands);
+
+        assert_eq!(
+            app.splits[0].panels,
+            vec![UserSplits::Panel(0), UserSplits::Panel(1), UserSplits::Panel(2)]
+        );
+        assert_eq!(app.messages[0].channel, MessageChannel::INFO);
+    }
+
+    #[test]
+    fn swap_static_panel() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        app.active_panel = 0;
+
+        app.swap_panel_with_next(KeyCode::Null, &mut panels, &mut commands);
+
+        assert_eq!(
+            app.splits[0].panels,
+            vec![UserSplits::Panel(0), UserSplits::Panel(1), UserSplits::Panel(2)]
+        );
+        assert_eq!(app.messages[0].channel, MessageChannel::INFO);
+    }
+
+    #[test]
+    fn move_panel_to_next_split() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        app.active_panel = 2;
+        app.split_current_panel_horizontal(KeyCode::Null, &mut panels, &mut commands);
+        app.active_panel = 1;
+
+        app.move_panel_to_next_split(KeyCode::Null, &mut panels, &mut commands);
+
+        assert_eq!(app.splits[0].panels, vec![UserSplits::Panel(0), UserSplits::Split(1)]);
+        assert_eq!(
+            app.splits[1].panels,
+            vec![UserSplits::Panel(1), UserSplits::Panel(2), UserSplits::Panel(3)]
+        );
+        assert_eq!(app.panels[1].split_index, 1);
+    }
+
+    #[test]
+    fn move_panel_to_previous_split() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        app.split_current_panel_horizontal(KeyCode::Null, &mut panels, &mut commands);
+        app.active_panel = 2;
+
+        app.move_panel_to_previous_split(KeyCode::Null, &mut panels, &mut commands);
+
+        assert_eq!(app.splits[0].panels, vec![UserSplits::Panel(0), UserSplits::Split(1)]);
+        assert_eq!(
+            app.splits[1].panels,
+            vec![UserSplits::Panel(1), UserSplits::Panel(3), UserSplits::Panel(2)]
+        );
+        assert_eq!(app.panels[2].split_index, 1);
+    }
+
+    #[test]
+    fn move_panel_no_adjacent_split() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        app.move_panel_to_next_split(KeyCode::Null, &mut panels, &mut commands);
+
+        assert_eq!(
+            app.splits[0].panels,
+            vec![UserSplits::Panel(0), UserSplits::Panel(1), UserSplits::Panel(2)]
+        );
+        assert_eq!(app.messages[0].channel, MessageChannel::INFO);
+    }
+
+    #[test]
+    fn move_panel_no_active_panel() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        app.active_panel = 100;
+
+        app.move_panel_to_next_split(KeyCode::Null, &mut panels, &mut commands);
+
+        assert_eq!(app.messages[0].channel, MessageChannel::ERROR);
+    }
+
     #[test]
     fn split_panel() {
         let mut panels = Panels::new();
@@ -1008,17 +3675,270 @@ mod tests {
         let mut commands = Manager::default();
         app.init(&mut panels, &mut commands);
 
-        app.split_current_panel_horizontal(KeyCode::Null, &mut panels, &mut commands);
-
-        assert_eq!(app.panels.len(), 4);
-        assert_eq!(app.splits.len(), 2);
-
-        assert_eq!(
-            app.splits[1].panels,
-            vec![UserSplits::Panel(1), UserSplits::Panel(3)]
-        );
+        app.split_current_panel_horizontal(KeyCode::Null, &mut panels, &mut commands);
+
+        assert_eq!(app.panels.len(), 4);
+        assert_eq!(app.splits.len(), 2);
+
+        assert_eq!(
+            app.splits[1].panels,
+            vec![UserSplits::Panel(1), UserSplits::Panel(3)]
+        );
+
+        assert_eq!(app.panels[3].split_index, 1);
+    }
+
+    #[test]
+    fn unfinished_key_sequence_expires() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        commands.set_sequence_timeout(Some(Duration::ZERO));
+
+        commands.advance(CommandKeyId::new(KeyCode::Char('p'), KeyModifiers::CONTROL), &mut app, &mut panels);
+        app.update(&mut panels, &mut commands);
+
+        assert!(commands.progress().is_empty());
+        assert_eq!(app.messages.last().unwrap().text(), "Abandoned key sequence C + p.");
+    }
+
+    #[test]
+    fn unfinished_key_sequence_kept_without_timeout() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        commands.set_sequence_timeout(None);
+
+        commands.advance(CommandKeyId::new(KeyCode::Char('p'), KeyModifiers::CONTROL), &mut app, &mut panels);
+        app.update(&mut panels, &mut commands);
+
+        assert_eq!(commands.progress().len(), 1);
+    }
+
+    #[test]
+    fn count_repeats_next_command() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        commands.advance(CommandKeyId::new(KeyCode::Char('1'), KeyModifiers::ALT), &mut app, &mut panels);
+        commands.advance(CommandKeyId::new(KeyCode::Char('2'), KeyModifiers::ALT), &mut app, &mut panels);
+
+        assert_eq!(commands.pending_count(), Some(12));
+
+        commands.advance(CommandKeyId::new(KeyCode::Char('a'), KeyModifiers::empty()), &mut app, &mut panels);
+
+        assert_eq!(panels.get(app.active_panel()).unwrap().text(), "a".repeat(12));
+        assert_eq!(commands.pending_count(), None);
+    }
+
+    #[test]
+    fn show_help_splits_once_then_reuses() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        app.show_help(KeyCode::Null, &mut panels, &mut commands);
+        let help = app.get_active_panel().unwrap().panel_index;
+        assert_eq!(panels.get(help).unwrap().panel_type(), HELP_PANEL_TYPE_ID);
+        assert_eq!(commands.current_panel().unwrap().0, HELP_PANEL_TYPE_ID);
+
+        app.set_active_panel(1);
+        app.show_help(KeyCode::Null, &mut panels, &mut commands);
+        assert_eq!(app.get_active_panel().unwrap().panel_index, help);
+        assert_eq!(app.panels.len(), 4);
+    }
+
+    #[test]
+    fn welcome_replaces_blank_edit_panel_until_key() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        app.show_welcome(KeyCode::Null, &mut panels, &mut commands);
+        assert_eq!(app.panels.len(), 3);
+        assert_eq!(panels.get(1).unwrap().panel_type(), WELCOME_PANEL_TYPE_ID);
+
+        app.dismiss_welcome(&mut panels);
+        commands.advance(CommandKeyId::new(KeyCode::Char('x'), KeyModifiers::empty()), &mut app, &mut panels);
+
+        assert_eq!(panels.get(1).unwrap().panel_type(), EDIT_PANEL_TYPE_ID);
+        assert_eq!(panels.get(1).unwrap().text(), "x");
+    }
+
+    #[test]
+    fn welcome_splits_beside_edited_panel() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        commands.advance(CommandKeyId::new(KeyCode::Char('x'), KeyModifiers::empty()), &mut app, &mut panels);
+
+        app.show_welcome(KeyCode::Null, &mut panels, &mut commands);
+
+        assert_eq!(app.panels.len(), 4);
+        assert_eq!(panels.get(1).unwrap().text(), "x");
+        let welcome = app.get_active_panel().unwrap().panel_index;
+        assert_eq!(panels.get(welcome).unwrap().panel_type(), WELCOME_PANEL_TYPE_ID);
+    }
+
+    // commands panel is last, at index 3
+    fn with_commands_panel() -> (Panels, AppState, Manager) {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        let layout = parse_layout("v(Edit, Messages, Commands)").unwrap();
+        app.init_with_layout(&layout, &mut panels, &mut commands).unwrap();
+
+        (panels, app, commands)
+    }
+
+    fn run_from_commands_panel(app: &mut AppState, panels: &mut Panels, commands: &mut Manager, search: &str) {
+        app.set_active_panel(3);
+        commands.replace_top_with_panel(COMMANDS_PANEL_TYPE_ID);
+
+        let keys = iter::once(KeyCode::Char('/'))
+            .chain(search.chars().map(KeyCode::Char))
+            .chain([KeyCode::Enter, KeyCode::Char('s'), KeyCode::Enter]);
+        for code in keys {
+            commands.advance(CommandKeyId::new(code, KeyModifiers::empty()), app, panels);
+        }
+    }
+
+    #[test]
+    fn commands_panel_runs_panel_command_on_previous_panel() {
+        let (mut panels, mut app, mut commands) = with_commands_panel();
+
+        commands.advance(CommandKeyId::new(KeyCode::Char('x'), KeyModifiers::empty()), &mut app, &mut panels);
+        assert_eq!(commands.menu_target(), Some(1));
+
+        run_from_commands_panel(&mut app, &mut panels, &mut commands, "toggle mark");
+
+        assert_eq!(app.active_panel(), 1);
+        assert_eq!(panels.get(1).unwrap().mark(), Some((0, 1)));
+        assert_eq!(panels.get(1).unwrap().text(), "x");
+        assert_eq!(commands.menu_target(), Some(1));
+    }
+
+    #[test]
+    fn commands_panel_runs_global_command() {
+        let (mut panels, mut app, mut commands) = with_commands_panel();
+
+        run_from_commands_panel(&mut app, &mut panels, &mut commands, "jump to messages");
+
+        assert_eq!(panels.get(app.active_panel()).unwrap().panel_type(), MESSAGE_PANEL_TYPE_ID);
+    }
+
+    #[test]
+    fn modal_editing_keys() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        panels.get_mut(1).unwrap().set_text("abcdef\nline");
+
+        let mut press = |app: &mut AppState, panels: &mut Panels, c: char, mods: KeyModifiers| {
+            commands.advance(CommandKeyId::new(KeyCode::Char(c), mods), app, panels)
+        };
+
+        press(&mut app, &mut panels, 'n', KeyModifiers::ALT);
+
+        assert_eq!(panels.get(1).unwrap().edit_mode(), EditMode::Normal);
+
+        // letters are motions and operators, digits are a count
+        press(&mut app, &mut panels, '3', KeyModifiers::empty());
+        press(&mut app, &mut panels, 'x', KeyModifiers::empty());
+        press(&mut app, &mut panels, 'j', KeyModifiers::empty());
+
+        assert_eq!(panels.get(1).unwrap().text(), "def\nline");
+        assert_eq!(panels.get(1).unwrap().current_line(), 1);
+
+        press(&mut app, &mut panels, 'A', KeyModifiers::SHIFT);
+        press(&mut app, &mut panels, 's', KeyModifiers::empty());
+
+        assert_eq!(panels.get(1).unwrap().text(), "def\nlines");
+
+        assert!(app.leave_insert_mode(&mut panels));
+        assert!(!app.leave_insert_mode(&mut panels));
+
+        press(&mut app, &mut panels, 'd', KeyModifiers::empty());
+        press(&mut app, &mut panels, 'd', KeyModifiers::empty());
+
+        assert_eq!(panels.get(1).unwrap().text(), "def");
+    }
+
+    #[test]
+    fn click_and_drag_line_numbers_selects_lines() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        panels.get_mut(1).unwrap().set_text("one\ntwo\nthree\nfour");
+        panels.get_mut(1).unwrap().set_viewport(Rect::new(10, 5, 30, 10));
+
+        let mouse = |kind, column, row| MouseEvent { kind, column, row, modifiers: KeyModifiers::empty() };
+
+        app.handle_mouse(mouse(MouseEventKind::Down(MouseButton::Left), 10, 6), &mut panels, &mut commands);
+
+        assert_eq!(app.active_panel(), 1);
+        assert_eq!(panels.get(1).unwrap().mark(), Some((1, 3)));
+        assert_eq!(panels.get(1).unwrap().current_line(), 1);
+
+        app.handle_mouse(mouse(MouseEventKind::Drag(MouseButton::Left), 25, 30), &mut panels, &mut commands);
+        app.handle_mouse(mouse(MouseEventKind::Up(MouseButton::Left), 25, 30), &mut panels, &mut commands);
+
+        assert_eq!(panels.get(1).unwrap().mark(), Some((1, 5)));
+        assert_eq!(panels.get(1).unwrap().current_line(), 3);
+
+        // text area isn't part of gutter
+        app.handle_mouse(mouse(MouseEventKind::Down(MouseButton::Left), 20, 5), &mut panels, &mut commands);
+
+        assert_eq!(panels.get(1).unwrap().current_line(), 3);
+    }
+
+    #[test]
+    fn split_view_shares_buffer() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        panels.get_mut(1).unwrap().set_text("one\ntwo");
+        panels.get_mut(1).unwrap().set_current_line(1);
+
+        app.split_current_panel_view(KeyCode::Null, &mut panels, &mut commands);
+
+        assert_eq!(app.panels.len(), 4);
+        assert_eq!(app.splits.len(), 2);
+
+        let view = panels.get(3).unwrap();
+        assert_eq!(view.panel_type(), EDIT_PANEL_TYPE_ID);
+        assert_eq!(view.text(), "one\ntwo");
+        assert_eq!(view.current_line(), 0);
+
+        panels.get_mut(3).unwrap().set_text("changed");
+        panels.propagate_edits(3);
+
+        assert_eq!(panels.get(1).unwrap().text(), "changed");
+        assert_eq!(panels.get(1).unwrap().current_line(), 0);
+    }
+
+    #[test]
+    fn split_view_of_non_edit_panel_logs_message() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        app.set_active_panel(2);
+
+        app.split_current_panel_view(KeyCode::Null, &mut panels, &mut commands);
 
-        assert_eq!(app.panels[3].split_index, 1);
+        assert_eq!(app.panels.len(), 3);
+        assert_eq!(app.messages[0].channel, MessageChannel::INFO);
     }
 
     #[test]
@@ -1079,7 +3999,7 @@ mod tests {
         assert_eq!(app.splits.len(), 1);
         assert!(app
             .messages
-            .contains(&Message::info("Cannot split static panel")));
+            .contains(&Message::info(Text::new("app.cannot_split_static_panel", "Cannot split static panel"))));
     }
 
     #[test]
@@ -1097,7 +4017,7 @@ mod tests {
 
         assert!(app
             .messages
-            .contains(&Message::info("Cannot delete static panel.")))
+            .contains(&Message::info(Text::new("app.cannot_delete_static_panel", "Cannot delete static panel."))))
     }
 
     #[test]
@@ -1162,6 +4082,80 @@ mod tests {
         assert_eq!(app.splits.len(), 1);
     }
 
+    #[test]
+    fn close_other_panels_collapses_splits() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        app.split_current_panel_horizontal(KeyCode::Null, &mut panels, &mut commands);
+        app.set_active_panel(app.panels.len() - 1);
+        app.split_current_panel_vertical(KeyCode::Null, &mut panels, &mut commands);
+        app.set_active_panel(1);
+
+        // messages panel counts too, only prompt is static
+        assert_eq!(app.other_panels().len(), 3);
+
+        app.close_other_panels(KeyCode::Null, &mut panels, &mut commands);
+
+        assert!(app.other_panels().is_empty());
+        // split emptied by closing is gone, active panel's own split stays
+        assert_eq!(app.splits.len(), 2);
+        assert_eq!(app.active_panel, 1);
+        assert_eq!(app.messages.last().unwrap().text(), "Closed 3 panel(s).");
+    }
+
+    #[test]
+    fn close_other_panels_asks_about_unsaved_changes() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        let second = app.panels.len();
+        app.split_current_panel_horizontal(KeyCode::Null, &mut panels, &mut commands);
+        let second_index = app.get_panel(second).unwrap().panel_index;
+        panels.get_mut(second_index).unwrap().set_modified(true);
+        app.set_active_panel(1);
+
+        app.close_other_panels(KeyCode::Null, &mut panels, &mut commands);
+
+        assert_eq!(app.state, State::WaitingCloseOthersConfirmation(1));
+        assert!(panels.get(second_index).unwrap().modified());
+    }
+
+    #[test]
+    fn save_all_skips_untitled() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        let dir = std::env::temp_dir().join(format!("edish_save_all_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let panel = panels.get_mut(1).unwrap();
+        panel.set_file_path(dir.join("one.txt"));
+        panel.set_text("one");
+        panel.set_modified(true);
+
+        // background buffer with a path, current one untitled
+        panel.new_buffer(KeyCode::Null, &mut app, &mut commands);
+        panel.set_text("untitled");
+        panel.set_modified(true);
+
+        app.save_all(KeyCode::Null, &mut panels, &mut commands);
+
+        assert_eq!(std::fs::read_to_string(dir.join("one.txt")).unwrap(), "one");
+        assert_eq!(panels.get(1).unwrap().text(), "untitled");
+        assert!(panels.get(1).unwrap().modified());
+        assert!(app.messages.iter().any(|message| message.text() == "Saved 1 buffer(s)."));
+        assert_eq!(app.messages.last().unwrap().text(), "Not saved, save each on its own: Untitled");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn delete_invalid_active_panel_logs_message() {
         let mut panels = Panels::new();
@@ -1420,19 +4414,71 @@ mod tests {
 
         assert_ne!(panels.get(1).unwrap().panel_type(), NULL_PANEL_TYPE_ID);
     }
+
+    #[test]
+    fn focus_panel_by_direction() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        app.add_panel_to_active_split(KeyCode::Null, &mut panels, &mut commands);
+
+        // 1 | 2
+        // -----
+        //   3
+        app.panels[1].set_rect(Rect::new(0, 0, 10, 10));
+        app.panels[2].set_rect(Rect::new(10, 0, 10, 10));
+        app.panels[3].set_rect(Rect::new(0, 10, 20, 10));
+        app.active_panel = 1;
+
+        app.focus_panel_right(KeyCode::Null, &mut panels, &mut commands);
+        assert_eq!(app.active_panel, 2);
+
+        app.focus_panel_right(KeyCode::Null, &mut panels, &mut commands);
+        assert_eq!(app.active_panel, 2);
+
+        app.focus_panel_down(KeyCode::Null, &mut panels, &mut commands);
+        assert_eq!(app.active_panel, 3);
+
+        app.focus_panel_up(KeyCode::Null, &mut panels, &mut commands);
+        assert_eq!(app.active_panel, 1);
+
+        app.active_panel = 2;
+        app.focus_panel_left(KeyCode::Null, &mut panels, &mut commands);
+        assert_eq!(app.active_panel, 1);
+    }
+
+    #[test]
+    fn focus_skips_panels_not_rendered() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        app.panels[1].set_rect(Rect::new(0, 0, 10, 10));
+        app.panels[2].set_rect(Rect::new(0, 10, 10, 10));
+
+        app.focus_panel_up(KeyCode::Null, &mut panels, &mut commands);
+        assert_eq!(app.active_panel, 1);
+
+        app.clear_panel_rects();
+        app.focus_panel_down(KeyCode::Null, &mut panels, &mut commands);
+        assert_eq!(app.active_panel, 1);
+    }
 }
 
 #[cfg(test)]
 mod state_changes {
-    use crossterm::event::KeyCode;
+    use crossterm::event::{KeyCode, KeyModifiers};
 
     use crate::app::StateChangeRequest::InputComplete;
     use crate::app::{
-        InputRequest, LayoutPanel, MessageChannel, State, StateChangeRequest, TOP_REQUESTOR_ID,
+        InputRequest, LayoutPanel, Message, MessageChannel, State, StateChangeRequest, TOP_REQUESTOR_ID,
     };
     use crate::autocomplete::PanelAutoCompleter;
-    use crate::panels::MESSAGE_PANEL_TYPE_ID;
-    use crate::{AppState, Panels, TextPanel};
+    use crate::locale::Catalog;
+    use crate::panels::{MESSAGE_PANEL_TYPE_ID, NULL_PANEL_TYPE_ID};
+    use crate::{AppState, CommandKeyId, Panels, TextPanel};
     use crate::commands::Manager;
 
     #[allow(dead_code)]
@@ -1463,7 +4509,7 @@ mod state_changes {
         );
 
         let request = app.input_request().unwrap();
-        assert_eq!(request.prompt, "Test Input".to_string());
+        assert_eq!(request.prompt, "Test Input");
         assert_eq!(request.requestor_id, 1);
         assert!(request.auto_completer.is_some());
         assert_eq!(app.active_panel, 0);
@@ -1514,9 +4560,11 @@ mod state_changes {
         let mut commands = Manager::default();
         app.init(&mut panels, &mut commands);
         app.input_request = Some(InputRequest {
-            prompt: "Test Input".to_string(),
+            prompt: "Test Input".into(),
             requestor_id: 1,
             auto_completer: None,
+            sequence: None,
+            validator: None,
         });
         app.active_panel = 0;
 
@@ -1566,9 +4614,11 @@ mod state_changes {
         let mut commands = Manager::default();
         app.init(&mut panels, &mut commands);
         app.input_request = Some(InputRequest {
-            prompt: "Test Input".to_string(),
+            prompt: "Test Input".into(),
             requestor_id: 10,
             auto_completer: None,
+            sequence: None,
+            validator: None,
         });
 
         let mut panel = TextPanel::default();
@@ -1613,7 +4663,7 @@ mod state_changes {
         assert_eq!(app.state, State::WaitingPanelType(1));
 
         let request = app.input_request().unwrap();
-        assert_eq!(request.prompt, "Panel Type".to_string());
+        assert_eq!(request.prompt, "Panel Type");
         assert_eq!(request.requestor_id, TOP_REQUESTOR_ID);
         assert!(request.auto_completer.is_some());
     }
@@ -1627,9 +4677,11 @@ mod state_changes {
         app.active_panel = 0;
         app.state = State::WaitingPanelType(1);
         app.input_request = Some(InputRequest {
-            prompt: "Panel Type".to_string(),
+            prompt: "Panel Type".into(),
             requestor_id: TOP_REQUESTOR_ID,
             auto_completer: None,
+            sequence: None,
+            validator: None,
         });
 
         app.handle_changes(
@@ -1642,4 +4694,274 @@ mod state_changes {
         assert_eq!(app.state, State::Normal);
         assert!(app.input_request.is_none())
     }
+
+    #[test]
+    fn delete_modified_panel_requests_confirmation() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        panels.get_mut(1).unwrap().set_modified(true);
+
+        app.delete_active_panel(KeyCode::Null, &mut panels, &mut commands);
+
+        assert_eq!(app.active_panel, 0);
+        assert_eq!(app.state, State::WaitingDeleteConfirmation(1));
+        assert_eq!(app.input_request().unwrap().requestor_id, TOP_REQUESTOR_ID);
+        assert_ne!(panels.get(1).unwrap().panel_type(), NULL_PANEL_TYPE_ID);
+    }
+
+    #[test]
+    fn delete_modified_panel_confirmed() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        app.add_panel_to_active_split(KeyCode::Null, &mut panels, &mut commands);
+        panels.get_mut(1).unwrap().set_modified(true);
+
+        app.delete_active_panel(KeyCode::Null, &mut panels, &mut commands);
+        app.handle_changes(vec![InputComplete("y".to_string())], &mut panels, &mut commands);
+
+        assert_eq!(panels.get(1).unwrap().panel_type(), NULL_PANEL_TYPE_ID);
+        assert_eq!(app.state, State::Normal);
+        assert!(app.input_request.is_none());
+    }
+
+    #[test]
+    fn delete_modified_panel_declined() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        app.add_panel_to_active_split(KeyCode::Null, &mut panels, &mut commands);
+        panels.get_mut(1).unwrap().set_modified(true);
+
+        app.delete_active_panel(KeyCode::Null, &mut panels, &mut commands);
+        app.handle_changes(vec![InputComplete("n".to_string())], &mut panels, &mut commands);
+
+        assert_ne!(panels.get(1).unwrap().panel_type(), NULL_PANEL_TYPE_ID);
+        assert_eq!(app.active_panel, 1);
+        assert_eq!(app.state, State::Normal);
+    }
+
+    #[test]
+    fn request_quit_without_changes() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        assert!(app.request_quit(&mut panels, &mut commands));
+        assert!(app.input_request.is_none());
+    }
+
+    #[test]
+    fn modified_background_buffer_asks_before_quit_and_delete() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        let path = std::env::temp_dir().join(format!("edish_background_{}.txt", std::process::id()));
+        let swap = crate::swap::swap_path(&path).unwrap();
+        std::fs::write(&swap, "unsaved").unwrap();
+
+        let panel = panels.get_mut(1).unwrap();
+        panel.set_file_path(path);
+        panel.set_modified(true);
+        panel.new_buffer(KeyCode::Null, &mut app, &mut commands);
+        assert!(!panels.get(1).unwrap().modified());
+
+        app.delete_active_panel(KeyCode::Null, &mut panels, &mut commands);
+        assert_eq!(app.state, State::WaitingDeleteConfirmation(1));
+        app.handle_changes(vec![InputComplete("n".to_string())], &mut panels, &mut commands);
+
+        assert!(!app.request_quit(&mut panels, &mut commands));
+        app.handle_changes(vec![InputComplete("y".to_string())], &mut panels, &mut commands);
+
+        assert!(app.should_quit());
+        assert!(!swap.exists());
+    }
+
+    #[test]
+    fn request_quit_with_changes_confirmed() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        panels.get_mut(1).unwrap().set_modified(true);
+
+        assert!(!app.request_quit(&mut panels, &mut commands));
+        assert_eq!(app.state, State::WaitingQuitConfirmation(1));
+        assert!(!app.should_quit());
+
+        app.handle_changes(vec![InputComplete("yes".to_string())], &mut panels, &mut commands);
+
+        assert!(app.should_quit());
+        assert_eq!(app.active_panel, 1);
+    }
+
+    #[test]
+    fn request_quit_with_changes_declined() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        panels.get_mut(1).unwrap().set_modified(true);
+
+        app.request_quit(&mut panels, &mut commands);
+        app.handle_changes(vec![InputComplete("n".to_string())], &mut panels, &mut commands);
+
+        assert!(!app.should_quit());
+        assert_eq!(app.state, State::Normal);
+    }
+
+    #[test]
+    fn quit_command_shuts_down_panels() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        let path = std::env::temp_dir().join(format!("edish_quit_{}.txt", std::process::id()));
+        let swap = crate::swap::swap_path(&path).unwrap();
+        std::fs::write(&swap, "unsaved").unwrap();
+        panels.get_mut(1).unwrap().set_file_path(path);
+        panels.get_mut(1).unwrap().set_modified(true);
+
+        commands.advance(CommandKeyId::new(KeyCode::Char('q'), KeyModifiers::CONTROL), &mut app, &mut panels);
+
+        assert!(!app.should_quit());
+        assert!(swap.exists());
+
+        app.handle_changes(vec![InputComplete("y".to_string())], &mut panels, &mut commands);
+
+        assert!(app.should_quit());
+        assert!(!swap.exists());
+    }
+
+    #[test]
+    fn cancel_key_sequence() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        assert!(!app.cancel_key_sequence(&mut commands));
+
+        commands.advance(CommandKeyId::new(KeyCode::Char('p'), KeyModifiers::CONTROL), &mut app, &mut panels);
+
+        assert!(app.cancel_key_sequence(&mut commands));
+        assert!(commands.progress().is_empty());
+        assert_eq!(app.messages.last().unwrap().text(), "Abandoned key sequence C + p.");
+    }
+
+    #[test]
+    fn command_palette_runs_panel_command() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        app.open_command_palette(KeyCode::Null, &mut panels, &mut commands);
+
+        assert_eq!(app.state, State::WaitingCommand(1));
+        assert_eq!(app.active_panel, 0);
+
+        app.handle_changes(vec![InputComplete("Toggle Wrap".to_string())], &mut panels, &mut commands);
+
+        assert_eq!(app.state, State::Normal);
+        assert_eq!(app.active_panel, 1);
+        assert!(!panels.get(1).unwrap().wrap());
+    }
+
+    #[test]
+    fn keymap_export_then_import() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        let path = std::env::temp_dir().join(format!("edish_keymap_{}.tsv", std::process::id()));
+        let path_str = path.to_str().unwrap().to_string();
+
+        app.export_keymap(KeyCode::Null, &mut panels, &mut commands);
+        assert_eq!(app.state, State::WaitingKeymapExport(1));
+
+        app.handle_changes(vec![InputComplete(path_str.clone())], &mut panels, &mut commands);
+        assert_eq!(app.state, State::Normal);
+        assert_eq!(app.active_panel, 1);
+
+        // same keymap again changes nothing
+        app.import_keymap(KeyCode::Null, &mut panels, &mut commands);
+        app.handle_changes(vec![InputComplete(path_str.clone())], &mut panels, &mut commands);
+
+        let last = app.get_messages().last().unwrap().clone();
+        assert_eq!(last.text(), format!("Imported 0 key binding(s) from {:?}, 0 conflict(s).", path_str).as_str());
+
+        std::fs::write(&path, "Edit\tC + o\tToggle Wrap\n").unwrap();
+        app.import_keymap(KeyCode::Null, &mut panels, &mut commands);
+        app.handle_changes(vec![InputComplete(path_str.clone())], &mut panels, &mut commands);
+
+        assert!(app
+            .get_messages()
+            .contains(&Message::warning("Edit C + o: Open File replaced by Toggle Wrap.")));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn command_palette_uses_fuzzy_match() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        app.open_command_palette(KeyCode::Null, &mut panels, &mut commands);
+        app.handle_changes(vec![InputComplete("togwrap".to_string())], &mut panels, &mut commands);
+
+        assert!(!panels.get(1).unwrap().wrap());
+    }
+
+    #[test]
+    fn command_palette_uses_translated_names() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        app.set_catalog(Catalog::parse("command.Toggle Wrap = Umbruch umschalten").unwrap());
+
+        app.open_command_palette(KeyCode::Null, &mut panels, &mut commands);
+        app.handle_changes(vec![InputComplete("Umbruch".to_string())], &mut panels, &mut commands);
+
+        assert!(!panels.get(1).unwrap().wrap());
+    }
+
+    #[test]
+    fn command_palette_shared_translation_shows_english() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        app.set_catalog(Catalog::parse("command.Toggle Wrap = Umschalten\ncommand.Toggle Sync Cursors = Umschalten").unwrap());
+
+        app.open_command_palette(KeyCode::Null, &mut panels, &mut commands);
+        app.handle_changes(vec![InputComplete("Umschalten (Toggle Wrap)".to_string())], &mut panels, &mut commands);
+
+        assert!(!panels.get(1).unwrap().wrap());
+    }
+
+    #[test]
+    fn command_palette_unknown_command() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        app.open_command_palette(KeyCode::Null, &mut panels, &mut commands);
+        app.handle_changes(vec![InputComplete("zzzz".to_string())], &mut panels, &mut commands);
+
+        assert_eq!(app.messages.last().unwrap().channel(), MessageChannel::ERROR);
+    }
 }
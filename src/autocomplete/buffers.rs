@@ -0,0 +1,53 @@
+use crate::autocomplete::{AutoCompleter, Completion, MatchMode};
+
+// buffers of an edit panel, listed as 'number name'
+// also used for checkpoint labels
+pub struct BufferAutoCompleter {
+    names: Vec<String>,
+}
+
+impl BufferAutoCompleter {
+    pub fn new(names: Vec<String>) -> Self {
+        Self { names }
+    }
+}
+
+impl AutoCompleter for BufferAutoCompleter {
+    fn get_options(&self, s: &str) -> Vec<Completion> {
+        self.names
+            .iter()
+            .filter_map(|name| MatchMode::Fuzzy.complete(name, s))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::autocomplete::buffers::BufferAutoCompleter;
+    use crate::autocomplete::AutoCompleter;
+
+    fn completer() -> BufferAutoCompleter {
+        BufferAutoCompleter::new(vec![
+            "1 src/main.rs".to_string(),
+            "2 Untitled".to_string(),
+            "3 src/app.rs".to_string(),
+        ])
+    }
+
+    #[test]
+    fn empty_input_lists_all_in_order() {
+        let options = completer().ranked_options("");
+
+        assert_eq!(
+            options.iter().map(|o| o.option().as_str()).collect::<Vec<&str>>(),
+            vec!["1 src/main.rs", "2 Untitled", "3 src/app.rs"]
+        );
+    }
+
+    #[test]
+    fn match_by_name() {
+        let options = completer().ranked_options("app");
+
+        assert_eq!(options[0].option(), "3 src/app.rs");
+    }
+}
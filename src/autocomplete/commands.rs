@@ -0,0 +1,84 @@
+use crate::autocomplete::{fuzzy_match, AutoCompleter, Completion};
+
+pub struct CommandAutoCompleter {
+    names: Vec<String>,
+}
+
+impl CommandAutoCompleter {
+    pub fn new(names: Vec<String>) -> Self {
+        Self { names }
+    }
+}
+
+impl AutoCompleter for CommandAutoCompleter {
+    fn get_options(&self, s: &str) -> Vec<Completion> {
+        let mut matches = self
+            .names
+            .iter()
+            .filter_map(|name| fuzzy_match(name, s).map(|rank| (rank, name)))
+            .collect::<Vec<((usize, usize), &String)>>();
+
+        matches.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(b.1)));
+
+        matches
+            .iter()
+            .map(|(_, name)| {
+                // only prefix matches can be completed in place
+                let remaining = match name.starts_with(s) {
+                    true => String::from(&name[s.len()..]),
+                    false => String::new(),
+                };
+
+                Completion::new(name.to_string(), remaining)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::autocomplete::commands::CommandAutoCompleter;
+    use crate::autocomplete::{AutoCompleter, Completion};
+
+    fn completer() -> CommandAutoCompleter {
+        CommandAutoCompleter::new(vec![
+            "Open File".to_string(),
+            "Save".to_string(),
+            "Split Horizontal".to_string(),
+            "Split Vertical".to_string(),
+        ])
+    }
+
+    #[test]
+    fn empty_input_returns_all() {
+        assert_eq!(completer().get_options("").len(), 4);
+    }
+
+    #[test]
+    fn prefix_match() {
+        assert_eq!(
+            completer().get_options("Sav"),
+            vec![Completion::new("Save".to_string(), "e".to_string())]
+        );
+    }
+
+    #[test]
+    fn fuzzy_match_ignores_case() {
+        assert_eq!(
+            completer().get_options("spv"),
+            vec![Completion::new("Split Vertical".to_string(), "".to_string())]
+        );
+    }
+
+    #[test]
+    fn tighter_match_ranked_first() {
+        let options = completer().get_options("sh");
+
+        assert_eq!(options[0].option(), "Split Horizontal");
+    }
+
+    #[test]
+    fn no_match() {
+        assert_eq!(completer().get_options("xyz"), Vec::<Completion>::new());
+    }
+}
@@ -0,0 +1,55 @@
+use crate::autocomplete::{AutoCompleter, Completion};
+
+// answers for yes/no questions
+pub struct ConfirmAutoCompleter {}
+
+impl ConfirmAutoCompleter {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    fn options() -> Vec<&'static str> {
+        vec!["yes", "no"]
+    }
+}
+
+impl AutoCompleter for ConfirmAutoCompleter {
+    fn get_options(&self, s: &str) -> Vec<Completion> {
+        let lower = s.to_lowercase();
+        ConfirmAutoCompleter::options()
+            .iter()
+            .filter(|o| o.starts_with(lower.as_str()))
+            .map(|o| Completion::new(o.to_string(), String::from(&o[s.len()..])))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::autocomplete::confirm::ConfirmAutoCompleter;
+    use crate::autocomplete::{AutoCompleter, Completion};
+
+    #[test]
+    fn empty_input_returns_both() {
+        let completer = ConfirmAutoCompleter::new();
+
+        assert_eq!(completer.get_options("").len(), 2);
+    }
+
+    #[test]
+    fn ignores_case() {
+        let completer = ConfirmAutoCompleter::new();
+
+        assert_eq!(
+            completer.get_options("Y"),
+            vec![Completion::new("yes".to_string(), "es".to_string())]
+        );
+    }
+
+    #[test]
+    fn no_match() {
+        let completer = ConfirmAutoCompleter::new();
+
+        assert!(completer.get_options("maybe").is_empty());
+    }
+}
@@ -1,13 +1,19 @@
 use std::env;
 use std::path::{Component, PathBuf};
 
-use crate::autocomplete::{AutoCompleter, Completion};
+use crate::autocomplete::{AutoCompleter, Completion, MatchMode};
 
-pub struct FileAutoCompleter {}
+pub struct FileAutoCompleter {
+    mode: MatchMode,
+}
 
 impl FileAutoCompleter {
     pub fn new() -> Self {
-        Self {}
+        Self { mode: MatchMode::Prefix }
+    }
+
+    pub fn fuzzy() -> Self {
+        Self { mode: MatchMode::Fuzzy }
     }
 }
 
@@ -77,12 +83,19 @@ impl AutoCompleter for FileAutoCompleter {
             Ok(dir) => {
                 let mut options = vec![];
 
+                // loose matches replace input, so they need the directory that was typed
+                let typed_dir = &s[..s.len() - current_input.len()];
+
                 for d in dir {
                     if let Ok(entry) = d {
                         let entry_name = entry.file_name().to_string_lossy().to_string();
-                        if entry_name.starts_with(current_input.as_str()) {
-                            let remaining = String::from(&entry_name[current_input.len()..]);
-                            options.push(Completion::new(entry_name, remaining));
+                        match self.mode.complete(&entry_name, current_input.as_str()) {
+                            None => (),
+                            Some(completion) if self.mode == MatchMode::Fuzzy => options.push(
+                                Completion::new(format!("{}{}", typed_dir, entry_name), completion.remaining().clone())
+                                    .with_score(completion.score()),
+                            ),
+                            Some(completion) => options.push(completion),
                         }
                     }
                 }
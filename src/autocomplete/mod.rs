@@ -1,22 +1,119 @@
+pub use buffers::BufferAutoCompleter;
+pub use commands::CommandAutoCompleter;
+pub use confirm::ConfirmAutoCompleter;
 pub use files::FileAutoCompleter;
 pub use panels::PanelAutoCompleter;
+pub use project::ProjectFileAutoCompleter;
+pub use recent::RecentFilesAutoCompleter;
 
+mod buffers;
+mod commands;
+mod confirm;
 mod files;
 mod panels;
+mod project;
+mod recent;
+
+// characters of input appear in order within name, ignoring case
+// returns position of first match and distance covered for ranking
+pub(crate) fn fuzzy_match(name: &str, input: &str) -> Option<(usize, usize)> {
+    let name = name.to_lowercase();
+    let mut input_chars = input.to_lowercase().chars().collect::<Vec<char>>().into_iter().peekable();
+
+    let mut first = None;
+    let mut last = 0;
+
+    for (i, c) in name.chars().enumerate() {
+        match input_chars.peek() {
+            None => break,
+            Some(next) if *next == c => {
+                input_chars.next();
+                first = first.or(Some(i));
+                last = i;
+            }
+            Some(_) => (),
+        }
+    }
+
+    match input_chars.peek() {
+        Some(_) => None,
+        None => {
+            let first = first.unwrap_or(0);
+            Some((first, last - first))
+        }
+    }
+}
+
+const MAX_SCORE: usize = 10_000;
+
+// higher is better, exact prefixes first then tighter and earlier matches
+pub(crate) fn fuzzy_score(name: &str, input: &str) -> Option<usize> {
+    fuzzy_match(name, input).map(|(first, span)| {
+        let prefix_bonus = match name.starts_with(input) {
+            true => MAX_SCORE,
+            false => 0,
+        };
+
+        prefix_bonus + MAX_SCORE.saturating_sub(span * 2 + first)
+    })
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MatchMode {
+    Prefix,
+    Fuzzy,
+}
+
+impl MatchMode {
+    pub(crate) fn complete(&self, name: &str, input: &str) -> Option<Completion> {
+        match self {
+            MatchMode::Prefix => match name.starts_with(input) {
+                true => Some(Completion::new(name.to_string(), String::from(&name[input.len()..]))),
+                false => None,
+            },
+            MatchMode::Fuzzy => fuzzy_score(name, input).map(|score| {
+                // only prefix matches can be completed in place
+                let remaining = match name.starts_with(input) {
+                    true => String::from(&name[input.len()..]),
+                    false => String::new(),
+                };
+
+                Completion::new(name.to_string(), remaining).with_score(score)
+            }),
+        }
+    }
+}
 
 pub trait AutoCompleter {
     fn get_options(&self, s: &str) -> Vec<Completion>;
+
+    // best scores first, equal scores keep completer's order
+    fn ranked_options(&self, s: &str) -> Vec<Completion> {
+        let mut options = self.get_options(s);
+        options.sort_by_key(|option| std::cmp::Reverse(option.score()));
+        options
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Completion {
     option: String,
     remaining: String,
+    score: usize,
 }
 
 impl Completion {
     pub fn new(option: String, remaining: String) -> Self {
-        Self { option, remaining }
+        Self { option, remaining, score: 0 }
+    }
+
+    pub fn with_score(mut self, score: usize) -> Self {
+        self.score = score;
+        self
+    }
+
+    pub fn score(&self) -> usize {
+        self.score
     }
 
     pub fn option(&self) -> &String {
@@ -27,3 +124,59 @@ impl Completion {
         &self.remaining
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::autocomplete::{fuzzy_score, AutoCompleter, Completion, MatchMode};
+
+    struct Unordered {}
+
+    impl AutoCompleter for Unordered {
+        fn get_options(&self, _s: &str) -> Vec<Completion> {
+            vec![
+                Completion::new("low".to_string(), String::new()).with_score(1),
+                Completion::new("high".to_string(), String::new()).with_score(5),
+                Completion::new("unscored".to_string(), String::new()),
+            ]
+        }
+    }
+
+    #[test]
+    fn prefix_scores_above_loose_match() {
+        assert!(fuzzy_score("main.rs", "ma").unwrap() > fuzzy_score("format.rs", "ma").unwrap());
+    }
+
+    #[test]
+    fn tighter_match_scores_higher() {
+        assert!(fuzzy_score("src_main", "sm").unwrap() < fuzzy_score("some", "sm").unwrap());
+        assert!(fuzzy_score("split", "xyz").is_none());
+    }
+
+    #[test]
+    fn prefix_mode() {
+        assert_eq!(
+            MatchMode::Prefix.complete("Edit", "Ed"),
+            Some(Completion::new("Edit".to_string(), "it".to_string()))
+        );
+        assert_eq!(MatchMode::Prefix.complete("Edit", "dt"), None);
+    }
+
+    #[test]
+    fn fuzzy_mode() {
+        let completion = MatchMode::Fuzzy.complete("Edit", "dt").unwrap();
+
+        assert_eq!(completion.option(), "Edit");
+        assert_eq!(completion.remaining(), "");
+        assert!(completion.score() > 0);
+    }
+
+    #[test]
+    fn ranked_options_best_first() {
+        let options = Unordered {}.ranked_options("");
+
+        assert_eq!(
+            options.iter().map(|o| o.option().as_str()).collect::<Vec<&str>>(),
+            vec!["high", "low", "unscored"]
+        );
+    }
+}
@@ -1,15 +1,22 @@
-use crate::autocomplete::{AutoCompleter, Completion};
-use crate::panels::{COMMANDS_PANEL_TYPE_ID, EDIT_PANEL_TYPE_ID, MESSAGE_PANEL_TYPE_ID};
+use crate::autocomplete::{AutoCompleter, Completion, MatchMode};
+use crate::panels::{COMMANDS_PANEL_TYPE_ID, EDIT_PANEL_TYPE_ID, HELP_PANEL_TYPE_ID, MESSAGE_PANEL_TYPE_ID, TERMINAL_PANEL_TYPE_ID};
 
-pub struct PanelAutoCompleter {}
+pub struct PanelAutoCompleter {
+    mode: MatchMode,
+}
 
+#[allow(dead_code)]
 impl PanelAutoCompleter {
     pub fn new() -> Self {
-        Self {}
+        Self { mode: MatchMode::Prefix }
+    }
+
+    pub fn fuzzy() -> Self {
+        Self { mode: MatchMode::Fuzzy }
     }
 
     fn options() -> Vec<&'static str> {
-        vec![EDIT_PANEL_TYPE_ID, MESSAGE_PANEL_TYPE_ID, COMMANDS_PANEL_TYPE_ID]
+        vec![EDIT_PANEL_TYPE_ID, MESSAGE_PANEL_TYPE_ID, COMMANDS_PANEL_TYPE_ID, TERMINAL_PANEL_TYPE_ID, HELP_PANEL_TYPE_ID]
     }
 }
 
@@ -17,8 +24,7 @@ impl AutoCompleter for PanelAutoCompleter {
     fn get_options(&self, s: &str) -> Vec<Completion> {
         PanelAutoCompleter::options()
             .iter()
-            .filter(|o| o.starts_with(s))
-            .map(|o| Completion::new(o.to_string(), String::from(&o[s.len()..])))
+            .filter_map(|o| self.mode.complete(o, s))
             .collect()
     }
 }
@@ -60,4 +66,15 @@ mod tests {
         );
         assert_eq!(completer.get_options("Edits"), Vec::<Completion>::new());
     }
+
+    #[test]
+    fn fuzzy_finds_loose_match() {
+        let completer = PanelAutoCompleter::fuzzy();
+
+        let options = completer.ranked_options("cmd");
+
+        assert_eq!(options.len(), 1);
+        assert_eq!(options[0].option(), "Commands");
+        assert_eq!(options[0].remaining(), "");
+    }
 }
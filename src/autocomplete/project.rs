@@ -0,0 +1,163 @@
+use std::cell::RefCell;
+use std::env;
+use std::path::{Path, PathBuf};
+
+use crate::autocomplete::{AutoCompleter, Completion, MatchMode};
+
+// directories that are never worth opening files from
+pub const IGNORED_DIRECTORIES: [&str; 4] = [".git", "target", "node_modules", ".idea"];
+
+// stop indexing huge trees, rest can still be opened by path
+pub const MAX_INDEXED_FILES: usize = 20_000;
+
+// every file under a directory, matched loosely against the whole relative path
+pub struct ProjectFileAutoCompleter {
+    root: PathBuf,
+    // built on first request for options
+    index: RefCell<Option<Vec<String>>>,
+}
+
+impl ProjectFileAutoCompleter {
+    pub fn new() -> Self {
+        Self::in_dir(env::current_dir().unwrap_or_default())
+    }
+
+    pub fn in_dir<P: AsRef<Path>>(root: P) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            index: RefCell::new(None),
+        }
+    }
+
+    fn build_index(&self) -> Vec<String> {
+        let mut files = vec![];
+        let mut stack = vec![self.root.clone()];
+
+        while let Some(dir) = stack.pop() {
+            let entries = match dir.read_dir() {
+                Ok(entries) => entries,
+                // unreadable directories are skipped
+                Err(_) => continue,
+            };
+
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                let path = entry.path();
+                let file_type = match entry.file_type() {
+                    Ok(file_type) => file_type,
+                    Err(_) => continue,
+                };
+
+                if file_type.is_dir() {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    if !IGNORED_DIRECTORIES.contains(&name.as_str()) {
+                        stack.push(path);
+                    }
+                } else if file_type.is_file() {
+                    if files.len() >= MAX_INDEXED_FILES {
+                        return files;
+                    }
+
+                    let relative = path.strip_prefix(&self.root).unwrap_or(&path);
+                    files.push(relative.to_string_lossy().to_string());
+                }
+            }
+        }
+
+        files.sort();
+        files
+    }
+}
+
+impl AutoCompleter for ProjectFileAutoCompleter {
+    fn get_options(&self, s: &str) -> Vec<Completion> {
+        if self.index.borrow().is_none() {
+            self.index.replace(Some(self.build_index()));
+        }
+
+        let index = self.index.borrow();
+        let mut options = index
+            .iter()
+            .flatten()
+            .filter_map(|path| MatchMode::Fuzzy.complete(path, s))
+            .collect::<Vec<Completion>>();
+
+        // shallower files first when equally good
+        options.sort_by(|a, b| b.score().cmp(&a.score()).then(a.option().len().cmp(&b.option().len())));
+
+        options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use crate::autocomplete::project::ProjectFileAutoCompleter;
+    use crate::autocomplete::AutoCompleter;
+
+    fn project(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("edish_project_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+
+        fs::create_dir_all(root.join("src").join("panels")).unwrap();
+        fs::create_dir_all(root.join("target").join("debug")).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::write(root.join("README.md"), "").unwrap();
+        fs::write(root.join("src").join("main.rs"), "").unwrap();
+        fs::write(root.join("src").join("panels").join("edit.rs"), "").unwrap();
+        fs::write(root.join("target").join("debug").join("main.d"), "").unwrap();
+        fs::write(root.join(".git").join("HEAD"), "").unwrap();
+
+        root
+    }
+
+    fn options(completer: &ProjectFileAutoCompleter, s: &str) -> Vec<String> {
+        completer.get_options(s).iter().map(|o| o.option().clone()).collect()
+    }
+
+    #[test]
+    fn indexes_nested_files_without_ignored() {
+        let root = project("nested");
+        let completer = ProjectFileAutoCompleter::in_dir(&root);
+
+        let mut found = options(&completer, "");
+        found.sort();
+
+        let expected = vec![
+            "README.md".to_string(),
+            PathBuf::from("src").join("main.rs").to_string_lossy().to_string(),
+            PathBuf::from("src").join("panels").join("edit.rs").to_string_lossy().to_string(),
+        ];
+
+        assert_eq!(found, expected);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn deep_file_from_name() {
+        let root = project("deep");
+        let completer = ProjectFileAutoCompleter::in_dir(&root);
+
+        assert_eq!(
+            options(&completer, "edit").first(),
+            Some(&PathBuf::from("src").join("panels").join("edit.rs").to_string_lossy().to_string())
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn index_built_once() {
+        let root = project("once");
+        let completer = ProjectFileAutoCompleter::in_dir(&root);
+        assert_eq!(options(&completer, "main").len(), 1);
+
+        fs::write(root.join("main.txt"), "").unwrap();
+
+        assert_eq!(options(&completer, "main").len(), 1);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,101 @@
+use std::env;
+
+use crate::autocomplete::{fuzzy_match, AutoCompleter, Completion};
+
+// recently opened files first, then files in current directory
+pub struct RecentFilesAutoCompleter {
+    candidates: Vec<String>,
+}
+
+impl RecentFilesAutoCompleter {
+    pub fn new(recent: Vec<String>) -> Self {
+        let mut candidates = recent;
+
+        let entries = env::current_dir().and_then(|dir| dir.read_dir());
+        if let Ok(entries) = entries {
+            let mut files = entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_type().map(|t| t.is_file()).unwrap_or(false))
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .filter(|name| !candidates.contains(name))
+                .collect::<Vec<String>>();
+
+            files.sort();
+            candidates.extend(files);
+        }
+
+        Self { candidates }
+    }
+
+    #[cfg(test)]
+    fn with_candidates(candidates: Vec<String>) -> Self {
+        Self { candidates }
+    }
+}
+
+impl AutoCompleter for RecentFilesAutoCompleter {
+    fn get_options(&self, s: &str) -> Vec<Completion> {
+        let mut matches = self
+            .candidates
+            .iter()
+            .enumerate()
+            .filter_map(|(order, name)| fuzzy_match(name, s).map(|rank| (rank, order, name)))
+            .collect::<Vec<((usize, usize), usize, &String)>>();
+
+        // tighter matches first, recency breaks ties
+        matches.sort_by(|a, b| a.0 .1.cmp(&b.0 .1).then(a.1.cmp(&b.1)));
+
+        matches
+            .iter()
+            .map(|(_, _, name)| {
+                let remaining = match name.starts_with(s) {
+                    true => String::from(&name[s.len()..]),
+                    false => String::new(),
+                };
+
+                Completion::new(name.to_string(), remaining)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::autocomplete::recent::RecentFilesAutoCompleter;
+    use crate::autocomplete::AutoCompleter;
+
+    fn completer() -> RecentFilesAutoCompleter {
+        RecentFilesAutoCompleter::with_candidates(vec![
+            "src/main.rs".to_string(),
+            "README.md".to_string(),
+            "src/app.rs".to_string(),
+        ])
+    }
+
+    #[test]
+    fn empty_input_keeps_recent_order() {
+        let options = completer().get_options("");
+
+        assert_eq!(
+            options.iter().map(|o| o.option().as_str()).collect::<Vec<&str>>(),
+            vec!["src/main.rs", "README.md", "src/app.rs"]
+        );
+    }
+
+    #[test]
+    fn fuzzy_match_file() {
+        let options = completer().get_options("app");
+
+        assert_eq!(options.len(), 1);
+        assert_eq!(options[0].option(), "src/app.rs");
+        assert_eq!(options[0].remaining(), "");
+    }
+
+    #[test]
+    fn prefix_match_can_be_completed() {
+        let options = completer().get_options("src/m");
+
+        assert_eq!(options[0].option(), "src/main.rs");
+        assert_eq!(options[0].remaining(), "ain.rs");
+    }
+}
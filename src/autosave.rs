@@ -0,0 +1,552 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Duration;
+
+use crossterm::event::KeyCode;
+
+use crate::commands::Manager;
+use crate::locale::Text;
+use crate::panels::{EDIT_PANEL_TYPE_ID, INPUT_PANEL_TYPE_ID};
+use crate::positions::{make_private, private_path};
+use crate::timer::Timer;
+use crate::{AppState, Panels, TextPanel};
+
+pub const AUTOSAVE_ARG: &str = "--autosave";
+
+// followed by id of process that wrote the files
+const RECOVERY_PREFIX: &str = "recovery-";
+
+pub struct Autosave {
+    timer: Timer,
+    recovery_dir: PathBuf,
+    // contents last written for each unnamed buffer, by buffer id, so unchanged ones aren't written again
+    recovered: HashMap<u64, u64>,
+}
+
+impl Autosave {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            timer: Timer::new(interval),
+            recovery_dir: recovery_dir(process::id()),
+            recovered: HashMap::new(),
+        }
+    }
+
+    // interval in seconds following the autosave flag, 0 would save on every tick so it's ignored
+    pub fn from_args<T: Iterator<Item = String>>(args: T) -> Option<Self> {
+        let mut args = args.skip_while(|arg| arg != AUTOSAVE_ARG).skip(1);
+
+        args.next()
+            .and_then(|seconds| seconds.parse::<u64>().ok())
+            .filter(|seconds| *seconds > 0)
+            .map(|seconds| Autosave::new(Duration::from_secs(seconds)))
+    }
+
+    #[allow(dead_code)]
+    pub fn interval(&self) -> Duration {
+        self.timer.interval()
+    }
+
+    #[allow(dead_code)]
+    pub fn set_recovery_dir(&mut self, dir: PathBuf) {
+        self.recovery_dir = dir;
+    }
+
+    pub fn tick(&mut self, state: &mut AppState, panels: &mut Panels) {
+        if self.timer.ready() {
+            self.save_all(state, panels);
+        }
+    }
+
+    // files with a path are saved in place
+    // unnamed buffers go to recovery directory and stay modified
+    // background buffers of each panel are included, rotating through them ends back on current one
+    pub fn save_all(&mut self, state: &mut AppState, panels: &mut Panels) {
+        let mut saved = 0;
+        let mut unnamed = HashSet::new();
+        // split views hold copies of one buffer, first one found is saved
+        let mut views = HashSet::new();
+
+        for index in 0..panels.len() {
+            let panel = match panels.get_mut(index) {
+                Some(panel) if panel.panel_type() == EDIT_PANEL_TYPE_ID && panel.any_modified() => panel,
+                _ => continue,
+            };
+
+            for _ in 0..panel.buffer_count() {
+                if panel.modified() && panel.view_id().is_none_or(|id| views.insert(id)) {
+                    if panel.file_path().is_none() {
+                        unnamed.insert(panel.buffer_id());
+                    }
+
+                    match self.save_buffer(panel) {
+                        Ok(true) => saved += 1,
+                        Ok(false) => (),
+                        Err(err) => state.add_error(Text::new("autosave.failed", "Autosave failed. {0}").arg(err)),
+                    }
+                }
+
+                panel.switch_to_buffer(1);
+            }
+        }
+
+        // buffers saved under a name or closed since have nothing left to recover
+        let recovery_dir = &self.recovery_dir;
+        self.recovered.retain(|buffer_id, _| {
+            let keep = unnamed.contains(buffer_id);
+            if !keep {
+                fs::remove_file(recovery_file(recovery_dir, *buffer_id)).unwrap_or_default();
+            }
+            keep
+        });
+
+        if saved > 0 {
+            state.add_info(Text::new("autosave.saved", "Autosaved {0} buffer(s).").arg(saved));
+        }
+    }
+
+    // false when unnamed buffer is unchanged since last written
+    fn save_buffer(&mut self, panel: &mut TextPanel) -> Result<bool, String> {
+        if panel.file_path().is_some() {
+            return panel.write_file().map(|_| true);
+        }
+
+        let contents = panel.contents();
+        let hash = text_hash(&contents);
+        if self.recovered.get(&panel.buffer_id()) == Some(&hash) {
+            return Ok(false);
+        }
+
+        make_private(&self.recovery_dir)?;
+        fs::write(recovery_file(&self.recovery_dir, panel.buffer_id()), contents)
+            .map_err(|err| format!("Could not write recovery file. {}", err))?;
+        self.recovered.insert(panel.buffer_id(), hash);
+
+        Ok(true)
+    }
+
+    // nothing to recover after closing normally
+    pub fn remove_recovery_files(&self) {
+        fs::remove_dir_all(&self.recovery_dir).unwrap_or_default();
+    }
+}
+
+fn text_hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn recovery_file(dir: &Path, buffer_id: u64) -> PathBuf {
+    dir.join(format!("buffer-{}.txt", buffer_id))
+}
+
+fn recovery_dir(pid: u32) -> PathBuf {
+    private_path(&format!("{}{}", RECOVERY_PREFIX, pid))
+}
+
+// without /proc there's no telling, so a session that may still be running is left alone
+fn process_running(pid: u32) -> bool {
+    let proc = Path::new("/proc");
+    !proc.exists() || proc.join(pid.to_string()).exists()
+}
+
+// directories written by sessions that ended without closing normally
+pub fn leftover_recovery_dirs() -> Vec<PathBuf> {
+    let own = recovery_dir(process::id());
+    let prefix = own
+        .file_name()
+        .map(|name| name.to_string_lossy().trim_end_matches(char::is_numeric).to_string())
+        .unwrap_or_default();
+
+    match own.parent().map(fs::read_dir) {
+        Some(Ok(entries)) => entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                match name.strip_prefix(&prefix).map(|pid| pid.parse::<u32>()) {
+                    Some(Ok(pid)) => pid != process::id() && !process_running(pid),
+                    _ => false,
+                }
+            })
+            .map(|entry| entry.path())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+// each file becomes an unnamed, modified buffer in first edit panel, then is removed
+// files that can't be read are kept for next start, directories someone else could have written to are skipped
+pub fn recover_buffers(dirs: Vec<PathBuf>, state: &mut AppState, panels: &mut Panels, commands: &mut Manager) {
+    let index = match (0..panels.len()).find(|index| panels.get(*index).map(|panel| panel.panel_type()) == Some(EDIT_PANEL_TYPE_ID)) {
+        None => return,
+        Some(index) => index,
+    };
+
+    let mut recovered = 0;
+
+    for dir in dirs {
+        if let Err(err) = make_private(&dir) {
+            state.add_error(Text::new("autosave.recovery_skipped", "Skipped recovery files. {0}").arg(err));
+            continue;
+        }
+
+        let mut files: Vec<PathBuf> = match fs::read_dir(&dir) {
+            Err(_) => continue,
+            Ok(entries) => entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect(),
+        };
+        files.sort();
+
+        for file in files {
+            let text = match fs::read_to_string(&file) {
+                Err(err) => {
+                    state.add_error(
+                        Text::new("autosave.recovery_unreadable", "Could not read recovery file {0}. {1}")
+                            .arg(format!("{:?}", file))
+                            .arg(err),
+                    );
+                    continue;
+                }
+                Ok(text) => text,
+            };
+
+            let panel = match panels.get_mut(index) {
+                None => return,
+                Some(panel) => panel,
+            };
+
+            let blank = panel.file_path().is_none() && !panel.modified() && panel.contents().is_empty();
+            if !blank {
+                panel.new_buffer(KeyCode::Null, state, commands);
+            }
+
+            panel.set_text(text);
+            panel.set_modified(true);
+            recovered += 1;
+
+            fs::remove_file(&file).unwrap_or_default();
+        }
+
+        // only empty once every file was recovered
+        fs::remove_dir(&dir).unwrap_or_default();
+    }
+
+    if recovered > 0 {
+        state.add_warning(
+            Text::new("autosave.recovered", "Recovered {0} unnamed buffer(s) from a session that didn't close. Save them to keep them.")
+                .arg(recovered),
+        );
+    }
+}
+
+// saves modified files when another panel becomes active
+// terminal losing focus is handled by AppState::set_focused
+#[derive(Default)]
+pub struct FocusSave {
+    last_panel: Option<usize>,
+}
+
+impl FocusSave {
+    pub fn tick(&mut self, state: &mut AppState, panels: &mut Panels) {
+        let active = state.active_panel();
+
+        // prompts take focus only for a moment
+        match panels.get(active) {
+            Some(panel) if panel.panel_type() == INPUT_PANEL_TYPE_ID => return,
+            _ => (),
+        }
+
+        if self.last_panel.replace(active).is_some_and(|last| last != active) {
+            save_modified_files(state, panels);
+        }
+    }
+}
+
+// unnamed buffers are left alone, they'd need a prompt
+pub fn save_modified_files(state: &mut AppState, panels: &mut Panels) {
+    let mut saved = 0;
+
+    for index in 0..panels.len() {
+        match panels.get_mut(index) {
+            Some(panel)
+                if panel.panel_type() == EDIT_PANEL_TYPE_ID && panel.modified() && panel.file_path().is_some() =>
+            {
+                match panel.write_file() {
+                    Ok(()) => saved += 1,
+                    Err(err) => state.add_error(
+                        Text::new("autosave.leave_failed", "Could not save on leaving panel. {0}").arg(err),
+                    ),
+                }
+            }
+            _ => (),
+        }
+    }
+
+    if saved > 0 {
+        state.add_info(Text::new("autosave.leave_saved", "Saved {0} buffer(s) on leaving panel.").arg(saved));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use crossterm::event::KeyCode;
+
+    use crate::autosave::{recover_buffers, Autosave, FocusSave};
+    use crate::positions::make_private;
+    use crate::commands::Manager;
+    use crate::{AppState, Panels};
+
+    #[test]
+    fn from_args() {
+        let args = vec!["edish", "--autosave", "30"].into_iter().map(String::from);
+
+        assert_eq!(Autosave::from_args(args).unwrap().interval(), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn from_args_missing_or_invalid() {
+        let args = vec!["edish"].into_iter().map(String::from);
+        assert!(Autosave::from_args(args).is_none());
+
+        let args = vec!["edish", "--autosave", "soon"].into_iter().map(String::from);
+        assert!(Autosave::from_args(args).is_none());
+
+        let args = vec!["edish", "--autosave", "0"].into_iter().map(String::from);
+        assert!(Autosave::from_args(args).is_none());
+    }
+
+    #[test]
+    fn saves_modified_buffers() {
+        let dir = std::env::temp_dir().join(format!("edish_autosave_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        app.add_panel_to_active_split(crossterm::event::KeyCode::Null, &mut panels, &mut commands);
+
+        let path = dir.join("named.garnish");
+        let named = panels.get_mut(1).unwrap();
+        named.set_file_path(PathBuf::from(&path));
+        named.set_text("named");
+        named.set_modified(true);
+
+        let unnamed = panels.get_mut(3).unwrap();
+        unnamed.set_text("unnamed");
+        unnamed.set_modified(true);
+        let recovery_file = dir.join("recovery").join(format!("buffer-{}.txt", unnamed.buffer_id()));
+
+        let mut autosave = Autosave::new(Duration::ZERO);
+        autosave.set_recovery_dir(dir.join("recovery"));
+        autosave.tick(&mut app, &mut panels);
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "named");
+        assert!(!panels.get(1).unwrap().modified());
+        assert_eq!(std::fs::read_to_string(&recovery_file).unwrap(), "unnamed");
+        assert!(panels.get(3).unwrap().modified());
+        assert_eq!(app.get_messages().last().unwrap().text(), "Autosaved 2 buffer(s).");
+
+        // unnamed buffer is still modified but unchanged, nothing new to write
+        let messages = app.get_messages().len();
+        autosave.tick(&mut app, &mut panels);
+        assert_eq!(app.get_messages().len(), messages);
+
+        panels.get_mut(3).unwrap().set_text("unnamed again");
+        autosave.tick(&mut app, &mut panels);
+        assert_eq!(app.get_messages().last().unwrap().text(), "Autosaved 1 buffer(s).");
+        assert_eq!(std::fs::read_to_string(&recovery_file).unwrap(), "unnamed again");
+
+        autosave.remove_recovery_files();
+        assert!(!dir.join("recovery").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn saves_background_buffers() {
+        let dir = std::env::temp_dir().join(format!("edish_autosave_background_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        let path = dir.join("named.garnish");
+        let panel = panels.get_mut(1).unwrap();
+        panel.set_file_path(PathBuf::from(&path));
+        panel.set_text("named");
+        panel.set_modified(true);
+        panel.new_buffer(KeyCode::Null, &mut app, &mut commands);
+        panel.set_text("first");
+        panel.set_modified(true);
+        let first = panel.buffer_id();
+        panel.new_buffer(KeyCode::Null, &mut app, &mut commands);
+        panel.set_text("second");
+        panel.set_modified(true);
+        let second = panel.buffer_id();
+
+        let recovery = dir.join("recovery");
+        let mut autosave = Autosave::new(Duration::ZERO);
+        autosave.set_recovery_dir(recovery.clone());
+        autosave.tick(&mut app, &mut panels);
+
+        // each buffer has its own file, switching buffers doesn't overwrite another's
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "named");
+        assert_eq!(std::fs::read_to_string(recovery.join(format!("buffer-{}.txt", first))).unwrap(), "first");
+        assert_eq!(std::fs::read_to_string(recovery.join(format!("buffer-{}.txt", second))).unwrap(), "second");
+        assert_eq!(app.get_messages().last().unwrap().text(), "Autosaved 3 buffer(s).");
+
+        // rotation ends back on current buffer
+        let panel = panels.get_mut(1).unwrap();
+        assert_eq!(panel.contents(), "second");
+
+        // saved under a name, recovery file is no longer needed
+        panel.set_file_path(dir.join("second.garnish"));
+        autosave.tick(&mut app, &mut panels);
+        assert!(!recovery.join(format!("buffer-{}.txt", second)).exists());
+        assert!(recovery.join(format!("buffer-{}.txt", first)).exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn saves_split_views_once() {
+        let dir = std::env::temp_dir().join(format!("edish_autosave_views_{}", std::process::id()));
+
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        app.add_panel_to_active_split(KeyCode::Null, &mut panels, &mut commands);
+
+        let panel = panels.get_mut(1).unwrap();
+        panel.set_text("unnamed");
+        panel.set_modified(true);
+        panels.share_view(1, 3).unwrap();
+        assert!(panels.get(3).unwrap().modified());
+
+        let mut autosave = Autosave::new(Duration::ZERO);
+        autosave.set_recovery_dir(dir.clone());
+        autosave.tick(&mut app, &mut panels);
+
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        assert_eq!(app.get_messages().last().unwrap().text(), "Autosaved 1 buffer(s).");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn keeps_unreadable_recovery_files() {
+        let dir = std::env::temp_dir().join(format!("edish_recover_unreadable_{}", std::process::id()));
+        let recovery = dir.join("recovery");
+        std::fs::create_dir_all(&dir).unwrap();
+        make_private(&recovery).unwrap();
+        std::fs::write(recovery.join("buffer-1.txt"), "first").unwrap();
+        std::fs::write(recovery.join("buffer-2.txt"), [0xff, 0xfe, 0x00]).unwrap();
+
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        recover_buffers(vec![recovery.clone()], &mut app, &mut panels, &mut commands);
+
+        assert_eq!(panels.get(1).unwrap().contents(), "first");
+        assert!(!recovery.join("buffer-1.txt").exists());
+        assert!(recovery.join("buffer-2.txt").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn recovers_leftover_buffers() {
+        let dir = std::env::temp_dir().join(format!("edish_autosave_recover_{}", std::process::id()));
+        let recovery = dir.join("recovery");
+        std::fs::create_dir_all(&dir).unwrap();
+        make_private(&recovery).unwrap();
+        std::fs::write(recovery.join("buffer-1.txt"), "first").unwrap();
+        std::fs::write(recovery.join("buffer-3.txt"), "second").unwrap();
+
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        recover_buffers(vec![recovery.clone()], &mut app, &mut panels, &mut commands);
+
+        // blank buffer is used for first file, second gets a new buffer
+        let panel = panels.get(1).unwrap();
+        assert_eq!(panel.contents(), "second");
+        assert!(panel.modified());
+        assert!(panel.any_modified());
+        assert_eq!(panel.file_paths().len(), 0);
+        assert!(!recovery.exists());
+        assert_eq!(
+            app.get_messages().last().unwrap().text(),
+            "Recovered 2 unnamed buffer(s) from a session that didn't close. Save them to keep them."
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn nothing_to_save() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        Autosave::new(Duration::ZERO).tick(&mut app, &mut panels);
+
+        assert!(app.get_messages().is_empty());
+    }
+
+    #[test]
+    fn saves_named_buffers_when_panel_changes() {
+        let dir = std::env::temp_dir().join(format!("edish_focus_save_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+        app.add_panel_to_active_split(crossterm::event::KeyCode::Null, &mut panels, &mut commands);
+
+        let path = dir.join("named.garnish");
+        let named = panels.get_mut(1).unwrap();
+        named.set_file_path(PathBuf::from(&path));
+        named.set_text("named");
+        named.set_modified(true);
+
+        let unnamed = panels.get_mut(3).unwrap();
+        unnamed.set_text("unnamed");
+        unnamed.set_modified(true);
+
+        let mut focus_save = FocusSave::default();
+        app.set_active_panel(1);
+        focus_save.tick(&mut app, &mut panels);
+        focus_save.tick(&mut app, &mut panels);
+
+        // same panel, nothing saved yet
+        assert!(!path.exists());
+
+        app.set_active_panel(3);
+        focus_save.tick(&mut app, &mut panels);
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "named");
+        assert!(!panels.get(1).unwrap().modified());
+        assert!(panels.get(3).unwrap().modified());
+        assert_eq!(app.get_messages().last().unwrap().text(), "Saved 1 buffer(s) on leaving panel.");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
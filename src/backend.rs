@@ -0,0 +1,90 @@
+use std::io;
+
+use tui::backend::{Backend, CrosstermBackend, TestBackend};
+use tui::buffer::{Buffer, Cell};
+use tui::layout::Rect;
+
+// real terminal when run normally, memory for tests and scripted runs
+pub enum EditorBackend {
+    Terminal(CrosstermBackend<io::Stdout>),
+    Memory(TestBackend),
+}
+
+impl EditorBackend {
+    pub fn terminal() -> Self {
+        EditorBackend::Terminal(CrosstermBackend::new(io::stdout()))
+    }
+
+    pub fn memory(width: u16, height: u16) -> Self {
+        EditorBackend::Memory(TestBackend::new(width, height))
+    }
+
+    // only memory keeps what was drawn
+    pub fn buffer(&self) -> Option<&Buffer> {
+        match self {
+            EditorBackend::Terminal(_) => None,
+            EditorBackend::Memory(backend) => Some(backend.buffer()),
+        }
+    }
+}
+
+impl Backend for EditorBackend {
+    fn draw<'a, I>(&mut self, content: I) -> Result<(), io::Error>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        match self {
+            EditorBackend::Terminal(backend) => backend.draw(content),
+            EditorBackend::Memory(backend) => backend.draw(content),
+        }
+    }
+
+    fn hide_cursor(&mut self) -> Result<(), io::Error> {
+        match self {
+            EditorBackend::Terminal(backend) => backend.hide_cursor(),
+            EditorBackend::Memory(backend) => backend.hide_cursor(),
+        }
+    }
+
+    fn show_cursor(&mut self) -> Result<(), io::Error> {
+        match self {
+            EditorBackend::Terminal(backend) => backend.show_cursor(),
+            EditorBackend::Memory(backend) => backend.show_cursor(),
+        }
+    }
+
+    fn get_cursor(&mut self) -> Result<(u16, u16), io::Error> {
+        match self {
+            EditorBackend::Terminal(backend) => backend.get_cursor(),
+            EditorBackend::Memory(backend) => backend.get_cursor(),
+        }
+    }
+
+    fn set_cursor(&mut self, x: u16, y: u16) -> Result<(), io::Error> {
+        match self {
+            EditorBackend::Terminal(backend) => backend.set_cursor(x, y),
+            EditorBackend::Memory(backend) => backend.set_cursor(x, y),
+        }
+    }
+
+    fn clear(&mut self) -> Result<(), io::Error> {
+        match self {
+            EditorBackend::Terminal(backend) => backend.clear(),
+            EditorBackend::Memory(backend) => backend.clear(),
+        }
+    }
+
+    fn size(&self) -> Result<Rect, io::Error> {
+        match self {
+            EditorBackend::Terminal(backend) => backend.size(),
+            EditorBackend::Memory(backend) => backend.size(),
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), io::Error> {
+        match self {
+            EditorBackend::Terminal(backend) => backend.flush(),
+            EditorBackend::Memory(backend) => backend.flush(),
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::commands::{KeyBindings, Manager, GLOBAL_KEYS_SECTION};
+use crate::panels::commands::format_key;
+use crate::script::{run_key_script, DEFAULT_SCRIPT_SIZE};
+
+pub const EXPORT_ARG: &str = "--export";
+pub const SIZE_ARG: &str = "--size";
+
+// run instead of editor, output goes to stdout
+#[derive(Debug, Eq, PartialEq)]
+pub enum Subcommand {
+    ExportKeys,
+    // replay keys without a terminal, '-' reads them from stdin
+    Script(PathBuf, (u16, u16)),
+}
+
+impl Subcommand {
+    // first argument when it isn't a flag for the editor
+    pub fn from_args<T: Iterator<Item = String>>(args: T) -> Option<Result<Self, String>> {
+        let mut args = args.skip(1);
+
+        match args.next() {
+            None => None,
+            Some(arg) if arg.starts_with("--") => None,
+            Some(arg) => Some(match arg.as_str() {
+                "keys" => match args.next() {
+                    Some(flag) if flag == EXPORT_ARG => Ok(Subcommand::ExportKeys),
+                    _ => Err(format!("Expected {} after keys.", EXPORT_ARG)),
+                },
+                "script" => match (args.next(), args.next(), args.next()) {
+                    (None, _, _) => Err("Expected key script path after script, or - for stdin.".to_string()),
+                    (Some(path), None, _) => Ok(Subcommand::Script(PathBuf::from(path), DEFAULT_SCRIPT_SIZE)),
+                    (Some(path), Some(flag), Some(size)) if flag == SIZE_ARG => {
+                        parse_size(&size).map(|size| Subcommand::Script(PathBuf::from(path), size))
+                    }
+                    (Some(_), Some(flag), _) => Err(format!("Expected {} WIDTHxHEIGHT, got {:?}.", SIZE_ARG, flag)),
+                },
+                _ => Err(format!("Unknown command {:?}. Available commands: keys, script", arg)),
+            }),
+        }
+    }
+
+    pub fn run(&self) -> Result<String, String> {
+        match self {
+            Subcommand::ExportKeys => Ok(export_keys(&Manager::default())),
+            Subcommand::Script(path, (width, height)) => {
+                let text = match path.to_str() {
+                    Some("-") => io::read_to_string(io::stdin()).map_err(|err| format!("Could not read stdin. {}", err)),
+                    _ => fs::read_to_string(path).map_err(|err| format!("Could not read {:?}. {}", path, err)),
+                }?;
+
+                run_key_script(&text, *width, *height)
+            }
+        }
+    }
+}
+
+// screen size as WIDTHxHEIGHT, e.g. 80x24
+fn parse_size(size: &str) -> Result<(u16, u16), String> {
+    match size.split_once('x').map(|(width, height)| (width.parse::<u16>(), height.parse::<u16>())) {
+        Some((Ok(width), Ok(height))) if width > 0 && height > 0 => Ok((width, height)),
+        _ => Err(format!("Invalid size {:?}. Expected WIDTHxHEIGHT, e.g. 80x24.", size)),
+    }
+}
+
+// one binding per line, tab separated: panel type, key sequence, command name
+pub fn export_keys(commands: &Manager) -> String {
+    let mut lines = format_bindings(GLOBAL_KEYS_SECTION, commands.global_key_bindings());
+
+    for (id, bindings) in commands.panel_key_bindings() {
+        lines.extend(format_bindings(id, bindings));
+    }
+
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+fn format_bindings(section: &str, bindings: KeyBindings) -> Vec<String> {
+    bindings
+        .iter()
+        .map(|(keys, details)| {
+            let keys = keys.iter().map(format_key).collect::<Vec<String>>().join(" -> ");
+
+            format!("{}\t{}\t{}", section, keys, details.name())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::cli::{export_keys, Subcommand};
+    use crate::commands::Manager;
+
+    fn args(args: &[&str]) -> impl Iterator<Item = String> {
+        args.iter().map(|arg| arg.to_string()).collect::<Vec<String>>().into_iter()
+    }
+
+    #[test]
+    fn no_subcommand() {
+        assert!(Subcommand::from_args(args(&["edish"])).is_none());
+        assert!(Subcommand::from_args(args(&["edish", "--log", "edish.log"])).is_none());
+    }
+
+    #[test]
+    fn keys_export() {
+        assert_eq!(
+            Subcommand::from_args(args(&["edish", "keys", "--export"])),
+            Some(Ok(Subcommand::ExportKeys))
+        );
+        assert!(Subcommand::from_args(args(&["edish", "keys"])).unwrap().is_err());
+    }
+
+    #[test]
+    fn script_path_and_size() {
+        assert_eq!(
+            Subcommand::from_args(args(&["edish", "script", "keys.txt"])),
+            Some(Ok(Subcommand::Script(PathBuf::from("keys.txt"), (80, 24))))
+        );
+        assert_eq!(
+            Subcommand::from_args(args(&["edish", "script", "-", "--size", "100x30"])),
+            Some(Ok(Subcommand::Script(PathBuf::from("-"), (100, 30))))
+        );
+        assert!(Subcommand::from_args(args(&["edish", "script"])).unwrap().is_err());
+        assert!(Subcommand::from_args(args(&["edish", "script", "-", "--size", "0x30"])).unwrap().is_err());
+        assert!(Subcommand::from_args(args(&["edish", "script", "-", "--wide"])).unwrap().is_err());
+    }
+
+    #[test]
+    fn unknown_subcommand() {
+        assert!(Subcommand::from_args(args(&["edish", "fmt", "main.rs"])).unwrap().is_err());
+    }
+
+    #[test]
+    fn exported_keys_include_global_and_panel_commands() {
+        let exported = export_keys(&Manager::default());
+
+        assert!(exported.lines().any(|line| line == "Global\tC + p -> m\tJump To Messages"));
+        assert!(exported.lines().any(|line| line == "Edit\tC + o\tOpen File"));
+    }
+}
@@ -1,13 +1,16 @@
+use std::time::{Duration, Instant};
+
 use crossterm::event::{KeyCode, KeyModifiers};
 
 use crate::app::StateChangeRequest;
-use crate::commands::{alt_catch_all, alt_key, code, shift_alt_key, shift_catch_all, CommandKey};
+use crate::commands::{alt_catch_all, alt_key, code, ctrl_alt_key, shift_alt_key, shift_catch_all, shift_key, CommandKey, KeyBindings};
 use crate::panels::{
-    InputPanel, PanelTypeID, COMMANDS_PANEL_TYPE_ID, EDIT_PANEL_TYPE_ID, INPUT_PANEL_TYPE_ID,
-    MESSAGE_PANEL_TYPE_ID,
+    DiffPanel, EditMode, HelpPanel, HexPanel, InputPanel, MessagesPanel, OutputPanel, PanelTypeID, TerminalPanel,
+    COMMANDS_PANEL_TYPE_ID, DIFF_PANEL_TYPE_ID, EDIT_PANEL_TYPE_ID, HELP_PANEL_TYPE_ID, HEX_PANEL_TYPE_ID, INPUT_PANEL_TYPE_ID,
+    MESSAGE_PANEL_TYPE_ID, OUTPUT_PANEL_TYPE_ID, TERMINAL_PANEL_TYPE_ID, WELCOME_PANEL_TYPE_ID,
 };
 use crate::{catch_all, ctrl_key, global_commands, AppState, CommandDetails, CommandKeyId, Commands, Panels, TextPanel, key};
-use crate::panels::commands::{next_command, previous_command};
+use crate::panels::commands::{next_command, previous_command, run_selected_command};
 
 type PanelCommand = fn(&mut TextPanel, KeyCode, &mut AppState, &mut Manager) -> (bool, Vec<StateChangeRequest>);
 
@@ -17,12 +20,41 @@ pub const EDIT_COMMAND_INDEX: usize = 0;
 pub const INPUT_COMMAND_INDEX: usize = 1;
 pub const MESSAGES_COMMAND_INDEX: usize = 2;
 pub const COMMANDS_COMMAND_INDEX: usize = 3;
+// used in place of edit commands while panel is in normal mode
+pub const NORMAL_MODE_COMMAND_INDEX: usize = 4;
+pub const TERMINAL_COMMAND_INDEX: usize = 5;
+pub const OUTPUT_COMMAND_INDEX: usize = 6;
+pub const DIFF_COMMAND_INDEX: usize = 7;
+pub const HEX_COMMAND_INDEX: usize = 8;
+pub const HELP_COMMAND_INDEX: usize = 9;
+
+pub const NORMAL_MODE_COMMANDS_ID: &str = "Edit Normal Mode";
+
+// section name for global commands in exported keymaps
+pub const GLOBAL_KEYS_SECTION: &str = "Global";
+
+pub const DEFAULT_SEQUENCE_TIMEOUT: Duration = Duration::from_secs(3);
+
+// keeps a mistyped count from stalling the editor
+pub const MAX_COUNT: usize = 9999;
 
 pub struct Manager {
     state_commands: Commands<GlobalAction>,
     command_stack: Vec<usize>,
     commands: Vec<(&'static str, Commands<PanelCommand>)>,
     progress: Vec<CommandKeyId>,
+    last_key: Instant,
+    // unfinished sequences are dropped after this long without a key
+    sequence_timeout: Option<Duration>,
+    // typed before a command to repeat it
+    count: Option<usize>,
+    // count given to command being run, whatever is left after it's done is used to repeat it
+    current_count: Option<usize>,
+    // mode of active panel, picks which edit commands are used
+    edit_mode: EditMode,
+    // last active panel besides commands and prompt, with index of its commands
+    // commands panel lists and runs these
+    menu_target: Option<(usize, usize)>,
 }
 
 impl Default for Manager {
@@ -35,23 +67,46 @@ impl Default for Manager {
                 (INPUT_PANEL_TYPE_ID, make_input_commands().unwrap()),
                 (MESSAGE_PANEL_TYPE_ID, make_messages_commands().unwrap()),
                 (COMMANDS_PANEL_TYPE_ID, make_commands_commands().unwrap()),
+                (NORMAL_MODE_COMMANDS_ID, make_normal_mode_commands().unwrap()),
+                (TERMINAL_PANEL_TYPE_ID, make_terminal_commands().unwrap()),
+                (OUTPUT_PANEL_TYPE_ID, make_output_commands().unwrap()),
+                (DIFF_PANEL_TYPE_ID, make_diff_commands().unwrap()),
+                (HEX_PANEL_TYPE_ID, make_hex_commands().unwrap()),
+                (HELP_PANEL_TYPE_ID, make_help_commands().unwrap()),
             ],
             progress: vec![],
+            last_key: Instant::now(),
+            sequence_timeout: Some(DEFAULT_SEQUENCE_TIMEOUT),
+            count: None,
+            current_count: None,
+            edit_mode: EditMode::Modeless,
+            menu_target: None,
         }
     }
 }
 
 impl Manager {
     pub fn advance(&mut self, by: CommandKeyId, state: &mut AppState, panels: &mut Panels) {
+        // typing into a panel's search, modified keys still work as commands
+        if (by.mods - KeyModifiers::SHIFT).is_empty()
+            && panels
+                .get_mut(state.active_panel())
+                .map(|panel| panel.search_key(by.code))
+                .unwrap_or(false)
+        {
+            return;
+        }
+
         self.progress.push(by.clone());
+        self.last_key = Instant::now();
+        self.sync_edit_mode(state, panels);
+        self.sync_menu_target(state, panels);
 
         // state.add_info(format!("Checking stack {:?}", self.command_stack));
 
         let global_result = self.state_commands.get(&self.progress);
         let panel_result = self
-            .command_stack
-            .last()
-            .and_then(|i| self.commands.get(*i))
+            .panel_commands()
             .and_then(|(id, commands)| commands.get(&self.progress));
 
         let fallthrough = match panel_result {
@@ -67,7 +122,17 @@ impl Manager {
                     Some(action) => match panels.get_mut(state.active_panel()) {
                         None => true,
                         Some(panel) => {
-                            let (handled, changes) = action(panel, by.code.clone(), state, self);
+                            self.current_count = self.count.take();
+                            let (handled, mut changes) = action(panel, by.code, state, self);
+
+                            let repeats = self.current_count.take().unwrap_or(1).saturating_sub(1);
+                            for _ in 0..repeats {
+                                changes.extend(action(panel, by.code, state, self).1);
+                            }
+
+                            if handled {
+                                panels.propagate_edits(state.active_panel());
+                            }
                             state.handle_changes(changes, panels, self);
 
                             !handled
@@ -88,21 +153,53 @@ impl Manager {
                     }
                     match action {
                         None => (),
-                        Some(action) => action(state, by.code.clone(), panels, self),
+                        Some(action) => {
+                            // global commands aren't repeated
+                            self.count = None;
+                            action(state, by.code, panels, self)
+                        }
                     }
                 }
             }
         }
+
+        // command may have switched mode or panel
+        self.sync_edit_mode(state, panels);
+        self.sync_menu_target(state, panels);
     }
 
-    pub fn push_commands_for_panel(&mut self, type_id: PanelTypeID) {
-        self.command_stack.push(match type_id {
-            EDIT_PANEL_TYPE_ID => EDIT_COMMAND_INDEX,
-            INPUT_PANEL_TYPE_ID => INPUT_COMMAND_INDEX,
-            MESSAGE_PANEL_TYPE_ID => MESSAGES_COMMAND_INDEX,
-            COMMANDS_PANEL_TYPE_ID => COMMANDS_COMMAND_INDEX,
+    fn sync_edit_mode(&mut self, state: &AppState, panels: &Panels) {
+        self.edit_mode = panels
+            .get(state.active_panel())
+            .map(|panel| panel.edit_mode())
+            .unwrap_or(EditMode::Modeless);
+    }
+
+    fn sync_menu_target(&mut self, state: &AppState, panels: &Panels) {
+        let panel = match panels.get(state.active_panel()) {
+            Some(panel) if ![COMMANDS_PANEL_TYPE_ID, INPUT_PANEL_TYPE_ID].contains(&panel.panel_type()) => panel,
             _ => return,
-        });
+        };
+
+        if let Some(index) = commands_index(panel) {
+            self.menu_target = Some((state.active_panel(), index));
+        }
+    }
+
+    fn panel_commands(&self) -> Option<&(&'static str, Commands<PanelCommand>)> {
+        self.command_stack
+            .last()
+            .map(|i| match (*i, self.edit_mode) {
+                (EDIT_COMMAND_INDEX, EditMode::Normal) => NORMAL_MODE_COMMAND_INDEX,
+                (i, _) => i,
+            })
+            .and_then(|i| self.commands.get(i))
+    }
+
+    pub fn push_commands_for_panel(&mut self, type_id: PanelTypeID) {
+        if let Some(index) = type_commands_index(type_id) {
+            self.command_stack.push(index);
+        }
     }
 
     pub fn replace_top_with_panel(&mut self, type_id: PanelTypeID) {
@@ -118,15 +215,255 @@ impl Manager {
     }
 
     pub fn current_panel(&self) -> Option<(&str, &CommandKey<PanelCommand>)> {
-        self.command_stack
-            .last()
-            .and_then(|i| self.commands.get(*i))
+        self.panel_commands()
             .and_then(|(id, commands)| commands.get_node(&self.progress).map(|k| (*id, k)))
     }
 
+    // commands of menu target, active panel's until there is one
+    pub fn menu_commands(&self) -> Option<(&str, &CommandKey<PanelCommand>)> {
+        match self.menu_target {
+            None => self.current_panel(),
+            Some((_, index)) => self
+                .commands
+                .get(index)
+                .and_then(|(id, commands)| commands.get_node(&self.progress).map(|k| (*id, k))),
+        }
+    }
+
+    pub fn menu_target(&self) -> Option<usize> {
+        self.menu_target.map(|(panel, _)| panel)
+    }
+
+    // continuations of unfinished sequence, panel keys hide global ones same as when dispatching
+    pub fn next_keys(&self) -> Vec<(CommandKeyId, String)> {
+        if self.progress.is_empty() {
+            return vec![];
+        }
+
+        let mut keys = self.current_panel().map(|(_, command)| command.next_keys()).unwrap_or_default();
+
+        for (id, name) in self.current_global().map(|command| command.next_keys()).unwrap_or_default() {
+            if !keys.iter().any(|(existing, _)| *existing == id) {
+                keys.push((id, name));
+            }
+        }
+
+        keys
+    }
+
     pub fn last_progress(&self) -> Option<&CommandKeyId> {
         self.progress.last()
     }
+
+    pub fn progress(&self) -> &Vec<CommandKeyId> {
+        &self.progress
+    }
+
+    // count given to running command, left in place unlike take_count
+    pub fn current_count(&self) -> Option<usize> {
+        self.current_count
+    }
+
+    // count typed so far, not yet given to a command
+    pub fn pending_count(&self) -> Option<usize> {
+        self.count
+    }
+
+    // for commands that use count themselves instead of being repeated
+    #[allow(dead_code)]
+    pub fn take_count(&mut self) -> Option<usize> {
+        self.current_count.take()
+    }
+
+    // adds a digit to the end of the count given to the running command, leaving it for the next one
+    pub fn push_count_digit(&mut self, digit: usize) {
+        let count = self.current_count.take().unwrap_or(0).saturating_mul(10).saturating_add(digit);
+
+        self.count = match count {
+            0 => None,
+            count => Some(count.min(MAX_COUNT)),
+        };
+    }
+
+    pub fn set_sequence_timeout(&mut self, timeout: Option<Duration>) {
+        self.sequence_timeout = timeout;
+    }
+
+    // keys of an unfinished sequence, dropped so next key starts over
+    pub fn cancel_progress(&mut self) -> Option<Vec<CommandKeyId>> {
+        self.count = None;
+
+        match self.progress.is_empty() {
+            true => None,
+            false => Some(std::mem::take(&mut self.progress)),
+        }
+    }
+
+    // keys of a sequence left unfinished past the timeout, progress is reset when returned
+    pub fn expire_progress(&mut self) -> Option<Vec<CommandKeyId>> {
+        match self.sequence_timeout {
+            Some(timeout) if self.last_key.elapsed() >= timeout => self.count = None,
+            _ => (),
+        }
+
+        match self.sequence_timeout {
+            Some(timeout) if !self.progress.is_empty() && self.last_key.elapsed() >= timeout => {
+                Some(std::mem::take(&mut self.progress))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn global_key_bindings(&self) -> KeyBindings<'_> {
+        self.state_commands.key_bindings()
+    }
+
+    pub fn panel_key_bindings(&self) -> Vec<(&str, KeyBindings<'_>)> {
+        self.commands
+            .iter()
+            .map(|(id, commands)| (*id, commands.key_bindings()))
+            .collect()
+    }
+
+    // binds existing command in section to another key sequence, name of any replaced command is returned
+    pub fn bind(&mut self, section: &str, path: &Vec<CommandKeyId>, name: &str) -> Result<Option<String>, String> {
+        match section {
+            GLOBAL_KEYS_SECTION => bind_by_name(&mut self.state_commands, path, name),
+            _ => match self.commands.iter_mut().find(|(id, _)| *id == section) {
+                None => Err(format!("No commands for {:?}.", section)),
+                Some((_, commands)) => bind_by_name(commands, path, name),
+            },
+        }
+    }
+
+    pub fn command_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .state_commands
+            .commands()
+            .iter()
+            .map(|(details, _)| details.name().clone())
+            .collect();
+
+        match self.panel_commands() {
+            None => (),
+            Some((_, commands)) => {
+                names.extend(commands.commands().iter().map(|(details, _)| details.name().clone()))
+            }
+        }
+
+        names.sort();
+        names.dedup();
+
+        names
+    }
+
+    // menu target is made active first, so prompts and messages go to it
+    // false when it was closed or changed type since it was last active
+    pub fn execute_for_menu(&mut self, name: &str, state: &mut AppState, panels: &mut Panels) -> bool {
+        let (panel_index, index) = match self.menu_target {
+            None => return false,
+            Some(target) => target,
+        };
+
+        let action = match self.commands.get(index).and_then(|(_, commands)| commands.get_by_name(name)) {
+            None => return false,
+            Some(action) => action,
+        };
+
+        let panel = match panels.get_mut(panel_index) {
+            Some(panel) if commands_index(panel) == Some(index) => panel,
+            _ => return false,
+        };
+
+        state.set_active_panel(panel_index);
+        self.replace_top_with_panel(panel.panel_type());
+        self.sync_edit_mode(state, panels);
+
+        let panel = match panels.get_mut(panel_index) {
+            None => return false,
+            Some(panel) => panel,
+        };
+
+        let (handled, changes) = action(panel, KeyCode::Null, state, self);
+        if handled {
+            panels.propagate_edits(panel_index);
+        }
+        state.handle_changes(changes, panels, self);
+        self.sync_edit_mode(state, panels);
+
+        true
+    }
+
+    pub fn execute_global_by_name(&mut self, name: &str, state: &mut AppState, panels: &mut Panels) -> bool {
+        match self.state_commands.get_by_name(name) {
+            None => false,
+            Some(action) => {
+                action(state, KeyCode::Null, panels, self);
+                true
+            }
+        }
+    }
+
+    // panel commands take precedence over global, same as key dispatch
+    pub fn execute_by_name(&mut self, name: &str, state: &mut AppState, panels: &mut Panels) -> bool {
+        let panel_action = self
+            .panel_commands()
+            .and_then(|(_, commands)| commands.get_by_name(name));
+
+        match panel_action {
+            Some(action) => match panels.get_mut(state.active_panel()) {
+                None => false,
+                Some(panel) => {
+                    let (handled, changes) = action(panel, KeyCode::Null, state, self);
+                    if handled {
+                        panels.propagate_edits(state.active_panel());
+                    }
+                    state.handle_changes(changes, panels, self);
+
+                    true
+                }
+            },
+            None => match self.state_commands.get_by_name(name) {
+                None => false,
+                Some(action) => {
+                    action(state, KeyCode::Null, panels, self);
+                    true
+                }
+            },
+        }
+    }
+}
+
+fn type_commands_index(type_id: PanelTypeID) -> Option<usize> {
+    match type_id {
+        EDIT_PANEL_TYPE_ID => Some(EDIT_COMMAND_INDEX),
+        // welcome becomes an edit panel on first key, keys are for it by then
+        WELCOME_PANEL_TYPE_ID => Some(EDIT_COMMAND_INDEX),
+        INPUT_PANEL_TYPE_ID => Some(INPUT_COMMAND_INDEX),
+        MESSAGE_PANEL_TYPE_ID => Some(MESSAGES_COMMAND_INDEX),
+        COMMANDS_PANEL_TYPE_ID => Some(COMMANDS_COMMAND_INDEX),
+        TERMINAL_PANEL_TYPE_ID => Some(TERMINAL_COMMAND_INDEX),
+        OUTPUT_PANEL_TYPE_ID => Some(OUTPUT_COMMAND_INDEX),
+        DIFF_PANEL_TYPE_ID => Some(DIFF_COMMAND_INDEX),
+        HEX_PANEL_TYPE_ID => Some(HEX_COMMAND_INDEX),
+        HELP_PANEL_TYPE_ID => Some(HELP_COMMAND_INDEX),
+        _ => None,
+    }
+}
+
+// same as panel_commands picks, including normal mode
+fn commands_index(panel: &TextPanel) -> Option<usize> {
+    match (type_commands_index(panel.panel_type()), panel.edit_mode()) {
+        (Some(EDIT_COMMAND_INDEX), EditMode::Normal) => Some(NORMAL_MODE_COMMAND_INDEX),
+        (index, _) => index,
+    }
+}
+
+fn bind_by_name<T: Copy>(commands: &mut Commands<T>, path: &Vec<CommandKeyId>, name: &str) -> Result<Option<String>, String> {
+    match commands.find_by_name(name) {
+        None => Err(format!("No command named {:?}.", name)),
+        Some((details, action)) => commands.bind(path, details, action),
+    }
 }
 
 //
@@ -156,11 +493,173 @@ pub fn make_edit_commands() -> Result<Commands<PanelCommand>, String> {
                 TextPanel::handle_key_stroke)
     })?;
 
+    add_edit_commands(&mut commands)?;
+
+    Ok(commands)
+}
+
+// shared by both edit command sets, none are plain keys so normal mode keeps them too
+fn add_edit_commands(commands: &mut Commands<PanelCommand>) -> Result<(), String> {
+    commands.insert(|b| {
+        b.node(alt_catch_all())
+            .action(
+                CommandDetails::new(
+                    "Count",
+                    "Repeat next command by pressing ALT then a number 0-9, e.g. ALT 1, ALT 0, then a command to do it ten times.",
+                ), TextPanel::count_digit)
+    })?;
+
     commands.insert(|b| {
         b.node(ctrl_key('o'))
             .action(CommandDetails::open_file(), TextPanel::open_file)
     })?;
 
+    commands.insert(|b| {
+        b.node(alt_key('b'))
+            .node(key('c'))
+            .action(
+                CommandDetails::new(
+                    "New Buffer",
+                    "Start an empty buffer in this panel, keeping current one open.",
+                ), TextPanel::new_buffer)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('b'))
+            .node(key('n'))
+            .action(
+                CommandDetails::new(
+                    "Next Buffer",
+                    "Switch to next buffer open in this panel.",
+                ), TextPanel::next_buffer)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('b'))
+            .node(key('p'))
+            .action(
+                CommandDetails::new(
+                    "Previous Buffer",
+                    "Switch to previous buffer open in this panel.",
+                ), TextPanel::previous_buffer)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('b'))
+            .node(key('x'))
+            .action(
+                CommandDetails::new(
+                    "Close Buffer",
+                    "Close current buffer. Buffers with unsaved changes stay open.",
+                ), TextPanel::close_buffer)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('b'))
+            .node(key('k'))
+            .action(
+                CommandDetails::new(
+                    "Create Checkpoint",
+                    "Keep a copy of this buffer under a label for the rest of the session.",
+                ), TextPanel::start_checkpoint)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('b'))
+            .node(key('r'))
+            .action(
+                CommandDetails::new(
+                    "Restore Checkpoint",
+                    "Replace buffer contents with a checkpoint chosen in input panel.",
+                ), TextPanel::start_restore_checkpoint)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('b'))
+            .node(key('d'))
+            .action(
+                CommandDetails::new(
+                    "Compare With Checkpoint",
+                    "Show differences between a checkpoint and current buffer in a diff panel beside this one.",
+                ), TextPanel::start_compare_checkpoint)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('b'))
+            .node(key('l'))
+            .action(
+                CommandDetails::new(
+                    "List Buffers",
+                    "Switch to a buffer of this panel by choosing it in input panel.",
+                ), TextPanel::list_buffers)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('c'))
+            .action(
+                CommandDetails::new(
+                    "Compare",
+                    "Diff this buffer against the clipboard or another panel. Result opens in a new panel beside this one.",
+                ), TextPanel::compare)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('x'))
+            .action(
+                CommandDetails::new(
+                    "Export Buffer",
+                    "Write buffer with line numbers and theme colors to an HTML file, or ANSI colored text for any other extension.",
+                ), TextPanel::export_buffer)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('e'))
+            .node(key('h'))
+            .action(
+                CommandDetails::new(
+                    "Host Session",
+                    "Experimental. Share this buffer over a local socket so another editor can follow along read only.",
+                ), TextPanel::host_session)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('e'))
+            .node(key('j'))
+            .action(
+                CommandDetails::new(
+                    "Join Session",
+                    "Experimental. Show a buffer hosted by another editor in this panel, read only.",
+                ), TextPanel::join_session)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('e'))
+            .node(key('x'))
+            .action(
+                CommandDetails::new(
+                    "Leave Session",
+                    "Stop hosting or following a shared buffer.",
+                ), TextPanel::leave_session)
+    })?;
+
+    commands.insert(|b| {
+        b.node(shift_alt_key('O'))
+            .action(
+                CommandDetails::new(
+                    "Open Project File",
+                    "Open any file under current directory by typing part of its path.",
+                ), TextPanel::open_project_file)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('o'))
+            .action(
+                CommandDetails::new(
+                    "Quick Open",
+                    "Open a recently used file or one in current directory, matching loosely on name.",
+                ), TextPanel::quick_open)
+    })?;
+
     commands.insert(|b| {
         b.node(ctrl_key('s'))
             .action(
@@ -207,109 +706,1115 @@ pub fn make_edit_commands() -> Result<Commands<PanelCommand>, String> {
     })?;
 
     commands.insert(|b| {
-        b.node(alt_key('w'))
+        b.node(code(KeyCode::PageUp))
             .action(
                 CommandDetails::new(
-                    "Previous Line",
-                    "Move cursor to previous line. Cursor will appear at end if current line is longer than previous.",
-                ), TextPanel::move_to_previous_line)
+                    "Scroll Up Page",
+                    "Move view up by height of panel. Cursor remains where it is.",
+                ), TextPanel::scroll_up_page)
     })?;
 
     commands.insert(|b| {
-        b.node(alt_key('a')).action(
-            CommandDetails::new(
-                "Previous Character",
-                "Move cursor to previous character. Cursor go to previous line if at beginning.",
-            ),
-            TextPanel::move_to_previous_character,
-        )
+        b.node(code(KeyCode::PageDown))
+            .action(
+                CommandDetails::new(
+                    "Scroll Down Page",
+                    "Move view down by height of panel. Cursor remains where it is.",
+                ), TextPanel::scroll_down_page)
     })?;
 
     commands.insert(|b| {
-        b.node(alt_key('s'))
+        b.node(ctrl_alt_key('i'))
             .action(
                 CommandDetails::new(
-                    "Next Line",
-                    "Move cursor to next line. Cursor will appear at end if current line is longer than next.",
-                ),TextPanel::move_to_next_line)
+                    "Scroll Up Half Page",
+                    "Move view up by half the height of panel. Cursor remains where it is.",
+                ), TextPanel::scroll_up_half_page)
     })?;
 
     commands.insert(|b| {
-        b.node(alt_key('d'))
+        b.node(ctrl_alt_key('k'))
             .action(
                 CommandDetails::new(
-                    "Next Character",
-                    "Move cursor to next character. Cursor go to next line if at end.",
-                ), TextPanel::move_to_next_character)
+                    "Scroll Down Half Page",
+                    "Move view down by half the height of panel. Cursor remains where it is.",
+                ), TextPanel::scroll_down_half_page)
     })?;
 
-    Ok(commands)
-}
-
-pub fn make_input_commands() -> Result<Commands<PanelCommand>, String> {
-    let mut commands = Commands::<PanelCommand>::new();
-
     commands.insert(|b| {
-        b.node(catch_all())
+        b.node(alt_key('j'))
             .action(
                 CommandDetails::new(
-                    "Insert Character",
-                    "Insert basic characters. Includes letters, special characters, numbers, enter, backspace and delete.",
-                ),InputPanel::handle_key_stroke)
+                    "Scroll Left",
+                    "Move view left by a single column when wrapping is off. Cursor remains where it is.",
+                ), TextPanel::scroll_left_one)
     })?;
 
     commands.insert(|b| {
-        b.node(shift_catch_all())
+        b.node(alt_key('l'))
             .action(
                 CommandDetails::new(
-                    "Insert Shifted Character",
-                    "Insert shifted characters. Includes uppercase letters, special characters.",
-                ), InputPanel::handle_key_stroke)
+                    "Scroll Right",
+                    "Move view right by a single column when wrapping is off. Cursor remains where it is.",
+                ), TextPanel::scroll_right_one)
     })?;
 
     commands.insert(|b| {
-        b.node(alt_catch_all())
+        b.node(shift_alt_key('J'))
             .action(
                 CommandDetails::new(
-                    "Selected Autocomplete",
-                    "Selected one autocomplete option by pressing ALT then a number 0-9.",
-                ), InputPanel::fill_quick_select)
+                    "Scroll Left 10",
+                    "Move view left by ten columns when wrapping is off. Cursor remains where it is.",
+                ), TextPanel::scroll_left_ten)
     })?;
 
     commands.insert(|b| {
-        b.node(code(KeyCode::Tab)).action(
-            CommandDetails::new(
-                "Fill Autocomplete",
-                "Selected the current highlighted autocomplete option.",
-            ),
-            InputPanel::fill_current_quick_select,
-        )
+        b.node(shift_alt_key('L'))
+            .action(
+                CommandDetails::new(
+                    "Scroll Right 10",
+                    "Move view right by ten columns when wrapping is off. Cursor remains where it is.",
+                ), TextPanel::scroll_right_ten)
     })?;
 
     commands.insert(|b| {
-        b.node(alt_key('='))
+        b.node(alt_key('y'))
             .action(
                 CommandDetails::new(
-                    "Next Autocomplete",
-                    "Highlight next autocomplete option.",
-                ), InputPanel::next_quick_select)
+                    "Toggle Sync Cursors",
+                    "Have other panels viewing the same file follow this panel's cursor.",
+                ), TextPanel::toggle_sync_cursors)
     })?;
 
     commands.insert(|b| {
-        b.node(alt_key('-'))
+        b.node(alt_key('z'))
             .action(
                 CommandDetails::new(
-                    "Previous Autocomplete",
-                    "Highlight previous autocomplete option.",
-                ), InputPanel::previous_quick_select)
+                    "Toggle Wrap",
+                    "Switch between wrapping long lines and clipping them with horizontal scrolling.",
+                ), TextPanel::toggle_wrap)
     })?;
 
-    Ok(commands)
+    commands.insert(|b| {
+        b.node(alt_key('w'))
+            .action(
+                CommandDetails::new(
+                    "Previous Line",
+                    "Move cursor to previous line. Cursor will appear at end if current line is longer than previous.",
+                ), TextPanel::move_to_previous_line)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('a')).action(
+            CommandDetails::new(
+                "Previous Character",
+                "Move cursor to previous character. Cursor go to previous line if at beginning.",
+            ),
+            TextPanel::move_to_previous_character,
+        )
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('s'))
+            .action(
+                CommandDetails::new(
+                    "Next Line",
+                    "Move cursor to next line. Cursor will appear at end if current line is longer than next.",
+                ),TextPanel::move_to_next_line)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('d'))
+            .action(
+                CommandDetails::new(
+                    "Next Character",
+                    "Move cursor to next character. Cursor go to next line if at end.",
+                ), TextPanel::move_to_next_character)
+    })?;
+
+    commands.insert(|b| {
+        b.node(shift_alt_key('M'))
+            .action(
+                CommandDetails::new(
+                    "Jump To Matching Bracket",
+                    "Move cursor to bracket paired with the one at or just before cursor, nesting is followed across lines.",
+                ), TextPanel::jump_to_matching_bracket)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('g'))
+            .action(
+                CommandDetails::new(
+                    "Go To Line",
+                    "Move cursor to start of line with given number. Position left can be returned to with Jump Back.",
+                ), TextPanel::go_to_line)
+    })?;
+
+    commands.insert(|b| {
+        b.node(code(KeyCode::Left).mods(KeyModifiers::ALT))
+            .action(
+                CommandDetails::new(
+                    "Jump Back",
+                    "Return to position before last jump. Go to line, matching bracket, next TODO and restoring position on open are jumps.",
+                ), TextPanel::jump_back)
+    })?;
+
+    commands.insert(|b| {
+        b.node(code(KeyCode::Right).mods(KeyModifiers::ALT))
+            .action(
+                CommandDetails::new(
+                    "Jump Forward",
+                    "Undo Jump Back, returning to position it left.",
+                ), TextPanel::jump_forward)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('m'))
+            .action(
+                CommandDetails::new(
+                    "Toggle Mark",
+                    "Set mark at cursor or clear existing mark. Area between mark and cursor is used by block commands.",
+                ), TextPanel::toggle_mark)
+    })?;
+
+    commands.insert(|b| {
+        b.node(shift_alt_key('C'))
+            .action(
+                CommandDetails::new(
+                    "Column Select",
+                    "Toggle column selection. Moving cursor grows block from mark, typing edits every marked line at the same column. Enter or Escape ends it.",
+                ), TextPanel::toggle_column_select)
+    })?;
+
+    commands.insert(|b| {
+        b.node(shift_alt_key('X'))
+            .action(
+                CommandDetails::new(
+                    "Delete Block",
+                    "Delete columns between mark and cursor from each marked line.",
+                ), TextPanel::delete_block)
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('c'))
+            .action(
+                CommandDetails::new(
+                    "Copy Block",
+                    "Copy columns between mark and cursor on each marked line. Copies current line if no mark is set.",
+                ), TextPanel::copy_block)
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('v'))
+            .action(
+                CommandDetails::new(
+                    "Paste Block",
+                    "Paste copied lines column-wise, each starting at the cursor column on successive lines.",
+                ), TextPanel::paste_block)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('v'))
+            .action(
+                CommandDetails::new(
+                    "Insert On Marked Lines",
+                    "Insert first copied line at the cursor column of every line between mark and cursor.",
+                ), TextPanel::insert_on_marked_lines)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('r'))
+            .action(
+                CommandDetails::new(
+                    "Set Ruler",
+                    "Set column of vertical ruler. Also used as width when reflowing. Zero hides ruler.",
+                ), TextPanel::set_ruler_column)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('q'))
+            .action(
+                CommandDetails::new(
+                    "Reflow",
+                    "Rewrap marked lines, or paragraph at cursor, to ruler width.",
+                ), TextPanel::reflow)
+    })?;
+
+    commands.insert(|b| {
+        b.node(shift_alt_key('D'))
+            .action(
+                CommandDetails::new(
+                    "Next Word",
+                    "Move cursor to start of next word. Cursor go to next line if at end.",
+                ), TextPanel::move_to_next_word)
+    })?;
+
+    commands.insert(|b| {
+        b.node(shift_alt_key('A'))
+            .action(
+                CommandDetails::new(
+                    "Previous Word",
+                    "Move cursor to start of current or previous word. Cursor go to previous line if at beginning.",
+                ), TextPanel::move_to_previous_word)
+    })?;
+
+    commands.insert(|b| {
+        b.node(code(KeyCode::Delete).mods(KeyModifiers::ALT))
+            .action(
+                CommandDetails::new(
+                    "Delete Next Word",
+                    "Delete from cursor to start of next word.",
+                ), TextPanel::delete_next_word)
+    })?;
+
+    commands.insert(|b| {
+        b.node(code(KeyCode::Backspace).mods(KeyModifiers::ALT))
+            .action(
+                CommandDetails::new(
+                    "Delete Previous Word",
+                    "Delete from start of current or previous word to cursor.",
+                ), TextPanel::delete_previous_word)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('u'))
+            .action(
+                CommandDetails::new(
+                    "Toggle Sub-word Motions",
+                    "Toggle whether word motions and deletions stop at camel case humps and underscores.",
+                ), TextPanel::toggle_sub_word_motions)
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('t'))
+            .action(
+                CommandDetails::new(
+                    "Transpose Characters",
+                    "Swap characters on either side of cursor and move forward. Swaps last two characters if at end of line.",
+                ), TextPanel::transpose_characters)
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('x'))
+            .node(ctrl_key('t'))
+            .action(
+                CommandDetails::new(
+                    "Transpose Lines",
+                    "Swap current line with previous line and move to next line.",
+                ), TextPanel::transpose_lines)
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('d'))
+            .action(
+                CommandDetails::new(
+                    "Duplicate Line",
+                    "Insert copy of current line below it and move cursor to the copy.",
+                ), TextPanel::duplicate_line)
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('k'))
+            .action(
+                CommandDetails::new(
+                    "Delete Line",
+                    "Remove current line. Cursor moves to the following line.",
+                ), TextPanel::delete_line)
+    })?;
+
+    commands.insert(|b| {
+        b.node(shift_alt_key('W'))
+            .action(
+                CommandDetails::new(
+                    "Move Line Up",
+                    "Swap current line with the line above it.",
+                ), TextPanel::move_line_up)
+    })?;
+
+    commands.insert(|b| {
+        b.node(shift_alt_key('S'))
+            .action(
+                CommandDetails::new(
+                    "Move Line Down",
+                    "Swap current line with the line below it.",
+                ), TextPanel::move_line_down)
+    })?;
+
+    commands.insert(|b| {
+        b.node(code(KeyCode::Tab))
+            .action(
+                CommandDetails::new(
+                    "Indent",
+                    "Insert indentation at cursor. Spaces fill to the next tab stop.",
+                ), TextPanel::indent)
+    })?;
+
+    commands.insert(|b| {
+        b.node(code(KeyCode::BackTab).mods(KeyModifiers::SHIFT))
+            .action(
+                CommandDetails::new(
+                    "Dedent",
+                    "Remove one level of indentation from start of current line.",
+                ), TextPanel::dedent)
+    })?;
+
+    commands.insert(|b| {
+        b.node(shift_alt_key('V'))
+            .action(
+                CommandDetails::new(
+                    "Toggle Whitespace",
+                    "Toggle showing spaces as · and tabs as →, including trailing ones.",
+                ), TextPanel::toggle_show_whitespace)
+    })?;
+
+    commands.insert(|b| {
+        b.node(shift_alt_key('F'))
+            .action(
+                CommandDetails::new(
+                    "Toggle Long Line Flag",
+                    "Toggle highlighting of text past the ruler column.",
+                ), TextPanel::toggle_flag_long_lines)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('('))
+            .action(
+                CommandDetails::new(
+                    "Toggle Auto Close Pairs",
+                    "Toggle whether typing an opening bracket or quote adds its closing one after the cursor.",
+                ), TextPanel::toggle_auto_close_pairs)
+    })?;
+
+    commands.insert(|b| {
+        b.node(shift_alt_key('T'))
+            .action(
+                CommandDetails::new(
+                    "Next TODO",
+                    "Move to next line containing TODO or FIXME, as found when typing last paused.",
+                ), TextPanel::next_todo)
+    })?;
+
+    commands.insert(|b| {
+        b.node(shift_alt_key('E'))
+            .action(
+                CommandDetails::new(
+                    "Convert Line Endings",
+                    "Switch buffer between LF and CRLF line endings, applied on next save.",
+                ), TextPanel::convert_line_endings)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('t'))
+            .action(
+                CommandDetails::new(
+                    "Toggle Indent Style",
+                    "Switch between indenting with spaces and indenting with tab characters.",
+                ), TextPanel::toggle_indent_with_tabs)
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('x'))
+            .node(key('d'))
+            .action(
+                CommandDetails::new(
+                    "Duplicate Selection",
+                    "Prompt for a count and insert that many copies of marked lines, or current line, below them.",
+                ), TextPanel::start_duplicate_selection)
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('x'))
+            .node(key('c'))
+            .action(
+                CommandDetails::new(
+                    "Copy Lines",
+                    "Copy a range of lines below another line. Input is 'start,end t destination', e.g. '3,5t10'.",
+                ), TextPanel::start_copy_lines)
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('r'))
+            .action(
+                CommandDetails::new(
+                    "Replace All",
+                    "Replace every occurrence of text. Asks for text to find then its replacement.",
+                ), TextPanel::start_replace_all)
+    })?;
+
+    commands.insert(|b| {
+        b.node(shift_alt_key('R'))
+            .action(
+                CommandDetails::new(
+                    "Replace In Range",
+                    "Replace occurrences only inside the marked block, or in a range of lines asked for when nothing is marked.",
+                ), TextPanel::start_replace_in_range)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('n'))
+            .action(
+                CommandDetails::new(
+                    "Toggle Modal Editing",
+                    "Switch between modeless editing and vim style normal and insert modes.",
+                ), TextPanel::toggle_modal_editing)
+    })?;
+
+    Ok(())
+}
+
+// plain keys are motions and operators, text is only typed after switching to insert mode
+pub fn make_normal_mode_commands() -> Result<Commands<PanelCommand>, String> {
+    let mut commands = Commands::<PanelCommand>::new();
+
+    commands.insert(|b| {
+        b.node(catch_all())
+            .action(
+                CommandDetails::new(
+                    "Count (Normal Mode)",
+                    "Repeat next command by typing a number first, e.g. 10 then j to move down ten lines.",
+                ), TextPanel::count_digit)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('h'))
+            .action(
+                CommandDetails::new(
+                    "Left (Normal Mode)",
+                    "Move cursor to previous character.",
+                ), TextPanel::move_to_previous_character)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('j'))
+            .action(
+                CommandDetails::new(
+                    "Down (Normal Mode)",
+                    "Move cursor to next line.",
+                ), TextPanel::move_to_next_line)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('k'))
+            .action(
+                CommandDetails::new(
+                    "Up (Normal Mode)",
+                    "Move cursor to previous line.",
+                ), TextPanel::move_to_previous_line)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('l'))
+            .action(
+                CommandDetails::new(
+                    "Right (Normal Mode)",
+                    "Move cursor to next character.",
+                ), TextPanel::move_to_next_character)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('w'))
+            .action(
+                CommandDetails::new(
+                    "Next Word (Normal Mode)",
+                    "Move cursor to start of next word.",
+                ), TextPanel::move_to_next_word)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('b'))
+            .action(
+                CommandDetails::new(
+                    "Previous Word (Normal Mode)",
+                    "Move cursor to start of previous word.",
+                ), TextPanel::move_to_previous_word)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('0'))
+            .action(
+                CommandDetails::new(
+                    "Line Start (Normal Mode)",
+                    "Move cursor to start of line. Continues a count if one is being typed.",
+                ), TextPanel::move_to_line_start_or_count)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('$'))
+            .action(
+                CommandDetails::new(
+                    "Line End (Normal Mode)",
+                    "Move cursor to end of line.",
+                ), TextPanel::move_to_line_end)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('%'))
+            .action(
+                CommandDetails::new(
+                    "Matching Bracket (Normal Mode)",
+                    "Move cursor to bracket paired with the one at cursor.",
+                ), TextPanel::jump_to_matching_bracket)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('i'))
+            .action(
+                CommandDetails::new(
+                    "Insert (Normal Mode)",
+                    "Switch to insert mode at cursor.",
+                ), TextPanel::enter_insert_mode)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('a'))
+            .action(
+                CommandDetails::new(
+                    "Append (Normal Mode)",
+                    "Switch to insert mode after character at cursor.",
+                ), TextPanel::append)
+    })?;
+
+    commands.insert(|b| {
+        b.node(shift_key('I'))
+            .action(
+                CommandDetails::new(
+                    "Insert At Line Start (Normal Mode)",
+                    "Switch to insert mode at start of line.",
+                ), TextPanel::insert_at_line_start)
+    })?;
+
+    commands.insert(|b| {
+        b.node(shift_key('A'))
+            .action(
+                CommandDetails::new(
+                    "Append At Line End (Normal Mode)",
+                    "Switch to insert mode at end of line.",
+                ), TextPanel::append_at_line_end)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('o'))
+            .action(
+                CommandDetails::new(
+                    "Open Line Below (Normal Mode)",
+                    "Start a new line below current one in insert mode.",
+                ), TextPanel::open_line_below)
+    })?;
+
+    commands.insert(|b| {
+        b.node(shift_key('O'))
+            .action(
+                CommandDetails::new(
+                    "Open Line Above (Normal Mode)",
+                    "Start a new line above current one in insert mode.",
+                ), TextPanel::open_line_above)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('x'))
+            .action(
+                CommandDetails::new(
+                    "Delete Character (Normal Mode)",
+                    "Delete character at cursor.",
+                ), TextPanel::delete_character)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('d'))
+            .node(key('d'))
+            .action(
+                CommandDetails::new(
+                    "Delete Line (Normal Mode)",
+                    "Delete current line.",
+                ), TextPanel::delete_line)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('v'))
+            .action(
+                CommandDetails::new(
+                    "Toggle Mark (Normal Mode)",
+                    "Set mark at cursor or clear existing mark.",
+                ), TextPanel::toggle_mark)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('y'))
+            .action(
+                CommandDetails::new(
+                    "Copy (Normal Mode)",
+                    "Copy block between mark and cursor, or current line if no mark is set.",
+                ), TextPanel::copy_block)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('p'))
+            .action(
+                CommandDetails::new(
+                    "Paste (Normal Mode)",
+                    "Paste copied lines at cursor.",
+                ), TextPanel::paste_block)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('>'))
+            .action(
+                CommandDetails::new(
+                    "Indent (Normal Mode)",
+                    "Indent current line.",
+                ), TextPanel::indent)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('<'))
+            .action(
+                CommandDetails::new(
+                    "Dedent (Normal Mode)",
+                    "Dedent current line.",
+                ), TextPanel::dedent)
+    })?;
+
+    add_edit_commands(&mut commands)?;
+
+    Ok(commands)
+}
+
+pub fn make_input_commands() -> Result<Commands<PanelCommand>, String> {
+    let mut commands = Commands::<PanelCommand>::new();
+
+    commands.insert(|b| {
+        b.node(catch_all())
+            .action(
+                CommandDetails::new(
+                    "Insert Character",
+                    "Insert basic characters. Includes letters, special characters, numbers, enter, backspace and delete.",
+                ),InputPanel::handle_key_stroke)
+    })?;
+
+    commands.insert(|b| {
+        b.node(shift_catch_all())
+            .action(
+                CommandDetails::new(
+                    "Insert Shifted Character",
+                    "Insert shifted characters. Includes uppercase letters, special characters.",
+                ), InputPanel::handle_key_stroke)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_catch_all())
+            .action(
+                CommandDetails::new(
+                    "Selected Autocomplete",
+                    "Selected one autocomplete option by pressing ALT then a number 0-9.",
+                ), InputPanel::fill_quick_select)
+    })?;
+
+    commands.insert(|b| {
+        b.node(code(KeyCode::Tab)).action(
+            CommandDetails::new(
+                "Fill Autocomplete",
+                "Selected the current highlighted autocomplete option.",
+            ),
+            InputPanel::fill_current_quick_select,
+        )
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('='))
+            .action(
+                CommandDetails::new(
+                    "Next Autocomplete",
+                    "Highlight next autocomplete option.",
+                ), InputPanel::next_quick_select)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('-'))
+            .action(
+                CommandDetails::new(
+                    "Previous Autocomplete",
+                    "Highlight previous autocomplete option.",
+                ), InputPanel::previous_quick_select)
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('c'))
+            .action(
+                CommandDetails::new(
+                    "Cancel Input",
+                    "Close input panel without answering. Esc does the same.",
+                ), InputPanel::cancel)
+    })?;
+
+    commands.insert(|b| {
+        b.node(code(KeyCode::Up))
+            .action(
+                CommandDetails::new(
+                    "Previous Input",
+                    "Replace input with previous answer to same prompt.",
+                ), InputPanel::previous_history)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('p'))
+            .action(
+                CommandDetails::new(
+                    "Previous Input",
+                    "Replace input with previous answer to same prompt.",
+                ), InputPanel::previous_history)
+    })?;
+
+    commands.insert(|b| {
+        b.node(code(KeyCode::Down))
+            .action(
+                CommandDetails::new(
+                    "Next Input",
+                    "Replace input with next answer to same prompt, or what was typed before recalling.",
+                ), InputPanel::next_history)
+    })?;
+
+    commands.insert(|b| {
+        b.node(alt_key('n'))
+            .action(
+                CommandDetails::new(
+                    "Next Input",
+                    "Replace input with next answer to same prompt, or what was typed before recalling.",
+                ), InputPanel::next_history)
+    })?;
+
+    Ok(commands)
 }
 
 pub fn make_messages_commands() -> Result<Commands<PanelCommand>, String> {
     let mut commands = Commands::<PanelCommand>::new();
 
+    commands.insert(|b| {
+        b.node(key('w'))
+            .action(
+                CommandDetails::new(
+                    "Scroll Back",
+                    "Show older messages.",
+                ), MessagesPanel::scroll_back)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('s'))
+            .action(
+                CommandDetails::new(
+                    "Scroll Forward",
+                    "Show newer messages.",
+                ), MessagesPanel::scroll_forward)
+    })?;
+
+    commands.insert(|b| {
+        b.node(code(KeyCode::End))
+            .action(
+                CommandDetails::new(
+                    "Scroll To Newest",
+                    "Show newest messages.",
+                ), MessagesPanel::scroll_to_newest)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('e'))
+            .action(
+                CommandDetails::new(
+                    "Show Errors",
+                    "Only show error messages.",
+                ), MessagesPanel::show_errors)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('r'))
+            .action(
+                CommandDetails::new(
+                    "Show Warnings",
+                    "Only show warning messages.",
+                ), MessagesPanel::show_warnings)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('i'))
+            .action(
+                CommandDetails::new(
+                    "Show Info",
+                    "Only show info messages.",
+                ), MessagesPanel::show_info)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('a'))
+            .action(
+                CommandDetails::new(
+                    "Show All",
+                    "Show messages from every channel.",
+                ), MessagesPanel::show_all)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('c'))
+            .action(
+                CommandDetails::new(
+                    "Clear Messages",
+                    "Remove all messages from the log.",
+                ), MessagesPanel::clear)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('/'))
+            .action(
+                CommandDetails::new(
+                    "Search",
+                    "Type to only show matching lines. Enter stops typing, Escape clears search.",
+                ), TextPanel::start_search)
+    })?;
+
+    Ok(commands)
+}
+
+pub fn make_terminal_commands() -> Result<Commands<PanelCommand>, String> {
+    let mut commands = Commands::<PanelCommand>::new();
+
+    commands.insert(|b| {
+        b.node(catch_all())
+            .action(
+                CommandDetails::new(
+                    "Type To Shell",
+                    "Type a line for the shell, Enter sends it. Includes letters, special characters, numbers, tab and backspace.",
+                ), TerminalPanel::handle_key_stroke)
+    })?;
+
+    commands.insert(|b| {
+        b.node(shift_catch_all())
+            .action(
+                CommandDetails::new(
+                    "Type Shifted To Shell",
+                    "Type shifted characters for the shell. Includes uppercase letters, special characters.",
+                ), TerminalPanel::handle_key_stroke)
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('d'))
+            .action(
+                CommandDetails::new(
+                    "End Input",
+                    "Close the shell's input, most shells exit after finishing what was sent.",
+                ), TerminalPanel::end_input)
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('c'))
+            .action(
+                CommandDetails::new(
+                    "Stop Shell",
+                    "Kill the shell process.",
+                ), TerminalPanel::stop)
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('r'))
+            .action(
+                CommandDetails::new(
+                    "Restart Shell",
+                    "Stop the shell if running and start a new one.",
+                ), TerminalPanel::restart)
+    })?;
+
+    Ok(commands)
+}
+
+pub fn make_output_commands() -> Result<Commands<PanelCommand>, String> {
+    let mut commands = Commands::<PanelCommand>::new();
+
+    commands.insert(|b| {
+        b.node(key('w'))
+            .action(
+                CommandDetails::new(
+                    "Scroll Back",
+                    "Show earlier output.",
+                ), OutputPanel::scroll_back)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('s'))
+            .action(
+                CommandDetails::new(
+                    "Scroll Forward",
+                    "Show later output.",
+                ), OutputPanel::scroll_forward)
+    })?;
+
+    commands.insert(|b| {
+        b.node(code(KeyCode::End))
+            .action(
+                CommandDetails::new(
+                    "Scroll To Newest",
+                    "Show latest output.",
+                ), OutputPanel::scroll_to_newest)
+    })?;
+
+    commands.insert(|b| {
+        b.node(ctrl_key('c'))
+            .action(
+                CommandDetails::new(
+                    "Stop Command",
+                    "Kill the running command.",
+                ), OutputPanel::stop)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('r'))
+            .action(
+                CommandDetails::new(
+                    "Rerun Command",
+                    "Stop the command if running and run it again.",
+                ), OutputPanel::rerun)
+    })?;
+
+    Ok(commands)
+}
+
+pub fn make_diff_commands() -> Result<Commands<PanelCommand>, String> {
+    let mut commands = Commands::<PanelCommand>::new();
+
+    commands.insert(|b| {
+        b.node(key('n'))
+            .action(
+                CommandDetails::new(
+                    "Next Change",
+                    "Scroll to start of next group of changed lines.",
+                ), DiffPanel::next_hunk)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('p'))
+            .action(
+                CommandDetails::new(
+                    "Previous Change",
+                    "Scroll to start of previous group of changed lines.",
+                ), DiffPanel::previous_hunk)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('w'))
+            .action(
+                CommandDetails::new(
+                    "Scroll Up",
+                    "Scroll up one row.",
+                ), DiffPanel::scroll_up)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('s'))
+            .action(
+                CommandDetails::new(
+                    "Scroll Down",
+                    "Scroll down one row.",
+                ), DiffPanel::scroll_down)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('t'))
+            .action(
+                CommandDetails::new(
+                    "Toggle Side By Side",
+                    "Switch between unified and side by side layout.",
+                ), DiffPanel::toggle_side_by_side)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('y'))
+            .action(
+                CommandDetails::new(
+                    "Copy Diff",
+                    "Copy diff to clipboard as unified text.",
+                ), DiffPanel::copy_diff)
+    })?;
+
+    Ok(commands)
+}
+
+pub fn make_hex_commands() -> Result<Commands<PanelCommand>, String> {
+    let mut commands = Commands::<PanelCommand>::new();
+
+    commands.insert(|b| {
+        b.node(key('w'))
+            .action(
+                CommandDetails::new(
+                    "Scroll Up",
+                    "Scroll up one row of sixteen bytes.",
+                ), HexPanel::scroll_up)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('s'))
+            .action(
+                CommandDetails::new(
+                    "Scroll Down",
+                    "Scroll down one row of sixteen bytes.",
+                ), HexPanel::scroll_down)
+    })?;
+
+    commands.insert(|b| {
+        b.node(code(KeyCode::PageUp))
+            .action(
+                CommandDetails::new(
+                    "Page Up",
+                    "Scroll up one page.",
+                ), HexPanel::page_up)
+    })?;
+
+    commands.insert(|b| {
+        b.node(code(KeyCode::PageDown))
+            .action(
+                CommandDetails::new(
+                    "Page Down",
+                    "Scroll down one page.",
+                ), HexPanel::page_down)
+    })?;
+
+    Ok(commands)
+}
+
+pub fn make_help_commands() -> Result<Commands<PanelCommand>, String> {
+    let mut commands = Commands::<PanelCommand>::new();
+
+    commands.insert(|b| {
+        b.node(key('w'))
+            .action(
+                CommandDetails::new(
+                    "Scroll Up",
+                    "Scroll up one line.",
+                ), HelpPanel::scroll_up)
+    })?;
+
+    commands.insert(|b| {
+        b.node(key('s'))
+            .action(
+                CommandDetails::new(
+                    "Scroll Down",
+                    "Scroll down one line.",
+                ), HelpPanel::scroll_down)
+    })?;
+
+    commands.insert(|b| {
+        b.node(code(KeyCode::PageUp))
+            .action(
+                CommandDetails::new(
+                    "Page Up",
+                    "Scroll up one page.",
+                ), HelpPanel::page_up)
+    })?;
+
+    commands.insert(|b| {
+        b.node(code(KeyCode::PageDown))
+            .action(
+                CommandDetails::new(
+                    "Page Down",
+                    "Scroll down one page.",
+                ), HelpPanel::page_down)
+    })?;
+
     Ok(commands)
 }
 
@@ -334,5 +1839,23 @@ pub fn make_commands_commands() -> Result<Commands<PanelCommand>, String> {
                 ),previous_command)
     })?;
 
+    commands.insert(|b| {
+        b.node(key('/'))
+            .action(
+                CommandDetails::new(
+                    "Search",
+                    "Type to only show matching lines. Enter stops typing, Escape clears search.",
+                ), TextPanel::start_search)
+    })?;
+
+    commands.insert(|b| {
+        b.node(code(KeyCode::Enter))
+            .action(
+                CommandDetails::new(
+                    "Run Command",
+                    "Run highlighted command. Panel commands run on the panel that was active before this one.",
+                ), run_selected_command)
+    })?;
+
     Ok(commands)
 }
@@ -4,9 +4,14 @@ use std::hash::Hash;
 
 use crossterm::event::{KeyCode, KeyModifiers};
 
-pub use manager::Manager;
+pub use manager::{Manager, GLOBAL_KEYS_SECTION, MAX_COUNT};
+pub use translation::{KeyProfile, KeyTranslator};
 
 mod manager;
+mod translation;
+
+// key sequence leading to each command
+pub type KeyBindings<'a> = Vec<(Vec<CommandKeyId>, &'a CommandDetails)>;
 
 #[derive(Clone)]
 pub enum CommandKey<T> {
@@ -20,6 +25,31 @@ pub enum CommandKey<T> {
 }
 
 impl<T> CommandKey<T> {
+    // keys that can come next, named by their command or how many commands are further down
+    pub fn next_keys(&self) -> Vec<(CommandKeyId, String)> {
+        match self {
+            CommandKey::Leaf(..) => vec![],
+            CommandKey::Node(_, _, children, _) => children
+                .iter()
+                .map(|(id, child)| {
+                    let name = match child {
+                        CommandKey::Leaf(_, _, details, _) => details.name().clone(),
+                        CommandKey::Node(..) => format!("+{} commands", child.command_count()),
+                    };
+
+                    (id.clone(), name)
+                })
+                .collect(),
+        }
+    }
+
+    fn command_count(&self) -> usize {
+        match self {
+            CommandKey::Leaf(..) => 1,
+            CommandKey::Node(_, _, children, _) => children.values().map(|child| child.command_count()).sum(),
+        }
+    }
+
     fn get_hash(&self) -> CommandKeyId {
         let (c, m) = match self {
             CommandKey::Node(c, m, _, _) => (c, m),
@@ -98,6 +128,14 @@ impl CommandDetails {
         }
     }
 
+    pub fn split_view() -> Self {
+        CommandDetails {
+            name: "Split View".to_string(),
+            description: "Split active edit panel into two panels showing the same buffer."
+                .to_string(),
+        }
+    }
+
     pub fn add_panel() -> Self {
         CommandDetails {
             name: "Add Panel".to_string(),
@@ -112,6 +150,34 @@ impl CommandDetails {
         }
     }
 
+    pub fn swap_panel_next() -> Self {
+        CommandDetails {
+            name: "Swap Next".to_string(),
+            description: "Swap active panel with the one after it in its split.".to_string(),
+        }
+    }
+
+    pub fn swap_panel_previous() -> Self {
+        CommandDetails {
+            name: "Swap Previous".to_string(),
+            description: "Swap active panel with the one before it in its split.".to_string(),
+        }
+    }
+
+    pub fn move_panel_next_split() -> Self {
+        CommandDetails {
+            name: "Move To Next Split".to_string(),
+            description: "Move active panel into the next split beside it.".to_string(),
+        }
+    }
+
+    pub fn move_panel_previous_split() -> Self {
+        CommandDetails {
+            name: "Move To Previous Split".to_string(),
+            description: "Move active panel into the previous split beside it.".to_string(),
+        }
+    }
+
     pub fn change_panel_type() -> Self {
         CommandDetails {
             name: "Change Panel Type".to_string(),
@@ -133,6 +199,34 @@ impl CommandDetails {
         }
     }
 
+    pub fn focus_panel_left() -> Self {
+        CommandDetails {
+            name: "Focus Left".to_string(),
+            description: "Activate panel to the left of active panel".to_string(),
+        }
+    }
+
+    pub fn focus_panel_right() -> Self {
+        CommandDetails {
+            name: "Focus Right".to_string(),
+            description: "Activate panel to the right of active panel".to_string(),
+        }
+    }
+
+    pub fn focus_panel_up() -> Self {
+        CommandDetails {
+            name: "Focus Up".to_string(),
+            description: "Activate panel above active panel".to_string(),
+        }
+    }
+
+    pub fn focus_panel_down() -> Self {
+        CommandDetails {
+            name: "Focus Down".to_string(),
+            description: "Activate panel below active panel".to_string(),
+        }
+    }
+
     pub fn select_panel() -> Self {
         CommandDetails {
             name: "Activate Panel".to_string(),
@@ -140,6 +234,76 @@ impl CommandDetails {
         }
     }
 
+    pub fn jump_to_messages() -> Self {
+        CommandDetails {
+            name: "Jump To Messages".to_string(),
+            description: "Activate messages panel, or return to previous panel if already there.".to_string(),
+        }
+    }
+
+    pub fn close_other_panels() -> Self {
+        CommandDetails {
+            name: "Close Others".to_string(),
+            description: "Remove every panel except the active one, asking first when any have unsaved changes.".to_string(),
+        }
+    }
+
+    pub fn save_all() -> Self {
+        CommandDetails {
+            name: "Save All".to_string(),
+            description: "Save every modified buffer that has a file path, in all panels.".to_string(),
+        }
+    }
+
+    pub fn quit() -> Self {
+        CommandDetails {
+            name: "Quit".to_string(),
+            description: "Close the editor, asking first when buffers have unsaved changes.".to_string(),
+        }
+    }
+
+    pub fn run_shell_command() -> Self {
+        CommandDetails {
+            name: "Run Shell Command".to_string(),
+            description: "Run a command with the shell, output is shown in an output panel.".to_string(),
+        }
+    }
+
+    pub fn export_keymap() -> Self {
+        CommandDetails {
+            name: "Export Keymap".to_string(),
+            description: "Write current key bindings to a file that can be shared and imported.".to_string(),
+        }
+    }
+
+    pub fn import_keymap() -> Self {
+        CommandDetails {
+            name: "Import Keymap".to_string(),
+            description: "Add key bindings from an exported keymap file, replaced bindings are reported as warnings.".to_string(),
+        }
+    }
+
+    pub fn show_help() -> Self {
+        CommandDetails {
+            name: "Show Help".to_string(),
+            description: "Open a panel listing every key binding, grouped by panel type.".to_string(),
+        }
+    }
+
+    pub fn show_welcome() -> Self {
+        CommandDetails {
+            name: "Show Welcome".to_string(),
+            description: "Show quick start keys and recent files again, any key goes back to editing.".to_string(),
+        }
+    }
+
+    pub fn command_palette() -> Self {
+        CommandDetails {
+            name: "Command Palette".to_string(),
+            description: "Run a command by typing its name in input panel.".to_string(),
+        }
+    }
+
     pub fn open_file() -> Self {
         CommandDetails {
             name: "Open File".to_string(),
@@ -330,6 +494,114 @@ where
         })
     }
 
+    // all complete commands, catch alls are excluded since they need a key to act on
+    pub fn commands(&self) -> Vec<(&CommandDetails, T)> {
+        let mut commands = vec![];
+        let mut stack = vec![&self.root];
+
+        while let Some(command) = stack.pop() {
+            match command {
+                CommandKey::Node(_, _, children, _) => {
+                    for value in children.values() {
+                        stack.push(value);
+                    }
+                }
+                CommandKey::Leaf(KeyCode::Null, ..) => (),
+                CommandKey::Leaf(_, _, details, action) => commands.push((details, *action)),
+            }
+        }
+
+        commands.sort_by(|a, b| a.0.name().cmp(b.0.name()));
+
+        commands
+    }
+
+    // every command with the keys that lead to it, catch alls included
+    pub fn key_bindings(&self) -> KeyBindings<'_> {
+        let mut bindings = vec![];
+        let mut stack = vec![(vec![], &self.root)];
+
+        while let Some((path, command)) = stack.pop() {
+            match command {
+                CommandKey::Node(_, _, children, _) => {
+                    for (id, value) in children.iter() {
+                        let mut path = path.clone();
+                        path.push(id.clone());
+                        stack.push((path, value));
+                    }
+                }
+                CommandKey::Leaf(_, _, details, _) => bindings.push((path, details)),
+            }
+        }
+
+        bindings.sort_by(|a, b| a.1.name().cmp(b.1.name()).then(a.0.len().cmp(&b.0.len())));
+
+        bindings
+    }
+
+    // same as get_by_name but catch alls are included and details come along
+    pub fn find_by_name(&self, name: &str) -> Option<(CommandDetails, T)> {
+        self.key_bindings()
+            .into_iter()
+            .find(|(_, details)| details.name() == name)
+            .and_then(|(path, details)| match self.get(&path) {
+                Some((_, Some(action))) => Some((details.clone(), action)),
+                _ => None,
+            })
+    }
+
+    // puts command at end of path, replacing any command already there
+    // returns name of replaced command
+    pub fn bind(&mut self, path: &Vec<CommandKeyId>, details: CommandDetails, action: T) -> Result<Option<String>, String> {
+        let (last, rest) = match path.split_last() {
+            None => return Err("Empty key sequence.".to_string()),
+            Some(split) => split,
+        };
+
+        // check whole path first so a failed bind doesn't leave empty nodes behind
+        let mut current = Some(&self.root);
+        for id in path {
+            current = match current {
+                None => break,
+                Some(CommandKey::Node(_, _, children, _)) => children.get(id),
+                Some(CommandKey::Leaf(..)) => return Err("Existing command in sequence.".to_string()),
+            };
+        }
+
+        if let Some(CommandKey::Node(..)) = current {
+            return Err("Keys start other commands.".to_string());
+        }
+
+        let mut current_node = &mut self.root;
+        for id in rest {
+            match current_node {
+                CommandKey::Node(_, _, children, _) => {
+                    let n = CommandKey::Node(id.code, id.mods, HashMap::new(), None);
+                    current_node = children.entry(id.clone()).or_insert(n)
+                }
+                CommandKey::Leaf(..) => return Err("Existing command in sequence.".to_string()),
+            }
+        }
+
+        match current_node {
+            CommandKey::Node(_, _, children, _) => {
+                let n = CommandKey::Leaf(last.code, last.mods, details, action);
+                Ok(match children.insert(last.clone(), n) {
+                    Some(CommandKey::Leaf(_, _, replaced, _)) => Some(replaced.name().clone()),
+                    _ => None,
+                })
+            }
+            CommandKey::Leaf(..) => Err("Existing command in sequence.".to_string()),
+        }
+    }
+
+    pub fn get_by_name(&self, name: &str) -> Option<T> {
+        self.commands()
+            .iter()
+            .find(|(details, _)| details.name() == name)
+            .map(|(_, action)| *action)
+    }
+
     pub fn get_node(&self, path: &Vec<CommandKeyId>) -> Option<&CommandKey<T>> {
         let mut current = &self.root;
         for c in path {
@@ -417,6 +689,14 @@ pub fn key<T>(key: char) -> CommandKeyBuilder<T> {
     }
 }
 
+pub fn shift_key<T>(key: char) -> CommandKeyBuilder<T> {
+    CommandKeyBuilder {
+        code: KeyCode::Char(key),
+        mods: KeyModifiers::SHIFT,
+        action: None,
+    }
+}
+
 #[allow(dead_code)]
 pub fn code<T>(code: KeyCode) -> CommandKeyBuilder<T> {
     CommandKeyBuilder {
@@ -524,6 +804,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn next_keys_name_commands_and_count_nested() {
+        let mut commands = Commands::<CommandAction>::new();
+
+        commands
+            .insert(|b| b.node(key('a')).node(key('b')).action(CommandDetails::split_horizontal(), no_op))
+            .unwrap();
+        commands
+            .insert(|b| b.node(key('a')).node(key('c')).node(key('d')).action(CommandDetails::split_vertical(), no_op))
+            .unwrap();
+        commands
+            .insert(|b| b.node(key('a')).node(key('c')).node(key('e')).action(CommandDetails::add_panel(), no_op))
+            .unwrap();
+
+        let node = commands.get_node(&vec![CommandKeyId::new_code(KeyCode::Char('a'))]).unwrap();
+        let mut next = node.next_keys();
+        next.sort_by_key(|(id, _)| format!("{:?}", id.code()));
+
+        assert_eq!(
+            next,
+            vec![
+                (CommandKeyId::new_code(KeyCode::Char('b')), "Split Horizontal".to_string()),
+                (CommandKeyId::new_code(KeyCode::Char('c')), "+2 commands".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn insert_basic() {
         let mut commands = Commands::<CommandAction>::new();
@@ -771,4 +1078,68 @@ mod tests {
 
         assert!(commands.is_end(&progress));
     }
+
+    #[test]
+    fn list_commands() {
+        let mut commands = Commands::<CommandAction>::new();
+
+        commands
+            .insert(|b| b.node(key('b')).action(CommandDetails::new("Bravo", ""), no_op))
+            .unwrap();
+        commands
+            .insert(|b| {
+                b.node(key('a'))
+                    .node(key('c'))
+                    .action(CommandDetails::new("Alpha", ""), no_op)
+            })
+            .unwrap();
+        commands
+            .insert(|b| b.node(key('c')).node(code(KeyCode::Null)).action(CommandDetails::new("Catch", ""), no_op))
+            .unwrap();
+
+        let names: Vec<&String> = commands.commands().iter().map(|(details, _)| details.name()).collect();
+
+        assert_eq!(names, vec!["Alpha", "Bravo"]);
+    }
+
+    #[test]
+    fn get_by_name() {
+        let mut commands = Commands::<CommandAction>::new();
+
+        commands
+            .insert(|b| b.node(key('b')).action(CommandDetails::new("Bravo", ""), no_op))
+            .unwrap();
+
+        let mut state = AppState::new();
+        let action = commands.get_by_name("Bravo").unwrap();
+        action(&mut state, KeyCode::Null);
+
+        assert_eq!(state.active_panel(), 100);
+        assert!(commands.get_by_name("Alpha").is_none());
+    }
+
+    #[test]
+    fn bind_replaces_and_refuses_overlaps() {
+        let mut commands = Commands::<CommandAction>::new();
+
+        commands
+            .insert(|b| b.node(key('a')).node(key('b')).action(CommandDetails::new("Alpha", ""), no_op))
+            .unwrap();
+        commands
+            .insert(|b| b.node(key('c')).action(CommandDetails::new("Charlie", ""), no_op))
+            .unwrap();
+
+        let path = |keys: &str| keys.chars().map(|c| CommandKeyId::new_code(KeyCode::Char(c))).collect::<Vec<_>>();
+        let bravo = || CommandDetails::new("Bravo", "");
+
+        assert_eq!(commands.bind(&path("c"), bravo(), no_op), Ok(Some("Charlie".to_string())));
+        assert_eq!(commands.bind(&path("de"), bravo(), no_op), Ok(None));
+        assert!(commands.bind(&path("a"), bravo(), no_op).is_err());
+        assert!(commands.bind(&path("cf"), bravo(), no_op).is_err());
+        assert!(commands.get_node(&path("cf")).is_some());
+
+        assert_sequence(&commands.root, &['d', 'e']);
+        assert_eq!(commands.find_by_name("Bravo").unwrap().0.name(), "Bravo");
+        assert!(commands.get_by_name("Charlie").is_none());
+    }
 }
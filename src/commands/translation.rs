@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::env;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::commands::CommandKeyId;
+
+pub const KEY_PROFILE_VAR: &str = "EDISH_KEY_PROFILE";
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum KeyProfile {
+    // modifier cleanup only
+    Standard,
+    // terminals that send control codes for some editing keys
+    Legacy,
+    // terminals with kitty keyboard protocol, keys like Ctrl+Shift+letter and Ctrl+number are reported
+    Enhanced,
+}
+
+impl KeyProfile {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "standard" => Some(KeyProfile::Standard),
+            "legacy" => Some(KeyProfile::Legacy),
+            "enhanced" => Some(KeyProfile::Enhanced),
+            _ => None,
+        }
+    }
+
+    // best guess from environment when profile isn't given
+    pub fn detect() -> Self {
+        match env::var(KEY_PROFILE_VAR).ok().and_then(|name| KeyProfile::from_name(&name)) {
+            Some(profile) => profile,
+            None => match env::var("TERM") {
+                Ok(term) if term == "linux" || term.starts_with("vt") => KeyProfile::Legacy,
+                Ok(term) if ENHANCED_TERMS.iter().any(|name| term.starts_with(name)) => KeyProfile::Enhanced,
+                _ => match env::var("TERM_PROGRAM") {
+                    Ok(program) if ENHANCED_PROGRAMS.contains(&program.as_str()) => KeyProfile::Enhanced,
+                    _ => KeyProfile::Standard,
+                },
+            },
+        }
+    }
+
+    // terminals without support ignore the request, but then keys are reported as usual anyway
+    // so it's only asked for where support is known
+    pub fn enhanced_keys(&self) -> bool {
+        *self == KeyProfile::Enhanced
+    }
+}
+
+// TERM prefixes and TERM_PROGRAM values of terminals known to support kitty keyboard protocol
+const ENHANCED_TERMS: [&str; 4] = ["xterm-kitty", "foot", "wezterm", "xterm-ghostty"];
+const ENHANCED_PROGRAMS: [&str; 2] = ["WezTerm", "ghostty"];
+
+// sits between crossterm events and command lookup
+// so bindings don't depend on terminal quirks
+pub struct KeyTranslator {
+    remaps: HashMap<CommandKeyId, CommandKeyId>,
+}
+
+impl KeyTranslator {
+    pub fn new(profile: KeyProfile) -> Self {
+        let mut remaps = HashMap::new();
+
+        match profile {
+            KeyProfile::Standard | KeyProfile::Enhanced => (),
+            KeyProfile::Legacy => {
+                // backspace is sent as ^H
+                remaps.insert(
+                    CommandKeyId::new(KeyCode::Char('h'), KeyModifiers::CONTROL),
+                    CommandKeyId::new_code(KeyCode::Backspace),
+                );
+                // delete is sent as DEL character
+                remaps.insert(
+                    CommandKeyId::new_code(KeyCode::Char('\x7f')),
+                    CommandKeyId::new_code(KeyCode::Delete),
+                );
+            }
+        }
+
+        Self { remaps }
+    }
+
+    pub fn translate(&self, event: KeyEvent) -> CommandKeyId {
+        let id = normalize(event.code, event.modifiers);
+
+        match self.remaps.get(&id) {
+            Some(remapped) => remapped.clone(),
+            None => id,
+        }
+    }
+}
+
+impl Default for KeyTranslator {
+    fn default() -> Self {
+        KeyTranslator::new(KeyProfile::Standard)
+    }
+}
+
+fn normalize(code: KeyCode, mods: KeyModifiers) -> CommandKeyId {
+    match code {
+        // case already says whether shift was held
+        // some terminals drop the modifier for letters and others add it for symbols
+        KeyCode::Char(c) if c.is_ascii_uppercase() => CommandKeyId::new(code, mods | KeyModifiers::SHIFT),
+        // enhanced keyboard reports base key with shift, bindings use the shifted letter
+        KeyCode::Char(c) if c.is_ascii_lowercase() && mods.contains(KeyModifiers::SHIFT) => {
+            CommandKeyId::new(KeyCode::Char(c.to_ascii_uppercase()), mods)
+        }
+        KeyCode::Char(c) if c.is_ascii_lowercase() => CommandKeyId::new(code, mods),
+        KeyCode::Char(_) => CommandKeyId::new(code, mods - KeyModifiers::SHIFT),
+        // back tab is only reachable with shift
+        KeyCode::BackTab => CommandKeyId::new(code, mods | KeyModifiers::SHIFT),
+        _ => CommandKeyId::new(code, mods),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use crate::commands::translation::{KeyProfile, KeyTranslator};
+    use crate::commands::CommandKeyId;
+
+    #[test]
+    fn uppercase_gets_shift() {
+        let translator = KeyTranslator::default();
+
+        assert_eq!(
+            translator.translate(KeyEvent::new(KeyCode::Char('W'), KeyModifiers::ALT)),
+            CommandKeyId::new(KeyCode::Char('W'), KeyModifiers::ALT | KeyModifiers::SHIFT)
+        );
+    }
+
+    #[test]
+    fn symbols_lose_shift() {
+        let translator = KeyTranslator::default();
+
+        assert_eq!(
+            translator.translate(KeyEvent::new(KeyCode::Char('?'), KeyModifiers::SHIFT)),
+            CommandKeyId::new_code(KeyCode::Char('?'))
+        );
+    }
+
+    #[test]
+    fn back_tab_gets_shift() {
+        let translator = KeyTranslator::default();
+
+        assert_eq!(
+            translator.translate(KeyEvent::new(KeyCode::BackTab, KeyModifiers::empty())),
+            CommandKeyId::new(KeyCode::BackTab, KeyModifiers::SHIFT)
+        );
+    }
+
+    #[test]
+    fn legacy_backspace() {
+        let translator = KeyTranslator::new(KeyProfile::Legacy);
+
+        assert_eq!(
+            translator.translate(KeyEvent::new(KeyCode::Char('h'), KeyModifiers::CONTROL)),
+            CommandKeyId::new_code(KeyCode::Backspace)
+        );
+        assert_eq!(
+            KeyTranslator::default().translate(KeyEvent::new(KeyCode::Char('h'), KeyModifiers::CONTROL)),
+            CommandKeyId::new(KeyCode::Char('h'), KeyModifiers::CONTROL)
+        );
+    }
+
+    #[test]
+    fn enhanced_shifted_letters_match_legacy() {
+        let translator = KeyTranslator::new(KeyProfile::Enhanced);
+
+        assert_eq!(
+            translator.translate(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::ALT | KeyModifiers::SHIFT)),
+            translator.translate(KeyEvent::new(KeyCode::Char('C'), KeyModifiers::ALT))
+        );
+        assert_eq!(
+            translator.translate(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL | KeyModifiers::SHIFT)),
+            CommandKeyId::new(KeyCode::Char('P'), KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        );
+    }
+
+    #[test]
+    fn enhanced_control_numbers_kept() {
+        let translator = KeyTranslator::new(KeyProfile::Enhanced);
+
+        assert_eq!(
+            translator.translate(KeyEvent::new(KeyCode::Char('1'), KeyModifiers::CONTROL)),
+            CommandKeyId::new(KeyCode::Char('1'), KeyModifiers::CONTROL)
+        );
+    }
+
+    #[test]
+    fn profile_names() {
+        assert_eq!(KeyProfile::from_name("Enhanced"), Some(KeyProfile::Enhanced));
+        assert!(KeyProfile::Enhanced.enhanced_keys());
+        assert!(!KeyProfile::Standard.enhanced_keys());
+        assert_eq!(KeyProfile::from_name("Legacy"), Some(KeyProfile::Legacy));
+        assert_eq!(KeyProfile::from_name("standard"), Some(KeyProfile::Standard));
+        assert_eq!(KeyProfile::from_name("other"), None);
+    }
+}
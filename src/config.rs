@@ -0,0 +1,485 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tui::layout::Direction;
+
+use crate::panels::EditMode;
+use crate::splits::SplitSize;
+use crate::TextPanel;
+
+pub const CONFIG_PATH_VAR: &str = "EDISH_CONFIG";
+pub const LOCAL_CONFIG_NAME: &str = ".garnish-editor.toml";
+
+// toml, nested tables flatten into dotted keys, `tab_width` under `[editor]` reads as `editor.tab_width`
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    values: HashMap<String, String>,
+}
+
+impl Config {
+    // no setting takes an array
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let table = match text.parse::<toml::Value>() {
+            Err(err) => return Err(format!("Invalid toml. {}", err)),
+            Ok(toml::Value::Table(table)) => table,
+            Ok(_) => return Err("Expected toml table at top level.".to_string()),
+        };
+
+        let mut values = HashMap::new();
+        flatten_table("", table, &mut values)?;
+
+        Ok(Self { values })
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        match env::var(CONFIG_PATH_VAR) {
+            Ok(path) => Some(PathBuf::from(path)),
+            Err(_) => env::var("HOME")
+                .ok()
+                .map(|home| PathBuf::from(home).join(".config").join("edish").join("config")),
+        }
+    }
+
+    // missing file is not an error, just no config
+    pub fn load() -> Result<Option<Self>, String> {
+        match Config::path() {
+            Some(path) if path.exists() => fs::read_to_string(&path)
+                .map_err(|err| format!("Could not read config {:?}. {}", path, err))
+                .and_then(|text| Config::parse(&text))
+                .map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&String> {
+        self.values.get(key)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.values.keys()
+    }
+
+    // values in other win
+    pub fn merge(&mut self, other: &Config) {
+        for (key, value) in other.values.iter() {
+            self.values.insert(key.clone(), value.clone());
+        }
+    }
+
+    // nearest project config from dir upwards
+    pub fn find_local(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .map(|dir| dir.join(LOCAL_CONFIG_NAME))
+            .find(|path| path.is_file())
+    }
+
+    pub fn load_local(dir: &Path) -> Result<Option<Self>, String> {
+        match Config::find_local(dir) {
+            None => Ok(None),
+            Some(path) => fs::read_to_string(&path)
+                .map_err(|err| format!("Could not read config {:?}. {}", path, err))
+                .and_then(|text| Config::parse(&text).map_err(|err| format!("{:?}: {}", path, err)))
+                .map(Some),
+        }
+    }
+
+    // settings that belong to a file being edited
+    pub fn apply_to_panel(&self, panel: &mut TextPanel) -> Result<(), String> {
+        match self.get("tab_width") {
+            None => (),
+            Some(width) => match width.parse::<usize>() {
+                Ok(width) => panel.set_tab_width(width),
+                Err(_) => return Err(format!("Invalid tab_width {:?} in config.", width)),
+            },
+        }
+
+        match self.get("indent_with_tabs").map(|v| v.as_str()) {
+            None => (),
+            Some("true") => panel.set_indent_with_tabs(true),
+            Some("false") => panel.set_indent_with_tabs(false),
+            Some(other) => return Err(format!("Invalid indent_with_tabs {:?} in config.", other)),
+        }
+
+        match self.get("auto_close_pairs").map(|v| v.as_str()) {
+            None => (),
+            Some("true") => panel.set_auto_close_pairs(true),
+            Some("false") => panel.set_auto_close_pairs(false),
+            Some(other) => return Err(format!("Invalid auto_close_pairs {:?} in config.", other)),
+        }
+
+        match self.get("trim_trailing_whitespace").map(|v| v.as_str()) {
+            None => (),
+            Some("true") => panel.set_trim_trailing_whitespace(true),
+            Some("false") => panel.set_trim_trailing_whitespace(false),
+            Some(other) => return Err(format!("Invalid trim_trailing_whitespace {:?} in config.", other)),
+        }
+
+        match self.get("final_newline").map(|v| v.as_str()) {
+            None => (),
+            Some("true") => panel.set_final_newline(true),
+            Some("false") => panel.set_final_newline(false),
+            Some(other) => return Err(format!("Invalid final_newline {:?} in config.", other)),
+        }
+
+        // new files start in normal mode, panels already in insert mode are left alone
+        match self.get("modal_editing").map(|v| v.as_str()) {
+            None => (),
+            Some("true") if panel.edit_mode() == EditMode::Modeless => panel.set_edit_mode(EditMode::Normal),
+            Some("true") => (),
+            Some("false") => panel.set_edit_mode(EditMode::Modeless),
+            Some(other) => return Err(format!("Invalid modal_editing {:?} in config.", other)),
+        }
+
+        // 0 hides it, same as Set Ruler
+        match self.get("ruler") {
+            None => (),
+            Some(column) => match column.parse::<usize>() {
+                Ok(0) => panel.set_ruler(None),
+                Ok(column) => panel.set_ruler(Some(column)),
+                Err(_) => return Err(format!("Invalid ruler {:?} in config.", column)),
+            },
+        }
+
+        match self.get("flag_long_lines").map(|v| v.as_str()) {
+            None => (),
+            Some("true") => panel.set_flag_long_lines(true),
+            Some("false") => panel.set_flag_long_lines(false),
+            Some(other) => return Err(format!("Invalid flag_long_lines {:?} in config.", other)),
+        }
+
+        match self.get("keep_bom").map(|v| v.as_str()) {
+            None => (),
+            Some("true") => panel.set_keep_bom(true),
+            Some("false") => panel.set_keep_bom(false),
+            Some(other) => return Err(format!("Invalid keep_bom {:?} in config.", other)),
+        }
+
+        Ok(())
+    }
+}
+
+fn flatten_table(prefix: &str, table: toml::value::Table, values: &mut HashMap<String, String>) -> Result<(), String> {
+    for (key, value) in table {
+        let key = format!("{}{}", prefix, key);
+        let value = match value {
+            toml::Value::String(value) => value,
+            toml::Value::Integer(value) => value.to_string(),
+            toml::Value::Float(value) => value.to_string(),
+            toml::Value::Boolean(value) => value.to_string(),
+            toml::Value::Datetime(value) => value.to_string(),
+            toml::Value::Table(table) => {
+                flatten_table(&format!("{}.", key), table, values)?;
+                continue;
+            }
+            toml::Value::Array(_) => return Err(format!("Config key {:?} is an array, which isn't supported.", key)),
+        };
+
+        values.insert(key, value);
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum LayoutSpec {
+    Split(Direction, Vec<LayoutSpec>),
+    // panel type and optional file to open
+    Panel(String, Option<String>),
+    // panel or split with a length in its parent split
+    Sized(SplitSize, Box<LayoutSpec>),
+}
+
+// v(...) stacks children, h(...) places them side by side
+// panels are a type name with an optional file, e.g. Edit:src/main.garnish
+// either can end with @ and a size, cells or a percent of parent split, e.g. Edit@30% or v(...)@10
+pub fn parse_layout(text: &str) -> Result<LayoutSpec, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut index = 0;
+
+    let layout = parse_layout_item(&chars, &mut index)?;
+
+    skip_whitespace(&chars, &mut index);
+    if index < chars.len() {
+        return Err(format!("Unexpected {:?} in layout at {}.", chars[index], index));
+    }
+
+    Ok(layout)
+}
+
+fn skip_whitespace(chars: &[char], index: &mut usize) {
+    while *index < chars.len() && chars[*index].is_whitespace() {
+        *index += 1;
+    }
+}
+
+fn parse_layout_item(chars: &[char], index: &mut usize) -> Result<LayoutSpec, String> {
+    skip_whitespace(chars, index);
+
+    let start = *index;
+    while *index < chars.len() && !['(', ')', ','].contains(&chars[*index]) {
+        *index += 1;
+    }
+
+    let word: String = chars[start..*index].iter().collect::<String>().trim().to_string();
+
+    if *index < chars.len() && chars[*index] == '(' {
+        let direction = match word.as_str() {
+            "v" => Direction::Vertical,
+            "h" => Direction::Horizontal,
+            _ => return Err(format!("Unknown split {:?} in layout. Expected 'v' or 'h'.", word)),
+        };
+
+        // consume '('
+        *index += 1;
+        let mut children = vec![];
+
+        loop {
+            children.push(parse_layout_item(chars, index)?);
+            skip_whitespace(chars, index);
+
+            match chars.get(*index) {
+                Some(',') => *index += 1,
+                Some(')') => {
+                    *index += 1;
+                    break;
+                }
+                _ => return Err("Unclosed split in layout.".to_string()),
+            }
+        }
+
+        let split = LayoutSpec::Split(direction, children);
+
+        skip_whitespace(chars, index);
+        if chars.get(*index) != Some(&'@') {
+            return Ok(split);
+        }
+
+        *index += 1;
+        let start = *index;
+        while *index < chars.len() && ![')', ','].contains(&chars[*index]) {
+            *index += 1;
+        }
+
+        let size: String = chars[start..*index].iter().collect();
+        return parse_size(size.trim()).map(|size| LayoutSpec::Sized(size, Box::new(split)));
+    }
+
+    if word.is_empty() {
+        return Err(format!("Missing panel in layout at {}.", start));
+    }
+
+    let (word, size) = match word.rsplit_once('@') {
+        None => (word, None),
+        Some((panel, size)) => match (parse_size(size.trim()), panel.contains(':')) {
+            (Ok(size), _) => (panel.trim().to_string(), Some(size)),
+            // file names can have an @ too
+            (Err(_), true) => (word, None),
+            (Err(err), false) => return Err(err),
+        },
+    };
+
+    let panel = match word.split_once(':') {
+        None => LayoutSpec::Panel(word, None),
+        Some((panel_type, file)) => {
+            LayoutSpec::Panel(panel_type.trim().to_string(), Some(file.trim().to_string()))
+        }
+    };
+
+    Ok(match size {
+        None => panel,
+        Some(size) => LayoutSpec::Sized(size, Box::new(panel)),
+    })
+}
+
+// whole cells, or a percent of parent split ending in %
+fn parse_size(size: &str) -> Result<SplitSize, String> {
+    let parsed = match size.strip_suffix('%') {
+        Some(percent) => percent.parse::<u16>().ok().filter(|p| *p <= 100).map(SplitSize::Percent),
+        None => size.parse::<u16>().ok().map(SplitSize::Cells),
+    };
+
+    match parsed {
+        Some(SplitSize::Cells(0)) | Some(SplitSize::Percent(0)) | None => {
+            Err(format!("Invalid size {:?} in layout. Expected cells, e.g. 30, or a percent, e.g. 25%.", size))
+        }
+        Some(size) => Ok(size),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tui::layout::Direction;
+
+    use crate::config::{parse_layout, Config, LayoutSpec, LOCAL_CONFIG_NAME};
+    use crate::splits::SplitSize;
+    use crate::TextPanel;
+
+    #[test]
+    fn parse_config() {
+        let config = Config::parse("# comment\n\nlayout = 'v(Edit)' # startup\n  other=\"value\"  ").unwrap();
+
+        assert_eq!(config.get("layout").unwrap(), "v(Edit)");
+        assert_eq!(config.get("other").unwrap(), "value");
+        assert!(config.get("missing").is_none());
+    }
+
+    #[test]
+    fn parse_tables_as_dotted_keys() {
+        let config = Config::parse("[editor]\ntab_width = 2\n[editor.save]\nfinal_newline = true").unwrap();
+
+        assert_eq!(config.get("editor.tab_width").unwrap(), "2");
+        assert_eq!(config.get("editor.save.final_newline").unwrap(), "true");
+        assert!(Config::parse("tasks = [\"build\"]").is_err());
+    }
+
+    #[test]
+    fn parse_config_invalid_line() {
+        assert!(Config::parse("layout").is_err());
+    }
+
+    #[test]
+    fn parse_toml_values() {
+        let config = Config::parse(
+            "# project settings\ntab_width = 2 # narrow\nindent_with_tabs = true\nlayout = 'v(Edit, Messages)'\nname = \"value with spaces\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(config.get("tab_width").unwrap(), "2");
+        assert_eq!(config.get("indent_with_tabs").unwrap(), "true");
+        assert_eq!(config.get("layout").unwrap(), "v(Edit, Messages)");
+        assert_eq!(config.get("name").unwrap(), "value with spaces");
+    }
+
+    #[test]
+    fn merge_overrides() {
+        let mut config = Config::parse("tab_width = 4\nlayout = 'v(Edit)'").unwrap();
+        config.merge(&Config::parse("tab_width = 2").unwrap());
+
+        assert_eq!(config.get("tab_width").unwrap(), "2");
+        assert_eq!(config.get("layout").unwrap(), "v(Edit)");
+    }
+
+    #[test]
+    fn find_local_in_parent() {
+        let root = std::env::temp_dir().join(format!("edish_local_config_{}", std::process::id()));
+        let nested = root.join("src").join("deep");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(root.join(LOCAL_CONFIG_NAME), "tab_width = 2\nindent_with_tabs = true").unwrap();
+
+        let config = Config::load_local(&nested).unwrap().unwrap();
+        let mut panel = TextPanel::edit_panel();
+        config.apply_to_panel(&mut panel).unwrap();
+
+        assert_eq!(panel.tab_width(), 2);
+        assert!(panel.indent_with_tabs());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn apply_auto_close_pairs() {
+        let mut panel = TextPanel::edit_panel();
+
+        Config::parse("auto_close_pairs = true").unwrap().apply_to_panel(&mut panel).unwrap();
+        assert!(panel.auto_close_pairs());
+
+        assert!(Config::parse("auto_close_pairs = \"yes\"").unwrap().apply_to_panel(&mut panel).is_err());
+    }
+
+    #[test]
+    fn apply_save_settings() {
+        let mut panel = TextPanel::edit_panel();
+
+        Config::parse("trim_trailing_whitespace = true\nfinal_newline = true")
+            .unwrap()
+            .apply_to_panel(&mut panel)
+            .unwrap();
+        assert!(panel.trim_trailing_whitespace());
+        assert!(panel.final_newline());
+
+        assert!(Config::parse("final_newline = 1").unwrap().apply_to_panel(&mut panel).is_err());
+    }
+
+    #[test]
+    fn apply_ruler_settings() {
+        let mut panel = TextPanel::edit_panel();
+
+        Config::parse("ruler = 100\nflag_long_lines = true").unwrap().apply_to_panel(&mut panel).unwrap();
+        assert_eq!(panel.ruler(), Some(100));
+        assert!(panel.flag_long_lines());
+
+        Config::parse("ruler = 0").unwrap().apply_to_panel(&mut panel).unwrap();
+        assert_eq!(panel.ruler(), None);
+
+        assert!(Config::parse("ruler = \"wide\"").unwrap().apply_to_panel(&mut panel).is_err());
+    }
+
+    #[test]
+    fn apply_invalid_value() {
+        let config = Config::parse("tab_width = \"wide\"").unwrap();
+
+        assert!(config.apply_to_panel(&mut TextPanel::edit_panel()).is_err());
+    }
+
+    #[test]
+    fn parse_nested_layout() {
+        assert_eq!(
+            parse_layout("v(Edit:main.garnish, h(Edit, Commands), Messages)").unwrap(),
+            LayoutSpec::Split(
+                Direction::Vertical,
+                vec![
+                    LayoutSpec::Panel("Edit".to_string(), Some("main.garnish".to_string())),
+                    LayoutSpec::Split(
+                        Direction::Horizontal,
+                        vec![
+                            LayoutSpec::Panel("Edit".to_string(), None),
+                            LayoutSpec::Panel("Commands".to_string(), None),
+                        ]
+                    ),
+                    LayoutSpec::Panel("Messages".to_string(), None),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn parse_layout_sizes() {
+        assert_eq!(
+            parse_layout("h(Edit:main.garnish@30%, v(Messages, Commands) @ 10, Edit:me@home.garnish)").unwrap(),
+            LayoutSpec::Split(
+                Direction::Horizontal,
+                vec![
+                    LayoutSpec::Sized(
+                        SplitSize::Percent(30),
+                        Box::new(LayoutSpec::Panel("Edit".to_string(), Some("main.garnish".to_string())))
+                    ),
+                    LayoutSpec::Sized(
+                        SplitSize::Cells(10),
+                        Box::new(LayoutSpec::Split(
+                            Direction::Vertical,
+                            vec![
+                                LayoutSpec::Panel("Messages".to_string(), None),
+                                LayoutSpec::Panel("Commands".to_string(), None),
+                            ]
+                        ))
+                    ),
+                    LayoutSpec::Panel("Edit".to_string(), Some("me@home.garnish".to_string())),
+                ]
+            )
+        );
+
+        assert!(parse_layout("v(Edit@0)").is_err());
+        assert!(parse_layout("v(Edit)@150%").is_err());
+        assert!(parse_layout("v(v(Edit)@wide)").is_err());
+    }
+
+    #[test]
+    fn parse_layout_errors() {
+        assert!(parse_layout("v(Edit").is_err());
+        assert!(parse_layout("x(Edit)").is_err());
+        assert!(parse_layout("v(Edit,)").is_err());
+        assert!(parse_layout("v(Edit) Edit").is_err());
+    }
+}
@@ -0,0 +1,299 @@
+use crate::app::{StateChange, StateChangeRequest};
+use crate::commands::Manager;
+use std::fs;
+
+use crate::locale::Text;
+use crate::panels::{buffer_name, decode, DiffView, PanelFactory};
+use crate::{AppState, Panels};
+
+pub const CLIPBOARD_TARGET: &str = "clipboard";
+
+// table below is lines times lines, past this comparing would stall the editor
+const MAX_COMPARED_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum DiffLine {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+// longest common subsequence of lines, after skipping matching start and end
+pub fn diff_lines(old: &[String], new: &[String]) -> Result<Vec<DiffLine>, String> {
+    let prefix = old.iter().zip(new.iter()).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    if old_middle.len().saturating_mul(new_middle.len()) > MAX_COMPARED_CELLS {
+        return Err(format!(
+            "Too many differing lines to compare ({} and {}).",
+            old_middle.len(),
+            new_middle.len()
+        ));
+    }
+
+    // lengths[i][j] is common length of old_middle[i..] and new_middle[j..]
+    let width = new_middle.len() + 1;
+    let mut lengths = vec![0usize; (old_middle.len() + 1) * width];
+    for i in (0..old_middle.len()).rev() {
+        for j in (0..new_middle.len()).rev() {
+            lengths[i * width + j] = match old_middle[i] == new_middle[j] {
+                true => lengths[(i + 1) * width + j + 1] + 1,
+                false => lengths[(i + 1) * width + j].max(lengths[i * width + j + 1]),
+            };
+        }
+    }
+
+    let mut lines: Vec<DiffLine> = old[..prefix].iter().cloned().map(DiffLine::Same).collect();
+
+    let (mut i, mut j) = (0, 0);
+    while i < old_middle.len() || j < new_middle.len() {
+        if i < old_middle.len() && j < new_middle.len() && old_middle[i] == new_middle[j] {
+            lines.push(DiffLine::Same(old_middle[i].clone()));
+            i += 1;
+            j += 1;
+        } else if i < old_middle.len()
+            && (j == new_middle.len() || lengths[(i + 1) * width + j] >= lengths[i * width + j + 1])
+        {
+            lines.push(DiffLine::Removed(old_middle[i].clone()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new_middle[j].clone()));
+            j += 1;
+        }
+    }
+
+    lines.extend(old[old.len() - suffix..].iter().cloned().map(DiffLine::Same));
+
+    Ok(lines)
+}
+
+pub fn format_diff(old_name: &str, new_name: &str, lines: &[DiffLine]) -> String {
+    let mut text = vec![format!("--- {}", old_name), format!("+++ {}", new_name)];
+
+    text.extend(lines.iter().map(|line| match line {
+        DiffLine::Same(line) => format!("  {}", line),
+        DiffLine::Removed(line) => format!("- {}", line),
+        DiffLine::Added(line) => format!("+ {}", line),
+    }));
+
+    text.join("\n")
+}
+
+// compares active panel with clipboard, panel of given id or a file, result opens in diff panel beside it
+pub struct CompareWith {
+    target: String,
+}
+
+impl CompareWith {
+    pub fn new(target: String) -> Self {
+        Self { target }
+    }
+}
+
+impl StateChange for CompareWith {
+    fn apply(self: Box<Self>, state: &mut AppState, panels: &mut Panels, commands: &mut Manager) -> Vec<StateChangeRequest> {
+        let (old_name, old) = match panels.get(state.active_panel()) {
+            None => return vec![StateChangeRequest::error(Text::new("diff.no_panel", "No panel to compare."))],
+            Some(panel) => (
+                buffer_name(panel.title(), panel.file_path()),
+                panel.lines().to_vec(),
+            ),
+        };
+
+        let target = self.target.trim();
+        let mut chars = target.chars();
+        let (new_name, new) = match (target, chars.next(), chars.next()) {
+            ("", _, _) => {
+                return vec![StateChangeRequest::error(
+                    Text::new("diff.invalid_target", "Expected '{0}', a panel ID or a file path.").arg(CLIPBOARD_TARGET),
+                )]
+            }
+            (CLIPBOARD_TARGET, _, _) => ("Clipboard".to_string(), state.clipboard().clone()),
+            (_, Some(id), None) => match state.panel_index_by_id(id).and_then(|index| panels.get(index)) {
+                None => return vec![StateChangeRequest::error(Text::new("diff.no_panel", "No panel with ID '{0}'").arg(id))],
+                Some(panel) => (
+                    buffer_name(panel.title(), panel.file_path()),
+                    panel.lines().to_vec(),
+                ),
+            },
+            // split the same as opening file into a panel, so an unchanged buffer has no differences
+            _ => match fs::read(target).map_err(|err| err.to_string()).and_then(|bytes| decode(&bytes)) {
+                Err(err) => return vec![StateChangeRequest::error(
+                    Text::new("diff.unreadable", "Could not read {0}. {1}").arg(format!("{:?}", target)).arg(err),
+                )],
+                Ok((text, _)) => (
+                    target.to_string(),
+                    text.split('\n').map(|line| line.to_string()).collect(),
+                ),
+            },
+        };
+
+        show_diff(state, panels, commands, old_name, &old, new_name, &new)
+    }
+}
+
+// checkpoint of active panel's buffer against what it holds now
+pub struct CompareWithCheckpoint {
+    label: String,
+    lines: Vec<String>,
+}
+
+impl CompareWithCheckpoint {
+    pub fn new(label: String, lines: Vec<String>) -> Self {
+        Self { label, lines }
+    }
+}
+
+impl StateChange for CompareWithCheckpoint {
+    fn apply(self: Box<Self>, state: &mut AppState, panels: &mut Panels, commands: &mut Manager) -> Vec<StateChangeRequest> {
+        let (new_name, new) = match panels.get(state.active_panel()) {
+            None => return vec![StateChangeRequest::error(Text::new("diff.no_panel", "No panel to compare."))],
+            Some(panel) => (
+                buffer_name(panel.title(), panel.file_path()),
+                panel.lines().to_vec(),
+            ),
+        };
+
+        let old_name = format!("Checkpoint {}", self.label);
+        show_diff(state, panels, commands, old_name, &self.lines, new_name, &new)
+    }
+}
+
+// result opens in diff panel beside active one
+fn show_diff(
+    state: &mut AppState,
+    panels: &mut Panels,
+    commands: &mut Manager,
+    old_name: String,
+    old: &[String],
+    new_name: String,
+    new: &[String],
+) -> Vec<StateChangeRequest> {
+    let lines = match diff_lines(old, new) {
+        Err(err) => return vec![StateChangeRequest::error(err)],
+        Ok(lines) => lines,
+    };
+
+    if lines.iter().all(|line| matches!(line, DiffLine::Same(_))) {
+        return vec![StateChangeRequest::info(
+            Text::new("diff.no_differences", "No differences between {0} and {1}.").arg(old_name).arg(new_name),
+        )];
+    }
+
+    match state.split_active_panel(panels, commands).and_then(|index| panels.get_mut(index)) {
+        None => vec![],
+        Some(panel) => {
+            *panel = PanelFactory::diff();
+            panel.set_diff(Some(DiffView::new(old_name, new_name, lines)));
+            vec![]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::StateChange;
+    use crate::commands::Manager;
+    use crate::diff::{diff_lines, format_diff, CompareWith, CompareWithCheckpoint, DiffLine};
+    use crate::panels::DIFF_PANEL_TYPE_ID;
+    use crate::snapshots::lines;
+    use crate::{AppState, Panels};
+
+    #[test]
+    fn identical() {
+        let diff = diff_lines(&lines("a\nb"), &lines("a\nb")).unwrap();
+
+        assert_eq!(diff, vec![DiffLine::Same("a".to_string()), DiffLine::Same("b".to_string())]);
+    }
+
+    #[test]
+    fn changed_line() {
+        let diff = diff_lines(&lines("a\nb\nc"), &lines("a\nx\nc")).unwrap();
+
+        assert_eq!(
+            format_diff("old", "new", &diff),
+            "--- old\n+++ new\n  a\n- b\n+ x\n  c"
+        );
+    }
+
+    #[test]
+    fn added_and_removed() {
+        let diff = diff_lines(&lines("one\ntwo\nthree\nfour"), &lines("zero\none\nthree\nfour\nfive")).unwrap();
+
+        assert_eq!(
+            diff,
+            vec![
+                DiffLine::Added("zero".to_string()),
+                DiffLine::Same("one".to_string()),
+                DiffLine::Removed("two".to_string()),
+                DiffLine::Same("three".to_string()),
+                DiffLine::Same("four".to_string()),
+                DiffLine::Added("five".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn against_empty() {
+        let diff = diff_lines(&lines("a\nb"), &[]).unwrap();
+
+        assert_eq!(diff, vec![DiffLine::Removed("a".to_string()), DiffLine::Removed("b".to_string())]);
+    }
+
+    #[test]
+    fn compare_with_file_opens_diff_panel() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        let path = std::env::temp_dir().join(format!("edish_compare_{}", std::process::id()));
+        std::fs::write(&path, "a\nc").unwrap();
+        panels.get_mut(app.active_panel()).unwrap().set_text("a\nb");
+
+        let compare = Box::new(CompareWith::new(path.to_string_lossy().to_string()));
+        let changes = compare.apply(&mut app, &mut panels, &mut commands);
+        assert!(changes.is_empty());
+
+        let index = (0..panels.len())
+            .find(|i| panels.get(*i).unwrap().panel_type() == DIFF_PANEL_TYPE_ID)
+            .unwrap();
+        let view = panels.get(index).unwrap().diff().unwrap();
+        assert_eq!(view.hunk_starts(), vec![1]);
+        assert!(view.text().ends_with("  a\n- b\n+ c"));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn compare_with_checkpoint_shows_changes_since() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        let panel = panels.get_mut(app.active_panel()).unwrap();
+        panel.set_text("a\nb");
+        panel.add_checkpoint("first");
+        panel.set_text("a\nc");
+
+        let lines = panel.checkpoint("first").unwrap().to_vec();
+        let compare = Box::new(CompareWithCheckpoint::new("first".to_string(), lines));
+        assert!(compare.apply(&mut app, &mut panels, &mut commands).is_empty());
+
+        let index = (0..panels.len())
+            .find(|i| panels.get(*i).unwrap().panel_type() == DIFF_PANEL_TYPE_ID)
+            .unwrap();
+        let view = panels.get(index).unwrap().diff().unwrap();
+        assert!(view.text().starts_with("--- Checkpoint first\n"));
+        assert!(view.text().ends_with("  a\n- b\n+ c"));
+    }
+}
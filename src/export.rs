@@ -0,0 +1,268 @@
+use std::fs;
+use std::path::Path;
+
+use tui::style::{Color, Style};
+
+use crate::app::{StateChange, StateChangeRequest};
+use crate::commands::Manager;
+use crate::locale::Text;
+use crate::theme::Theme;
+use crate::{AppState, Panels};
+
+// lines drawn with theme's text and gutter styles
+// there's no syntax highlighting yet, when there is its styles belong here too
+pub fn to_ansi(lines: &[String], theme: &Theme) -> String {
+    let width = lines.len().to_string().len();
+
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            format!(
+                "{}{:>width$} \x1b[0m{}{}\x1b[0m\n",
+                ansi_style(&theme.gutter),
+                i + 1,
+                ansi_style(&theme.text),
+                line,
+                width = width
+            )
+        })
+        .collect()
+}
+
+pub fn to_html(title: &str, lines: &[String], theme: &Theme) -> String {
+    let width = lines.len().to_string().len();
+    let body: String = lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            format!(
+                "<span class=\"gutter\">{:>width$} </span>{}\n",
+                i + 1,
+                escape_html(line),
+                width = width
+            )
+        })
+        .collect();
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n\
+         pre {{ {} }}\n.gutter {{ {} }}\n</style>\n</head>\n<body>\n<pre>\n{}</pre>\n</body>\n</html>\n",
+        escape_html(title),
+        css_style(&theme.text),
+        css_style(&theme.gutter),
+        body
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn ansi_style(style: &Style) -> String {
+    let mut codes = vec![];
+
+    if let Some(fg) = style.fg {
+        codes.push(ansi_color(fg, 30));
+    }
+
+    if let Some(bg) = style.bg {
+        codes.push(ansi_color(bg, 40));
+    }
+
+    match codes.is_empty() {
+        true => String::new(),
+        false => format!("\x1b[{}m", codes.join(";")),
+    }
+}
+
+// base is 30 for foreground and 40 for background
+fn ansi_color(color: Color, base: u8) -> String {
+    match color {
+        Color::Reset => (base + 9).to_string(),
+        Color::Black => base.to_string(),
+        Color::Red => (base + 1).to_string(),
+        Color::Green => (base + 2).to_string(),
+        Color::Yellow => (base + 3).to_string(),
+        Color::Blue => (base + 4).to_string(),
+        Color::Magenta => (base + 5).to_string(),
+        Color::Cyan => (base + 6).to_string(),
+        Color::Gray => (base + 7).to_string(),
+        Color::DarkGray => (base + 60).to_string(),
+        Color::LightRed => (base + 61).to_string(),
+        Color::LightGreen => (base + 62).to_string(),
+        Color::LightYellow => (base + 63).to_string(),
+        Color::LightBlue => (base + 64).to_string(),
+        Color::LightMagenta => (base + 65).to_string(),
+        Color::LightCyan => (base + 66).to_string(),
+        Color::White => (base + 67).to_string(),
+        Color::Indexed(index) => format!("{};5;{}", base + 8, index),
+        Color::Rgb(r, g, b) => format!("{};2;{};{};{}", base + 8, r, g, b),
+    }
+}
+
+fn css_style(style: &Style) -> String {
+    let mut properties = vec![];
+
+    if let Some(rgb) = style.fg.and_then(rgb) {
+        properties.push(format!("color: {};", css_color(rgb)));
+    }
+
+    if let Some(rgb) = style.bg.and_then(rgb) {
+        properties.push(format!("background-color: {};", css_color(rgb)));
+    }
+
+    properties.join(" ")
+}
+
+fn css_color((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+// xterm's default palette, reset has no fixed color
+fn rgb(color: Color) -> Option<(u8, u8, u8)> {
+    const BASIC: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 0, 0),
+        (0, 205, 0),
+        (205, 205, 0),
+        (0, 0, 238),
+        (205, 0, 205),
+        (0, 205, 205),
+        (229, 229, 229),
+        (127, 127, 127),
+        (255, 0, 0),
+        (0, 255, 0),
+        (255, 255, 0),
+        (92, 92, 255),
+        (255, 0, 255),
+        (0, 255, 255),
+        (255, 255, 255),
+    ];
+
+    let index = match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => return Some((r, g, b)),
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+        Color::Indexed(index) => index,
+    };
+
+    Some(match index {
+        0..=15 => BASIC[index as usize],
+        // 6x6x6 color cube
+        16..=231 => {
+            let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+            let cube = index - 16;
+            (level(cube / 36), level(cube / 6 % 6), level(cube % 6))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    })
+}
+
+pub struct ExportBuffer {
+    path: String,
+}
+
+impl ExportBuffer {
+    pub fn new(path: String) -> Self {
+        Self { path }
+    }
+}
+
+impl StateChange for ExportBuffer {
+    fn apply(self: Box<Self>, state: &mut AppState, panels: &mut Panels, _commands: &mut Manager) -> Vec<StateChangeRequest> {
+        let panel = match panels.get(state.active_panel()) {
+            None => return vec![StateChangeRequest::error(Text::new("export.no_panel", "No panel to export."))],
+            Some(panel) => panel,
+        };
+
+        let path = Path::new(self.path.trim());
+        let is_html = path
+            .extension()
+            .map(|extension| extension == "html" || extension == "htm")
+            .unwrap_or(false);
+
+        let contents = match is_html {
+            true => to_html(panel.title(), &panel.lines().to_vec(), state.theme()),
+            false => to_ansi(&panel.lines().to_vec(), state.theme()),
+        };
+
+        match fs::write(path, contents) {
+            Err(err) => vec![StateChangeRequest::error(
+                Text::new("export.failed", "Could not export to {0}. {1}").arg(format!("{:?}", path)).arg(err),
+            )],
+            Ok(()) => vec![StateChangeRequest::info(
+                Text::new("export.done", "Exported {0} as {1} to {2}.")
+                    .arg(panel.title())
+                    .arg(if is_html { "HTML" } else { "ANSI text" })
+                    .arg(format!("{:?}", path)),
+            )],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tui::style::{Color, Style};
+
+    use crate::export::{ansi_color, rgb, to_ansi, to_html};
+    use crate::snapshots::lines;
+    use crate::theme::Theme;
+
+    #[test]
+    fn ansi_colors() {
+        assert_eq!(ansi_color(Color::Red, 30), "31");
+        assert_eq!(ansi_color(Color::White, 40), "107");
+        assert_eq!(ansi_color(Color::Indexed(214), 30), "38;5;214");
+        assert_eq!(ansi_color(Color::Rgb(1, 2, 3), 40), "48;2;1;2;3");
+    }
+
+    #[test]
+    fn ansi_lines_numbered() {
+        let theme = Theme {
+            text: Style::default(),
+            gutter: Style::default(),
+            ..Theme::default()
+        };
+
+        assert_eq!(to_ansi(&lines("a\nb"), &theme), "1 \x1b[0ma\x1b[0m\n2 \x1b[0mb\x1b[0m\n");
+    }
+
+    #[test]
+    fn html_escapes_text() {
+        let html = to_html("<title>", &lines("if a < b && c > d {"), &Theme::default());
+
+        assert!(html.contains("<title>&lt;title&gt;</title>"));
+        assert!(html.contains("if a &lt; b &amp;&amp; c &gt; d {"));
+        assert!(html.contains("pre { color: #ffffff; background-color: #000000; }"));
+    }
+
+    #[test]
+    fn palette_colors() {
+        assert_eq!(rgb(Color::Indexed(16)), Some((0, 0, 0)));
+        assert_eq!(rgb(Color::Indexed(214)), Some((255, 175, 0)));
+        assert_eq!(rgb(Color::Indexed(232)), Some((8, 8, 8)));
+        assert_eq!(rgb(Color::Reset), None);
+    }
+}
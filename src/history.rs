@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+pub const HISTORY_LIMIT: usize = 50;
+
+// previous answers to input requests, kept per prompt
+#[derive(Default)]
+pub struct InputHistory {
+    entries: HashMap<String, Vec<String>>,
+    // index from newest while recalling
+    position: Option<usize>,
+    // text being typed before recall started
+    draft: String,
+}
+
+impl InputHistory {
+    pub fn push(&mut self, prompt: &str, entry: String) {
+        self.reset_position();
+
+        if entry.is_empty() {
+            return;
+        }
+
+        let entries = self.entries.entry(prompt.to_string()).or_default();
+        if entries.last() == Some(&entry) {
+            return;
+        }
+
+        entries.push(entry);
+        if entries.len() > HISTORY_LIMIT {
+            entries.remove(0);
+        }
+    }
+
+    pub fn reset_position(&mut self) {
+        self.position = None;
+        self.draft.clear();
+    }
+
+    pub fn previous(&mut self, prompt: &str, current: &str) -> Option<String> {
+        let entries = self.entries.get(prompt)?;
+        let next = match self.position {
+            None => {
+                self.draft = current.to_string();
+                0
+            }
+            Some(position) => position + 1,
+        };
+
+        if next >= entries.len() {
+            return None;
+        }
+
+        self.position = Some(next);
+        entries.get(entries.len() - 1 - next).cloned()
+    }
+
+    // past newest entry gives back draft
+    pub fn next(&mut self, prompt: &str) -> Option<String> {
+        let entries = self.entries.get(prompt)?;
+        match self.position {
+            None => None,
+            Some(0) => {
+                self.position = None;
+                Some(std::mem::take(&mut self.draft))
+            }
+            Some(position) => {
+                self.position = Some(position - 1);
+                entries.get(entries.len() - position).cloned()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::history::{InputHistory, HISTORY_LIMIT};
+
+    #[test]
+    fn recall_previous_and_next() {
+        let mut history = InputHistory::default();
+        history.push("Open", "one.txt".to_string());
+        history.push("Open", "two.txt".to_string());
+
+        assert_eq!(history.previous("Open", "dra"), Some("two.txt".to_string()));
+        assert_eq!(history.previous("Open", "two.txt"), Some("one.txt".to_string()));
+        assert_eq!(history.previous("Open", "one.txt"), None);
+        assert_eq!(history.next("Open"), Some("two.txt".to_string()));
+        assert_eq!(history.next("Open"), Some("dra".to_string()));
+        assert_eq!(history.next("Open"), None);
+    }
+
+    #[test]
+    fn separate_per_prompt() {
+        let mut history = InputHistory::default();
+        history.push("Open", "one.txt".to_string());
+        history.push("Save", "two.txt".to_string());
+
+        assert_eq!(history.previous("Save", ""), Some("two.txt".to_string()));
+        assert_eq!(history.previous("Save", ""), None);
+        assert_eq!(history.previous("Ruler", ""), None);
+    }
+
+    #[test]
+    fn skips_empty_and_repeated() {
+        let mut history = InputHistory::default();
+        history.push("Open", "one.txt".to_string());
+        history.push("Open", "one.txt".to_string());
+        history.push("Open", "".to_string());
+
+        assert_eq!(history.previous("Open", ""), Some("one.txt".to_string()));
+        assert_eq!(history.previous("Open", ""), None);
+    }
+
+    #[test]
+    fn capped() {
+        let mut history = InputHistory::default();
+        for i in 0..HISTORY_LIMIT + 10 {
+            history.push("Open", i.to_string());
+        }
+
+        let mut count = 0;
+        while history.previous("Open", "").is_some() {
+            count += 1;
+        }
+
+        assert_eq!(count, HISTORY_LIMIT);
+    }
+}
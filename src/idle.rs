@@ -0,0 +1,75 @@
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_IDLE_DELAY: Duration = Duration::from_millis(300);
+
+// runs analysis once input stops for a while, so typing never waits on it
+// starts pending so files opened at launch are looked at too
+pub struct IdleWork {
+    delay: Duration,
+    last_input: Instant,
+    pending: bool,
+}
+
+impl IdleWork {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            last_input: Instant::now(),
+            pending: true,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    // call for every key or mouse event
+    pub fn input(&mut self) {
+        self.last_input = Instant::now();
+        self.pending = true;
+    }
+
+    // true once per burst of input, after delay has passed without more
+    pub fn ready(&mut self) -> bool {
+        if !self.pending || self.last_input.elapsed() < self.delay {
+            return false;
+        }
+
+        self.pending = false;
+        true
+    }
+}
+
+impl Default for IdleWork {
+    fn default() -> Self {
+        IdleWork::new(DEFAULT_IDLE_DELAY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::idle::IdleWork;
+
+    #[test]
+    fn waits_for_input_to_stop() {
+        let mut idle = IdleWork::new(Duration::from_secs(60));
+
+        idle.input();
+        assert!(!idle.ready());
+    }
+
+    #[test]
+    fn ready_once_per_input() {
+        let mut idle = IdleWork::new(Duration::ZERO);
+
+        assert!(idle.ready());
+        assert!(!idle.ready());
+
+        idle.input();
+        assert!(idle.ready());
+        assert!(!idle.ready());
+    }
+}
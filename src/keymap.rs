@@ -0,0 +1,219 @@
+use std::fs;
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use crate::cli::export_keys;
+use crate::commands::{CommandKeyId, Manager};
+
+// bindings read from a keymap file
+// conflicts are bindings that replaced another command or couldn't be added
+pub struct KeymapImport {
+    added: usize,
+    conflicts: Vec<String>,
+}
+
+impl KeymapImport {
+    pub fn added(&self) -> usize {
+        self.added
+    }
+
+    pub fn conflicts(&self) -> &Vec<String> {
+        &self.conflicts
+    }
+}
+
+// same format as keys --export, so a file from either can be imported
+pub fn export_keymap(path: &str, commands: &Manager) -> Result<usize, String> {
+    let text = export_keys(commands);
+
+    match fs::write(path, &text) {
+        Err(err) => Err(format!("Could not export keymap to {:?}. {}", path, err)),
+        Ok(()) => Ok(text.lines().count()),
+    }
+}
+
+pub fn import_keymap(path: &str, commands: &mut Manager) -> Result<KeymapImport, String> {
+    match fs::read_to_string(path) {
+        Err(err) => Err(format!("Could not read keymap {:?}. {}", path, err)),
+        Ok(text) => Ok(merge_keymap(&text, commands)),
+    }
+}
+
+// existing bindings are kept unless the same keys are bound in the keymap
+pub fn merge_keymap(text: &str, commands: &mut Manager) -> KeymapImport {
+    let mut import = KeymapImport {
+        added: 0,
+        conflicts: vec![],
+    };
+
+    for (number, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (section, keys, name) = match line.split('\t').collect::<Vec<&str>>()[..] {
+            [section, keys, name] => (section, keys, name),
+            _ => {
+                import.conflicts.push(format!(
+                    "Line {}: expected panel type, keys and command separated by tabs.",
+                    number + 1
+                ));
+                continue;
+            }
+        };
+
+        let path = match parse_keys(keys) {
+            Err(err) => {
+                import.conflicts.push(format!("Line {}: {}", number + 1, err));
+                continue;
+            }
+            Ok(path) => path,
+        };
+
+        match commands.bind(section, &path, name) {
+            Err(err) => import
+                .conflicts
+                .push(format!("{} {}: {} not bound. {}", section, keys, name, err)),
+            Ok(Some(replaced)) if replaced == name => (),
+            Ok(Some(replaced)) => {
+                import.added += 1;
+                import
+                    .conflicts
+                    .push(format!("{} {}: {} replaced by {}.", section, keys, replaced, name));
+            }
+            Ok(None) => import.added += 1,
+        }
+    }
+
+    import
+}
+
+// reverse of format_key for each key in sequence
+pub fn parse_keys(keys: &str) -> Result<Vec<CommandKeyId>, String> {
+    keys.split(" -> ").map(parse_key).collect()
+}
+
+pub fn parse_key(key: &str) -> Result<CommandKeyId, String> {
+    let (mods, code) = match key.split_once(" + ") {
+        None => (KeyModifiers::empty(), key),
+        Some((mods, code)) => {
+            let mut modifiers = KeyModifiers::empty();
+            for c in mods.chars() {
+                modifiers |= match c {
+                    'S' => KeyModifiers::SHIFT,
+                    'C' => KeyModifiers::CONTROL,
+                    'A' => KeyModifiers::ALT,
+                    _ => return Err(format!("Unknown modifier {:?} in {:?}.", c, key)),
+                };
+            }
+
+            (modifiers, code)
+        }
+    };
+
+    let mut chars = code.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some('*'), None) => KeyCode::Null,
+        (Some(c), None) => KeyCode::Char(c),
+        _ => match code {
+            "Backspace" => KeyCode::Backspace,
+            "Enter" => KeyCode::Enter,
+            "Left" => KeyCode::Left,
+            "Right" => KeyCode::Right,
+            "Up" => KeyCode::Up,
+            "Down" => KeyCode::Down,
+            "Home" => KeyCode::Home,
+            "End" => KeyCode::End,
+            "PageUp" => KeyCode::PageUp,
+            "PageDown" => KeyCode::PageDown,
+            "Tab" => KeyCode::Tab,
+            "BackTab" => KeyCode::BackTab,
+            "Delete" => KeyCode::Delete,
+            "Insert" => KeyCode::Insert,
+            "Esc" => KeyCode::Esc,
+            _ => match code.strip_prefix("F(").and_then(|rest| rest.strip_suffix(')')) {
+                Some(number) => match number.parse::<u8>() {
+                    Ok(number) => KeyCode::F(number),
+                    Err(_) => return Err(format!("Unknown key {:?}.", key)),
+                },
+                None => return Err(format!("Unknown key {:?}.", key)),
+            },
+        },
+    };
+
+    Ok(CommandKeyId::new(code, mods))
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    use crate::commands::{CommandKeyId, Manager};
+    use crate::keymap::{merge_keymap, parse_key, parse_keys};
+    use crate::panels::commands::format_key;
+
+    #[test]
+    fn every_default_binding_parses_back() {
+        let commands = Manager::default();
+        let mut bindings = commands.global_key_bindings();
+        for (_, panel_bindings) in commands.panel_key_bindings() {
+            bindings.extend(panel_bindings);
+        }
+
+        for (path, details) in bindings {
+            let formatted = path.iter().map(format_key).collect::<Vec<String>>().join(" -> ");
+
+            assert_eq!(parse_keys(&formatted), Ok(path), "{} {}", formatted, details.name());
+        }
+    }
+
+    #[test]
+    fn parse_keys_with_modifiers() {
+        assert_eq!(
+            parse_key("SC + +"),
+            Ok(CommandKeyId::new(KeyCode::Char('+'), KeyModifiers::SHIFT | KeyModifiers::CONTROL))
+        );
+        assert_eq!(parse_key("A + F(5)"), Ok(CommandKeyId::new(KeyCode::F(5), KeyModifiers::ALT)));
+        assert!(parse_key("X + a").is_err());
+        assert!(parse_key("Enterr").is_err());
+    }
+
+    #[test]
+    fn merge_reports_conflicts() {
+        let mut commands = Manager::default();
+
+        let keymap = "# shared preset\n\
+                      Global\tC + p -> m\tJump To Messages\n\
+                      Global\tC + p -> m\tRun Shell Command\n\
+                      Global\tC + p -> m -> r\tRun Shell Command\n\
+                      Edit\tC + o\tNo Such Command\n\
+                      Global\tF(9)\tRun Shell Command\n\
+                      not a binding\n";
+
+        let import = merge_keymap(keymap, &mut commands);
+
+        assert_eq!(import.added(), 2);
+        assert_eq!(
+            import.conflicts(),
+            &vec![
+                "Global C + p -> m: Jump To Messages replaced by Run Shell Command.".to_string(),
+                "Global C + p -> m -> r: Run Shell Command not bound. Existing command in sequence.".to_string(),
+                "Edit C + o: No Such Command not bound. No command named \"No Such Command\".".to_string(),
+                "Line 7: expected panel type, keys and command separated by tabs.".to_string(),
+            ]
+        );
+
+        let bindings = commands.global_key_bindings();
+        let bound_to = |keys: &str| {
+            bindings
+                .iter()
+                .find(|(path, _)| *path == parse_keys(keys).unwrap())
+                .map(|(_, details)| details.name().clone())
+        };
+
+        assert_eq!(bound_to("C + p -> m"), Some("Run Shell Command".to_string()));
+        assert_eq!(bound_to("F(9)"), Some("Run Shell Command".to_string()));
+        // old binding stays
+        assert_eq!(bound_to("CA + r"), Some("Run Shell Command".to_string()));
+    }
+}
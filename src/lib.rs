@@ -0,0 +1,421 @@
+extern crate core;
+
+use std::io;
+use std::time::Duration;
+
+use crossterm::event::{
+    poll, read, DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture, Event, KeyCode,
+    KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use tui::layout::Rect;
+use tui::{Frame, Terminal};
+
+pub use crate::app::{global_commands, AppState, MessagesPlacement, StateChange, StateChangeRequest};
+use crate::autosave::{leftover_recovery_dirs, recover_buffers, Autosave, FocusSave};
+use crate::cli::Subcommand;
+use crate::locale::Catalog;
+use crate::idle::IdleWork;
+use crate::lock::IdleLock;
+use crate::logger::Logger;
+use crate::positions::CursorPositions;
+use crate::recent::RecentFiles;
+use crate::config::{parse_layout, Config};
+pub use crate::commands::{
+    catch_all, ctrl_key, key, CommandDetails, CommandKeyId, Commands, KeyProfile, KeyTranslator, Manager,
+};
+pub use crate::panels::{PanelBuilder, PanelFactory, Panels, TextPanel};
+use crate::panels::commands::format_key;
+pub use crate::backend::EditorBackend;
+pub use crate::render::{render_app, CURSOR_MAX};
+use crate::splits::{PanelSplit, UserSplits};
+use crate::theme::Theme;
+use crate::timing::FrameTimer;
+use crate::watch::FileWatcher;
+
+pub mod app;
+mod autocomplete;
+mod autosave;
+mod backend;
+mod cli;
+pub mod commands;
+mod config;
+mod diff;
+mod export;
+mod history;
+mod idle;
+mod keymap;
+mod locale;
+mod lock;
+mod logger;
+pub mod panels;
+mod positions;
+mod process;
+mod recent;
+mod remote;
+pub mod render;
+mod script;
+mod share;
+#[cfg(test)]
+mod snapshots;
+mod splits;
+mod swap;
+mod tasks;
+mod timer;
+mod theme;
+mod timing;
+mod watch;
+
+pub type EditorFrame<'a> = Frame<'a, EditorBackend>;
+
+// how long to wait for input before updating and redrawing anyway
+const TICK_RATE: Duration = Duration::from_millis(100);
+
+// start without anything that was saved or configured, in case it's what is broken
+const SAFE_MODE_ARG: &str = "--safe-mode";
+
+fn init_default_layout(app_state: &mut AppState, panels: &mut Panels, commands: &mut commands::Manager) {
+    app_state.init(panels, commands);
+
+    // default when config doesn't give a layout
+    // don't want to change layout in state defaults everytime since it would continually break tests
+    app_state.set_active_panel(2);
+    app_state.split_current_panel_vertical(KeyCode::Null, panels, commands);
+    match panels.get_mut(3) {
+        None => app_state.add_error("Failed to update panel to commands."),
+        Some(panel) => *panel = TextPanel::commands_panel(),
+    }
+    app_state.set_active_panel(1);
+}
+
+// backs out of prompt, key sequence, insert mode or search before quitting
+// true when editor should close
+fn handle_escape(app_state: &mut AppState, panels: &mut Panels, commands: &mut commands::Manager) -> bool {
+    if app_state.input_request().is_some() {
+        app_state.handle_changes(vec![StateChangeRequest::InputCanceled], panels, commands);
+        return false;
+    }
+
+    if app_state.cancel_key_sequence(commands)
+        || app_state.leave_insert_mode(panels)
+        || app_state.leave_column_select(panels)
+        || app_state.cancel_search(panels)
+    {
+        return false;
+    }
+
+    if app_state.escape_quits() {
+        app_state.quit(KeyCode::Esc, panels, commands);
+    }
+
+    app_state.should_quit()
+}
+
+// runs editor in current terminal until quit, or a subcommand given in arguments
+pub fn run() -> Result<(), String> {
+    match Subcommand::from_args(std::env::args()) {
+        None => (),
+        Some(subcommand) => {
+            print!("{}", subcommand?.run()?);
+            return Ok(());
+        }
+    }
+
+    enable_raw_mode().map_err(|err| err.to_string())?;
+
+    execute!(io::stdout(), EnterAlternateScreen, DisableMouseCapture, EnableFocusChange)
+        .map_err(|err| err.to_string())?;
+    let mut terminal = Terminal::new(EditorBackend::terminal()).map_err(|err| err.to_string())?;
+
+    // keys are still reported the usual way if this fails, so it isn't an error
+    let profile = KeyProfile::detect();
+    let enhanced_keys = profile.enhanced_keys()
+        && execute!(io::stdout(), PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)).is_ok();
+
+    let mut panels = Panels::new();
+    let mut app_state = AppState::new();
+    let mut commands = commands::Manager::default();
+
+    let size = terminal.size().unwrap_or_default();
+    app_state.resize(size.width, size.height, &mut panels);
+    let translator = KeyTranslator::new(profile);
+    let mut frame_timer = FrameTimer::default();
+    app_state.set_autosave(Autosave::from_args(std::env::args()));
+
+    let safe_mode = std::env::args().any(|arg| arg == SAFE_MODE_ARG);
+    app_state.set_safe_mode(safe_mode);
+
+    let config = match safe_mode {
+        true => None,
+        false => match Config::load() {
+            Err(err) => {
+                app_state.add_error(err);
+                None
+            }
+            Ok(config) => config,
+        },
+    };
+
+    app_state.set_config(config.clone().unwrap_or_default());
+
+    match config.as_ref().and_then(|c| c.get("locale")) {
+        None => (),
+        Some(locale) => match Catalog::load(locale) {
+            Ok(catalog) => app_state.set_catalog(catalog),
+            Err(err) => app_state.add_error(err),
+        },
+    }
+
+    match config.as_ref().map(Theme::from_config) {
+        None => (),
+        Some(Ok(theme)) => app_state.set_theme(theme),
+        Some(Err(err)) => app_state.add_error(err),
+    }
+
+    // seconds without a key press before panels are hidden, off when missing or 0
+    match config.as_ref().and_then(|c| c.get("lock_after")) {
+        None => (),
+        Some(seconds) => match seconds.parse::<u64>() {
+            Ok(0) => (),
+            Ok(seconds) => app_state.set_idle_lock(Some(IdleLock::new(Duration::from_secs(seconds)))),
+            Err(_) => app_state.add_error(format!("Invalid lock_after {:?} in config. Expected seconds.", seconds)),
+        },
+    }
+
+    // milliseconds without input before buffers are scanned again
+    match config.as_ref().and_then(|c| c.get("idle_delay")) {
+        None => (),
+        Some(millis) => match millis.parse::<u64>() {
+            Ok(millis) => app_state.set_idle_work(IdleWork::new(Duration::from_millis(millis))),
+            Err(_) => app_state.add_error(format!("Invalid idle_delay {:?} in config. Expected milliseconds.", millis)),
+        },
+    }
+
+    // command line takes priority over config
+    let logger = Logger::from_args(std::env::args())
+        .or_else(|| config.as_ref().and_then(|c| c.get("log")).map(Logger::open));
+
+    match logger {
+        None => (),
+        Some(Ok(logger)) => app_state.set_logger(logger),
+        Some(Err(err)) => app_state.add_error(err),
+    }
+
+    match config.as_ref().and_then(|c| c.get("messages")) {
+        None => (),
+        Some(name) => match MessagesPlacement::from_name(name) {
+            Some(placement) => app_state.set_messages_placement(placement),
+            None => app_state.add_error(format!(
+                "Invalid messages placement {:?} in config. Expected split, strip, auto or status.",
+                name
+            )),
+        },
+    }
+
+    // on unless turned off, asks before open files changed elsewhere are overwritten
+    if config.as_ref().and_then(|c| c.get("watch_files")).map(|v| v.as_str()) != Some("false") {
+        app_state.set_file_watcher(Some(FileWatcher::default()));
+    }
+
+    // off unless turned on, saves when switching panels
+    if config.as_ref().and_then(|c| c.get("save_on_focus_change")).map(|v| v.as_str()) == Some("true") {
+        app_state.set_focus_save(Some(FocusSave::default()));
+    }
+
+    // on unless turned off, Quit command closes editor either way
+    if config.as_ref().and_then(|c| c.get("escape_quits")).map(|v| v.as_str()) == Some("false") {
+        app_state.set_escape_quits(false);
+    }
+
+    // off unless turned on
+    if config.as_ref().and_then(|c| c.get("dim_unfocused")).map(|v| v.as_str()) == Some("true") {
+        app_state.set_dim_unfocused(true);
+    }
+
+    // on unless turned off, capturing mouse takes over terminal's own text selection
+    if config.as_ref().and_then(|c| c.get("mouse")).map(|v| v.as_str()) != Some("false") {
+        execute!(io::stdout(), EnableMouseCapture).map_err(|err| err.to_string())?;
+    }
+
+    // on unless turned off
+    let remember_cursor = config.as_ref().and_then(|c| c.get("remember_cursor")).map(|v| v.as_str()) != Some("false");
+    match CursorPositions::default_file() {
+        Some(file) if remember_cursor && !safe_mode => match CursorPositions::load(file) {
+            Ok(positions) => app_state.set_cursor_positions(Some(positions)),
+            Err(err) => app_state.add_error(err),
+        },
+        _ => (),
+    }
+
+    match RecentFiles::default_file() {
+        Some(file) if !safe_mode => match RecentFiles::load(file) {
+            Ok(recent_files) => app_state.set_recent_files(recent_files),
+            Err(err) => app_state.add_error(err),
+        },
+        _ => (),
+    }
+
+    if safe_mode {
+        app_state.add_info(
+            "Safe mode. Skipped user config, project config, cursor positions, recent files and recovery of \
+             unsaved buffers. Nothing from this session will be remembered.",
+        );
+    }
+
+    let layout = config.as_ref().and_then(|c| c.get("layout").map(|l| parse_layout(l)));
+
+    match layout {
+        Some(Ok(layout)) => {
+            if let Err(err) = app_state.init_with_layout(&layout, &mut panels, &mut commands) {
+                app_state.add_error(format!("Invalid layout in config. {}", err));
+                panels = Panels::new();
+                commands = commands::Manager::default();
+                init_default_layout(&mut app_state, &mut panels, &mut commands);
+            }
+        }
+        Some(Err(err)) => {
+            app_state.add_error(format!("Invalid layout in config. {}", err));
+            init_default_layout(&mut app_state, &mut panels, &mut commands);
+        }
+        None => init_default_layout(&mut app_state, &mut panels, &mut commands),
+    }
+
+    // milliseconds, 0 waits forever like before timeouts existed
+    match config.as_ref().and_then(|c| c.get("key_timeout")) {
+        None => (),
+        Some(millis) => match millis.parse::<u64>() {
+            Ok(0) => commands.set_sequence_timeout(None),
+            Ok(millis) => commands.set_sequence_timeout(Some(Duration::from_millis(millis))),
+            Err(_) => app_state.add_error(format!("Invalid key_timeout {:?} in config. Expected milliseconds.", millis)),
+        },
+    }
+
+    // unnamed buffers autosaved by a session that was killed or crashed
+    if !safe_mode {
+        recover_buffers(leftover_recovery_dirs(), &mut app_state, &mut panels, &mut commands);
+    }
+
+    // on unless turned off, editor doesn't take files to open so it's shown at every start
+    if config.as_ref().and_then(|c| c.get("welcome")).map(|v| v.as_str()) != Some("false") {
+        app_state.show_welcome(KeyCode::Null, &mut panels, &mut commands);
+    }
+
+    // while unfocused, only redraw after an event
+    let mut redraw = true;
+
+    loop {
+        app_state.update(&mut panels, &mut commands);
+
+        if app_state.focused() || redraw {
+            terminal
+                .draw(|frame| render_app(&app_state, &commands, &panels, frame))
+                .map_err(|err| err.to_string())?;
+        }
+        redraw = false;
+
+        match frame_timer.finish() {
+            None => (),
+            Some(slow) => app_state.add_warning(format!(
+                "Slow frame: {}ms after {}",
+                slow.elapsed().as_millis(),
+                slow.cause()
+            )),
+        }
+
+        // hide cursor if at max
+        if terminal.get_cursor().unwrap_or_default() == CURSOR_MAX {
+            terminal.hide_cursor().unwrap_or_default();
+        } else {
+            terminal.show_cursor().unwrap_or_default();
+        }
+
+        // nothing pressed, loop around to update and redraw
+        if !poll(TICK_RATE).map_err(|err| err.to_string())? {
+            continue;
+        }
+
+        redraw = true;
+
+        match read().map_err(|err| err.to_string())? {
+            Event::Key(event) => {
+                app_state.note_input();
+
+                if app_state.handle_lock_key(event.code) {
+                    continue;
+                }
+
+                app_state.dismiss_welcome(&mut panels);
+
+                // Loop breaking doesn't work with current implementation
+                if event.code == KeyCode::Esc {
+                    if handle_escape(&mut app_state, &mut panels, &mut commands) {
+                        break;
+                    }
+
+                    continue;
+                }
+
+                // allow active panel to receive first
+                // unless global is in progress
+                // if active panel doesn't handle event
+                // then check global
+
+                // Note for available controls as of crossterm "0.25"
+                // without enhanced keys (see KeyProfile::Enhanced):
+                // CTRL with number keys and their symbols don't work
+                // CTRL with 'i' and 'm' currently don't work
+                // All ALT and SHIFT letters, numbers and symbols work
+                //      shift on symbols is dropped by the translator
+                //      since the given char is already correct
+                // Shift not working with Backspace or Enter
+
+                // app_state.add_info(format!("Received key: {:?} {:?}", event.code, event.modifiers));
+
+                let key = translator.translate(event);
+                frame_timer.start(format_key(&key));
+
+                commands.advance(key, &mut app_state, &mut panels);
+
+                if app_state.should_quit() {
+                    break;
+                }
+            }
+            Event::Mouse(event) => {
+                app_state.note_input();
+                app_state.handle_mouse(event, &mut panels, &mut commands)
+            }
+            Event::FocusGained => app_state.set_focused(true, &mut panels),
+            Event::FocusLost => app_state.set_focused(false, &mut panels),
+            Event::Resize(width, height) => {
+                // size from event, backend may not report new one yet, clearing redraws everything
+                terminal
+                    .resize(Rect::new(0, 0, width, height))
+                    .map_err(|err| err.to_string())?;
+                app_state.resize(width, height, &mut panels);
+            }
+            Event::Paste(_) => (),
+        }
+    }
+
+    // terminal is about to be restored, nowhere to show an error
+    app_state.save_cursor_positions(&panels).unwrap_or_default();
+
+    if enhanced_keys {
+        execute!(io::stdout(), PopKeyboardEnhancementFlags).unwrap_or_default();
+    }
+
+    disable_raw_mode().map_err(|err| err.to_string())?;
+    execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableFocusChange
+    )
+    .map_err(|err| err.to_string())?;
+    terminal.show_cursor().map_err(|err| err.to_string())?;
+
+    Ok(())
+}
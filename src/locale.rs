@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::Config;
+
+pub const DEFAULT_LOCALE: &str = "en";
+
+// shown to the user, catalog looks up key and fills {0}, {1}... with args in order
+// english is shown when locale has no entry, text without a key is shown as is, e.g. io errors
+#[derive(Debug, Clone, Default, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Text {
+    key: Option<&'static str>,
+    english: String,
+    args: Vec<String>,
+}
+
+impl Text {
+    pub fn new(key: &'static str, english: &str) -> Self {
+        Self {
+            key: Some(key),
+            english: english.to_string(),
+            args: vec![],
+        }
+    }
+
+    pub fn arg<T: ToString>(mut self, arg: T) -> Self {
+        self.args.push(arg.to_string());
+        self
+    }
+}
+
+impl From<String> for Text {
+    fn from(text: String) -> Self {
+        Self {
+            key: None,
+            english: text,
+            args: vec![],
+        }
+    }
+}
+
+impl From<&str> for Text {
+    fn from(text: &str) -> Self {
+        Text::from(text.to_string())
+    }
+}
+
+impl fmt::Display for Text {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.key {
+            None => f.write_str(&self.english),
+            Some(_) => f.write_str(&fill(&self.english, &self.args)),
+        }
+    }
+}
+
+// compares english as shown when no catalog is loaded
+impl PartialEq<str> for Text {
+    fn eq(&self, other: &str) -> bool {
+        self.to_string().as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Text {
+    fn eq(&self, other: &&str) -> bool {
+        self.to_string().as_str() == *other
+    }
+}
+
+// one pass so an argument containing {1} isn't filled in again
+fn fill(template: &str, args: &[String]) -> String {
+    let mut text = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        let arg = after
+            .find('}')
+            .and_then(|end| after[..end].parse::<usize>().ok().and_then(|index| args.get(index)).map(|arg| (end, arg)));
+
+        match arg {
+            Some((end, arg)) => {
+                text.push_str(arg);
+                rest = &after[end + 1..];
+            }
+            None => {
+                text.push('{');
+                rest = after;
+            }
+        }
+    }
+
+    text.push_str(rest);
+    text
+}
+
+// translations by key, one `key = translation` per line
+// commands and panel types are registered by their english name, their keys are `command.<name>`,
+// `description.<name>` and `panel.<type>` so registration code doesn't change
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    entries: HashMap<String, String>,
+}
+
+impl Catalog {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut entries = HashMap::new();
+
+        for (number, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+
+            // spaced separator so translations can contain '='
+            match line.split_once(" = ") {
+                None => return Err(format!("Translation line {} is missing ' = '.", number + 1)),
+                Some((key, translation)) => {
+                    entries.insert(key.trim().to_string(), translation.trim().to_string());
+                }
+            }
+        }
+
+        Ok(Self { entries })
+    }
+
+    // catalogs live next to config, named after their locale
+    pub fn path(locale: &str) -> Option<PathBuf> {
+        Config::path()
+            .and_then(|path| path.parent().map(|dir| dir.to_path_buf()))
+            .map(|dir| dir.join("locales").join(locale))
+    }
+
+    pub fn load(locale: &str) -> Result<Self, String> {
+        if locale == DEFAULT_LOCALE {
+            return Ok(Catalog::default());
+        }
+
+        match Catalog::path(locale) {
+            None => Err(format!("No place to look for translations for locale {:?}.", locale)),
+            Some(path) => fs::read_to_string(&path)
+                .map_err(|err| format!("Could not read translations {:?}. {}", path, err))
+                .and_then(|text| Catalog::parse(&text)),
+        }
+    }
+
+    // english when locale has no entry for text's key
+    pub fn text(&self, text: &Text) -> String {
+        match text.key.and_then(|key| self.entries.get(key)) {
+            None => text.to_string(),
+            Some(translation) => fill(translation, &text.args),
+        }
+    }
+
+    pub fn command_name<'a>(&'a self, name: &'a str) -> &'a str {
+        self.named(&format!("command.{}", name), name)
+    }
+
+    pub fn command_description<'a>(&'a self, name: &str, description: &'a str) -> &'a str {
+        self.named(&format!("description.{}", name), description)
+    }
+
+    pub fn panel_name<'a>(&'a self, panel_type: &'a str) -> &'a str {
+        self.named(&format!("panel.{}", panel_type), panel_type)
+    }
+
+    fn named<'a>(&'a self, key: &str, english: &'a str) -> &'a str {
+        self.entries.get(key).map(|t| t.as_str()).unwrap_or(english)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::locale::{Catalog, Text, DEFAULT_LOCALE};
+
+    #[test]
+    fn translates_by_key() {
+        let catalog = Catalog::parse("# messages\nsaved = Gespeichert\nopened = {0} geöffnet\n").unwrap();
+
+        assert_eq!(catalog.text(&Text::new("saved", "Saved.")), "Gespeichert");
+        assert_eq!(catalog.text(&Text::new("opened", "Opened {0}.").arg("a.txt")), "a.txt geöffnet");
+        assert_eq!(catalog.text(&Text::new("quit", "Quit {0}?").arg("now")), "Quit now?");
+    }
+
+    #[test]
+    fn translation_may_reorder_args() {
+        let catalog = Catalog::parse("moved = {1} <- {0}").unwrap();
+        let text = Text::new("moved", "Moved {0} to {1}.").arg("a").arg("b");
+
+        assert_eq!(text.to_string(), "Moved a to b.");
+        assert_eq!(catalog.text(&text), "b <- a");
+    }
+
+    #[test]
+    fn args_are_filled_once() {
+        let text = Text::new("pair", "{0} and {1}").arg("{1}").arg("b");
+
+        assert_eq!(text.to_string(), "{1} and b");
+        assert_eq!(Text::new("brace", "{x} {2}").arg("a").to_string(), "{x} {2}");
+    }
+
+    #[test]
+    fn text_without_key_not_translated() {
+        let catalog = Catalog::parse("Save = Speichern").unwrap();
+
+        assert_eq!(catalog.text(&Text::from("Save")), "Save");
+        assert_eq!(catalog.text(&Text::from("{0} stays")), "{0} stays");
+    }
+
+    #[test]
+    fn names_keyed_by_kind() {
+        let catalog =
+            Catalog::parse("command.Save = Speichern\ndescription.Save = Datei speichern\npanel.Edit = Bearbeiten").unwrap();
+
+        assert_eq!(catalog.command_name("Save"), "Speichern");
+        assert_eq!(catalog.command_name("Quit"), "Quit");
+        assert_eq!(catalog.command_description("Save", "Save file"), "Datei speichern");
+        assert_eq!(catalog.panel_name("Edit"), "Bearbeiten");
+        assert_eq!(catalog.panel_name("Save"), "Save");
+    }
+
+    #[test]
+    fn translation_may_contain_equals() {
+        let catalog = Catalog::parse("command.Compare = a = b").unwrap();
+
+        assert_eq!(catalog.command_name("Compare"), "a = b");
+    }
+
+    #[test]
+    fn invalid_line() {
+        assert!(Catalog::parse("Save").is_err());
+    }
+
+    #[test]
+    fn default_locale_needs_no_file() {
+        assert_eq!(Catalog::load(DEFAULT_LOCALE).unwrap().command_name("Save"), "Save");
+    }
+}
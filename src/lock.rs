@@ -0,0 +1,126 @@
+use std::time::{Duration, Instant};
+
+use crossterm::event::KeyCode;
+
+pub const LOCKED_TEXT: &str = "Locked. Press any key to resume.";
+pub const CONFIRM_TEXT: &str = "Resume session? (y/n)";
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LockState {
+    Unlocked,
+    Locked,
+    // key was pressed while locked, waiting on confirmation
+    Confirming,
+}
+
+// blanks the editor after no keys have been pressed for a while
+pub struct IdleLock {
+    timeout: Duration,
+    last_activity: Instant,
+    state: LockState,
+}
+
+impl IdleLock {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            last_activity: Instant::now(),
+            state: LockState::Unlocked,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn state(&self) -> LockState {
+        self.state
+    }
+
+    pub fn tick(&mut self) {
+        if self.state == LockState::Unlocked && self.last_activity.elapsed() >= self.timeout {
+            self.state = LockState::Locked;
+        }
+    }
+
+    // true when key was used by lock and shouldn't reach the editor
+    pub fn handle_key(&mut self, code: KeyCode) -> bool {
+        match self.state {
+            LockState::Unlocked => {
+                self.last_activity = Instant::now();
+                false
+            }
+            LockState::Locked => {
+                self.state = LockState::Confirming;
+                true
+            }
+            LockState::Confirming => {
+                match code {
+                    KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                        self.state = LockState::Unlocked;
+                        self.last_activity = Instant::now();
+                    }
+                    _ => self.state = LockState::Locked,
+                }
+
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crossterm::event::KeyCode;
+
+    use crate::lock::{IdleLock, LockState};
+
+    #[test]
+    fn stays_unlocked_before_timeout() {
+        let mut lock = IdleLock::new(Duration::from_secs(60));
+        lock.tick();
+
+        assert_eq!(lock.state(), LockState::Unlocked);
+    }
+
+    #[test]
+    fn locks_after_timeout() {
+        let mut lock = IdleLock::new(Duration::ZERO);
+        lock.tick();
+
+        assert_eq!(lock.state(), LockState::Locked);
+    }
+
+    #[test]
+    fn keys_pass_through_when_unlocked() {
+        let mut lock = IdleLock::new(Duration::from_secs(60));
+
+        assert!(!lock.handle_key(KeyCode::Char('a')));
+    }
+
+    #[test]
+    fn resume_needs_key_and_confirmation() {
+        let mut lock = IdleLock::new(Duration::ZERO);
+        lock.tick();
+
+        assert!(lock.handle_key(KeyCode::Char('a')));
+        assert_eq!(lock.state(), LockState::Confirming);
+
+        assert!(lock.handle_key(KeyCode::Char('y')));
+        assert_eq!(lock.state(), LockState::Unlocked);
+    }
+
+    #[test]
+    fn declining_confirmation_stays_locked() {
+        let mut lock = IdleLock::new(Duration::ZERO);
+        lock.tick();
+
+        lock.handle_key(KeyCode::Enter);
+        assert!(lock.handle_key(KeyCode::Char('n')));
+
+        assert_eq!(lock.state(), LockState::Locked);
+    }
+}
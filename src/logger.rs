@@ -0,0 +1,111 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::app::{Message, MessageChannel};
+
+pub const LOG_ARG: &str = "--log";
+
+// mirrors messages to a file so they survive the UI
+#[derive(Default)]
+pub struct Logger {
+    file: Option<File>,
+}
+
+impl Logger {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path.as_ref())
+            .map(|file| Self { file: Some(file) })
+            .map_err(|err| format!("Could not open log file {:?}. {}", path.as_ref(), err))
+    }
+
+    // path following the log flag
+    pub fn from_args<T: Iterator<Item = String>>(args: T) -> Option<Result<Self, String>> {
+        let mut args = args.skip_while(|arg| arg != LOG_ARG).skip(1);
+
+        args.next().map(Logger::open)
+    }
+
+    pub fn log(&mut self, message: &Message) {
+        let file = match self.file.as_mut() {
+            None => return,
+            Some(file) => file,
+        };
+
+        let result = writeln!(file, "{}", format_entry(message, SystemTime::now()))
+            .and_then(|_| file.flush());
+
+        // nowhere left to report to, stop trying
+        if result.is_err() {
+            self.file = None;
+        }
+    }
+}
+
+fn format_entry(message: &Message, time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let channel = match message.channel() {
+        MessageChannel::ERROR => "ERROR",
+        MessageChannel::WARNING => "WARNING",
+        MessageChannel::INFO => "INFO",
+    };
+
+    format!(
+        "{}.{:03} [{}] {}",
+        since_epoch.as_secs(),
+        since_epoch.subsec_millis(),
+        channel,
+        message.text()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::app::Message;
+    use crate::logger::{format_entry, Logger};
+
+    #[test]
+    fn entry_has_time_and_channel() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_650_000_000_042);
+
+        assert_eq!(
+            format_entry(&Message::error("Bad thing"), time),
+            "1650000000.042 [ERROR] Bad thing"
+        );
+    }
+
+    #[test]
+    fn from_args() {
+        let args = vec!["edish".to_string(), "--log".to_string()];
+        assert!(Logger::from_args(args.into_iter()).is_none());
+
+        let args = vec!["edish".to_string()];
+        assert!(Logger::from_args(args.into_iter()).is_none());
+    }
+
+    #[test]
+    fn appends_to_file() {
+        let path = std::env::temp_dir().join(format!("edish-logger-test-{}.log", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut logger = Logger::open(&path).unwrap();
+        logger.log(&Message::info("One"));
+        logger.log(&Message::warning("Two"));
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("[INFO] One"));
+        assert!(lines[1].ends_with("[WARNING] Two"));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,116 @@
+// byte order mark found at start of a file
+// hidden from buffer, written back on save unless config says otherwise
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Bom {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Bom {
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        [Bom::Utf8, Bom::Utf16Le, Bom::Utf16Be]
+            .into_iter()
+            .find(|bom| bytes.starts_with(bom.bytes()))
+    }
+
+    pub fn bytes(&self) -> &'static [u8] {
+        match self {
+            Bom::Utf8 => &[0xEF, 0xBB, 0xBF],
+            Bom::Utf16Le => &[0xFF, 0xFE],
+            Bom::Utf16Be => &[0xFE, 0xFF],
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Bom::Utf8 => "UTF-8 BOM",
+            Bom::Utf16Le => "UTF-16LE BOM",
+            Bom::Utf16Be => "UTF-16BE BOM",
+        }
+    }
+}
+
+// text without its mark, and the mark if there was one
+pub fn decode(bytes: &[u8]) -> Result<(String, Option<Bom>), String> {
+    let bom = Bom::detect(bytes);
+    let rest = &bytes[bom.map(|bom| bom.bytes().len()).unwrap_or(0)..];
+
+    let text = match bom {
+        None | Some(Bom::Utf8) => String::from_utf8(rest.to_vec()).map_err(|e| e.to_string())?,
+        Some(Bom::Utf16Le) | Some(Bom::Utf16Be) => {
+            if !rest.len().is_multiple_of(2) {
+                return Err("UTF-16 file has an odd number of bytes.".to_string());
+            }
+
+            let units: Vec<u16> = rest
+                .chunks(2)
+                .map(|pair| match bom {
+                    Some(Bom::Utf16Be) => u16::from_be_bytes([pair[0], pair[1]]),
+                    _ => u16::from_le_bytes([pair[0], pair[1]]),
+                })
+                .collect();
+
+            String::from_utf16(&units).map_err(|e| e.to_string())?
+        }
+    };
+
+    Ok((text, bom))
+}
+
+// without the mark UTF-16 couldn't be told apart on next open, so it's written as plain UTF-8
+pub fn encode(text: &str, bom: Option<Bom>, keep_bom: bool) -> Vec<u8> {
+    let bom = match keep_bom {
+        true => bom,
+        false => None,
+    };
+
+    let mut bytes = bom.map(|bom| bom.bytes().to_vec()).unwrap_or_default();
+
+    match bom {
+        None | Some(Bom::Utf8) => bytes.extend_from_slice(text.as_bytes()),
+        Some(Bom::Utf16Le) => bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes)),
+        Some(Bom::Utf16Be) => bytes.extend(text.encode_utf16().flat_map(u16::to_be_bytes)),
+    }
+
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::panels::bom::{decode, encode, Bom};
+
+    #[test]
+    fn no_bom() {
+        assert_eq!(decode(b"abc"), Ok(("abc".to_string(), None)));
+        assert_eq!(encode("abc", None, true), b"abc".to_vec());
+    }
+
+    #[test]
+    fn utf8_bom_hidden_and_restored() {
+        let bytes = b"\xEF\xBB\xBFabc\n".to_vec();
+        let (text, bom) = decode(&bytes).unwrap();
+
+        assert_eq!(text, "abc\n");
+        assert_eq!(bom, Some(Bom::Utf8));
+        assert_eq!(encode(&text, bom, true), bytes);
+        assert_eq!(encode(&text, bom, false), b"abc\n".to_vec());
+    }
+
+    #[test]
+    fn utf16_round_trip() {
+        let le = b"\xFF\xFEa\x00\xe9\x00".to_vec();
+        let be = b"\xFE\xFF\x00a\x00\xe9".to_vec();
+
+        assert_eq!(decode(&le), Ok(("aé".to_string(), Some(Bom::Utf16Le))));
+        assert_eq!(decode(&be), Ok(("aé".to_string(), Some(Bom::Utf16Be))));
+        assert_eq!(encode("aé", Some(Bom::Utf16Le), true), le);
+        assert_eq!(encode("aé", Some(Bom::Utf16Be), true), be);
+        assert_eq!(encode("aé", Some(Bom::Utf16Be), false), "aé".as_bytes().to_vec());
+    }
+
+    #[test]
+    fn utf16_odd_length() {
+        assert!(decode(b"\xFF\xFEa").is_err());
+    }
+}
@@ -0,0 +1,107 @@
+use crate::panels::lines::Lines;
+
+const PAIRS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+
+// line and byte index of bracket paired with the one at given index
+// only the same kind of bracket counts toward nesting, brackets in strings and comments aren't skipped
+pub fn matching_bracket(lines: &Lines, line: usize, index: usize) -> Option<(usize, usize)> {
+    let c = lines.get(line)?.get(index..)?.chars().next()?;
+
+    PAIRS.iter().find_map(|(open, close)| {
+        if c == *open {
+            find_close(lines, line, index + c.len_utf8(), *open, *close)
+        } else if c == *close {
+            find_open(lines, line, index, *open, *close)
+        } else {
+            None
+        }
+    })
+}
+
+fn find_close(lines: &Lines, line: usize, start: usize, open: char, close: char) -> Option<(usize, usize)> {
+    let mut depth = 0;
+
+    for (line_index, text) in lines.iter().enumerate().skip(line) {
+        let from = match line_index == line {
+            true => start,
+            false => 0,
+        };
+
+        for (i, c) in text[from..].char_indices() {
+            if c == open {
+                depth += 1;
+            } else if c == close {
+                if depth == 0 {
+                    return Some((line_index, from + i));
+                }
+                depth -= 1;
+            }
+        }
+    }
+
+    None
+}
+
+fn find_open(lines: &Lines, line: usize, end: usize, open: char, close: char) -> Option<(usize, usize)> {
+    let mut depth = 0;
+
+    for line_index in (0..=line).rev() {
+        let text = &lines[line_index];
+        let to = match line_index == line {
+            true => end,
+            false => text.len(),
+        };
+
+        for (i, c) in text[..to].char_indices().rev() {
+            if c == close {
+                depth += 1;
+            } else if c == open {
+                if depth == 0 {
+                    return Some((line_index, i));
+                }
+                depth -= 1;
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::panels::brackets::matching_bracket;
+    use crate::panels::lines::Lines;
+    use crate::snapshots::lines;
+
+    #[test]
+    fn nested_across_lines() {
+        let text: Lines = lines("fn a() {\n    if (b[0]) {\n    }\n}").into();
+
+        assert_eq!(matching_bracket(&text, 0, 7), Some((3, 0)));
+        assert_eq!(matching_bracket(&text, 3, 0), Some((0, 7)));
+        assert_eq!(matching_bracket(&text, 1, 7), Some((1, 12)));
+        assert_eq!(matching_bracket(&text, 1, 12), Some((1, 7)));
+        assert_eq!(matching_bracket(&text, 1, 14), Some((2, 4)));
+        assert_eq!(matching_bracket(&text, 0, 4), Some((0, 5)));
+    }
+
+    #[test]
+    fn not_on_bracket_or_unmatched() {
+        let text: Lines = lines("(a\n[b)").into();
+
+        assert_eq!(matching_bracket(&text, 0, 1), None);
+        assert_eq!(matching_bracket(&text, 1, 0), None);
+        assert_eq!(matching_bracket(&text, 5, 0), None);
+        assert_eq!(matching_bracket(&text, 0, 10), None);
+        // other kinds of brackets don't interfere
+        assert_eq!(matching_bracket(&text, 0, 0), Some((1, 2)));
+    }
+
+    #[test]
+    fn multi_byte_characters() {
+        let text: Lines = lines("(é[ü])").into();
+
+        assert_eq!(matching_bracket(&text, 0, 0), Some((0, 7)));
+        assert_eq!(matching_bracket(&text, 0, 6), Some((0, 3)));
+    }
+}
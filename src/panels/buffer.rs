@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::panels::bom::Bom;
+use crate::panels::jumps::JumpList;
+use crate::panels::line_ending::LineEnding;
+use crate::panels::lines::Lines;
+use crate::panels::loader::FileLoader;
+use crate::panels::text::next_buffer_id;
+use crate::remote::RemotePath;
+
+// contents and position of an edit panel buffer that isn't shown
+// current buffer lives in panel itself, these are traded in when switching
+#[derive(Default)]
+pub struct Buffer {
+    pub(crate) buffer_id: u64,
+    pub(crate) lines: Lines,
+    pub(crate) title: String,
+    pub(crate) file_path: Option<PathBuf>,
+    pub(crate) current_line: usize,
+    pub(crate) cursor_index_in_line: usize,
+    pub(crate) scroll_x: u16,
+    pub(crate) scroll_y: u16,
+    pub(crate) mark: Option<(usize, usize)>,
+    pub(crate) modified: bool,
+    pub(crate) loader: Option<FileLoader>,
+    pub(crate) view_id: Option<usize>,
+    pub(crate) remote: Option<RemotePath>,
+    pub(crate) bom: Option<Bom>,
+    pub(crate) line_ending: LineEnding,
+    pub(crate) checkpoints: Vec<Checkpoint>,
+    pub(crate) disk_time: Option<SystemTime>,
+    pub(crate) reported_disk_time: Option<SystemTime>,
+    pub(crate) jumps: JumpList,
+}
+
+// labelled copy of buffer lines, kept until editor closes
+#[derive(Clone)]
+pub struct Checkpoint {
+    pub(crate) label: String,
+    pub(crate) lines: Lines,
+}
+
+impl Buffer {
+    pub fn new() -> Self {
+        Self { buffer_id: next_buffer_id(), ..Buffer::default() }
+    }
+
+    pub fn name(&self) -> String {
+        buffer_name(&self.title, self.file_path.as_ref())
+    }
+}
+
+pub(crate) fn buffer_name(title: &str, file_path: Option<&PathBuf>) -> String {
+    match (title.is_empty(), file_path) {
+        (false, _) => title.to_string(),
+        (true, Some(path)) => path.to_string_lossy().to_string(),
+        (true, None) => "Untitled".to_string(),
+    }
+}
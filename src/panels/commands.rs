@@ -1,17 +1,18 @@
 use crossterm::event::{KeyCode, KeyModifiers};
 use tui::layout::{Constraint, Direction, Layout, Rect};
-use tui::style::{Color, Style};
+use tui::style::Style;
 use tui::text::{Span, Spans, Text};
 use tui::widgets::Paragraph;
 
-use crate::app::StateChangeRequest;
-use crate::commands::{CommandKey, Manager};
+use crate::app::{StateChange, StateChangeRequest};
+use crate::commands::{CommandKey, CommandKeyId, Manager};
+use crate::locale::{self, Catalog};
 use crate::panels::text::RenderDetails;
-use crate::{AppState, EditorFrame, TextPanel, CURSOR_MAX, CommandDetails};
+use crate::{AppState, EditorFrame, Panels, TextPanel, CURSOR_MAX, CommandDetails};
 
 pub(crate) fn render_handler(
     panel: &TextPanel,
-    _state: &AppState,
+    state: &AppState,
     commands: &Manager,
     frame: &mut EditorFrame,
     rect: Rect,
@@ -20,14 +21,14 @@ pub(crate) fn render_handler(
 
     let (selected_details, global_panel_spans) = match commands.current_global() {
         None => (None, vec![]),
-        Some(command) => format_commands(panel, command, total_count),
+        Some(command) => format_commands(panel, state.catalog(), state.theme().selection, command, total_count),
     };
 
     total_count += global_panel_spans.len();
 
-    let (current_panel_id, (current_selected_details, current_panel_spans)) = match commands.current_panel() {
+    let (current_panel_id, (current_selected_details, current_panel_spans)) = match commands.menu_commands() {
         None => ("", (None, vec![])),
-        Some((id, command)) => (id, format_commands(panel, command, total_count)),
+        Some((id, command)) => (id, format_commands(panel, state.catalog(), state.theme().selection, command, total_count)),
     };
 
     let mut all_spans = vec![];
@@ -35,14 +36,16 @@ pub(crate) fn render_handler(
     if !global_panel_spans.is_empty() {
         all_spans.push(Spans::from(vec![Span::from(format!(
             "{:-<width$}",
-            "Global Commands",
+            state.text(&locale::Text::new("commands.global", "Global Commands")),
             width = rect.width as usize
         ))]));
         all_spans.extend(global_panel_spans);
         all_spans.push(Spans::default());
     }
 
-    let current_panel_title = format!("{} Commands", current_panel_id);
+    let current_panel_title = state.text(
+        &locale::Text::new("commands.panel", "{0} Commands").arg(state.catalog().panel_name(current_panel_id)),
+    );
 
     if !current_panel_spans.is_empty() {
         all_spans.push(Spans::from(vec![Span::from(format!(
@@ -63,9 +66,10 @@ pub(crate) fn render_handler(
                 ])
                 .split(rect);
 
+            let name = state.catalog().command_name(details.name());
             let spans = vec![
-                Spans::from(Span::from(format!("{:=<width$}", details.name(), width=rect.width as usize))),
-                Spans::from(details.description().as_str()),
+                Spans::from(Span::from(format!("{:=<width$}", name, width=rect.width as usize))),
+                Spans::from(state.catalog().command_description(details.name(), details.description())),
             ];
 
             let para = Paragraph::new(Text::from(spans));
@@ -78,24 +82,29 @@ pub(crate) fn render_handler(
     };
 
     let para = Paragraph::new(Text::from(all_spans))
-        .style(Style::default().fg(Color::White).bg(Color::Black));
+        .style(state.theme().text);
 
     frame.render_widget(para, commands_rect);
 
-    RenderDetails::new("Commands".to_string(), CURSOR_MAX)
+    let title = match panel.search_label() {
+        None => locale::Text::new("commands.title", "Commands"),
+        Some(label) => locale::Text::new("commands.title_search", "Commands {0}").arg(label),
+    };
+
+    RenderDetails::new(state.text(&title), CURSOR_MAX)
 }
 
 pub fn next_command(
     panel: &mut TextPanel,
     _code: KeyCode,
-    _state: &mut AppState,
+    state: &mut AppState,
     commands: &mut Manager,
 ) -> (bool, Vec<StateChangeRequest>) {
-    let count = match commands.current_panel() {
-        Some(commands) => count_commands(commands.1),
+    let count = match commands.menu_commands() {
+        Some(commands) => count_commands(panel, state.catalog(), commands.1),
         None => 0,
     } + match commands.current_global() {
-        Some(command) => count_commands(command),
+        Some(command) => count_commands(panel, state.catalog(), command),
         None => 0,
     };
 
@@ -111,14 +120,14 @@ pub fn next_command(
 pub fn previous_command(
     panel: &mut TextPanel,
     _code: KeyCode,
-    _state: &mut AppState,
+    state: &mut AppState,
     commands: &mut Manager,
 ) -> (bool, Vec<StateChangeRequest>) {
-    let count = match commands.current_panel() {
-        Some(commands) => count_commands(commands.1),
+    let count = match commands.menu_commands() {
+        Some(commands) => count_commands(panel, state.catalog(), commands.1),
         None => 0,
     } + match commands.current_global() {
-        Some(command) => count_commands(command),
+        Some(command) => count_commands(panel, state.catalog(), command),
         None => 0,
     };
 
@@ -184,10 +193,59 @@ fn format_code(code: KeyCode) -> String {
     }
 }
 
-fn format_commands<'a, T>(panel: &'a TextPanel, command: &'a CommandKey<T>, total_count: usize) -> (Option<&'a CommandDetails>, Vec<Spans<'a>>) {
-    let mut items = vec![];
+pub(crate) fn format_key(key: &CommandKeyId) -> String {
+    match key.mods().is_empty() {
+        true => format_code(key.code()),
+        false => format!(
+            "{} + {}",
+            format_modifiers_concise(key.mods()),
+            format_code(key.code())
+        ),
+    }
+}
+
+fn format_commands<'a, T: Copy>(panel: &'a TextPanel, catalog: &'a Catalog, selected_style: Style, command: &'a CommandKey<T>, total_count: usize) -> (Option<&'a CommandDetails>, Vec<Spans<'a>>) {
+    // width of all names, so it doesn't change while searching
+    let name_length = command_entries(command)
+        .iter()
+        .map(|(details, ..)| catalog.command_name(details.name()).chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut selected = None;
+
+    let items = listed_commands(panel, catalog, command)
+        .into_iter()
+        .enumerate()
+        .map(|(i, (details, span, _))| {
+            let style = match panel.selection() {
+                0 => Style::default(),
+                n => match total_count + i == n - 1 {
+                    true => {
+                        selected = Some(details);
+                        selected_style
+                    }
+                    false => Style::default(),
+                },
+            };
+
+            Spans::from(vec![
+                Span::styled(
+                    format!("{:<width$}", catalog.command_name(details.name()), width = name_length),
+                    style,
+                ),
+                Span::styled(" | ", style),
+                Span::styled(span, style),
+            ])
+        })
+        .collect();
 
-    let mut name_length = 0;
+    (selected, items)
+}
+
+// every command under root with its key sequence
+fn command_entries<T: Copy>(command: &CommandKey<T>) -> Vec<(&CommandDetails, String, T)> {
+    let mut items = vec![];
 
     let mut stack = vec![(0, "".to_string(), command)];
     while let Some((depth, base, command)) = stack.pop() {
@@ -216,7 +274,7 @@ fn format_commands<'a, T>(panel: &'a TextPanel, command: &'a CommandKey<T>, tota
                     stack.push((depth + 1, base.to_string(), value));
                 }
             }
-            CommandKey::Leaf(code, modifiers, details, _) => {
+            CommandKey::Leaf(code, modifiers, details, action) => {
                 let our_str = match modifiers.is_empty() {
                     true => format_code(*code),
                     false => format!(
@@ -231,65 +289,82 @@ fn format_commands<'a, T>(panel: &'a TextPanel, command: &'a CommandKey<T>, tota
                     false => format!("{} -> {}", base, our_str),
                 };
 
-                if details.name().len() > name_length {
-                    name_length = details.name().len();
-                }
-
                 // push entire command to spans
-                items.push((details, base));
+                items.push((details, base, *action));
             }
         }
     }
 
-    items.sort_by(|item, item2| item.0.name().cmp(item2.0.name()));
+    items
+}
 
-    let mut selected = None;
+// in the order shown, only those matching panel's search
+fn listed_commands<'a, T: Copy>(panel: &TextPanel, catalog: &Catalog, command: &'a CommandKey<T>) -> Vec<(&'a CommandDetails, String, T)> {
+    let mut items = command_entries(command);
 
-    let items = items
-        .iter()
-        .enumerate()
-        .map(|(i, (details, span))| {
-            let style = match panel.selection() {
-                0 => Style::default(),
-                n => match total_count + i == n - 1 {
-                    true => {
-                        selected = Some(*details);
-                        Style::default().bg(Color::DarkGray)
-                    }
-                    false => Style::default(),
-                },
-            };
+    items.retain(|(details, ..)| panel.matches_search(catalog.command_name(details.name())));
+    items.sort_by(|item, item2| catalog.command_name(item.0.name()).cmp(catalog.command_name(item2.0.name())));
 
-            Spans::from(vec![
-                Span::styled(
-                    format!("{:<width$}", details.name(), width = name_length),
-                    style,
-                ),
-                Span::styled(" | ", style),
-                Span::styled(span.clone(), style),
-            ])
-        })
-        .collect();
+    items
+}
 
-    (selected, items)
+// only those matching panel's search, same as listed
+fn count_commands<T: Copy>(panel: &TextPanel, catalog: &Catalog, root: &CommandKey<T>) -> usize {
+    listed_commands(panel, catalog, root).len()
 }
 
-fn count_commands<T>(root: &CommandKey<T>) -> usize {
-    let mut count = 0;
-    let mut stack = vec![root];
+// global commands come first in selection, same as listed
+// true with name when selected command is global
+fn selected_command(panel: &TextPanel, catalog: &Catalog, commands: &Manager) -> Option<(bool, String)> {
+    let index = panel.selection().checked_sub(1)?;
 
-    while let Some(command) = stack.pop() {
-        match command {
-            CommandKey::Node(_, _, children, _) => {
-                for value in children.values() {
-                    stack.push(value);
-                }
-            }
-            CommandKey::Leaf(..) => {
-                count += 1;
-            }
-        }
+    let global = match commands.current_global() {
+        None => vec![],
+        Some(command) => listed_commands(panel, catalog, command),
+    };
+
+    if let Some((details, ..)) = global.get(index) {
+        return Some((true, details.name().clone()));
+    }
+
+    match commands.menu_commands() {
+        None => None,
+        Some((_, command)) => listed_commands(panel, catalog, command)
+            .get(index - global.len())
+            .map(|(details, ..)| (false, details.name().clone())),
+    }
+}
+
+pub fn run_selected_command(
+    panel: &mut TextPanel,
+    _code: KeyCode,
+    state: &mut AppState,
+    commands: &mut Manager,
+) -> (bool, Vec<StateChangeRequest>) {
+    match selected_command(panel, state.catalog(), commands) {
+        None => (true, vec![StateChangeRequest::info(locale::Text::new("commands.none_selected", "No command selected."))]),
+        Some((global, name)) => (true, vec![StateChangeRequest::custom(RunListedCommand { global, name })]),
     }
+}
+
+// run once commands panel's action is done, since panel commands go to another panel
+struct RunListedCommand {
+    global: bool,
+    name: String,
+}
 
-    count
+impl StateChange for RunListedCommand {
+    fn apply(self: Box<Self>, state: &mut AppState, panels: &mut Panels, commands: &mut Manager) -> Vec<StateChangeRequest> {
+        let ran = match self.global {
+            true => commands.execute_global_by_name(&self.name, state, panels),
+            false => commands.execute_for_menu(&self.name, state, panels),
+        };
+
+        match ran {
+            true => vec![],
+            false => vec![StateChangeRequest::error(
+                locale::Text::new("commands.run_failed", "Couldn't run {0}.").arg(&self.name),
+            )],
+        }
+    }
 }
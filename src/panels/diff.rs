@@ -0,0 +1,333 @@
+use crossterm::event::KeyCode;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::text::{Span, Spans};
+use tui::widgets::Paragraph;
+
+use crate::app::StateChangeRequest;
+use crate::commands::Manager;
+use crate::diff::{format_diff, DiffLine};
+use crate::locale::Text;
+use crate::panels::text::RenderDetails;
+use crate::{AppState, EditorFrame, TextPanel, CURSOR_MAX};
+
+// one screen row, indexes into lines for each side
+// unified rows only use left
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct DiffRow {
+    // first line shown in row, keeps place when switching layout
+    line: usize,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+pub struct DiffView {
+    old_name: String,
+    new_name: String,
+    lines: Vec<DiffLine>,
+    side_by_side: bool,
+}
+
+impl DiffView {
+    pub fn new(old_name: String, new_name: String, lines: Vec<DiffLine>) -> Self {
+        Self {
+            old_name,
+            new_name,
+            lines,
+            side_by_side: false,
+        }
+    }
+
+    pub fn side_by_side(&self) -> bool {
+        self.side_by_side
+    }
+
+    pub fn rows(&self) -> Vec<DiffRow> {
+        match self.side_by_side {
+            false => (0..self.lines.len())
+                .map(|i| DiffRow { line: i, left: Some(i), right: None })
+                .collect(),
+            true => self.paired_rows(),
+        }
+    }
+
+    // removed and added lines of a change are put beside each other
+    fn paired_rows(&self) -> Vec<DiffRow> {
+        let mut rows = vec![];
+        let mut removed = vec![];
+        let mut added = vec![];
+
+        for (i, line) in self.lines.iter().enumerate() {
+            match line {
+                DiffLine::Removed(_) => removed.push(i),
+                DiffLine::Added(_) => added.push(i),
+                DiffLine::Same(_) => {
+                    rows.extend(pair(&removed, &added));
+                    removed.clear();
+                    added.clear();
+                    rows.push(DiffRow { line: i, left: Some(i), right: Some(i) });
+                }
+            }
+        }
+
+        rows.extend(pair(&removed, &added));
+        rows
+    }
+
+    fn is_change(&self, row: &DiffRow) -> bool {
+        [row.left, row.right]
+            .iter()
+            .flatten()
+            .any(|i| !matches!(self.lines.get(*i), Some(DiffLine::Same(_)) | None))
+    }
+
+    // row each run of changes starts on
+    pub fn hunk_starts(&self) -> Vec<usize> {
+        let rows = self.rows();
+
+        (0..rows.len())
+            .filter(|i| self.is_change(&rows[*i]) && (*i == 0 || !self.is_change(&rows[i - 1])))
+            .collect()
+    }
+
+    pub fn text(&self) -> String {
+        format_diff(&self.old_name, &self.new_name, &self.lines)
+    }
+
+    fn cell(&self, index: Option<usize>, state: &AppState) -> Span<'static> {
+        match index.and_then(|i| self.lines.get(i)) {
+            None => Span::raw(""),
+            Some(DiffLine::Same(line)) => Span::styled(format!("  {}", line), state.theme().text),
+            Some(DiffLine::Removed(line)) => Span::styled(format!("- {}", line), state.theme().diff_removed),
+            Some(DiffLine::Added(line)) => Span::styled(format!("+ {}", line), state.theme().diff_added),
+        }
+    }
+}
+
+fn pair(removed: &[usize], added: &[usize]) -> Vec<DiffRow> {
+    (0..removed.len().max(added.len()))
+        .map(|k| {
+            let (left, right) = (removed.get(k).copied(), added.get(k).copied());
+            DiffRow {
+                line: left.or(right).unwrap_or(0),
+                left,
+                right,
+            }
+        })
+        .collect()
+}
+
+pub struct DiffPanel {}
+
+impl DiffPanel {
+    pub fn render_handler(panel: &TextPanel, state: &AppState, _: &Manager, frame: &mut EditorFrame, rect: Rect) -> RenderDetails {
+        let view = match panel.diff() {
+            None => return RenderDetails::new(state.text(&Text::new("diff.title", "Diff")), CURSOR_MAX),
+            Some(view) => view,
+        };
+
+        let rows: Vec<DiffRow> = view
+            .rows()
+            .into_iter()
+            .skip(panel.scroll_y() as usize)
+            .take(rect.height as usize)
+            .collect();
+
+        let column = |cells: Vec<Span<'static>>| {
+            Paragraph::new(cells.into_iter().map(Spans::from).collect::<Vec<Spans>>()).style(state.theme().text)
+        };
+
+        match view.side_by_side() {
+            false => frame.render_widget(column(rows.iter().map(|row| view.cell(row.left, state)).collect()), rect),
+            true => {
+                let halves = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints(vec![Constraint::Percentage(50), Constraint::Length(1), Constraint::Min(0)])
+                    .split(rect);
+
+                let divider = vec![Span::styled("│", state.theme().inactive_border); rows.len()];
+
+                frame.render_widget(column(rows.iter().map(|row| view.cell(row.left, state)).collect()), halves[0]);
+                frame.render_widget(column(divider), halves[1]);
+                frame.render_widget(column(rows.iter().map(|row| view.cell(row.right, state)).collect()), halves[2]);
+            }
+        }
+
+        let title = Text::new("diff.title_names", "Diff {0} / {1}").arg(&view.old_name).arg(&view.new_name);
+
+        RenderDetails::new(state.text(&title), CURSOR_MAX)
+    }
+
+    fn scroll_to_hunk(panel: &mut TextPanel, forward: bool) -> (bool, Vec<StateChangeRequest>) {
+        let starts = match panel.diff() {
+            None => return (false, vec![]),
+            Some(view) => view.hunk_starts(),
+        };
+
+        let current = panel.scroll_y() as usize;
+        let next = match forward {
+            true => starts.into_iter().find(|start| *start > current),
+            false => starts.into_iter().rev().find(|start| *start < current),
+        };
+
+        match next {
+            None => (
+                false,
+                vec![StateChangeRequest::info(match forward {
+                    true => "No more changes after this.",
+                    false => "No more changes before this.",
+                })],
+            ),
+            Some(start) => {
+                panel.set_scroll_y(start as u16);
+                (true, vec![])
+            }
+        }
+    }
+
+    pub fn next_hunk(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        DiffPanel::scroll_to_hunk(panel, true)
+    }
+
+    pub fn previous_hunk(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        DiffPanel::scroll_to_hunk(panel, false)
+    }
+
+    pub fn scroll_up(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        panel.set_scroll_y(panel.scroll_y().saturating_sub(1));
+
+        (true, vec![])
+    }
+
+    pub fn scroll_down(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let rows = panel.diff().map(|view| view.rows().len()).unwrap_or(0) as u16;
+        if panel.scroll_y() + 1 < rows {
+            panel.set_scroll_y(panel.scroll_y() + 1);
+        }
+
+        (true, vec![])
+    }
+
+    pub fn toggle_side_by_side(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let scroll_y = panel.scroll_y() as usize;
+        let scroll_y = match panel.diff_mut() {
+            None => return (false, vec![]),
+            Some(view) => {
+                let top = view.rows().get(scroll_y).map(|row| row.line).unwrap_or(0);
+                view.side_by_side = !view.side_by_side;
+
+                view.rows().iter().position(|row| row.line >= top).unwrap_or(0)
+            }
+        };
+
+        panel.set_scroll_y(scroll_y as u16);
+
+        (true, vec![])
+    }
+
+    pub fn copy_diff(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        match panel.diff() {
+            None => (false, vec![]),
+            Some(view) => {
+                state.set_clipboard(view.text().split('\n').map(|line| line.to_string()).collect());
+                (true, vec![StateChangeRequest::info(Text::new("diff.copied", "Copied diff."))])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyCode;
+
+    use crate::commands::Manager;
+    use crate::diff::diff_lines;
+    use crate::panels::diff::{DiffPanel, DiffView};
+    use crate::snapshots::lines;
+    use crate::{AppState, TextPanel};
+
+    fn setup() -> (TextPanel, AppState, Manager) {
+        let diff = diff_lines(&lines("a\nb\nc\nd\ne\nf"), &lines("a\nx\ny\nc\nd\ne\nz")).unwrap();
+
+        let mut panel = TextPanel::diff_panel();
+        panel.set_diff(Some(DiffView::new("old".to_string(), "new".to_string(), diff)));
+
+        (panel, AppState::new(), Manager::default())
+    }
+
+    #[test]
+    fn side_by_side_pairs_changes() {
+        let (mut panel, mut state, mut commands) = setup();
+
+        // a, -b, +x, +y, c, d, e, -f, +z
+        assert_eq!(panel.diff().unwrap().rows().len(), 9);
+        assert_eq!(panel.diff().unwrap().hunk_starts(), vec![1, 7]);
+
+        DiffPanel::toggle_side_by_side(&mut panel, KeyCode::Null, &mut state, &mut commands);
+
+        // a, b|x, |y, c, d, e, f|z
+        assert_eq!(panel.diff().unwrap().rows().len(), 7);
+        assert_eq!(panel.diff().unwrap().hunk_starts(), vec![1, 6]);
+    }
+
+    #[test]
+    fn jump_between_hunks() {
+        let (mut panel, mut state, mut commands) = setup();
+
+        DiffPanel::next_hunk(&mut panel, KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(panel.scroll_y(), 1);
+
+        DiffPanel::next_hunk(&mut panel, KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(panel.scroll_y(), 7);
+
+        let (_, changes) = DiffPanel::next_hunk(&mut panel, KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(panel.scroll_y(), 7);
+        assert_eq!(changes.len(), 1);
+
+        // place is kept when layout changes
+        DiffPanel::toggle_side_by_side(&mut panel, KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(panel.scroll_y(), 6);
+
+        DiffPanel::previous_hunk(&mut panel, KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(panel.scroll_y(), 1);
+    }
+
+    #[test]
+    fn copy_unified_text() {
+        let (mut panel, mut state, mut commands) = setup();
+
+        DiffPanel::copy_diff(&mut panel, KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(state.clipboard()[0], "--- old");
+        assert_eq!(state.clipboard()[3], "- b");
+    }
+}
@@ -1,22 +1,55 @@
 use std::fs;
-use std::io::{Read, Write};
+use std::io::{Read, Seek, SeekFrom};
 use std::path::PathBuf;
 use std::{env, iter};
 
 use crossterm::event::{KeyCode, KeyEvent};
 use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
-use tui::style::{Color, Style};
+use tui::style::Style;
 use tui::text::{Span, Spans, Text};
-use tui::widgets::{Block, Paragraph};
+use tui::buffer::Buffer;
+use tui::widgets::{Block, Paragraph, Widget};
 
-use crate::app::StateChangeRequest;
-use crate::autocomplete::FileAutoCompleter;
+use crate::app::{is_confirmation, StateChangeRequest};
+use crate::autocomplete::{AutoCompleter, BufferAutoCompleter};
 use crate::commands::{alt_key, Manager, shift_alt_key, shift_catch_all};
+use crate::locale;
 use crate::{catch_all, ctrl_key, AppState, CommandDetails, CommandKeyId, Commands, EditorFrame, CURSOR_MAX, TextPanel};
-use crate::panels::text::{PanelState, RenderDetails};
+use crate::panels::bom::{decode, Bom};
+use crate::panels::hex::{looks_binary, HexView, ShowHex};
+use crate::panels::image::describe_image;
+use crate::panels::line_ending::normalize;
+use crate::panels::loader::{FileLoader, LAZY_LOAD_THRESHOLD};
+use crate::panels::text::{
+    create_parent_directories, missing_directory, missing_directory_message, parse_copy_lines, parse_disk_change_choice,
+    parse_line_range, PanelState, RenderDetails,
+};
+use crate::diff::{CompareWith, CompareWithCheckpoint};
+use crate::export::ExportBuffer;
+use crate::remote::{DownloadRemote, RemotePath};
+use crate::share::JoinSession;
+use crate::swap::{remove_swap, swap_path};
 
 pub struct TextEditPanel {}
 
+// enough of a large file to recognize its type
+const HEAD_SIZE: u64 = 64 * 1024;
+
+// colors background of area without changing contents
+struct Highlight {
+    style: Style,
+}
+
+impl Widget for Highlight {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                buf.get_mut(x, y).set_style(self.style);
+            }
+        }
+    }
+}
+
 #[allow(dead_code)]
 impl TextEditPanel {
     pub fn input_handler(panel: &mut TextPanel, input: String) -> Vec<StateChangeRequest> {
@@ -24,9 +57,26 @@ impl TextEditPanel {
 
         match panel.state() {
             PanelState::WaitingToOpen => {
+                // contents arrive once download finishes
+                match RemotePath::parse(&input) {
+                    None => panel.set_remote(None),
+                    Some(remote) => {
+                        panel.stop_loading();
+                        panel.set_text("");
+                        panel.set_title(remote.url());
+                        panel.clear_file_path();
+                        panel.set_modified(false);
+                        panel.set_remote(Some(remote.clone()));
+                        panel.set_scroll_y(0);
+                        panel.set_state(PanelState::Normal);
+                        changes.push(StateChangeRequest::custom(DownloadRemote::new(remote)));
+                        return changes;
+                    }
+                }
+
                 let current_dir = match env::current_dir() {
                     Err(e) => {
-                        changes.push(StateChangeRequest::error(e));
+                        changes.push(StateChangeRequest::error(e.to_string()));
                         return changes;
                     }
                     Ok(p) => p,
@@ -36,18 +86,80 @@ impl TextEditPanel {
                 file_path.push(input);
 
                 match fs::File::open(&file_path) {
-                    Err(e) => changes.push(StateChangeRequest::error(e)),
+                    Err(e) => changes.push(StateChangeRequest::error(e.to_string())),
                     Ok(mut file) => {
-                        let mut s = String::new();
-                        match file.read_to_string(&mut s) {
+                        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+                        // large files only get their head read here, rest is loaded during updates
+                        let mut bytes = vec![];
+                        let read = match size > LAZY_LOAD_THRESHOLD {
+                            true => (&mut file).take(HEAD_SIZE).read_to_end(&mut bytes),
+                            false => file.read_to_end(&mut bytes),
+                        };
+
+                        if let Err(e) = read {
+                            changes.push(StateChangeRequest::error(e.to_string()));
+                            return changes;
+                        }
+
+                        if let Some(description) = describe_image(&bytes, size) {
+                            // placeholder only, clear path so saving can't overwrite image or file opened before
+                            panel.stop_loading();
+                            panel.clear_file_path();
+                            panel.set_text(format!("{}\n{}", file_path.to_string_lossy(), description));
+                            panel.set_modified(false);
+                            panel.set_scroll_y(0);
+                            panel.set_state(PanelState::Normal);
+                            return changes;
+                        }
+
+                        let bom = Bom::detect(&bytes);
+
+                        // UTF-16 text is full of zero bytes
+                        if !matches!(bom, Some(Bom::Utf16Le) | Some(Bom::Utf16Be)) && looks_binary(&bytes) {
+                            changes.push(StateChangeRequest::custom(ShowHex::new(HexView::new(
+                                file_path.to_string_lossy().to_string(),
+                                bytes,
+                                size,
+                            ))));
+                            panel.set_state(PanelState::Normal);
+                            return changes;
+                        }
+
+                        let text = match (size > LAZY_LOAD_THRESHOLD, bom) {
+                            (false, _) => decode(&bytes).map(|(text, _)| text),
+                            // loader reads lines as UTF-8
+                            (true, Some(Bom::Utf16Le)) | (true, Some(Bom::Utf16Be)) => {
+                                Err("Files this large can only be opened as UTF-8.".to_string())
+                            }
+                            // loader starts after the mark
+                            (true, _) => file
+                                .seek(SeekFrom::Start(bom.map(|bom| bom.bytes().len() as u64).unwrap_or(0)))
+                                .map(|_| String::new())
+                                .map_err(|e| e.to_string()),
+                        };
+
+                        match text {
                             Err(e) => changes.push(StateChangeRequest::error(e)),
-                            Ok(_) => {
-                                panel.set_text(s);
+                            Ok(s) => {
+                                if size > LAZY_LOAD_THRESHOLD {
+                                    panel.start_loading(FileLoader::new(file, size));
+                                    changes.push(StateChangeRequest::info(
+                                        locale::Text::new("edit.loading", "Loading {0} bytes in the background.").arg(size),
+                                    ));
+                                } else {
+                                    panel.stop_loading();
+                                    let (text, line_ending) = normalize(&s);
+                                    panel.set_text(text);
+                                    panel.set_line_ending(line_ending);
+                                }
+                                panel.set_bom(bom);
+                                panel.set_modified(false);
 
                                 panel.set_title(if file_path.starts_with(&current_dir) {
                                     match file_path.strip_prefix(&current_dir) {
                                         Err(e) => {
-                                            changes.push(StateChangeRequest::error(e));
+                                            changes.push(StateChangeRequest::error(e.to_string()));
                                             file_path.to_string_lossy().to_string()
                                         }
                                         Ok(p) => p.as_os_str().to_string_lossy().to_string(),
@@ -58,15 +170,57 @@ impl TextEditPanel {
                             }
                         }
                         panel.set_file_path(file_path.clone());
+                        panel.record_disk_time();
+                        panel.clear_jumps();
                     }
                 };
 
                 panel.set_scroll_y(0);
+                panel.set_state(PanelState::Normal);
+
+                match swap_path(&file_path) {
+                    Some(swap) if swap.exists() => {
+                        panel.set_state(PanelState::WaitingToRecover);
+                        changes.push(StateChangeRequest::Input(
+                            locale::Text::new("prompt.recover_swap", "Unsaved changes found from previous session. Recover? (y/n)"),
+                            None,
+                        ));
+                    }
+                    _ => (),
+                }
+            }
+            PanelState::WaitingToRecover => {
+                panel.set_state(PanelState::Normal);
+
+                let swap = match panel.file_path().and_then(|path| swap_path(path)) {
+                    None => return changes,
+                    Some(swap) => swap,
+                };
+
+                match input.trim().to_lowercase().as_str() {
+                    "y" | "yes" => match fs::read_to_string(&swap) {
+                        Err(e) => changes.push(StateChangeRequest::error(e.to_string())),
+                        Ok(contents) => {
+                            panel.stop_loading();
+                            // swap always ends with newline from writing each line
+                            panel.set_text(contents.strip_suffix('\n').unwrap_or(&contents));
+                            panel.set_modified(true);
+                            changes.push(StateChangeRequest::info(
+                                locale::Text::new("edit.recovered_unsaved_changes", "Recovered unsaved changes."),
+                            ));
+                        }
+                    },
+                    _ => changes.push(StateChangeRequest::info(
+                        locale::Text::new("edit.discarded_unsaved_changes", "Discarded unsaved changes."),
+                    )),
+                }
+
+                fs::remove_file(&swap).unwrap_or_default();
             }
             PanelState::WaitingToSave => {
                 let current_dir = match env::current_dir() {
                     Err(e) => {
-                        changes.push(StateChangeRequest::error(e));
+                        changes.push(StateChangeRequest::error(e.to_string()));
                         return changes;
                     }
                     Ok(p) => p,
@@ -74,17 +228,319 @@ impl TextEditPanel {
 
                 let mut file_path = (&current_dir).clone();
                 file_path.push(input);
+
+                // saving over the panel's own file needs no confirmation
+                if file_path.exists() && panel.file_path() != Some(&file_path) {
+                    changes.push(StateChangeRequest::confirm(
+                        locale::Text::new("edit.overwrite", "{0} exists. Overwrite?").arg(file_path.to_string_lossy()),
+                    ));
+                    panel.set_pending_save_path(Some(file_path));
+                    panel.set_state(PanelState::WaitingToConfirmOverwrite);
+                    return changes;
+                }
+
+                match missing_directory(&file_path) {
+                    None => (),
+                    Some(dir) if dir.exists() => {
+                        panel.set_state(PanelState::Normal);
+                        changes.push(StateChangeRequest::error(missing_directory_message(&dir)));
+                        return changes;
+                    }
+                    Some(dir) => {
+                        changes.push(StateChangeRequest::confirm(
+                            locale::Text::new("edit.create_directory", "Directory {0} doesn't exist. Create it?")
+                                .arg(dir.to_string_lossy()),
+                        ));
+                        panel.set_pending_save_path(Some(file_path));
+                        panel.set_state(PanelState::WaitingToCreateDirectories);
+                        return changes;
+                    }
+                }
+
+                // saved somewhere local, no longer a copy of the remote file
+                panel.set_remote(None);
                 panel.set_file_path(file_path.clone());
+                panel.set_state(PanelState::Normal);
 
                 changes.extend(panel.save());
             }
+            PanelState::WaitingToConfirmOverwrite => {
+                panel.set_state(PanelState::Normal);
+
+                match panel.take_pending_save_path() {
+                    Some(file_path) if is_confirmation(&input) => {
+                        panel.set_remote(None);
+                        panel.set_file_path(file_path);
+                        changes.extend(panel.save());
+                    }
+                    _ => changes.push(StateChangeRequest::info(locale::Text::new("edit.save_canceled", "Save canceled."))),
+                }
+            }
+            PanelState::WaitingToCreateDirectories => {
+                panel.set_state(PanelState::Normal);
+
+                match panel.take_pending_save_path() {
+                    Some(file_path) if is_confirmation(&input) => match create_parent_directories(&file_path) {
+                        Err(err) => changes.push(StateChangeRequest::error(err)),
+                        Ok(()) => {
+                            panel.set_remote(None);
+                            panel.set_file_path(file_path);
+                            changes.extend(panel.save());
+                        }
+                    },
+                    _ => changes.push(StateChangeRequest::info(locale::Text::new("edit.save_canceled", "Save canceled."))),
+                }
+            }
+            PanelState::WaitingToCompare => {
+                panel.set_state(PanelState::Normal);
+
+                match input.trim().is_empty() {
+                    true => changes.push(StateChangeRequest::info(
+                        locale::Text::new("edit.compare_canceled", "Compare canceled."),
+                    )),
+                    false => changes.push(StateChangeRequest::custom(CompareWith::new(input))),
+                }
+            }
+            PanelState::WaitingToCheckpoint => {
+                panel.set_state(PanelState::Normal);
+
+                match input.trim() {
+                    "" => changes.push(StateChangeRequest::info(
+                        locale::Text::new("edit.checkpoint_canceled", "Checkpoint canceled."),
+                    )),
+                    label => {
+                        panel.add_checkpoint(label);
+                        changes.push(StateChangeRequest::info(
+                            locale::Text::new("edit.checkpoint_saved", "Checkpoint {0} saved with {1} line(s).")
+                                .arg(format!("{:?}", label))
+                                .arg(panel.lines().len()),
+                        ));
+                    }
+                }
+            }
+            PanelState::WaitingToRestoreCheckpoint => {
+                panel.set_state(PanelState::Normal);
+
+                let label = TextEditPanel::checkpoint_label(panel, &input);
+                match panel.restore_checkpoint(&label) {
+                    Err(err) => changes.push(StateChangeRequest::error(err)),
+                    Ok(()) => changes.push(StateChangeRequest::info(
+                        locale::Text::new("edit.checkpoint_restored", "Restored checkpoint {0}.").arg(format!("{:?}", label)),
+                    )),
+                }
+            }
+            PanelState::WaitingToCompareCheckpoint => {
+                panel.set_state(PanelState::Normal);
+
+                let label = TextEditPanel::checkpoint_label(panel, &input);
+                match panel.checkpoint(&label) {
+                    None => changes.push(StateChangeRequest::error(
+                        locale::Text::new("edit.no_checkpoint", "No checkpoint named {0}.").arg(format!("{:?}", label)),
+                    )),
+                    Some(lines) => changes.push(StateChangeRequest::custom(CompareWithCheckpoint::new(
+                        label.clone(),
+                        lines.to_vec(),
+                    ))),
+                }
+            }
+            PanelState::WaitingToResolveChange | PanelState::WaitingToResolveChangeBeforeSave => {
+                let before_save = panel.state() == PanelState::WaitingToResolveChangeBeforeSave;
+                panel.set_state(PanelState::Normal);
+
+                let path = match panel.file_path() {
+                    None => return changes,
+                    Some(path) => path.to_string_lossy().to_string(),
+                };
+
+                match parse_disk_change_choice(&input) {
+                    Some('r') => {
+                        panel.set_state(PanelState::WaitingToOpen);
+                        changes.extend(TextEditPanel::input_handler(panel, path));
+                    }
+                    Some('k') => {
+                        // disk version is now the one being replaced
+                        panel.record_disk_time();
+                        match before_save {
+                            true => changes.extend(panel.save()),
+                            false => changes.push(StateChangeRequest::info(
+                                locale::Text::new("edit.keeping_buffer", "Keeping buffer, saving will replace file."),
+                            )),
+                        }
+                    }
+                    Some(_) => changes.push(StateChangeRequest::custom(CompareWith::new(path))),
+                    None => changes.push(StateChangeRequest::info(
+                        locale::Text::new("edit.disk_change_ignored", "Change on disk ignored."),
+                    )),
+                }
+            }
+            PanelState::WaitingToExport => {
+                panel.set_state(PanelState::Normal);
+
+                match input.trim().is_empty() {
+                    true => changes.push(StateChangeRequest::info(locale::Text::new("edit.export_canceled", "Export canceled."))),
+                    false => changes.push(StateChangeRequest::custom(ExportBuffer::new(input))),
+                }
+            }
+            PanelState::WaitingToJoinSession => {
+                panel.set_state(PanelState::Normal);
+
+                match input.trim().is_empty() {
+                    true => changes.push(StateChangeRequest::info(locale::Text::new("edit.join_canceled", "Join canceled."))),
+                    false => changes.push(StateChangeRequest::custom(JoinSession::new(input))),
+                }
+            }
+            PanelState::WaitingToSwitchBuffer => {
+                panel.set_state(PanelState::Normal);
+
+                // names start with their number, otherwise take best match
+                let name = BufferAutoCompleter::new(panel.buffer_names())
+                    .ranked_options(&input)
+                    .first()
+                    .map(|completion| completion.option().clone())
+                    .unwrap_or(input.clone());
+
+                let index = name
+                    .split_whitespace()
+                    .next()
+                    .and_then(|number| number.parse::<usize>().ok())
+                    .filter(|number| *number > 0);
+
+                match index {
+                    Some(number) if panel.switch_to_buffer(number - 1) => (),
+                    _ => changes.push(StateChangeRequest::error(
+                        locale::Text::new("edit.no_buffer", "No buffer named {0}.").arg(format!("{:?}", input)),
+                    )),
+                }
+            }
+            PanelState::WaitingToSetRuler => {
+                match input.trim().parse::<usize>() {
+                    Err(_) => changes.push(StateChangeRequest::error(
+                        locale::Text::new("edit.invalid_ruler", "Invalid ruler column: {0}").arg(format!("{:?}", input)),
+                    )),
+                    Ok(0) => panel.set_ruler(None),
+                    Ok(column) => panel.set_ruler(Some(column)),
+                }
+
+                panel.set_state(PanelState::Normal);
+            }
+            PanelState::WaitingToGoToLine => {
+                match input.trim().parse::<usize>() {
+                    Err(_) => changes.push(StateChangeRequest::error(
+                        locale::Text::new("edit.invalid_line", "Invalid line number: {0}").arg(format!("{:?}", input)),
+                    )),
+                    Ok(number) => panel.go_to_line_number(number),
+                }
+
+                panel.set_state(PanelState::Normal);
+            }
+            PanelState::WaitingToDuplicate => {
+                match input.trim().parse::<usize>() {
+                    Err(_) => changes.push(StateChangeRequest::error(
+                        locale::Text::new("edit.invalid_duplicate_count", "Invalid duplicate count: {0}")
+                            .arg(format!("{:?}", input)),
+                    )),
+                    Ok(count) => panel.duplicate_selection(count),
+                }
+
+                panel.set_state(PanelState::Normal);
+            }
+            PanelState::WaitingToCopyLines => {
+                match parse_copy_lines(input.as_str()) {
+                    None => changes.push(StateChangeRequest::error(
+                        locale::Text::new("edit.invalid_copy", "Invalid copy command: {0}. Expected 'start,end t destination'.")
+                            .arg(format!("{:?}", input)),
+                    )),
+                    Some((start, end, destination)) => {
+                        if let Err(e) = panel.copy_lines(start, end, destination) {
+                            changes.push(StateChangeRequest::error(e));
+                        }
+                    }
+                }
+
+                panel.set_state(PanelState::Normal);
+            }
+            // expects answers from a sequence
+            PanelState::WaitingToReplace
+            | PanelState::WaitingToReplaceInSelection
+            | PanelState::WaitingToReplaceInLines => panel.set_state(PanelState::Normal),
             PanelState::Normal => (),
         }
 
         changes
     }
 
-    pub fn render_handler(panel: &TextPanel, _state: &AppState, _: &Manager, frame: &mut EditorFrame, rect: Rect) -> RenderDetails {
+    pub fn answers_handler(panel: &mut TextPanel, answers: Vec<String>) -> Vec<StateChangeRequest> {
+        let mut changes = vec![];
+
+        match panel.state() {
+            PanelState::WaitingToReplace => {
+                match answers.as_slice() {
+                    [find, with] => {
+                        let count = panel.replace_all(find, with);
+                        changes.push(StateChangeRequest::info(
+                            locale::Text::new("edit.replaced", "Replaced {0} occurrence(s).").arg(count),
+                        ));
+                    }
+                    _ => changes.push(StateChangeRequest::error(
+                        locale::Text::new("edit.replace_expects_two", "Replace expects find and replacement text."),
+                    )),
+                }
+
+                panel.set_state(PanelState::Normal);
+            }
+            PanelState::WaitingToReplaceInSelection => {
+                match answers.as_slice() {
+                    [find, with] => match panel.replace_in_selection(find, with) {
+                        Err(err) => changes.push(StateChangeRequest::error(err)),
+                        Ok(count) => changes.push(StateChangeRequest::info(
+                            locale::Text::new("edit.replaced_selection", "Replaced {0} occurrence(s) in selection.").arg(count),
+                        )),
+                    },
+                    _ => changes.push(StateChangeRequest::error(
+                        locale::Text::new("edit.replace_expects_two", "Replace expects find and replacement text."),
+                    )),
+                }
+
+                panel.set_state(PanelState::Normal);
+            }
+            PanelState::WaitingToReplaceInLines => {
+                match answers.as_slice() {
+                    [range, find, with] => match parse_line_range(range) {
+                        None => changes.push(StateChangeRequest::error(
+                            locale::Text::new("edit.invalid_range", "Invalid line range: {0}. Expected 'start,end'.")
+                                .arg(format!("{:?}", range)),
+                        )),
+                        Some((start, end)) => match panel.replace_in_lines(find, with, start, end) {
+                            Err(err) => changes.push(StateChangeRequest::error(err)),
+                            Ok(count) => changes.push(StateChangeRequest::info(
+                                locale::Text::new("edit.replaced_lines", "Replaced {0} occurrence(s) in lines {1} to {2}.")
+                                    .arg(count)
+                                    .arg(start)
+                                    .arg(end),
+                            )),
+                        },
+                    },
+                    _ => changes.push(StateChangeRequest::error(
+                        locale::Text::new("edit.replace_expects_three", "Replace expects lines, find and replacement text."),
+                    )),
+                }
+
+                panel.set_state(PanelState::Normal);
+            }
+            _ => (),
+        }
+
+        changes
+    }
+
+    // clean exit, any unsaved work was discarded on purpose
+    pub fn shutdown_handler(panel: &mut TextPanel) {
+        for path in panel.file_paths() {
+            remove_swap(path);
+        }
+    }
+
+    pub fn render_handler(panel: &TextPanel, state: &AppState, _: &Manager, frame: &mut EditorFrame, rect: Rect) -> RenderDetails {
         if !panel.lines().is_empty() {
             let line_count = panel.lines().len();
             let line_count_size = line_count.to_string().len().min(u16::MAX as usize) as u16;
@@ -115,36 +571,139 @@ impl TextEditPanel {
 
             frame.render_widget(line_numbers_para, layout[0]);
 
-            let gutter = Block::default().style(Style::default().bg(Color::DarkGray));
+            let gutter = Block::default().style(state.theme().gutter);
 
             frame.render_widget(gutter, gutter_layout[1]);
 
             let para =
-                Paragraph::new(para_text).style(Style::default().fg(Color::White).bg(Color::Black));
+                Paragraph::new(para_text).style(state.theme().text);
 
             frame.render_widget(para, layout[2]);
 
-            let title = match panel.file_path() {
-                None => "Buffer".to_string(),
-                Some(path) => path.to_string_lossy().to_string()
+            // wrapped lines never scroll sideways
+            let ruler_x = match panel.wrap() {
+                true => panel.ruler(),
+                false => panel.ruler().and_then(|column| column.checked_sub(panel.scroll_x() as usize)),
             };
 
-            return RenderDetails::new(title, cursor)
+            match ruler_x {
+                Some(column) if column < layout[2].width as usize => {
+                    let ruler_rect = Rect::new(layout[2].x + column as u16, layout[2].y, 1, layout[2].height);
+                    frame.render_widget(Highlight { style: state.theme().ruler }, ruler_rect);
+                }
+                _ => (),
+            }
+
+            match panel.ruler() {
+                Some(column) if panel.flag_long_lines() => {
+                    let first_visible = panel.scroll_y() as usize;
+                    let last_visible = (first_visible + layout[2].height as usize).min(line_count);
+                    let area = layout[2].width as usize * layout[2].height as usize;
+
+                    for line in first_visible..last_visible {
+                        let end = panel.lines()[line].len().min(column.max(panel.scroll_x() as usize) + area);
+                        for index in column..end {
+                            if let Some((x, y)) = panel.screen_position(line, index, layout[2]) {
+                                frame.render_widget(Highlight { style: state.theme().long_line }, Rect::new(x, y, 1, 1));
+                            }
+                        }
+                    }
+                }
+                _ => (),
+            }
+
+            if let Some((start_line, end_line, start_column, end_column)) = panel.block() {
+                // column select shows which lines get typed into even before block has width
+                let end_column = match panel.column_select() {
+                    true => end_column.max(start_column + 1),
+                    false => end_column,
+                };
+                // no line shows more columns than fit in the whole area
+                let end_column = end_column.min(
+                    start_column.max(panel.scroll_x() as usize) + layout[2].width as usize * layout[2].height as usize,
+                );
+                let first_visible = start_line.max(panel.scroll_y() as usize);
+                let last_visible = end_line.min(panel.scroll_y() as usize + layout[2].height as usize);
+
+                for line in first_visible..=last_visible {
+                    for column in start_column..end_column {
+                        if let Some((x, y)) = panel.screen_position(line, column, layout[2]) {
+                            frame.render_widget(Highlight { style: state.theme().selection }, Rect::new(x, y, 1, 1));
+                        }
+                    }
+                }
+            }
+
+            match panel
+                .matching_bracket()
+                .and_then(|(line, index)| panel.screen_position(line, index, layout[2]))
+            {
+                None => (),
+                Some((x, y)) => {
+                    frame.render_widget(Highlight { style: state.theme().matching_bracket }, Rect::new(x, y, 1, 1))
+                }
+            }
+
+            return RenderDetails::new(TextEditPanel::title(panel, state), cursor)
+        }
+
+        RenderDetails::new(TextEditPanel::title(panel, state), CURSOR_MAX)
+    }
+
+    fn title(panel: &TextPanel, state: &AppState) -> String {
+        let mut title = match panel.file_path() {
+            None => state.text(&locale::Text::new("edit.title", "Buffer")),
+            Some(path) => path.to_string_lossy().to_string()
+        };
+
+        if panel.modified() {
+            title.push_str(" *");
+        }
+
+        if panel.buffer_count() > 1 {
+            let buffers = locale::Text::new("edit.title_buffers", " (+{0} buffers)").arg(panel.buffer_count() - 1);
+            title.push_str(&state.text(&buffers));
+        }
+
+        match panel.loading_progress() {
+            Some((loaded, total)) if total > 0 => {
+                title.push_str(&state.text(&locale::Text::new("edit.title_loading", " (loading {0}%)").arg(loaded * 100 / total)))
+            }
+            _ => (),
         }
 
-        RenderDetails::new("Buffer".to_string(), CURSOR_MAX)
+        title
+    }
+
+    // best match among labels, typed text if nothing matches
+    fn checkpoint_label(panel: &TextPanel, input: &str) -> String {
+        BufferAutoCompleter::new(panel.checkpoint_labels())
+            .ranked_options(input)
+            .first()
+            .map(|completion| completion.option().clone())
+            .unwrap_or(input.trim().to_string())
     }
 }
 
 
 #[cfg(test)]
 mod tests {
+    use std::iter;
+
     use crossterm::event::KeyCode;
     use tui::layout::Rect;
+    use tui::style::{Modifier, Style};
     use tui::text::{Span, Spans};
 
-    use crate::{AppState, TextPanel};
+    use crate::app::StateChangeRequest;
+    use crate::commands::{Manager, MAX_COUNT};
+    use crate::panels::text::{parse_copy_lines, PanelState};
+    use crate::panels::HEX_PANEL_TYPE_ID;
+    use crate::{AppState, Panels, TextPanel, CURSOR_MAX};
+    use crate::panels::bom::Bom;
     use crate::panels::edit::TextEditPanel;
+    use crate::panels::line_ending::LineEnding;
+    use crate::panels::loader::LAZY_LOAD_THRESHOLD;
 
     #[test]
     fn set_text() {
@@ -162,6 +721,45 @@ mod tests {
         )
     }
 
+    #[test]
+    fn whitespace_shown_as_glyphs() {
+        let mut edit = TextPanel::default();
+        edit.set_text("a b\t\nc  ");
+        edit.set_cursor_index(3);
+        edit.set_show_whitespace(true);
+
+        let dim = Style::default().add_modifier(Modifier::DIM);
+        let (spans, cursor, _) = edit.make_text_content(Rect::new(10, 10, 20, 20));
+
+        assert_eq!(
+            spans,
+            vec![
+                Spans::from(vec![
+                    Span::from("a"),
+                    Span::styled("·", dim),
+                    Span::from("b"),
+                    Span::styled("→", dim),
+                ]),
+                Spans::from(vec![Span::from("c"), Span::styled("··", dim)]),
+            ]
+        );
+        assert_eq!(cursor, (13, 10));
+    }
+
+    #[test]
+    fn whitespace_glyphs_wrap_at_same_place() {
+        let mut edit = TextPanel::default();
+        edit.set_text("aaaa bbbb cccc");
+        edit.set_show_whitespace(true);
+
+        let (spans, ..) = edit.make_text_content(Rect::new(0, 0, 10, 10));
+
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].width(), 10);
+        assert_eq!(spans[0].0.last(), Some(&Span::styled("·", Style::default().add_modifier(Modifier::DIM))));
+        assert_eq!(spans[1].0[1], Span::from("cccc"));
+    }
+
     #[test]
     fn cursor_is_one_past_end() {
         let mut edit = TextPanel::default();
@@ -321,8 +919,9 @@ mod tests {
     fn handle_character_key() {
         let mut edit = TextPanel::default();
         let mut state = AppState::new();
+        let mut commands = Manager::default();
 
-        edit.handle_key_stroke(KeyCode::Char('a'), &mut state);
+        edit.handle_key_stroke(KeyCode::Char('a'), &mut state, &mut commands);
 
         assert_eq!(edit.lines(), &vec!["a".to_string()]);
         assert_eq!(edit.cursor_index_in_line(), 1);
@@ -336,7 +935,9 @@ mod tests {
 
         let mut state = AppState::new();
 
-        edit.handle_key_stroke(KeyCode::Char('b'), &mut state);
+        let mut commands = Manager::default();
+
+        edit.handle_key_stroke(KeyCode::Char('b'), &mut state, &mut commands);
 
         assert_eq!(edit.lines(), &vec!["abc".to_string()]);
         assert_eq!(edit.cursor_index_in_line(), 2);
@@ -350,7 +951,9 @@ mod tests {
 
         let mut state = AppState::new();
 
-        edit.handle_key_stroke(KeyCode::Char('b'), &mut state);
+        let mut commands = Manager::default();
+
+        edit.handle_key_stroke(KeyCode::Char('b'), &mut state, &mut commands);
 
         assert_eq!(edit.lines(), &vec!["ab".to_string()]);
         assert_eq!(edit.cursor_index_in_line(), 2);
@@ -364,7 +967,9 @@ mod tests {
 
         let mut state = AppState::new();
 
-        edit.handle_key_stroke(KeyCode::Enter, &mut state);
+        let mut commands = Manager::default();
+
+        edit.handle_key_stroke(KeyCode::Enter, &mut state, &mut commands);
 
         assert_eq!(edit.lines(), &vec!["a".to_string(), String::new()]);
         assert_eq!(edit.current_line(), 1);
@@ -379,7 +984,9 @@ mod tests {
 
         let mut state = AppState::new();
 
-        edit.handle_key_stroke(KeyCode::Backspace, &mut state);
+        let mut commands = Manager::default();
+
+        edit.handle_key_stroke(KeyCode::Backspace, &mut state, &mut commands);
 
         assert_eq!(edit.lines(), &vec!["".to_string()]);
         assert_eq!(edit.current_line(), 0);
@@ -394,7 +1001,9 @@ mod tests {
 
         let mut state = AppState::new();
 
-        edit.handle_key_stroke(KeyCode::Backspace, &mut state);
+        let mut commands = Manager::default();
+
+        edit.handle_key_stroke(KeyCode::Backspace, &mut state, &mut commands);
 
         assert_eq!(edit.lines(), &vec!["ac".to_string()]);
         assert_eq!(edit.current_line(), 0);
@@ -410,7 +1019,9 @@ mod tests {
 
         let mut state = AppState::new();
 
-        edit.handle_key_stroke(KeyCode::Backspace, &mut state);
+        let mut commands = Manager::default();
+
+        edit.handle_key_stroke(KeyCode::Backspace, &mut state, &mut commands);
 
         assert_eq!(edit.lines(), &vec!["abcdef".to_string()]);
         assert_eq!(edit.current_line(), 0);
@@ -426,8 +1037,10 @@ mod tests {
 
         let mut state = AppState::new();
 
-        edit.handle_key_stroke(KeyCode::Backspace, &mut state);
-        edit.handle_key_stroke(KeyCode::Backspace, &mut state);
+        let mut commands = Manager::default();
+
+        edit.handle_key_stroke(KeyCode::Backspace, &mut state, &mut commands);
+        edit.handle_key_stroke(KeyCode::Backspace, &mut state, &mut commands);
 
         assert_eq!(edit.lines(), &vec!["a".to_string()]);
         assert_eq!(edit.current_line(), 0);
@@ -442,7 +1055,9 @@ mod tests {
 
         let mut state = AppState::new();
 
-        edit.handle_key_stroke(KeyCode::Delete, &mut state);
+        let mut commands = Manager::default();
+
+        edit.handle_key_stroke(KeyCode::Delete, &mut state, &mut commands);
 
         assert_eq!(edit.lines(), &vec!["".to_string()]);
         assert_eq!(edit.current_line(), 0);
@@ -457,7 +1072,9 @@ mod tests {
 
         let mut state = AppState::new();
 
-        edit.handle_key_stroke(KeyCode::Delete, &mut state);
+        let mut commands = Manager::default();
+
+        edit.handle_key_stroke(KeyCode::Delete, &mut state, &mut commands);
 
         assert_eq!(edit.lines(), &vec!["ac".to_string()]);
         assert_eq!(edit.current_line(), 0);
@@ -473,7 +1090,9 @@ mod tests {
 
         let mut state = AppState::new();
 
-        edit.handle_key_stroke(KeyCode::Delete, &mut state);
+        let mut commands = Manager::default();
+
+        edit.handle_key_stroke(KeyCode::Delete, &mut state, &mut commands);
 
         assert_eq!(edit.lines(), &vec!["abc".to_string(), "def".to_string()]);
         assert_eq!(edit.current_line(), 1);
@@ -489,7 +1108,9 @@ mod tests {
 
         let mut state = AppState::new();
 
-        edit.handle_key_stroke(KeyCode::Delete, &mut state);
+        let mut commands = Manager::default();
+
+        edit.handle_key_stroke(KeyCode::Delete, &mut state, &mut commands);
 
         assert_eq!(edit.lines(), &vec!["abcdef".to_string()]);
         assert_eq!(edit.current_line(), 0);
@@ -509,7 +1130,9 @@ mod tests {
 
         let mut state = AppState::new();
 
-        edit.scroll_down_one(KeyCode::Null, &mut state);
+        let mut commands = Manager::default();
+
+        edit.scroll_down_one(KeyCode::Null, &mut state, &mut commands);
 
         assert_eq!(edit.scroll_y(), 96);
     }
@@ -527,18 +1150,64 @@ mod tests {
 
         let mut state = AppState::new();
 
-        edit.scroll_down_ten(KeyCode::Null, &mut state);
+        let mut commands = Manager::default();
+
+        edit.scroll_down_ten(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.scroll_y(), 100);
+    }
+
+    #[test]
+    fn scroll_by_rendered_height() {
+        let mut edit = TextPanel::default();
+        edit.set_text(
+            (100..200)
+                .map(|i| i.to_string())
+                .collect::<Vec<String>>()
+                .join("\n"),
+        );
+        edit.set_viewport(Rect::new(0, 0, 20, 30));
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.scroll_down_page(KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(edit.scroll_y(), 30);
+
+        edit.scroll_down_half_page(KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(edit.scroll_y(), 45);
+
+        edit.scroll_up_page(KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(edit.scroll_y(), 15);
+
+        edit.scroll_up_half_page(KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(edit.scroll_y(), 0);
 
+        for _ in 0..5 {
+            edit.scroll_down_page(KeyCode::Null, &mut state, &mut commands);
+        }
         assert_eq!(edit.scroll_y(), 100);
     }
 
+    #[test]
+    fn scroll_page_before_render() {
+        let mut edit = TextPanel::default();
+        edit.set_text("1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12");
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.scroll_down_page(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.scroll_y(), 10);
+    }
+
     #[test]
     fn scroll_up_one() {
         let mut edit = TextPanel::default();
         let mut state = AppState::new();
+        let mut commands = Manager::default();
         edit.set_scroll_y(6);
 
-        edit.scroll_up_one(KeyCode::Null, &mut state);
+        edit.scroll_up_one(KeyCode::Null, &mut state, &mut commands);
 
         assert_eq!(edit.scroll_y(), 5);
     }
@@ -547,8 +1216,9 @@ mod tests {
     fn scroll_up_one_at_zero() {
         let mut edit = TextPanel::default();
         let mut state = AppState::new();
+        let mut commands = Manager::default();
 
-        edit.scroll_up_one(KeyCode::Null, &mut state);
+        edit.scroll_up_one(KeyCode::Null, &mut state, &mut commands);
 
         assert_eq!(edit.scroll_y(), 0);
     }
@@ -565,8 +1235,9 @@ mod tests {
         edit.set_current_line(2);
         edit.set_cursor_index(2);
         let mut state = AppState::new();
+        let mut commands = Manager::default();
 
-        edit.move_to_next_character(KeyCode::Null, &mut state);
+        edit.move_to_next_character(KeyCode::Null, &mut state, &mut commands);
 
         assert_eq!(edit.cursor_index_in_line(), 3);
     }
@@ -583,11 +1254,12 @@ mod tests {
         edit.set_current_line(2);
         edit.set_cursor_index(2);
         let mut state = AppState::new();
+        let mut commands = Manager::default();
 
-        edit.move_to_next_character(KeyCode::Null, &mut state);
+        edit.move_to_next_character(KeyCode::Null, &mut state, &mut commands);
         assert_eq!(edit.cursor_index_in_line(), 3);
 
-        edit.move_to_next_character(KeyCode::Null, &mut state);
+        edit.move_to_next_character(KeyCode::Null, &mut state, &mut commands);
         assert_eq!(edit.cursor_index_in_line(), 0);
         assert_eq!(edit.current_line(), 3);
     }
@@ -604,8 +1276,9 @@ mod tests {
         edit.set_current_line(2);
         edit.set_cursor_index(2);
         let mut state = AppState::new();
+        let mut commands = Manager::default();
 
-        edit.move_to_previous_character(KeyCode::Null, &mut state);
+        edit.move_to_previous_character(KeyCode::Null, &mut state, &mut commands);
 
         assert_eq!(edit.cursor_index_in_line(), 1);
     }
@@ -622,14 +1295,15 @@ mod tests {
         edit.set_current_line(2);
         edit.set_cursor_index(2);
         let mut state = AppState::new();
+        let mut commands = Manager::default();
 
-        edit.move_to_previous_character(KeyCode::Null, &mut state);
+        edit.move_to_previous_character(KeyCode::Null, &mut state, &mut commands);
         assert_eq!(edit.cursor_index_in_line(), 1);
 
-        edit.move_to_previous_character(KeyCode::Null, &mut state);
+        edit.move_to_previous_character(KeyCode::Null, &mut state, &mut commands);
         assert_eq!(edit.cursor_index_in_line(), 0);
 
-        edit.move_to_previous_character(KeyCode::Null, &mut state);
+        edit.move_to_previous_character(KeyCode::Null, &mut state, &mut commands);
         assert_eq!(edit.cursor_index_in_line(), 3);
         assert_eq!(edit.current_line(), 1);
     }
@@ -644,8 +1318,9 @@ mod tests {
                 .join("\n"),
         );
         let mut state = AppState::new();
+        let mut commands = Manager::default();
 
-        edit.move_to_previous_character(KeyCode::Null, &mut state);
+        edit.move_to_previous_character(KeyCode::Null, &mut state, &mut commands);
 
         assert_eq!(edit.cursor_index_in_line(), 0);
         assert_eq!(edit.current_line(), 0);
@@ -658,8 +1333,9 @@ mod tests {
         edit.set_current_line(0);
         edit.set_cursor_index(4);
         let mut state = AppState::new();
+        let mut commands = Manager::default();
 
-        edit.move_to_next_line(KeyCode::Null, &mut state);
+        edit.move_to_next_line(KeyCode::Null, &mut state, &mut commands);
 
         assert_eq!(edit.cursor_index_in_line(), 4);
         assert_eq!(edit.current_line(), 1);
@@ -672,8 +1348,9 @@ mod tests {
         edit.set_current_line(1);
         edit.set_cursor_index(4);
         let mut state = AppState::new();
+        let mut commands = Manager::default();
 
-        edit.move_to_next_line(KeyCode::Null, &mut state);
+        edit.move_to_next_line(KeyCode::Null, &mut state, &mut commands);
 
         assert_eq!(edit.cursor_index_in_line(), 4);
         assert_eq!(edit.current_line(), 1);
@@ -686,8 +1363,9 @@ mod tests {
         edit.set_current_line(0);
         edit.set_cursor_index(9);
         let mut state = AppState::new();
+        let mut commands = Manager::default();
 
-        edit.move_to_next_line(KeyCode::Null, &mut state);
+        edit.move_to_next_line(KeyCode::Null, &mut state, &mut commands);
 
         assert_eq!(edit.cursor_index_in_line(), 5);
         assert_eq!(edit.current_line(), 1);
@@ -700,8 +1378,9 @@ mod tests {
         edit.set_current_line(1);
         edit.set_cursor_index(4);
         let mut state = AppState::new();
+        let mut commands = Manager::default();
 
-        edit.move_to_next_line(KeyCode::Null, &mut state);
+        edit.move_to_next_line(KeyCode::Null, &mut state, &mut commands);
 
         assert_eq!(edit.cursor_index_in_line(), 4);
         assert_eq!(edit.current_line(), 2);
@@ -714,8 +1393,9 @@ mod tests {
         edit.set_current_line(1);
         edit.set_cursor_index(4);
         let mut state = AppState::new();
+        let mut commands = Manager::default();
 
-        edit.move_to_previous_line(KeyCode::Null, &mut state);
+        edit.move_to_previous_line(KeyCode::Null, &mut state, &mut commands);
 
         assert_eq!(edit.cursor_index_in_line(), 4);
         assert_eq!(edit.current_line(), 0);
@@ -728,10 +1408,1997 @@ mod tests {
         edit.set_current_line(1);
         edit.set_cursor_index(9);
         let mut state = AppState::new();
+        let mut commands = Manager::default();
 
-        edit.move_to_previous_line(KeyCode::Null, &mut state);
+        edit.move_to_previous_line(KeyCode::Null, &mut state, &mut commands);
 
         assert_eq!(edit.cursor_index_in_line(), 5);
         assert_eq!(edit.current_line(), 0);
     }
+
+    #[test]
+    fn copy_block_between_mark_and_cursor() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abcdef\nab\nabcdef");
+        edit.set_mark(Some((0, 1)));
+        edit.set_current_line(2);
+        edit.set_cursor_index(4);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.copy_block(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(
+            state.clipboard(),
+            &vec!["bcd".to_string(), "b".to_string(), "bcd".to_string()]
+        );
+        assert_eq!(edit.mark(), None);
+    }
+
+    #[test]
+    fn copy_block_without_mark_copies_line() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abc\ndef");
+        edit.set_current_line(1);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.copy_block(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(state.clipboard(), &vec!["def".to_string()]);
+    }
+
+    #[test]
+    fn paste_block_pads_and_extends_lines() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abcdef\nab");
+        edit.set_cursor_index(3);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        state.set_clipboard(vec!["12".to_string(), "34".to_string(), "56".to_string()]);
+
+        edit.paste_block(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(
+            edit.lines(),
+            &vec![
+                "abc12def".to_string(),
+                "ab 34".to_string(),
+                "   56".to_string()
+            ]
+        );
+        assert_eq!(edit.current_line(), 0);
+        assert_eq!(edit.cursor_index_in_line(), 5);
+    }
+
+    #[test]
+    fn copy_block_keeps_to_character_boundaries() {
+        let mut edit = TextPanel::default();
+        edit.set_text("éé\nab");
+        edit.set_mark(Some((0, 0)));
+        edit.set_current_line(1);
+        edit.set_cursor_index(1);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.copy_block(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(state.clipboard(), &vec!["".to_string(), "a".to_string()]);
+    }
+
+    #[test]
+    fn paste_block_counts_characters() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abc\néé\né");
+        edit.set_cursor_index(1);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        state.set_clipboard(vec!["1".to_string(), "2".to_string(), "3".to_string(), "4".to_string()]);
+
+        edit.paste_block(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(
+            edit.lines(),
+            &vec!["a1bc".to_string(), "é2é".to_string(), "é3".to_string(), " 4".to_string()]
+        );
+        assert_eq!(edit.cursor_index_in_line(), 2);
+    }
+
+    #[test]
+    fn insert_on_marked_lines_counts_characters() {
+        let mut edit = TextPanel::default();
+        edit.set_text("éé\nab");
+        edit.set_mark(Some((0, 2)));
+        edit.set_current_line(1);
+        edit.set_cursor_index(1);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        state.set_clipboard(vec!["-".to_string()]);
+
+        edit.insert_on_marked_lines(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.lines(), &vec!["é-é".to_string(), "a-b".to_string()]);
+        assert_eq!(edit.cursor_index_in_line(), 2);
+    }
+
+    #[test]
+    fn insert_on_marked_lines() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abc\nabc\nabc\nabc");
+        edit.set_mark(Some((0, 1)));
+        edit.set_current_line(2);
+        edit.set_cursor_index(1);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        state.set_clipboard(vec!["--".to_string()]);
+
+        edit.insert_on_marked_lines(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(
+            edit.lines(),
+            &vec![
+                "a--bc".to_string(),
+                "a--bc".to_string(),
+                "a--bc".to_string(),
+                "abc".to_string()
+            ]
+        );
+        assert_eq!(edit.cursor_index_in_line(), 3);
+        assert_eq!(edit.mark(), None);
+    }
+
+    #[test]
+    fn column_select_types_on_every_line() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abc\nabc\na\nabc");
+        edit.set_cursor_index(2);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.toggle_column_select(KeyCode::Null, &mut state, &mut commands);
+        edit.set_current_line(2);
+        edit.handle_key_stroke(KeyCode::Char('-'), &mut state, &mut commands);
+        edit.handle_key_stroke(KeyCode::Char('-'), &mut state, &mut commands);
+
+        assert_eq!(
+            edit.lines(),
+            &vec![
+                "ab--c".to_string(),
+                "ab--c".to_string(),
+                "a --".to_string(),
+                "abc".to_string()
+            ]
+        );
+        assert_eq!(edit.mark(), Some((0, 4)));
+        assert_eq!(edit.cursor_index_in_line(), 4);
+        assert!(edit.modified());
+
+        edit.handle_key_stroke(KeyCode::Backspace, &mut state, &mut commands);
+        assert_eq!(edit.lines()[0], "ab-c");
+        assert_eq!(edit.lines()[2], "a -");
+        assert_eq!(edit.cursor_index_in_line(), 3);
+
+        edit.handle_key_stroke(KeyCode::Enter, &mut state, &mut commands);
+        assert!(!edit.column_select());
+        assert_eq!(edit.mark(), None);
+        assert_eq!(edit.lines().len(), 4);
+    }
+
+    #[test]
+    fn column_select_types_over_block() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abcdef\nab\nabcdef");
+        edit.set_cursor_index(1);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.toggle_column_select(KeyCode::Null, &mut state, &mut commands);
+        edit.set_current_line(2);
+        edit.set_cursor_index(4);
+        edit.handle_key_stroke(KeyCode::Char('x'), &mut state, &mut commands);
+
+        assert_eq!(edit.lines(), &vec!["axef".to_string(), "ax".to_string(), "axef".to_string()]);
+        assert_eq!(edit.mark(), Some((0, 2)));
+        assert_eq!(edit.cursor_index_in_line(), 2);
+
+        edit.toggle_column_select(KeyCode::Null, &mut state, &mut commands);
+        assert!(!edit.column_select());
+        edit.handle_key_stroke(KeyCode::Char('y'), &mut state, &mut commands);
+        assert_eq!(edit.lines()[2], "axyef");
+    }
+
+    #[test]
+    fn column_select_types_in_character_column() {
+        let mut edit = TextPanel::default();
+        edit.set_text("ééé\nabcd");
+        edit.set_cursor_index(2);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.toggle_column_select(KeyCode::Null, &mut state, &mut commands);
+        edit.set_current_line(1);
+        edit.handle_key_stroke(KeyCode::Char('x'), &mut state, &mut commands);
+
+        assert_eq!(edit.lines(), &vec!["ééxé".to_string(), "abxcd".to_string()]);
+        assert_eq!(edit.cursor_index_in_line(), 3);
+    }
+
+    #[test]
+    fn delete_block_removes_columns() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abcdef\nab\nabcdef\nabcdef");
+        edit.set_mark(Some((2, 4)));
+        edit.set_cursor_index(1);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.delete_block(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(
+            edit.lines(),
+            &vec!["aef".to_string(), "a".to_string(), "aef".to_string(), "abcdef".to_string()]
+        );
+        assert_eq!((edit.current_line(), edit.cursor_index_in_line()), (0, 1));
+        assert_eq!(edit.mark(), None);
+
+        let (_, changes) = edit.delete_block(KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(changes.len(), 1);
+    }
+
+    #[test]
+    fn insert_on_marked_lines_without_mark() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abc\nabc");
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        state.set_clipboard(vec!["--".to_string()]);
+
+        let (_, changes) = edit.insert_on_marked_lines(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.lines(), &vec!["abc".to_string(), "abc".to_string()]);
+        assert_eq!(changes.len(), 1);
+    }
+
+    #[test]
+    fn handle_key_stroke_sets_modified() {
+        let mut edit = TextPanel::default();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        assert!(!edit.modified());
+
+        edit.handle_key_stroke(KeyCode::Char('a'), &mut state, &mut commands);
+
+        assert!(edit.modified());
+    }
+
+    #[test]
+    fn unhandled_key_stroke_doesnt_set_modified() {
+        let mut edit = TextPanel::default();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.handle_key_stroke(KeyCode::F(1), &mut state, &mut commands);
+
+        assert!(!edit.modified());
+    }
+
+    #[test]
+    fn reflow_paragraph_at_cursor() {
+        let mut edit = TextPanel::default();
+        edit.set_text("first\n\n  one two three\nfour five six\n\nlast");
+        edit.set_ruler(Some(10));
+        edit.set_current_line(3);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.reflow(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(
+            edit.lines(),
+            &vec![
+                "first".to_string(),
+                "".to_string(),
+                "  one two".to_string(),
+                "  three".to_string(),
+                "  four".to_string(),
+                "  five six".to_string(),
+                "".to_string(),
+                "last".to_string(),
+            ]
+        );
+        assert_eq!(edit.current_line(), 5);
+        assert!(edit.modified());
+    }
+
+    #[test]
+    fn reflow_marked_lines() {
+        let mut edit = TextPanel::default();
+        edit.set_text("a b\nc d\ne f");
+        edit.set_mark(Some((0, 0)));
+        edit.set_current_line(1);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.reflow(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.lines(), &vec!["a b c d".to_string(), "e f".to_string()]);
+        assert_eq!(edit.mark(), None);
+    }
+
+    #[test]
+    fn reflow_long_word_on_own_line() {
+        let mut edit = TextPanel::default();
+        edit.set_text("a abcdefghijkl b");
+        edit.set_ruler(Some(5));
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.reflow(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(
+            edit.lines(),
+            &vec!["a".to_string(), "abcdefghijkl".to_string(), "b".to_string()]
+        );
+    }
+
+    #[test]
+    fn set_ruler_from_input() {
+        let mut edit = TextPanel::edit_panel();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.set_ruler_column(KeyCode::Null, &mut state, &mut commands);
+        edit.receive_input("72".to_string());
+
+        assert_eq!(edit.ruler(), Some(72));
+        assert_eq!(edit.state(), PanelState::Normal);
+    }
+
+    #[test]
+    fn next_word() {
+        let mut edit = TextPanel::default();
+        edit.set_text("let fooBar = baz_qux;");
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        let mut stops = vec![];
+        for _ in 0..5 {
+            edit.move_to_next_word(KeyCode::Null, &mut state, &mut commands);
+            stops.push(edit.cursor_index_in_line());
+        }
+
+        assert_eq!(stops, vec![4, 11, 13, 20, 21]);
+    }
+
+    #[test]
+    fn next_sub_word() {
+        let mut edit = TextPanel::default();
+        edit.set_text("let fooBar = baz_qux HTTPServer");
+        edit.set_sub_word_motions(true);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        let mut stops = vec![];
+        for _ in 0..7 {
+            edit.move_to_next_word(KeyCode::Null, &mut state, &mut commands);
+            stops.push(edit.cursor_index_in_line());
+        }
+
+        assert_eq!(stops, vec![4, 7, 11, 13, 17, 21, 25]);
+    }
+
+    #[test]
+    fn next_word_to_next_line() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abc\ndef");
+        edit.set_cursor_index(3);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.move_to_next_word(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.current_line(), 1);
+        assert_eq!(edit.cursor_index_in_line(), 0);
+    }
+
+    #[test]
+    fn previous_sub_word() {
+        let mut edit = TextPanel::default();
+        edit.set_text("let fooBar = baz_qux");
+        edit.set_sub_word_motions(true);
+        edit.set_cursor_to_end();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        let mut stops = vec![];
+        for _ in 0..6 {
+            edit.move_to_previous_word(KeyCode::Null, &mut state, &mut commands);
+            stops.push(edit.cursor_index_in_line());
+        }
+
+        assert_eq!(stops, vec![17, 13, 11, 7, 4, 0]);
+    }
+
+    #[test]
+    fn previous_word_to_previous_line() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abc\ndef");
+        edit.set_current_line(1);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.move_to_previous_word(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.current_line(), 0);
+        assert_eq!(edit.cursor_index_in_line(), 3);
+    }
+
+    #[test]
+    fn delete_previous_sub_word() {
+        let mut edit = TextPanel::default();
+        edit.set_text("someLongName");
+        edit.set_sub_word_motions(true);
+        edit.set_cursor_to_end();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.delete_previous_word(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.lines(), &vec!["someLong".to_string()]);
+        assert_eq!(edit.cursor_index_in_line(), 8);
+        assert!(edit.modified());
+    }
+
+    #[test]
+    fn delete_next_word() {
+        let mut edit = TextPanel::default();
+        edit.set_text("some_long name");
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.delete_next_word(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.lines(), &vec!["name".to_string()]);
+        assert_eq!(edit.cursor_index_in_line(), 0);
+    }
+
+    #[test]
+    fn transpose_characters() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abcd");
+        edit.set_cursor_index(1);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.transpose_characters(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.lines(), &vec!["bacd".to_string()]);
+        assert_eq!(edit.cursor_index_in_line(), 2);
+        assert!(edit.modified());
+    }
+
+    #[test]
+    fn transpose_characters_end_of_line() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abcd");
+        edit.set_cursor_index(4);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.transpose_characters(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.lines(), &vec!["abdc".to_string()]);
+        assert_eq!(edit.cursor_index_in_line(), 4);
+    }
+
+    #[test]
+    fn transpose_characters_start_of_line() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abcd");
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.transpose_characters(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.lines(), &vec!["abcd".to_string()]);
+        assert!(!edit.modified());
+    }
+
+    #[test]
+    fn transpose_lines() {
+        let mut edit = TextPanel::default();
+        edit.set_text("one\ntwo\nthree");
+        edit.set_current_line(1);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.transpose_lines(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(
+            edit.lines(),
+            &vec!["two".to_string(), "one".to_string(), "three".to_string()]
+        );
+        assert_eq!(edit.current_line(), 2);
+    }
+
+    #[test]
+    fn transpose_lines_first_line() {
+        let mut edit = TextPanel::default();
+        edit.set_text("one\ntwo");
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.transpose_lines(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.lines(), &vec!["one".to_string(), "two".to_string()]);
+        assert_eq!(edit.current_line(), 0);
+    }
+
+    #[test]
+    fn duplicate_line() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abc\ndef");
+        edit.set_cursor_index(2);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.duplicate_line(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(
+            edit.lines(),
+            &vec!["abc".to_string(), "abc".to_string(), "def".to_string()]
+        );
+        assert_eq!(edit.current_line(), 1);
+        assert_eq!(edit.cursor_index_in_line(), 2);
+        assert!(edit.modified());
+    }
+
+    #[test]
+    fn delete_line() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abc\ndefgh\ni");
+        edit.set_current_line(1);
+        edit.set_cursor_index(4);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.delete_line(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.lines(), &vec!["abc".to_string(), "i".to_string()]);
+        assert_eq!(edit.current_line(), 1);
+        assert_eq!(edit.cursor_index_in_line(), 1);
+    }
+
+    #[test]
+    fn delete_last_line() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abc\ndef");
+        edit.set_current_line(1);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.delete_line(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.lines(), &vec!["abc".to_string()]);
+        assert_eq!(edit.current_line(), 0);
+    }
+
+    #[test]
+    fn delete_only_line() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abc");
+        edit.set_cursor_index(2);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.delete_line(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.lines(), &vec!["".to_string()]);
+        assert_eq!(edit.current_line(), 0);
+        assert_eq!(edit.cursor_index_in_line(), 0);
+    }
+
+    #[test]
+    fn move_line_up() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abc\ndef\nghi");
+        edit.set_current_line(2);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.move_line_up(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(
+            edit.lines(),
+            &vec!["abc".to_string(), "ghi".to_string(), "def".to_string()]
+        );
+        assert_eq!(edit.current_line(), 1);
+    }
+
+    #[test]
+    fn move_line_up_first_line() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abc\ndef");
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.move_line_up(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.lines(), &vec!["abc".to_string(), "def".to_string()]);
+        assert_eq!(edit.current_line(), 0);
+        assert!(!edit.modified());
+    }
+
+    #[test]
+    fn move_line_down() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abc\ndef\nghi");
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.move_line_down(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(
+            edit.lines(),
+            &vec!["def".to_string(), "abc".to_string(), "ghi".to_string()]
+        );
+        assert_eq!(edit.current_line(), 1);
+    }
+
+    #[test]
+    fn move_line_down_last_line() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abc\ndef");
+        edit.set_current_line(1);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.move_line_down(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.lines(), &vec!["abc".to_string(), "def".to_string()]);
+        assert_eq!(edit.current_line(), 1);
+    }
+
+    #[test]
+    fn handle_enter_key_middle_of_line() {
+        let mut edit = TextPanel::default();
+        edit.set_text("abcdef\nghi");
+        edit.set_cursor_index(3);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.handle_key_stroke(KeyCode::Enter, &mut state, &mut commands);
+
+        assert_eq!(
+            edit.lines(),
+            &vec!["abc".to_string(), "def".to_string(), "ghi".to_string()]
+        );
+        assert_eq!(edit.current_line(), 1);
+        assert_eq!(edit.cursor_index_in_line(), 0);
+    }
+
+    #[test]
+    fn handle_enter_key_keeps_indentation() {
+        let mut edit = TextPanel::default();
+        edit.set_text("    let a = b;");
+        edit.set_cursor_index(9);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.handle_key_stroke(KeyCode::Enter, &mut state, &mut commands);
+
+        assert_eq!(
+            edit.lines(),
+            &vec!["    let a".to_string(), "    = b;".to_string()]
+        );
+        assert_eq!(edit.cursor_index_in_line(), 4);
+    }
+
+    #[test]
+    fn indent_with_spaces_to_tab_stop() {
+        let mut edit = TextPanel::default();
+        edit.set_text("ab");
+        edit.set_cursor_index(1);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.indent(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.lines(), &vec!["a   b".to_string()]);
+        assert_eq!(edit.cursor_index_in_line(), 4);
+    }
+
+    #[test]
+    fn indent_with_tabs() {
+        let mut edit = TextPanel::default();
+        edit.set_text("ab");
+        edit.set_indent_with_tabs(true);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.indent(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.lines(), &vec!["\tab".to_string()]);
+        assert_eq!(edit.cursor_index_in_line(), 1);
+    }
+
+    #[test]
+    fn dedent_spaces() {
+        let mut edit = TextPanel::default();
+        edit.set_text("      ab");
+        edit.set_cursor_index(7);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.dedent(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.lines(), &vec!["  ab".to_string()]);
+        assert_eq!(edit.cursor_index_in_line(), 3);
+    }
+
+    #[test]
+    fn dedent_tab() {
+        let mut edit = TextPanel::default();
+        edit.set_text("\t\tab");
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.dedent(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.lines(), &vec!["\tab".to_string()]);
+        assert_eq!(edit.cursor_index_in_line(), 0);
+    }
+
+    #[test]
+    fn duplicate_marked_lines() {
+        let mut edit = TextPanel::edit_panel();
+        edit.set_text("a\nb\nc");
+        edit.set_mark(Some((0, 0)));
+        edit.set_current_line(1);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.start_duplicate_selection(KeyCode::Null, &mut state, &mut commands);
+        edit.receive_input("2".to_string());
+
+        assert_eq!(
+            edit.lines(),
+            &vec![
+                "a".to_string(),
+                "b".to_string(),
+                "a".to_string(),
+                "b".to_string(),
+                "a".to_string(),
+                "b".to_string(),
+                "c".to_string()
+            ]
+        );
+        assert_eq!(edit.current_line(), 2);
+        assert_eq!(edit.state(), PanelState::Normal);
+    }
+
+    #[test]
+    fn duplicate_count_capped() {
+        let mut edit = TextPanel::default();
+        edit.set_text("a");
+
+        edit.duplicate_selection(usize::MAX);
+
+        assert_eq!(edit.lines().len(), MAX_COUNT + 1);
+    }
+
+    #[test]
+    fn duplicate_current_line_without_mark() {
+        let mut edit = TextPanel::default();
+        edit.set_text("a\nb");
+        edit.set_current_line(1);
+
+        edit.duplicate_selection(3);
+
+        assert_eq!(
+            edit.lines(),
+            &vec!["a".to_string(), "b".to_string(), "b".to_string(), "b".to_string(), "b".to_string()]
+        );
+    }
+
+    #[test]
+    fn copy_lines() {
+        let mut edit = TextPanel::edit_panel();
+        edit.set_text("1\n2\n3\n4");
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.start_copy_lines(KeyCode::Null, &mut state, &mut commands);
+        let changes = edit.receive_input("1,2t3".to_string());
+
+        assert!(changes.is_empty());
+        assert_eq!(
+            edit.lines(),
+            &vec![
+                "1".to_string(),
+                "2".to_string(),
+                "3".to_string(),
+                "1".to_string(),
+                "2".to_string(),
+                "4".to_string()
+            ]
+        );
+        assert_eq!(edit.current_line(), 3);
+    }
+
+    #[test]
+    fn copy_single_line_to_top() {
+        let mut edit = TextPanel::default();
+        edit.set_text("1\n2");
+
+        edit.copy_lines(2, 2, 0).unwrap();
+
+        assert_eq!(edit.lines(), &vec!["2".to_string(), "1".to_string(), "2".to_string()]);
+    }
+
+    #[test]
+    fn copy_lines_invalid_range() {
+        let mut edit = TextPanel::edit_panel();
+        edit.set_text("1\n2");
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.start_copy_lines(KeyCode::Null, &mut state, &mut commands);
+        let changes = edit.receive_input("1,5t0".to_string());
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(edit.lines(), &vec!["1".to_string(), "2".to_string()]);
+    }
+
+    #[test]
+    fn replace_all_from_answers() {
+        let mut edit = TextPanel::edit_panel();
+        edit.set_text("cat cat\ndog\ncat");
+        edit.set_cursor_index(7);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        let (_, changes) = edit.start_replace_all(KeyCode::Null, &mut state, &mut commands);
+        assert!(matches!(changes.last(), Some(StateChangeRequest::InputSequence(..))));
+
+        edit.receive_answers(vec!["cat".to_string(), "ox".to_string()]);
+
+        assert_eq!(edit.lines(), &vec!["ox ox".to_string(), "dog".to_string(), "ox".to_string()]);
+        assert_eq!(edit.cursor_index_in_line(), 5);
+        assert_eq!(edit.state(), PanelState::Normal);
+        assert!(edit.modified());
+    }
+
+    #[test]
+    fn replace_all_nothing_found() {
+        let mut edit = TextPanel::edit_panel();
+        edit.set_text("dog");
+
+        assert_eq!(edit.replace_all("cat", "ox"), 0);
+        assert_eq!(edit.replace_all("", "ox"), 0);
+        assert!(!edit.modified());
+    }
+
+    #[test]
+    fn replace_in_marked_block_only() {
+        let mut edit = TextPanel::edit_panel();
+        edit.set_text("cat cat cat\ncat cat cat\ncat cat cat");
+        edit.set_mark(Some((0, 4)));
+        edit.set_current_line(1);
+        edit.set_cursor_index(11);
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        let (_, changes) = edit.start_replace_in_range(KeyCode::Null, &mut state, &mut commands);
+        assert!(matches!(changes.last(), Some(StateChangeRequest::InputSequence(prompts)) if prompts.len() == 2));
+
+        let changes = edit.receive_answers(vec!["cat".to_string(), "ox".to_string()]);
+
+        assert_eq!(
+            edit.lines(),
+            &vec!["cat ox ox".to_string(), "cat ox ox".to_string(), "cat cat cat".to_string()]
+        );
+        assert!(matches!(changes.last(), Some(StateChangeRequest::Message(..))));
+        assert_eq!(edit.mark(), None);
+        assert_eq!(edit.cursor_index_in_line(), 9);
+        assert_eq!(edit.state(), PanelState::Normal);
+    }
+
+    #[test]
+    fn replace_in_line_range() {
+        let mut edit = TextPanel::edit_panel();
+        edit.set_text("cat\ncat\ncat\ncat");
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        let (_, changes) = edit.start_replace_in_range(KeyCode::Null, &mut state, &mut commands);
+        assert!(matches!(changes.last(), Some(StateChangeRequest::InputSequence(prompts)) if prompts.len() == 3));
+
+        edit.receive_answers(vec!["2,3".to_string(), "cat".to_string(), "ox".to_string()]);
+
+        assert_eq!(
+            edit.lines(),
+            &vec!["cat".to_string(), "ox".to_string(), "ox".to_string(), "cat".to_string()]
+        );
+        assert!(edit.modified());
+
+        assert!(edit.replace_in_lines("cat", "ox", 3, 5).is_err());
+        assert!(edit.replace_in_lines("cat", "ox", 0, 1).is_err());
+        assert_eq!(edit.replace_in_lines("cat", "ox", 4, 4), Ok(1));
+    }
+
+    #[test]
+    fn checkpoint_and_restore() {
+        let mut edit = TextPanel::edit_panel();
+        edit.set_text("one\ntwo");
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        let (_, changes) = edit.start_restore_checkpoint(KeyCode::Null, &mut state, &mut commands);
+        assert!(matches!(changes.last(), Some(StateChangeRequest::Message(..))));
+        assert_eq!(edit.state(), PanelState::Normal);
+
+        edit.start_checkpoint(KeyCode::Null, &mut state, &mut commands);
+        edit.receive_input("before rename".to_string());
+        assert_eq!(edit.checkpoint_labels(), vec!["before rename".to_string()]);
+
+        edit.set_text("one\nthree\nfour");
+        edit.set_current_line(2);
+        edit.set_cursor_index(4);
+
+        edit.start_restore_checkpoint(KeyCode::Null, &mut state, &mut commands);
+        edit.receive_input("rename".to_string());
+
+        assert_eq!(edit.lines(), &vec!["one".to_string(), "two".to_string()]);
+        assert_eq!(edit.current_line(), 1);
+        assert_eq!(edit.cursor_index_in_line(), 3);
+        assert!(edit.modified());
+        // still there to go back to again
+        assert!(edit.checkpoint("before rename").is_some());
+
+        assert!(edit.restore_checkpoint("missing").is_err());
+    }
+
+    #[test]
+    fn parse_copy_lines_command() {
+        assert_eq!(parse_copy_lines("3,5t10"), Some((3, 5, 10)));
+        assert_eq!(parse_copy_lines(" 3 , 5 t 10 "), Some((3, 5, 10)));
+        assert_eq!(parse_copy_lines("4t0"), Some((4, 4, 0)));
+        assert_eq!(parse_copy_lines("4,t0"), None);
+        assert_eq!(parse_copy_lines("4"), None);
+    }
+
+    #[test]
+    fn no_wrap_clips_lines() {
+        let mut edit = TextPanel::default();
+        edit.set_text("12345678901234567890123456789012345678901234567890\n123");
+        edit.set_wrap(false);
+        edit.set_scroll_x(5);
+        edit.set_cursor_index(12);
+
+        let (spans, cursor, gutter) = edit.make_text_content(Rect::new(10, 10, 20, 20));
+
+        assert_eq!(
+            spans,
+            vec![
+                Spans::from(Span::from("67890123456789012345")),
+                Spans::from(Span::from("")),
+            ]
+        );
+        assert_eq!(
+            gutter,
+            vec![Spans::from(Span::from("1")), Spans::from(Span::from("2"))]
+        );
+        assert_eq!(cursor, (17, 10));
+    }
+
+    #[test]
+    fn no_wrap_cursor_counts_characters() {
+        let mut edit = TextPanel::default();
+        edit.set_text("ééééééééééééééééééééé");
+        edit.set_wrap(false);
+        edit.set_scroll_x(5);
+        edit.set_cursor_index(24);
+
+        let (spans, cursor, _) = edit.make_text_content(Rect::new(10, 10, 20, 20));
+
+        assert_eq!(spans, vec![Spans::from(Span::from("éééééééééééééééé"))]);
+        assert_eq!(cursor, (17, 10));
+        assert_eq!(edit.screen_position(0, 24, Rect::new(10, 10, 20, 20)), Some((17, 10)));
+        assert_eq!(edit.screen_position(0, 25, Rect::new(10, 10, 20, 20)), None);
+    }
+
+    #[test]
+    fn no_wrap_cursor_left_of_view() {
+        let mut edit = TextPanel::default();
+        edit.set_text("12345678901234567890");
+        edit.set_wrap(false);
+        edit.set_scroll_x(5);
+        edit.set_cursor_index(2);
+
+        let (_, cursor, _) = edit.make_text_content(Rect::new(10, 10, 20, 20));
+
+        assert_eq!(cursor, CURSOR_MAX);
+    }
+
+    #[test]
+    fn toggle_wrap_resets_horizontal_scroll() {
+        let mut edit = TextPanel::default();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        edit.toggle_wrap(KeyCode::Null, &mut state, &mut commands);
+        edit.scroll_right_ten(KeyCode::Null, &mut state, &mut commands);
+        edit.scroll_left_one(KeyCode::Null, &mut state, &mut commands);
+
+        assert!(!edit.wrap());
+        assert_eq!(edit.scroll_x(), 9);
+
+        edit.toggle_wrap(KeyCode::Null, &mut state, &mut commands);
+
+        assert!(edit.wrap());
+        assert_eq!(edit.scroll_x(), 0);
+    }
+
+    #[test]
+    fn save_replaces_file_and_keeps_permissions() {
+        let dir = std::env::temp_dir().join(format!("edish_save_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.garnish");
+        std::fs::write(&path, "old contents\n").unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+        }
+
+        let mut edit = TextPanel::default();
+        edit.set_file_path(path.clone());
+        edit.set_text("one\ntwo");
+        edit.set_modified(true);
+
+        edit.save();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "one\ntwo");
+        assert!(!edit.modified());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o640);
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn save_through_symlink_keeps_link() {
+        let dir = std::env::temp_dir().join(format!("edish_save_symlink_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("target.garnish");
+        let link = dir.join("link.garnish");
+        std::fs::write(&target, "old contents\n").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        let mut edit = TextPanel::default();
+        edit.set_file_path(link.clone());
+        edit.set_text("one\ntwo");
+        edit.set_modified(true);
+
+        edit.save();
+
+        assert!(std::fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "one\ntwo");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn save_leaves_existing_temp_name_alone() {
+        let dir = std::env::temp_dir().join(format!("edish_save_temp_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.garnish");
+        let planted = dir.join(format!(".test.garnish.{}-0.edish-save", std::process::id()));
+        std::fs::write(&planted, "planted").unwrap();
+
+        let mut edit = TextPanel::default();
+        edit.set_file_path(path.clone());
+        edit.set_text("one");
+        edit.set_modified(true);
+
+        edit.save();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "one");
+        assert_eq!(std::fs::read_to_string(&planted).unwrap(), "planted");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn save_keeps_missing_final_newline() {
+        let dir = std::env::temp_dir().join(format!("edish_final_newline_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.garnish");
+
+        let mut edit = TextPanel::default();
+        edit.set_file_path(path.clone());
+
+        edit.set_text("one\ntwo\n");
+        edit.save();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "one\ntwo\n");
+
+        edit.set_text("one\ntwo");
+        edit.save();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "one\ntwo");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn save_trims_whitespace_and_ensures_final_newline() {
+        let dir = std::env::temp_dir().join(format!("edish_trim_on_save_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.garnish");
+
+        let mut edit = TextPanel::default();
+        edit.set_file_path(path.clone());
+        edit.set_trim_trailing_whitespace(true);
+        edit.set_final_newline(true);
+
+        edit.set_text("one  \ntwo\t\n\n\n");
+        edit.set_current_line(0);
+        edit.set_cursor_index(5);
+        edit.save();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "one\ntwo\n");
+        assert_eq!(edit.lines(), &vec!["one".to_string(), "two".to_string(), "".to_string()]);
+        assert_eq!(edit.cursor_index_in_line(), 3);
+        assert!(!edit.modified());
+
+        edit.set_text("three");
+        edit.save();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "three\n");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn save_as_existing_file_asks_to_overwrite() {
+        let dir = std::env::temp_dir().join(format!("edish_overwrite_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.garnish");
+        std::fs::write(&path, "old contents\n").unwrap();
+
+        let mut edit = TextPanel::edit_panel();
+        edit.set_text("new");
+
+        edit.set_state(PanelState::WaitingToSave);
+        let changes = edit.receive_input(path.to_string_lossy().to_string());
+
+        assert!(matches!(changes.last(), Some(StateChangeRequest::Confirm(..))));
+        assert_eq!(edit.state(), PanelState::WaitingToConfirmOverwrite);
+
+        edit.receive_input("n".to_string());
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "old contents\n");
+        assert_eq!(edit.file_path(), None);
+        assert_eq!(edit.state(), PanelState::Normal);
+
+        edit.set_state(PanelState::WaitingToSave);
+        edit.receive_input(path.to_string_lossy().to_string());
+        edit.receive_input("yes".to_string());
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(edit.file_path(), Some(&path));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn save_as_missing_directory_asks_to_create() {
+        let dir = std::env::temp_dir().join(format!("edish_create_dirs_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("one").join("two").join("test.garnish");
+
+        let mut edit = TextPanel::edit_panel();
+        edit.set_text("new");
+
+        edit.set_state(PanelState::WaitingToSave);
+        let changes = edit.receive_input(path.to_string_lossy().to_string());
+
+        assert!(matches!(changes.last(), Some(StateChangeRequest::Confirm(..))));
+        assert_eq!(edit.state(), PanelState::WaitingToCreateDirectories);
+
+        edit.receive_input("n".to_string());
+
+        assert!(!dir.join("one").exists());
+        assert_eq!(edit.file_path(), None);
+
+        edit.set_state(PanelState::WaitingToSave);
+        edit.receive_input(path.to_string_lossy().to_string());
+        edit.receive_input("y".to_string());
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(edit.file_path(), Some(&path));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn save_as_through_file_names_component() {
+        let dir = std::env::temp_dir().join(format!("edish_file_component_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let blocking = dir.join("notes");
+        std::fs::write(&blocking, "").unwrap();
+
+        let mut edit = TextPanel::edit_panel();
+        edit.set_state(PanelState::WaitingToSave);
+        let changes = edit.receive_input(blocking.join("test.garnish").to_string_lossy().to_string());
+
+        match changes.last() {
+            Some(StateChangeRequest::Message(message)) => {
+                assert!(message.text().to_string().contains(&format!("{:?}", blocking)))
+            }
+            _ => panic!("Expected error message"),
+        }
+        assert_eq!(edit.state(), PanelState::Normal);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn save_read_only_file_asks_for_new_name() {
+        let dir = std::env::temp_dir().join(format!("edish_read_only_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.garnish");
+        std::fs::write(&path, "old contents\n").unwrap();
+
+        let mut permissions = std::fs::metadata(&path).unwrap().permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(&path, permissions).unwrap();
+
+        let mut edit = TextPanel::default();
+        edit.set_file_path(path.clone());
+        edit.set_text("one");
+        edit.set_modified(true);
+
+        let changes = edit.save();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "old contents\n");
+        assert!(edit.modified());
+        assert_eq!(edit.state(), PanelState::WaitingToSave);
+        assert!(matches!(changes.last(), Some(StateChangeRequest::Input(..))));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn open_with_swap_file_offers_recovery() {
+        let dir = std::env::temp_dir().join(format!("edish_swap_recover_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.garnish");
+        let swap = crate::swap::swap_path(&path).unwrap();
+        std::fs::write(&path, "saved\n").unwrap();
+        std::fs::write(&swap, "unsaved\n").unwrap();
+
+        let mut edit = TextPanel::default();
+        edit.set_state(PanelState::WaitingToOpen);
+
+        let changes = TextEditPanel::input_handler(&mut edit, path.to_string_lossy().to_string());
+
+        assert_eq!(edit.state(), PanelState::WaitingToRecover);
+        assert!(matches!(changes.last(), Some(StateChangeRequest::Input(..))));
+
+        TextEditPanel::input_handler(&mut edit, "y".to_string());
+
+        assert_eq!(edit.state(), PanelState::Normal);
+        assert_eq!(edit.lines(), &vec!["unsaved".to_string()]);
+        assert!(edit.modified());
+        assert!(!swap.exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn open_remote_starts_download() {
+        let mut edit = TextPanel::default();
+        edit.set_text("old");
+        edit.set_file_path(std::path::PathBuf::from("old.garnish"));
+        edit.set_state(PanelState::WaitingToOpen);
+
+        let changes = TextEditPanel::input_handler(&mut edit, "sftp://example.com/notes.txt".to_string());
+
+        assert_eq!(edit.state(), PanelState::Normal);
+        assert_eq!(edit.title(), "sftp://example.com/notes.txt");
+        assert_eq!(edit.file_path(), None);
+        assert_eq!(edit.text(), "");
+        assert!(edit.remote().is_some());
+        assert!(matches!(changes.last(), Some(StateChangeRequest::Custom(_))));
+    }
+
+    #[test]
+    fn discard_swap_file() {
+        let dir = std::env::temp_dir().join(format!("edish_discard_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.garnish");
+        let swap = crate::swap::swap_path(&path).unwrap();
+        std::fs::write(&path, "saved").unwrap();
+        std::fs::write(&swap, "unsaved\n").unwrap();
+
+        let mut edit = TextPanel::default();
+        edit.set_state(PanelState::WaitingToOpen);
+
+        TextEditPanel::input_handler(&mut edit, path.to_string_lossy().to_string());
+        TextEditPanel::input_handler(&mut edit, "n".to_string());
+
+        assert_eq!(edit.lines(), &vec!["saved".to_string()]);
+        assert!(!edit.modified());
+        assert!(!swap.exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn open_image_shows_placeholder() {
+        let dir = std::env::temp_dir().join(format!("edish_image_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("image.gif");
+        std::fs::write(&path, b"GIF89a\x10\x00\x20\x00").unwrap();
+
+        let mut edit = TextPanel::default();
+        edit.set_state(PanelState::WaitingToOpen);
+
+        TextEditPanel::input_handler(&mut edit, path.to_string_lossy().to_string());
+
+        assert_eq!(
+            edit.lines(),
+            &vec![
+                path.to_string_lossy().to_string(),
+                "[GIF image, 16x32, 10 bytes]".to_string()
+            ]
+        );
+        assert!(edit.file_path().is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn open_image_over_loading_file_keeps_placeholder() {
+        let dir = std::env::temp_dir().join(format!("edish_image_over_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let large = dir.join("large.txt");
+        std::fs::write(&large, "x\n".repeat(LAZY_LOAD_THRESHOLD as usize)).unwrap();
+        let image = dir.join("image.gif");
+        std::fs::write(&image, b"GIF89a\x10\x00\x20\x00").unwrap();
+
+        let mut edit = TextPanel::default();
+        edit.set_state(PanelState::WaitingToOpen);
+        TextEditPanel::input_handler(&mut edit, large.to_string_lossy().to_string());
+        assert!(edit.loading());
+
+        edit.set_state(PanelState::WaitingToOpen);
+        TextEditPanel::input_handler(&mut edit, image.to_string_lossy().to_string());
+        edit.update();
+
+        assert!(!edit.loading());
+        assert!(edit.file_path().is_none());
+        assert_eq!(edit.lines()[1], "[GIF image, 16x32, 10 bytes]");
+        assert!(edit.write_file().is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn open_binary_file_shows_hex_beside() {
+        let dir = std::env::temp_dir().join(format!("edish_binary_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("data.bin");
+        std::fs::write(&path, b"\x7fELF\x02\x01\x00\x00").unwrap();
+
+        let mut panels = Panels::new();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        state.init(&mut panels, &mut commands);
+
+        let edit = panels.get_mut(state.active_panel()).unwrap();
+        edit.set_text("kept");
+        edit.set_state(PanelState::WaitingToOpen);
+        let changes = TextEditPanel::input_handler(edit, path.to_string_lossy().to_string());
+
+        assert_eq!(edit.lines(), &vec!["kept".to_string()]);
+        assert_eq!(edit.file_path(), None);
+        assert_eq!(edit.state(), PanelState::Normal);
+
+        state.handle_changes(changes, &mut panels, &mut commands);
+
+        let index = (0..panels.len())
+            .find(|i| panels.get(*i).unwrap().panel_type() == HEX_PANEL_TYPE_ID)
+            .unwrap();
+        let view = panels.get(index).unwrap().hex().unwrap();
+        assert_eq!(view.rows(), 1);
+        assert!(view.row(0).starts_with("00000000  7f 45 4c 46 02 01 00 00"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn open_hides_bom_and_save_restores_it() {
+        let dir = std::env::temp_dir().join(format!("edish_bom_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("bom.txt");
+        std::fs::write(&path, b"\xEF\xBB\xBFone").unwrap();
+
+        let mut edit = TextPanel::default();
+        edit.set_state(PanelState::WaitingToOpen);
+
+        TextEditPanel::input_handler(&mut edit, path.to_string_lossy().to_string());
+
+        assert_eq!(edit.lines(), &vec!["one".to_string()]);
+        assert_eq!(edit.bom(), Some(Bom::Utf8));
+
+        edit.save();
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"\xEF\xBB\xBFone".to_vec());
+
+        edit.set_keep_bom(false);
+        edit.save();
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"one".to_vec());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn open_crlf_file_and_convert_to_lf() {
+        let dir = std::env::temp_dir().join(format!("edish_crlf_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("crlf.txt");
+        std::fs::write(&path, "one\r\ntwo\r\n").unwrap();
+
+        let mut edit = TextPanel::default();
+        edit.set_state(PanelState::WaitingToOpen);
+
+        TextEditPanel::input_handler(&mut edit, path.to_string_lossy().to_string());
+
+        assert_eq!(edit.lines(), &vec!["one".to_string(), "two".to_string(), "".to_string()]);
+        assert_eq!(edit.line_ending(), LineEnding::CrLf);
+
+        edit.save();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "one\r\ntwo\r\n");
+
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        edit.convert_line_endings(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.line_ending(), LineEnding::Lf);
+        assert!(edit.modified());
+
+        edit.save();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "one\ntwo\n");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // other program's write, dated ahead so it differs even on coarse clocks
+    fn write_elsewhere(path: &std::path::Path, text: &str) {
+        std::fs::write(path, text).unwrap();
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(10)).unwrap();
+    }
+
+    #[test]
+    fn save_asks_when_changed_on_disk_then_keep_overwrites() {
+        let dir = std::env::temp_dir().join(format!("edish_changed_keep_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("changed.txt");
+        std::fs::write(&path, "one").unwrap();
+
+        let mut edit = TextPanel::default();
+        edit.set_state(PanelState::WaitingToOpen);
+        TextEditPanel::input_handler(&mut edit, path.to_string_lossy().to_string());
+        edit.set_text("mine");
+        edit.set_modified(true);
+
+        write_elsewhere(&path, "theirs");
+
+        // autosave can't decide, file is left alone
+        assert!(edit.write_file().is_err());
+
+        let changes = edit.save();
+        assert!(changes.iter().any(|change| matches!(change, StateChangeRequest::ValidatedInput(..))));
+        assert_eq!(edit.state(), PanelState::WaitingToResolveChangeBeforeSave);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "theirs");
+
+        TextEditPanel::input_handler(&mut edit, "keep".to_string());
+
+        assert_eq!(edit.state(), PanelState::Normal);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "mine");
+        assert!(!edit.changed_on_disk());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reload_after_change_on_disk() {
+        let dir = std::env::temp_dir().join(format!("edish_changed_reload_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("changed.txt");
+        std::fs::write(&path, "one").unwrap();
+
+        let mut edit = TextPanel::default();
+        edit.set_state(PanelState::WaitingToOpen);
+        TextEditPanel::input_handler(&mut edit, path.to_string_lossy().to_string());
+
+        write_elsewhere(&path, "two");
+        assert!(edit.take_disk_change());
+        assert!(!edit.take_disk_change());
+
+        edit.ask_about_disk_change(false);
+        TextEditPanel::input_handler(&mut edit, "r".to_string());
+
+        assert_eq!(edit.lines(), &vec!["two".to_string()]);
+        assert_eq!(edit.state(), PanelState::Normal);
+        assert!(!edit.changed_on_disk());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn open_large_file_loads_during_updates() {
+        let dir = std::env::temp_dir().join(format!("edish_large_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("large.txt");
+        let line = "x".repeat(99);
+        let line_count = 20000;
+        let contents = iter::repeat_n(line.as_str(), line_count).collect::<Vec<&str>>().join("\n");
+        std::fs::write(&path, &contents).unwrap();
+
+        let mut edit = TextPanel::default();
+        edit.set_state(PanelState::WaitingToOpen);
+
+        TextEditPanel::input_handler(&mut edit, path.to_string_lossy().to_string());
+
+        assert!(edit.loading());
+        assert!(edit.lines().is_empty());
+        assert!(edit.write_file().is_err());
+
+        edit.update();
+
+        assert!(edit.loading());
+        assert_eq!(edit.lines().len(), 5000);
+        assert!(TextEditPanel::title(&edit, &AppState::new()).ends_with("(loading 25%)"));
+
+        while edit.loading() {
+            edit.update();
+        }
+
+        assert_eq!(edit.lines().len(), line_count);
+        assert_eq!(edit.text(), contents);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn buffers_keep_their_own_text_and_cursor() {
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        let mut edit = TextPanel::edit_panel();
+        edit.set_text("first");
+        edit.set_cursor_index(3);
+
+        edit.new_buffer(KeyCode::Null, &mut state, &mut commands);
+        edit.set_text("second");
+
+        assert_eq!(edit.buffer_count(), 2);
+        assert_eq!(edit.buffer_names(), vec!["1 Untitled".to_string(), "2 Untitled".to_string()]);
+
+        edit.next_buffer(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.text(), "first");
+        assert_eq!(edit.cursor_index_in_line(), 3);
+
+        edit.previous_buffer(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.text(), "second");
+    }
+
+    #[test]
+    fn buffers_cycle_in_order() {
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        let mut edit = TextPanel::edit_panel();
+        edit.set_text("one");
+        edit.new_buffer(KeyCode::Null, &mut state, &mut commands);
+        edit.set_text("two");
+        edit.new_buffer(KeyCode::Null, &mut state, &mut commands);
+        edit.set_text("three");
+
+        let mut seen = vec![];
+        for _ in 0..3 {
+            edit.next_buffer(KeyCode::Null, &mut state, &mut commands);
+            seen.push(edit.text());
+        }
+
+        assert_eq!(seen, vec!["one", "two", "three"]);
+    }
+
+    #[test]
+    fn close_buffer_keeps_modified() {
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        let mut edit = TextPanel::edit_panel();
+        edit.set_text("one");
+        edit.new_buffer(KeyCode::Null, &mut state, &mut commands);
+        edit.set_text("two");
+        edit.set_modified(true);
+
+        let (_, changes) = edit.close_buffer(KeyCode::Null, &mut state, &mut commands);
+
+        assert!(matches!(changes.last(), Some(StateChangeRequest::Message(..))));
+        assert_eq!(edit.buffer_count(), 2);
+
+        edit.set_modified(false);
+        edit.close_buffer(KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(edit.buffer_count(), 1);
+        assert_eq!(edit.text(), "one");
+    }
+
+    #[test]
+    fn switch_buffer_from_input() {
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        let mut edit = TextPanel::edit_panel();
+        edit.set_text("one");
+        edit.set_title("notes.txt".to_string());
+        edit.new_buffer(KeyCode::Null, &mut state, &mut commands);
+        edit.set_text("two");
+
+        edit.list_buffers(KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(edit.state(), PanelState::WaitingToSwitchBuffer);
+
+        let changes = edit.receive_input("notes".to_string());
+
+        assert!(changes.is_empty());
+        assert_eq!(edit.text(), "one");
+        assert_eq!(edit.state(), PanelState::Normal);
+
+        edit.set_state(PanelState::WaitingToSwitchBuffer);
+        let changes = edit.receive_input("9".to_string());
+
+        assert!(!changes.is_empty());
+    }
+
+    // small deterministic generator so failures can be replayed from seed
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+    }
+
+    type EditOperation = fn(&mut TextPanel, KeyCode, &mut AppState, &mut Manager) -> (bool, Vec<StateChangeRequest>);
+
+    const PROPERTY_SEEDS: u64 = 200;
+    const PROPERTY_STEPS: usize = 200;
+    const PROPERTY_CHARACTERS: [char; 8] = ['a', 'Z', ' ', '\t', '_', 'é', '日', '🦀'];
+
+    fn random_key(rng: &mut Rng) -> KeyCode {
+        match rng.below(10) {
+            0 => KeyCode::Backspace,
+            1 => KeyCode::Delete,
+            2 => KeyCode::Enter,
+            _ => KeyCode::Char(PROPERTY_CHARACTERS[rng.below(PROPERTY_CHARACTERS.len())]),
+        }
+    }
+
+    fn assert_cursor_in_bounds(edit: &TextPanel, seed: u64, step: usize) {
+        assert!(!edit.lines().is_empty() || edit.current_line() == 0, "seed {} step {}: no lines", seed, step);
+
+        if let Some(line) = edit.lines().get(edit.current_line()) {
+            assert!(
+                edit.cursor_index_in_line() <= line.len() && line.is_char_boundary(edit.cursor_index_in_line()),
+                "seed {} step {}: cursor {} outside of line {:?}",
+                seed,
+                step,
+                edit.cursor_index_in_line(),
+                line
+            );
+        } else {
+            assert!(
+                edit.current_line() == edit.lines().len() && edit.cursor_index_in_line() == 0,
+                "seed {} step {}: cursor on line {} of {}",
+                seed,
+                step,
+                edit.current_line(),
+                edit.lines().len()
+            );
+        }
+    }
+
+    #[test]
+    fn random_edits_keep_cursor_in_bounds() {
+        let operations: Vec<EditOperation> = vec![
+            TextPanel::handle_key_stroke,
+            TextPanel::move_to_next_character,
+            TextPanel::move_to_previous_character,
+            TextPanel::move_to_next_line,
+            TextPanel::move_to_previous_line,
+            TextPanel::move_to_next_word,
+            TextPanel::move_to_previous_word,
+            TextPanel::delete_next_word,
+            TextPanel::delete_previous_word,
+            TextPanel::transpose_characters,
+            TextPanel::transpose_lines,
+            TextPanel::duplicate_line,
+            TextPanel::delete_line,
+            TextPanel::move_line_up,
+            TextPanel::move_line_down,
+            TextPanel::indent,
+            TextPanel::dedent,
+        ];
+
+        for seed in 1..=PROPERTY_SEEDS {
+            let mut rng = Rng(seed);
+            let mut state = AppState::new();
+            let mut commands = Manager::default();
+            let mut edit = TextPanel::edit_panel();
+
+            for step in 0..PROPERTY_STEPS {
+                // typing is most of editing
+                let operation = match rng.below(2) {
+                    0 => TextPanel::handle_key_stroke,
+                    _ => operations[rng.below(operations.len())],
+                };
+                let key = random_key(&mut rng);
+
+                operation(&mut edit, key, &mut state, &mut commands);
+
+                assert_cursor_in_bounds(&edit, seed, step);
+            }
+        }
+    }
+
+    // plain text model of typing, without indentation so enter is a simple split
+    struct TypingModel {
+        lines: Vec<Vec<char>>,
+        line: usize,
+        column: usize,
+    }
+
+    impl TypingModel {
+        fn apply(&mut self, key: KeyCode) {
+            match key {
+                KeyCode::Char(c) => {
+                    self.lines[self.line].insert(self.column, c);
+                    self.column += 1;
+                }
+                KeyCode::Enter => {
+                    let rest = self.lines[self.line].split_off(self.column);
+                    self.lines.insert(self.line + 1, rest);
+                    self.line += 1;
+                    self.column = 0;
+                }
+                KeyCode::Backspace if self.column > 0 => {
+                    self.column -= 1;
+                    self.lines[self.line].remove(self.column);
+                }
+                KeyCode::Backspace if self.line > 0 => {
+                    let rest = self.lines.remove(self.line);
+                    self.line -= 1;
+                    self.column = self.lines[self.line].len();
+                    self.lines[self.line].extend(rest);
+                }
+                KeyCode::Delete if self.column < self.lines[self.line].len() => {
+                    self.lines[self.line].remove(self.column);
+                }
+                KeyCode::Delete if self.line + 1 < self.lines.len() => {
+                    let rest = self.lines.remove(self.line + 1);
+                    self.lines[self.line].extend(rest);
+                }
+                KeyCode::Left if self.column > 0 => self.column -= 1,
+                KeyCode::Left if self.line > 0 => {
+                    self.line -= 1;
+                    self.column = self.lines[self.line].len();
+                }
+                KeyCode::Right if self.column < self.lines[self.line].len() => self.column += 1,
+                KeyCode::Right if self.line + 1 < self.lines.len() => {
+                    self.line += 1;
+                    self.column = 0;
+                }
+                _ => (),
+            }
+        }
+
+        fn text(&self) -> String {
+            self.lines
+                .iter()
+                .map(|line| line.iter().collect::<String>())
+                .collect::<Vec<String>>()
+                .join("\n")
+        }
+    }
+
+    #[test]
+    fn random_typing_matches_model() {
+        for seed in 1..=PROPERTY_SEEDS {
+            let mut rng = Rng(seed);
+            let mut state = AppState::new();
+            let mut commands = Manager::default();
+            let mut edit = TextPanel::edit_panel();
+            edit.set_text("");
+            let mut model = TypingModel { lines: vec![vec![]], line: 0, column: 0 };
+
+            for step in 0..PROPERTY_STEPS {
+                let key = match rng.below(12) {
+                    0 => KeyCode::Left,
+                    1 => KeyCode::Right,
+                    _ => match random_key(&mut rng) {
+                        // leading whitespace is carried to new lines, which model leaves out
+                        KeyCode::Char(' ') | KeyCode::Char('\t') => KeyCode::Char('-'),
+                        key => key,
+                    },
+                };
+
+                match key {
+                    KeyCode::Left => TextPanel::move_to_previous_character(&mut edit, key, &mut state, &mut commands),
+                    KeyCode::Right => TextPanel::move_to_next_character(&mut edit, key, &mut state, &mut commands),
+                    _ => TextPanel::handle_key_stroke(&mut edit, key, &mut state, &mut commands),
+                };
+                model.apply(key);
+
+                assert_eq!(edit.text(), model.text(), "seed {} step {} after {:?}", seed, step, key);
+                assert_eq!(edit.current_line(), model.line, "seed {} step {} after {:?}", seed, step, key);
+                assert_eq!(
+                    edit.lines()[edit.current_line()][..edit.cursor_index_in_line()].chars().count(),
+                    model.column,
+                    "seed {} step {} after {:?}",
+                    seed,
+                    step,
+                    key
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn jump_to_matching_bracket_across_lines() {
+        let mut edit = TextPanel::default();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        edit.set_text("fn a() {\n    b(c[0]);\n}");
+        edit.set_current_line(0);
+        edit.set_cursor_index(7);
+
+        TextPanel::jump_to_matching_bracket(&mut edit, KeyCode::Null, &mut state, &mut commands);
+        assert_eq!((edit.current_line(), edit.cursor_index_in_line()), (2, 0));
+
+        TextPanel::jump_to_matching_bracket(&mut edit, KeyCode::Null, &mut state, &mut commands);
+        assert_eq!((edit.current_line(), edit.cursor_index_in_line()), (0, 7));
+
+        // cursor just past closing bracket
+        edit.set_current_line(1);
+        edit.set_cursor_index(11);
+        assert_eq!(edit.matching_bracket(), Some((1, 5)));
+
+        edit.set_cursor_index(0);
+        let (_, changes) = TextPanel::jump_to_matching_bracket(&mut edit, KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(changes.len(), 1);
+        assert_eq!((edit.current_line(), edit.cursor_index_in_line()), (1, 0));
+    }
+
+    #[test]
+    fn go_to_line_then_jump_back_and_forward() {
+        let mut edit = TextPanel::edit_panel();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        edit.set_text("one\ntwo\nthree\nfour");
+        edit.set_current_line(1);
+        edit.set_cursor_index(2);
+
+        edit.go_to_line(KeyCode::Null, &mut state, &mut commands);
+        edit.receive_input("4".to_string());
+        assert_eq!((edit.current_line(), edit.cursor_index_in_line()), (3, 0));
+        assert_eq!(edit.state(), PanelState::Normal);
+
+        edit.jump_back(KeyCode::Null, &mut state, &mut commands);
+        assert_eq!((edit.current_line(), edit.cursor_index_in_line()), (1, 2));
+
+        let (_, changes) = edit.jump_back(KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(changes.len(), 1);
+
+        edit.jump_forward(KeyCode::Null, &mut state, &mut commands);
+        assert_eq!((edit.current_line(), edit.cursor_index_in_line()), (3, 0));
+
+        let (_, changes) = edit.jump_forward(KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(changes.len(), 1);
+    }
+
+    #[test]
+    fn jump_back_from_bracket_into_shortened_text() {
+        let mut edit = TextPanel::default();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        edit.set_text("(\n\nx)");
+        edit.set_current_line(2);
+        edit.set_cursor_index(2);
+
+        TextPanel::jump_to_matching_bracket(&mut edit, KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(edit.current_line(), 0);
+
+        edit.set_text("(\nx");
+        edit.jump_back(KeyCode::Null, &mut state, &mut commands);
+        assert_eq!((edit.current_line(), edit.cursor_index_in_line()), (1, 1));
+    }
+
+    #[test]
+    fn bracket_screen_position_matches_cursor() {
+        let mut edit = TextPanel::default();
+        edit.set_text("x\n123456789012345678901234567(90)");
+        let rect = Rect::new(10, 10, 20, 20);
+
+        // cursor is drawn where the closing bracket would be highlighted
+        edit.set_current_line(1);
+        edit.set_cursor_index(30);
+        let (_, cursor, _) = edit.make_text_content(rect);
+
+        assert_eq!(edit.screen_position(1, 30, rect), Some(cursor));
+        assert_eq!(edit.screen_position(1, 27, rect), Some((10 + edit.continuation_marker().len() as u16 + 7, 12)));
+
+        edit.set_wrap(false);
+        edit.set_scroll_x(5);
+        assert_eq!(edit.screen_position(1, 20, rect), Some((25, 11)));
+        assert_eq!(edit.screen_position(1, 2, rect), None);
+
+        edit.set_scroll_y(1);
+        assert_eq!(edit.screen_position(0, 0, rect), None);
+    }
+
+    #[test]
+    fn auto_close_pairs() {
+        let mut edit = TextPanel::default();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        let mut type_text = |edit: &mut TextPanel, text: &str| {
+            for c in text.chars() {
+                TextPanel::handle_key_stroke(edit, KeyCode::Char(c), &mut state, &mut commands);
+            }
+        };
+
+        type_text(&mut edit, "f(");
+        assert_eq!(edit.text(), "f(");
+
+        edit.set_auto_close_pairs(true);
+        type_text(&mut edit, "a[\"b");
+        assert_eq!(edit.text(), "f(a[\"b\"]");
+        assert_eq!(edit.cursor_index_in_line(), 6);
+
+        // closing characters already there are stepped over, others are typed as usual
+        type_text(&mut edit, "\"])");
+        assert_eq!(edit.text(), "f(a[\"b\"])");
+        assert_eq!(edit.cursor_index_in_line(), 9);
+
+        // apostrophe in a word isn't paired
+        type_text(&mut edit, " it's {");
+        assert_eq!(edit.text(), "f(a[\"b\"]) it's {}");
+    }
 }
@@ -1,16 +1,135 @@
-use crate::panels::{EDIT_PANEL_TYPE_ID, MESSAGE_PANEL_TYPE_ID, NULL_PANEL_TYPE_ID, COMMANDS_PANEL_TYPE_ID};
+use std::cell::RefCell;
+
+use crate::panels::{EDIT_PANEL_TYPE_ID, MESSAGE_PANEL_TYPE_ID, NULL_PANEL_TYPE_ID, COMMANDS_PANEL_TYPE_ID, TERMINAL_PANEL_TYPE_ID,
+    OUTPUT_PANEL_TYPE_ID, DIFF_PANEL_TYPE_ID, HEX_PANEL_TYPE_ID, HELP_PANEL_TYPE_ID, WELCOME_PANEL_TYPE_ID, AnswersHandler, InputHandler, LengthHandler, PanelTypeID,
+    RenderHandler, ShutdownHandler, UpdateHandler};
 use crate::{TextPanel};
 
+thread_local! {
+    // types added with PanelFactory::register, looked up after built in ones
+    static CUSTOM_PANELS: RefCell<Vec<PanelBuilder>> = const { RefCell::new(vec![]) };
+}
+
+// panel type made from handler functions, handlers not given do nothing
+// e.g. PanelBuilder::new("Clock").render_handler(render_clock).build()
+#[derive(Clone, Copy)]
+pub struct PanelBuilder {
+    type_id: PanelTypeID,
+    length_handler: Option<LengthHandler>,
+    receive_input_handler: Option<InputHandler>,
+    receive_answers_handler: Option<AnswersHandler>,
+    update_handler: Option<UpdateHandler>,
+    render_handler: Option<RenderHandler>,
+    shutdown_handler: Option<ShutdownHandler>,
+}
+
+#[allow(dead_code)]
+impl PanelBuilder {
+    pub fn new(type_id: PanelTypeID) -> Self {
+        Self {
+            type_id,
+            length_handler: None,
+            receive_input_handler: None,
+            receive_answers_handler: None,
+            update_handler: None,
+            render_handler: None,
+            shutdown_handler: None,
+        }
+    }
+
+    pub fn type_id(&self) -> PanelTypeID {
+        self.type_id
+    }
+
+    pub fn length_handler(mut self, handler: LengthHandler) -> Self {
+        self.length_handler = Some(handler);
+        self
+    }
+
+    pub fn receive_input_handler(mut self, handler: InputHandler) -> Self {
+        self.receive_input_handler = Some(handler);
+        self
+    }
+
+    pub fn receive_answers_handler(mut self, handler: AnswersHandler) -> Self {
+        self.receive_answers_handler = Some(handler);
+        self
+    }
+
+    pub fn update_handler(mut self, handler: UpdateHandler) -> Self {
+        self.update_handler = Some(handler);
+        self
+    }
+
+    pub fn render_handler(mut self, handler: RenderHandler) -> Self {
+        self.render_handler = Some(handler);
+        self
+    }
+
+    pub fn shutdown_handler(mut self, handler: ShutdownHandler) -> Self {
+        self.shutdown_handler = Some(handler);
+        self
+    }
+
+    pub fn build(&self) -> TextPanel {
+        let mut panel = TextPanel::custom_panel(self.type_id);
+
+        if let Some(handler) = self.length_handler {
+            panel.length_handler = handler;
+        }
+
+        if let Some(handler) = self.receive_input_handler {
+            panel.receive_input_handler = handler;
+        }
+
+        if let Some(handler) = self.receive_answers_handler {
+            panel.receive_answers_handler = handler;
+        }
+
+        if let Some(handler) = self.update_handler {
+            panel.update_handler = handler;
+        }
+
+        if let Some(handler) = self.render_handler {
+            panel.render_handler = handler;
+        }
+
+        if let Some(handler) = self.shutdown_handler {
+            panel.shutdown_handler = handler;
+        }
+
+        panel
+    }
+}
+
 pub struct PanelFactory {}
 
 #[allow(dead_code)]
 impl PanelFactory {
     pub fn options() -> Vec<&'static str> {
-        vec![
+        let mut options = vec![
             NULL_PANEL_TYPE_ID,
             EDIT_PANEL_TYPE_ID,
             MESSAGE_PANEL_TYPE_ID,
-        ]
+            TERMINAL_PANEL_TYPE_ID,
+            HELP_PANEL_TYPE_ID,
+        ];
+
+        CUSTOM_PANELS.with(|custom| options.extend(custom.borrow().iter().map(|builder| builder.type_id())));
+
+        options
+    }
+
+    // makes type available everywhere a type id is asked for, e.g. layouts and changing panel type
+    // built in and already registered ids can't be replaced
+    pub fn register(builder: PanelBuilder) -> Result<(), String> {
+        if PanelFactory::panel(builder.type_id()).is_some() {
+            return Err(format!("Panel type {:?} already exists.", builder.type_id()));
+        }
+
+        CUSTOM_PANELS.with(|custom| custom.borrow_mut().push(builder));
+
+        Ok(())
     }
 
     pub fn panel(type_id: &str) -> Option<TextPanel> {
@@ -19,7 +138,19 @@ impl PanelFactory {
             EDIT_PANEL_TYPE_ID => Some(TextPanel::edit_panel()),
             MESSAGE_PANEL_TYPE_ID => Some(TextPanel::messages_panel()),
             COMMANDS_PANEL_TYPE_ID => Some(TextPanel::commands_panel()),
-            _ => None,
+            TERMINAL_PANEL_TYPE_ID => Some(TextPanel::terminal_panel()),
+            OUTPUT_PANEL_TYPE_ID => Some(TextPanel::output_panel()),
+            DIFF_PANEL_TYPE_ID => Some(TextPanel::diff_panel()),
+            HEX_PANEL_TYPE_ID => Some(TextPanel::hex_panel()),
+            HELP_PANEL_TYPE_ID => Some(TextPanel::help_panel()),
+            WELCOME_PANEL_TYPE_ID => Some(TextPanel::welcome_panel()),
+            _ => CUSTOM_PANELS.with(|custom| {
+                custom
+                    .borrow()
+                    .iter()
+                    .find(|builder| builder.type_id() == type_id)
+                    .map(|builder| builder.build())
+            }),
         }
     }
 
@@ -38,12 +169,87 @@ impl PanelFactory {
     pub fn edit() -> TextPanel {
         TextPanel::edit_panel()
     }
+
+    pub fn terminal() -> TextPanel {
+        TextPanel::terminal_panel()
+    }
+
+    pub fn output() -> TextPanel {
+        TextPanel::output_panel()
+    }
+
+    pub fn diff() -> TextPanel {
+        TextPanel::diff_panel()
+    }
+
+    pub fn hex() -> TextPanel {
+        TextPanel::hex_panel()
+    }
+
+    pub fn help() -> TextPanel {
+        TextPanel::help_panel()
+    }
+
+    pub fn welcome() -> TextPanel {
+        TextPanel::welcome_panel()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::panels::factory::PanelFactory;
-    use crate::panels::{EDIT_PANEL_TYPE_ID, MESSAGE_PANEL_TYPE_ID, NULL_PANEL_TYPE_ID};
+    use tui::layout::Rect;
+
+    use crate::app::StateChangeRequest;
+    use crate::commands::Manager;
+    use crate::config::parse_layout;
+    use crate::panels::factory::{PanelBuilder, PanelFactory};
+    use crate::panels::text::RenderDetails;
+    use crate::{AppState, EditorFrame, Panels, TextPanel, CURSOR_MAX};
+    use crate::panels::{EDIT_PANEL_TYPE_ID, MESSAGE_PANEL_TYPE_ID, NULL_PANEL_TYPE_ID, TERMINAL_PANEL_TYPE_ID};
+
+    fn render_title(_: &TextPanel, _: &AppState, _: &Manager, _: &mut EditorFrame, _: Rect) -> RenderDetails {
+        RenderDetails::new("Custom".to_string(), CURSOR_MAX)
+    }
+
+    fn echo_input(panel: &mut TextPanel, input: String) -> Vec<StateChangeRequest> {
+        panel.set_text(input);
+        vec![]
+    }
+
+    #[test]
+    fn register_custom_type() {
+        let builder = PanelBuilder::new("Echo")
+            .render_handler(render_title)
+            .receive_input_handler(echo_input);
+
+        assert!(PanelFactory::panel("Echo").is_none());
+        assert!(PanelFactory::register(builder).is_ok());
+        assert!(PanelFactory::options().contains(&"Echo"));
+
+        let mut panel = PanelFactory::panel("Echo").unwrap();
+        assert_eq!(panel.panel_type(), "Echo");
+
+        panel.receive_input("hello".to_string());
+        assert_eq!(panel.lines(), &vec!["hello".to_string()]);
+
+        // shown through panel type prompt and layouts like built in types
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init_with_layout(&parse_layout("v(Echo)").unwrap(), &mut panels, &mut commands).unwrap();
+        assert_eq!(
+            (0..panels.len()).filter(|i| panels.get(*i).unwrap().panel_type() == "Echo").count(),
+            1
+        );
+    }
+
+    #[test]
+    fn register_existing_type_fails() {
+        assert!(PanelFactory::register(PanelBuilder::new(EDIT_PANEL_TYPE_ID)).is_err());
+
+        PanelFactory::register(PanelBuilder::new("Twice")).unwrap();
+        assert!(PanelFactory::register(PanelBuilder::new("Twice")).is_err());
+    }
 
     #[test]
     fn create_invalid() {
@@ -79,4 +285,14 @@ mod tests {
             MESSAGE_PANEL_TYPE_ID
         );
     }
+
+    #[test]
+    fn create_terminal_boxed() {
+        assert_eq!(
+            PanelFactory::panel(TERMINAL_PANEL_TYPE_ID)
+                .unwrap()
+                .panel_type(),
+            TERMINAL_PANEL_TYPE_ID
+        );
+    }
 }
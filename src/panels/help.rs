@@ -0,0 +1,181 @@
+use crossterm::event::KeyCode;
+use tui::layout::Rect;
+use tui::text::{Span, Spans};
+use tui::widgets::Paragraph;
+
+use crate::app::StateChangeRequest;
+use crate::commands::{CommandDetails, CommandKeyId, Manager, GLOBAL_KEYS_SECTION};
+use crate::locale::{Catalog, Text};
+use crate::panels::commands::format_key;
+use crate::panels::text::RenderDetails;
+use crate::{AppState, EditorFrame, TextPanel, CURSOR_MAX};
+
+// key sequences are indented under their section header
+const INDENT: &str = "  ";
+
+// section header, then key sequence and command name for each binding, sections split by a blank line
+// generated from manager so rebound and imported keys show up
+pub fn help_lines(commands: &Manager, catalog: &Catalog) -> Vec<String> {
+    let global = catalog.text(&Text::new("help.global", GLOBAL_KEYS_SECTION));
+    let mut lines = section_lines(&global, commands.global_key_bindings(), catalog);
+
+    for (id, bindings) in commands.panel_key_bindings() {
+        lines.push(String::new());
+        lines.extend(section_lines(catalog.panel_name(id), bindings, catalog));
+    }
+
+    lines
+}
+
+pub(crate) fn format_keys(keys: &[CommandKeyId]) -> String {
+    keys.iter().map(format_key).collect::<Vec<String>>().join(" -> ")
+}
+
+fn section_lines(section: &str, bindings: Vec<(Vec<CommandKeyId>, &CommandDetails)>, catalog: &Catalog) -> Vec<String> {
+    let keys: Vec<String> = bindings
+        .iter()
+        .map(|(keys, _)| format_keys(keys))
+        .collect();
+
+    // names line up after longest key sequence
+    let width = keys.iter().map(|keys| keys.chars().count()).max().unwrap_or(0);
+
+    let mut lines = vec![section.to_string()];
+    lines.extend(
+        keys.iter()
+            .zip(bindings.iter())
+            .map(|(keys, (_, details))| {
+                format!("{}{:<width$}  {}", INDENT, keys, catalog.command_name(details.name()), width = width)
+            }),
+    );
+
+    lines
+}
+
+pub struct HelpPanel {}
+
+impl HelpPanel {
+    pub fn render_handler(panel: &TextPanel, state: &AppState, commands: &Manager, frame: &mut EditorFrame, rect: Rect) -> RenderDetails {
+        let rows: Vec<Spans> = help_lines(commands, state.catalog())
+            .into_iter()
+            .skip(panel.scroll_y() as usize)
+            .take(rect.height as usize)
+            .map(|line| match line.starts_with(INDENT) || line.is_empty() {
+                true => Spans::from(Span::raw(line)),
+                false => Spans::from(Span::styled(line, state.theme().panel_id)),
+            })
+            .collect();
+
+        frame.render_widget(Paragraph::new(rows).style(state.theme().text), rect);
+
+        RenderDetails::new(state.text(&Text::new("help.title", "Help")), CURSOR_MAX)
+    }
+
+    fn scroll_by(panel: &mut TextPanel, state: &AppState, commands: &Manager, rows: i64) -> (bool, Vec<StateChangeRequest>) {
+        let last = help_lines(commands, state.catalog()).len().saturating_sub(1) as i64;
+        let scroll_y = (panel.scroll_y() as i64 + rows).min(last).max(0);
+
+        panel.set_scroll_y(scroll_y.min(u16::MAX as i64) as u16);
+
+        (true, vec![])
+    }
+
+    pub fn scroll_up(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        state: &mut AppState,
+        commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        HelpPanel::scroll_by(panel, state, commands, -1)
+    }
+
+    pub fn scroll_down(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        state: &mut AppState,
+        commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        HelpPanel::scroll_by(panel, state, commands, 1)
+    }
+
+    pub fn page_up(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        state: &mut AppState,
+        commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let page = panel.page_height() as i64;
+        HelpPanel::scroll_by(panel, state, commands, -page)
+    }
+
+    pub fn page_down(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        state: &mut AppState,
+        commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let page = panel.page_height() as i64;
+        HelpPanel::scroll_by(panel, state, commands, page)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyCode;
+
+    use crate::commands::{CommandKeyId, Manager, GLOBAL_KEYS_SECTION};
+    use crate::locale::Catalog;
+    use crate::panels::help::{help_lines, HelpPanel};
+    use crate::panels::{PanelFactory, EDIT_PANEL_TYPE_ID, HELP_PANEL_TYPE_ID};
+    use crate::AppState;
+
+    #[test]
+    fn sections_for_global_and_each_panel_type() {
+        let commands = Manager::default();
+        let lines = help_lines(&commands, &Catalog::default());
+
+        assert_eq!(lines[0], GLOBAL_KEYS_SECTION);
+        assert!(lines.contains(&EDIT_PANEL_TYPE_ID.to_string()));
+        assert!(lines.contains(&HELP_PANEL_TYPE_ID.to_string()));
+        assert!(lines.iter().any(|line| line.starts_with("  C + q") && line.ends_with("  Quit")));
+    }
+
+    #[test]
+    fn sections_and_names_translated() {
+        let commands = Manager::default();
+        let catalog = Catalog::parse("help.global = Allgemein\npanel.Edit = Bearbeiten\ncommand.Quit = Beenden").unwrap();
+        let lines = help_lines(&commands, &catalog);
+
+        assert_eq!(lines[0], "Allgemein");
+        assert!(lines.contains(&"Bearbeiten".to_string()));
+        assert!(lines.iter().any(|line| line.starts_with("  C + q") && line.ends_with("  Beenden")));
+    }
+
+    #[test]
+    fn rebound_keys_listed() {
+        let mut commands = Manager::default();
+        commands
+            .bind(EDIT_PANEL_TYPE_ID, &vec![CommandKeyId::new_code(KeyCode::F(5))], "Go To Line")
+            .unwrap();
+
+        assert!(help_lines(&commands, &Catalog::default())
+            .iter()
+            .any(|line| line.starts_with("  F(5)") && line.ends_with("Go To Line")));
+    }
+
+    #[test]
+    fn scroll_stops_at_last_line() {
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        let mut panel = PanelFactory::help();
+        let last = help_lines(&commands, &Catalog::default()).len() - 1;
+
+        HelpPanel::scroll_up(&mut panel, KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(panel.scroll_y(), 0);
+
+        for _ in 0..last + 5 {
+            HelpPanel::scroll_down(&mut panel, KeyCode::Null, &mut state, &mut commands);
+        }
+        assert_eq!(panel.scroll_y() as usize, last);
+    }
+}
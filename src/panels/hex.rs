@@ -0,0 +1,232 @@
+use crossterm::event::KeyCode;
+use tui::layout::Rect;
+use tui::text::{Span, Spans};
+use tui::widgets::Paragraph;
+
+use crate::app::{StateChange, StateChangeRequest};
+use crate::commands::Manager;
+use crate::locale::Text;
+use crate::panels::text::RenderDetails;
+use crate::panels::PanelFactory;
+use crate::{AppState, EditorFrame, Panels, TextPanel, CURSOR_MAX};
+
+const BYTES_PER_ROW: usize = 16;
+
+// zero bytes or invalid UTF-8 mean file isn't text
+// bytes may be just the start of the file, so a character cut off at the end doesn't count
+pub fn looks_binary(bytes: &[u8]) -> bool {
+    if bytes.contains(&0) {
+        return true;
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(_) => false,
+        Err(err) => err.error_len().is_some(),
+    }
+}
+
+pub struct HexView {
+    name: String,
+    bytes: Vec<u8>,
+    // whole file, bytes is less when only the start was read
+    size: u64,
+}
+
+impl HexView {
+    pub fn new(name: String, bytes: Vec<u8>, size: u64) -> Self {
+        Self { name, bytes, size }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.bytes.len().div_ceil(BYTES_PER_ROW)
+    }
+
+    // offset, hex bytes split in two groups of eight, then printable ASCII with '.' for the rest
+    pub fn row(&self, row: usize) -> String {
+        let start = (row * BYTES_PER_ROW).min(self.bytes.len());
+        let end = (start + BYTES_PER_ROW).min(self.bytes.len());
+        let bytes = &self.bytes[start..end];
+
+        let mut hex = String::new();
+        for i in 0..BYTES_PER_ROW {
+            if i == BYTES_PER_ROW / 2 {
+                hex.push(' ');
+            }
+
+            match bytes.get(i) {
+                Some(byte) => hex.push_str(&format!("{:02x} ", byte)),
+                None => hex.push_str("   "),
+            }
+        }
+
+        let ascii: String = bytes
+            .iter()
+            .map(|byte| match byte.is_ascii_graphic() || *byte == b' ' {
+                true => *byte as char,
+                false => '.',
+            })
+            .collect();
+
+        format!("{:08x}  {} |{}|", start, hex, ascii)
+    }
+
+    pub fn title(&self) -> Text {
+        match self.bytes.len() as u64 == self.size {
+            true => Text::new("hex.title_file", "Hex {0}").arg(&self.name),
+            false => Text::new("hex.title_partial", "Hex {0} (first {1} of {2} bytes)")
+                .arg(&self.name)
+                .arg(self.bytes.len())
+                .arg(self.size),
+        }
+    }
+}
+
+// file that isn't text opens beside active panel instead of in it
+pub struct ShowHex {
+    view: HexView,
+}
+
+impl ShowHex {
+    pub fn new(view: HexView) -> Self {
+        Self { view }
+    }
+}
+
+impl StateChange for ShowHex {
+    fn apply(self: Box<Self>, state: &mut AppState, panels: &mut Panels, commands: &mut Manager) -> Vec<StateChangeRequest> {
+        let message = Text::new("hex.opened", "Opened {0} as binary.").arg(&self.view.name);
+
+        match state.split_active_panel(panels, commands).and_then(|index| panels.get_mut(index)) {
+            None => vec![StateChangeRequest::error(Text::new("hex.no_room", "No room to show hex view."))],
+            Some(panel) => {
+                *panel = PanelFactory::hex();
+                panel.set_hex(Some(self.view));
+                vec![StateChangeRequest::info(message)]
+            }
+        }
+    }
+}
+
+pub struct HexPanel {}
+
+impl HexPanel {
+    pub fn render_handler(panel: &TextPanel, state: &AppState, _: &Manager, frame: &mut EditorFrame, rect: Rect) -> RenderDetails {
+        let view = match panel.hex() {
+            None => return RenderDetails::new(state.text(&Text::new("hex.title", "Hex")), CURSOR_MAX),
+            Some(view) => view,
+        };
+
+        let rows: Vec<Spans> = (panel.scroll_y() as usize..view.rows())
+            .take(rect.height as usize)
+            .map(|row| Spans::from(Span::raw(view.row(row))))
+            .collect();
+
+        frame.render_widget(Paragraph::new(rows).style(state.theme().text), rect);
+
+        RenderDetails::new(state.text(&view.title()), CURSOR_MAX)
+    }
+
+    fn scroll_by(panel: &mut TextPanel, rows: i64) -> (bool, Vec<StateChangeRequest>) {
+        let last = panel.hex().map(|view| view.rows()).unwrap_or(0).saturating_sub(1) as i64;
+        let scroll_y = (panel.scroll_y() as i64 + rows).min(last).max(0);
+
+        panel.set_scroll_y(scroll_y.min(u16::MAX as i64) as u16);
+
+        (true, vec![])
+    }
+
+    pub fn scroll_up(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        HexPanel::scroll_by(panel, -1)
+    }
+
+    pub fn scroll_down(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        HexPanel::scroll_by(panel, 1)
+    }
+
+    pub fn page_up(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let page = panel.page_height() as i64;
+        HexPanel::scroll_by(panel, -page)
+    }
+
+    pub fn page_down(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let page = panel.page_height() as i64;
+        HexPanel::scroll_by(panel, page)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyCode;
+
+    use crate::commands::Manager;
+    use crate::panels::hex::{looks_binary, HexPanel, HexView};
+    use crate::{AppState, TextPanel};
+
+    #[test]
+    fn detects_binary() {
+        assert!(looks_binary(b"ab\x00cd"));
+        assert!(looks_binary(b"\xff\xfe\xfd text"));
+        assert!(!looks_binary("plain text é".as_bytes()));
+        // multi byte character cut off by head read
+        assert!(!looks_binary(&"text é".as_bytes()[..6]));
+    }
+
+    #[test]
+    fn rows_show_offset_hex_and_ascii() {
+        let view = HexView::new("data.bin".to_string(), b"Hello,\x00world!\n\x01\x02\x03abc".to_vec(), 20);
+
+        assert_eq!(view.rows(), 2);
+        assert_eq!(
+            view.row(0),
+            "00000000  48 65 6c 6c 6f 2c 00 77  6f 72 6c 64 21 0a 01 02  |Hello,.world!...|"
+        );
+        assert_eq!(
+            view.row(1),
+            "00000010  03 61 62 63                                       |.abc|"
+        );
+        assert_eq!(view.title().to_string(), "Hex data.bin");
+    }
+
+    #[test]
+    fn partial_file_in_title() {
+        let view = HexView::new("big.bin".to_string(), vec![0; 32], 1000);
+
+        assert_eq!(view.title().to_string(), "Hex big.bin (first 32 of 1000 bytes)");
+    }
+
+    #[test]
+    fn scroll_stays_inside_rows() {
+        let mut panel = TextPanel::hex_panel();
+        panel.set_hex(Some(HexView::new("data.bin".to_string(), vec![0; 40], 40)));
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        HexPanel::scroll_up(&mut panel, KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(panel.scroll_y(), 0);
+
+        for _ in 0..5 {
+            HexPanel::scroll_down(&mut panel, KeyCode::Null, &mut state, &mut commands);
+        }
+        assert_eq!(panel.scroll_y(), 2);
+    }
+}
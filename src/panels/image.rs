@@ -0,0 +1,140 @@
+// recognizes common image formats by their header
+// so they can be shown as a placeholder instead of binary text
+
+// bytes may be just the start of the file, size is total file size
+pub fn describe_image(bytes: &[u8], size: u64) -> Option<String> {
+    let (kind, dimensions) = if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        ("PNG", png_size(bytes))
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        ("GIF", gif_size(bytes))
+    } else if bytes.starts_with(b"\xff\xd8\xff") {
+        ("JPEG", jpeg_size(bytes))
+    } else if bytes.starts_with(b"BM") && bytes.len() >= 26 {
+        ("BMP", bmp_size(bytes))
+    } else if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        ("WebP", None)
+    } else {
+        return None;
+    };
+
+    Some(match dimensions {
+        Some((width, height)) => format!(
+            "[{} image, {}x{}, {} bytes]",
+            kind,
+            width,
+            height,
+            size
+        ),
+        None => format!("[{} image, {} bytes]", kind, size),
+    })
+}
+
+fn be_u16(bytes: &[u8], at: usize) -> Option<u32> {
+    bytes.get(at..at + 2).map(|b| u16::from_be_bytes([b[0], b[1]]) as u32)
+}
+
+fn le_u16(bytes: &[u8], at: usize) -> Option<u32> {
+    bytes.get(at..at + 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as u32)
+}
+
+fn png_size(bytes: &[u8]) -> Option<(u32, u32)> {
+    // IHDR is always first chunk
+    let width = bytes.get(16..20)?;
+    let height = bytes.get(20..24)?;
+
+    Some((
+        u32::from_be_bytes([width[0], width[1], width[2], width[3]]),
+        u32::from_be_bytes([height[0], height[1], height[2], height[3]]),
+    ))
+}
+
+fn gif_size(bytes: &[u8]) -> Option<(u32, u32)> {
+    Some((le_u16(bytes, 6)?, le_u16(bytes, 8)?))
+}
+
+fn bmp_size(bytes: &[u8]) -> Option<(u32, u32)> {
+    let width = bytes.get(18..22)?;
+    let height = bytes.get(22..26)?;
+
+    // height is negative for top down bitmaps
+    Some((
+        i32::from_le_bytes([width[0], width[1], width[2], width[3]]).unsigned_abs(),
+        i32::from_le_bytes([height[0], height[1], height[2], height[3]]).unsigned_abs(),
+    ))
+}
+
+fn jpeg_size(bytes: &[u8]) -> Option<(u32, u32)> {
+    // walk segments until a start of frame marker
+    let mut i = 2;
+    while i + 4 <= bytes.len() {
+        if bytes[i] != 0xff {
+            return None;
+        }
+
+        let marker = bytes[i + 1];
+        let length = be_u16(bytes, i + 2)? as usize;
+
+        match marker {
+            0xc0..=0xc3 | 0xc5..=0xc7 | 0xc9..=0xcb | 0xcd..=0xcf => {
+                return Some((be_u16(bytes, i + 7)?, be_u16(bytes, i + 5)?));
+            }
+            _ => i += 2 + length,
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::panels::image::describe_image;
+
+    #[test]
+    fn png() {
+        let mut bytes = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        bytes.extend(640u32.to_be_bytes());
+        bytes.extend(480u32.to_be_bytes());
+
+        assert_eq!(
+            describe_image(&bytes, bytes.len() as u64),
+            Some("[PNG image, 640x480, 24 bytes]".to_string())
+        );
+    }
+
+    #[test]
+    fn gif() {
+        let bytes = b"GIF89a\x10\x00\x20\x00";
+
+        assert_eq!(
+            describe_image(bytes, bytes.len() as u64),
+            Some("[GIF image, 16x32, 10 bytes]".to_string())
+        );
+    }
+
+    #[test]
+    fn jpeg() {
+        let mut bytes = vec![0xff, 0xd8];
+        // APP0 segment to skip
+        bytes.extend([0xff, 0xe0, 0x00, 0x04, 0x00, 0x00]);
+        // SOF0 precision, height, width
+        bytes.extend([0xff, 0xc0, 0x00, 0x0b, 0x08, 0x00, 0x30, 0x00, 0x40]);
+
+        assert_eq!(
+            describe_image(&bytes, bytes.len() as u64),
+            Some("[JPEG image, 64x48, 17 bytes]".to_string())
+        );
+    }
+
+    #[test]
+    fn truncated_header() {
+        assert_eq!(
+            describe_image(b"\x89PNG\r\n\x1a\n", 8),
+            Some("[PNG image, 8 bytes]".to_string())
+        );
+    }
+
+    #[test]
+    fn text_is_not_image() {
+        assert_eq!(describe_image(b"fn main() {}", 12), None);
+    }
+}
@@ -1,10 +1,10 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
-use tui::style::{Color, Style};
 use tui::text::{Span, Spans, Text};
 use tui::widgets::{Block, Paragraph};
 
 use crate::app::StateChangeRequest;
+use crate::autocomplete::Completion;
 use crate::commands::{alt_catch_all, code, Manager, shift_catch_all};
 use crate::{catch_all, AppState, CommandDetails, CommandKeyId, Commands, EditorFrame, TextPanel, CURSOR_MAX};
 use crate::panels::text::RenderDetails;
@@ -27,6 +27,59 @@ impl InputPanel {
         panel.set_selection(0);
     }
 
+    pub fn cancel(
+        _panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        (false, vec![StateChangeRequest::InputCanceled])
+    }
+
+    pub fn previous_history(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let prompt = match state.input_request() {
+            Some(request) => request.prompt().to_string(),
+            None => return (false, vec![]),
+        };
+
+        match state.input_history_mut().previous(&prompt, panel.text().as_str()) {
+            None => (),
+            Some(text) => InputPanel::replace_text(panel, text),
+        }
+
+        (false, vec![])
+    }
+
+    pub fn next_history(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let prompt = match state.input_request() {
+            Some(request) => request.prompt().to_string(),
+            None => return (false, vec![]),
+        };
+
+        match state.input_history_mut().next(&prompt) {
+            None => (),
+            Some(text) => InputPanel::replace_text(panel, text),
+        }
+
+        (false, vec![])
+    }
+
+    fn replace_text(panel: &mut TextPanel, text: String) {
+        panel.set_cursor_index(text.len());
+        panel.set_text(text);
+        panel.set_selection(0);
+    }
+
     pub fn next_quick_select(
         panel: &mut TextPanel,
         _code: KeyCode,
@@ -36,7 +89,7 @@ impl InputPanel {
         match state.input_request().and_then(|r| r.completer()) {
             None => (),
             Some(completer) => {
-                let option_count = completer.get_options(panel.text().as_str()).len();
+                let option_count = completer.ranked_options(panel.text().as_str()).len();
 
                 panel.set_selection(panel.selection() + 1);
                 if panel.selection() >= option_count {
@@ -57,7 +110,7 @@ impl InputPanel {
         match state.input_request().and_then(|r| r.completer()) {
             None => (),
             Some(completer) => {
-                let option_count = completer.get_options(panel.text().as_str()).len();
+                let option_count = completer.ranked_options(panel.text().as_str()).len();
 
                 panel.set_selection(if panel.selection() == 0 {
                     option_count - 1
@@ -80,7 +133,7 @@ impl InputPanel {
         match state.input_request().and_then(|r| r.completer()) {
             None => (),
             Some(completer) => {
-                let options = completer.get_options(panel.text().as_str());
+                let options = completer.ranked_options(panel.text().as_str());
                 let input = match code {
                     KeyCode::Char(c) => {
                         if ('1'..'9').contains(&c) {
@@ -93,12 +146,7 @@ impl InputPanel {
                 };
 
                 match options.get(input) {
-                    Some(selection) => {
-                        panel.append_text(selection.remaining());
-                        panel.set_cursor_index(
-                            panel.cursor_index_in_line() + selection.remaining().len(),
-                        );
-                    }
+                    Some(selection) => Self::fill_selection(panel, selection),
                     None => return (false, vec![]),
                 }
             }
@@ -117,14 +165,11 @@ impl InputPanel {
         match state.input_request().and_then(|r| r.completer()) {
             None => (),
             Some(completer) => {
-                let options = completer.get_options(panel.text().as_str());
+                let options = completer.ranked_options(panel.text().as_str());
                 match options.get(panel.selection()) {
                     // reset quick select to start
                     None => panel.set_selection(0),
-                    Some(selection) => {
-                        panel.append_text(selection.remaining());
-                        panel.set_cursor_index(panel.cursor_index_in_line() + selection.remaining().len());
-                    }
+                    Some(selection) => Self::fill_selection(panel, selection),
                 }
             }
         }
@@ -132,6 +177,20 @@ impl InputPanel {
         (false, vec![])
     }
 
+    // loose matches don't extend input, so option replaces it
+    fn fill_selection(panel: &mut TextPanel, selection: &Completion) {
+        let option = selection.option();
+        let matched = &option[..option.len().saturating_sub(selection.remaining().len())];
+
+        if panel.text().ends_with(matched) {
+            panel.append_text(selection.remaining());
+            panel.set_cursor_index(panel.cursor_index_in_line() + selection.remaining().len());
+        } else {
+            panel.set_text(selection.option().clone());
+            panel.set_cursor_index(selection.option().len());
+        }
+    }
+
     pub fn length_handler(
         panel: &TextPanel,
         fixed_length: u16,
@@ -172,7 +231,7 @@ impl InputPanel {
         {
             Some((prompt, Some(completer))) => (
                 completer
-                    .get_options(panel.text().as_str())
+                    .ranked_options(panel.text().as_str())
                     .iter()
                     .take(9)
                     .enumerate()
@@ -180,16 +239,10 @@ impl InputPanel {
                         vec![
                             Span::styled(
                                 format!("{} {}", i + 1, option.option()),
-                                Style::default()
-                                    .fg(match i % 2 {
-                                        0 => Color::Cyan,
-                                        1 => Color::Magenta,
-                                        _ => Color::White,
-                                    })
-                                    .bg(match panel.selection() == i {
-                                        true => Color::Gray,
-                                        false => Color::Black,
-                                    }),
+                                match panel.selection() == i {
+                                    true => state.theme().completion[i % 2].patch(state.theme().completion_selection),
+                                    false => state.theme().completion[i % 2],
+                                },
                             ),
                             Span::raw(" "),
                         ]
@@ -217,7 +270,7 @@ impl InputPanel {
                 .alignment(Alignment::Center);
 
             let complete_para = Paragraph::new(Spans::from(complete_text))
-                .style(Style::default().fg(Color::White).bg(Color::Black))
+                .style(state.theme().text)
                 .alignment(Alignment::Left);
 
             frame.render_widget(divider, layout[1]);
@@ -254,16 +307,21 @@ impl InputPanel {
 
         frame.render_widget(line_numbers_para, layout[0]);
 
-        let gutter = Block::default().style(Style::default().bg(Color::DarkGray));
+        let gutter = Block::default().style(state.theme().gutter);
 
         frame.render_widget(gutter, gutter_layout[1]);
 
         let para =
-            Paragraph::new(para_text).style(Style::default().fg(Color::White).bg(Color::Black));
+            Paragraph::new(para_text).style(state.theme().text);
 
         frame.render_widget(para, layout[2]);
 
-        return RenderDetails::new(prompt.unwrap_or(panel.title()).to_string(), cursor)
+        let title = match state.input_request().and_then(|r| r.step().map(|step| (r.prompt(), step))) {
+            Some((prompt, (step, total))) => format!("{} ({}/{})", state.text(prompt), step, total),
+            None => prompt.map_or(panel.title().clone(), |prompt| state.text(prompt)),
+        };
+
+        RenderDetails::new(title, cursor)
     }
 }
 
@@ -272,7 +330,7 @@ mod tests {
     use crossterm::event::KeyCode;
 
     use crate::app::StateChangeRequest;
-    use crate::autocomplete::{AutoCompleter, Completion};
+    use crate::autocomplete::{AutoCompleter, Completion, RecentFilesAutoCompleter};
     use crate::commands::Manager;
     use crate::{AppState, Panels, TextPanel};
     use crate::panels::input::InputPanel;
@@ -289,6 +347,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn recall_previous_input() {
+        let mut panels = Panels::new();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        state.init(&mut panels, &mut commands);
+        state.set_active_panel(1);
+        state.handle_changes(
+            vec![
+                StateChangeRequest::Input("Test".into(), None),
+                StateChangeRequest::input_complete("hello".to_string()),
+            ],
+            &mut panels, &mut commands
+        );
+
+        state.set_active_panel(1);
+        state.handle_changes(
+            vec![StateChangeRequest::Input("Test".into(), None)],
+            &mut panels, &mut commands
+        );
+
+        let mut input = TextPanel::input_panel();
+        input.set_text("typed");
+
+        InputPanel::previous_history(&mut input, KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(input.text(), "hello");
+        assert_eq!(input.cursor_index_in_line(), 5);
+
+        InputPanel::next_history(&mut input, KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(input.text(), "typed");
+    }
+
     #[test]
     fn next_quick_select() {
         let mut panels = Panels::new();
@@ -297,7 +387,7 @@ mod tests {
         state.init(&mut panels, &mut commands);
         state.handle_changes(
             vec![StateChangeRequest::Input(
-                "Test".to_string(),
+                "Test".into(),
                 Some(Box::new(TestCompleter {})),
             )],
             &mut panels, &mut commands
@@ -305,7 +395,7 @@ mod tests {
 
         let mut input = TextPanel::input_panel();
 
-        InputPanel::next_quick_select(&mut input, KeyCode::Null, &mut state);
+        InputPanel::next_quick_select(&mut input, KeyCode::Null, &mut state, &mut commands);
 
         assert_eq!(input.selection(), 1);
     }
@@ -318,7 +408,7 @@ mod tests {
         state.init(&mut panels, &mut commands);
         state.handle_changes(
             vec![StateChangeRequest::Input(
-                "Test".to_string(),
+                "Test".into(),
                 Some(Box::new(TestCompleter {})),
             )],
             &mut panels, &mut commands
@@ -327,7 +417,7 @@ mod tests {
         let mut input = TextPanel::input_panel();
         input.set_selection(4);
 
-        InputPanel::next_quick_select(&mut input, KeyCode::Null, &mut state);
+        InputPanel::next_quick_select(&mut input, KeyCode::Null, &mut state, &mut commands);
 
         assert_eq!(input.selection(), 0);
     }
@@ -340,7 +430,7 @@ mod tests {
         state.init(&mut panels, &mut commands);
         state.handle_changes(
             vec![StateChangeRequest::Input(
-                "Test".to_string(),
+                "Test".into(),
                 Some(Box::new(TestCompleter {})),
             )],
             &mut panels, &mut commands
@@ -349,7 +439,7 @@ mod tests {
         let mut input = TextPanel::input_panel();
         input.set_selection(3);
 
-        InputPanel::previous_quick_select(&mut input, KeyCode::Null, &mut state);
+        InputPanel::previous_quick_select(&mut input, KeyCode::Null, &mut state, &mut commands);
 
         assert_eq!(input.selection(), 2);
     }
@@ -362,7 +452,7 @@ mod tests {
         state.init(&mut panels, &mut commands);
         state.handle_changes(
             vec![StateChangeRequest::Input(
-                "Test".to_string(),
+                "Test".into(),
                 Some(Box::new(TestCompleter {})),
             )],
             &mut panels, &mut commands
@@ -371,7 +461,7 @@ mod tests {
         let mut input = TextPanel::input_panel();
         input.set_selection(0);
 
-        InputPanel::previous_quick_select(&mut input, KeyCode::Null, &mut state);
+        InputPanel::previous_quick_select(&mut input, KeyCode::Null, &mut state, &mut commands);
 
         assert_eq!(input.selection(), 4);
     }
@@ -384,7 +474,7 @@ mod tests {
         state.init(&mut panels, &mut commands);
         state.handle_changes(
             vec![StateChangeRequest::Input(
-                "Test".to_string(),
+                "Test".into(),
                 Some(Box::new(TestCompleter {})),
             )],
             &mut panels, &mut commands
@@ -393,11 +483,61 @@ mod tests {
         let mut input = TextPanel::input_panel();
         input.set_text("se".to_string());
 
-        InputPanel::fill_quick_select(&mut input, KeyCode::Char('1'), &mut state);
+        InputPanel::fill_quick_select(&mut input, KeyCode::Char('1'), &mut state, &mut commands);
 
         assert_eq!(input.text(), "sell".to_string());
     }
 
+    #[test]
+    fn fill_quick_select_loose_match_replaces_text() {
+        let mut panels = Panels::new();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        state.init(&mut panels, &mut commands);
+        state.handle_changes(
+            vec![StateChangeRequest::Input(
+                "Test".into(),
+                Some(Box::new(RecentFilesAutoCompleter::new(vec!["src/main.rs".to_string()]))),
+            )],
+            &mut panels, &mut commands
+        );
+
+        let mut input = TextPanel::input_panel();
+        input.set_text("mainrs".to_string());
+
+        InputPanel::fill_quick_select(&mut input, KeyCode::Char('1'), &mut state, &mut commands);
+
+        assert_eq!(input.text(), "src/main.rs".to_string());
+        assert_eq!(input.cursor_index_in_line(), 11);
+    }
+
+    #[test]
+    fn fill_quick_select_completes_last_part_of_text() {
+        struct FileNames {}
+
+        impl AutoCompleter for FileNames {
+            fn get_options(&self, _s: &str) -> Vec<Completion> {
+                vec![Completion::new("main.rs".to_string(), "in.rs".to_string())]
+            }
+        }
+
+        let mut panels = Panels::new();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        state.init(&mut panels, &mut commands);
+        state.handle_changes(
+            vec![StateChangeRequest::Input("Test".into(), Some(Box::new(FileNames {})))],
+            &mut panels, &mut commands
+        );
+
+        let mut input = TextPanel::input_panel();
+        input.set_text("src/ma".to_string());
+
+        InputPanel::fill_quick_select(&mut input, KeyCode::Char('1'), &mut state, &mut commands);
+
+        assert_eq!(input.text(), "src/main.rs".to_string());
+    }
+
     #[test]
     fn fill_quick_select_invalid_selection() {
         let mut panels = Panels::new();
@@ -406,7 +546,7 @@ mod tests {
         state.init(&mut panels, &mut commands);
         state.handle_changes(
             vec![StateChangeRequest::Input(
-                "Test".to_string(),
+                "Test".into(),
                 Some(Box::new(TestCompleter {})),
             )],
             &mut panels, &mut commands
@@ -415,7 +555,7 @@ mod tests {
         let mut input = TextPanel::input_panel();
         input.set_text("se".to_string());
 
-        InputPanel::fill_quick_select(&mut input, KeyCode::Char('0'), &mut state);
+        InputPanel::fill_quick_select(&mut input, KeyCode::Char('0'), &mut state, &mut commands);
 
         assert_eq!(input.text(), "se".to_string());
     }
@@ -428,7 +568,7 @@ mod tests {
         state.init(&mut panels, &mut commands);
         state.handle_changes(
             vec![StateChangeRequest::Input(
-                "Test".to_string(),
+                "Test".into(),
                 Some(Box::new(TestCompleter {})),
             )],
             &mut panels, &mut commands
@@ -437,7 +577,7 @@ mod tests {
         let mut input = TextPanel::input_panel();
         input.set_text("se".to_string());
 
-        InputPanel::fill_quick_select(&mut input, KeyCode::Enter, &mut state);
+        InputPanel::fill_quick_select(&mut input, KeyCode::Enter, &mut state, &mut commands);
 
         assert_eq!(input.text(), "se".to_string());
     }
@@ -450,7 +590,7 @@ mod tests {
         state.init(&mut panels, &mut commands);
         state.handle_changes(
             vec![StateChangeRequest::Input(
-                "Test".to_string(),
+                "Test".into(),
                 Some(Box::new(TestCompleter {})),
             )],
             &mut panels, &mut commands
@@ -459,7 +599,7 @@ mod tests {
         let mut input = TextPanel::input_panel();
         input.set_text("se".to_string());
 
-        InputPanel::fill_quick_select(&mut input, KeyCode::Char('9'), &mut state);
+        InputPanel::fill_quick_select(&mut input, KeyCode::Char('9'), &mut state, &mut commands);
 
         assert_eq!(input.text(), "se".to_string());
     }
@@ -472,7 +612,7 @@ mod tests {
         state.init(&mut panels, &mut commands);
         state.handle_changes(
             vec![StateChangeRequest::Input(
-                "Test".to_string(),
+                "Test".into(),
                 Some(Box::new(TestCompleter {})),
             )],
             &mut panels, &mut commands
@@ -482,7 +622,7 @@ mod tests {
         input.set_text("ca".to_string());
         input.set_selection(1);
 
-        InputPanel::fill_current_quick_select(&mut input, KeyCode::Null, &mut state);
+        InputPanel::fill_current_quick_select(&mut input, KeyCode::Null, &mut state, &mut commands);
 
         assert_eq!(input.text(), "capture".to_string());
     }
@@ -495,7 +635,7 @@ mod tests {
         state.init(&mut panels, &mut commands);
         state.handle_changes(
             vec![StateChangeRequest::Input(
-                "Test".to_string(),
+                "Test".into(),
                 Some(Box::new(TestCompleter {})),
             )],
             &mut panels, &mut commands
@@ -505,7 +645,7 @@ mod tests {
         input.set_text("ca".to_string());
         input.set_selection(9);
 
-        InputPanel::fill_current_quick_select(&mut input, KeyCode::Null, &mut state);
+        InputPanel::fill_current_quick_select(&mut input, KeyCode::Null, &mut state, &mut commands);
 
         assert_eq!(input.text(), "ca".to_string());
         assert_eq!(input.selection(), 0);
@@ -0,0 +1,82 @@
+// oldest are dropped past this
+const MAX_JUMPS: usize = 100;
+
+// line and column cursor was at before each jump, for going back and forth like a browser
+#[derive(Default)]
+pub struct JumpList {
+    back: Vec<(usize, usize)>,
+    forward: Vec<(usize, usize)>,
+}
+
+impl JumpList {
+    // new jump drops anything gone back past
+    pub fn push(&mut self, from: (usize, usize)) {
+        self.forward.clear();
+
+        if self.back.last() == Some(&from) {
+            return;
+        }
+
+        self.back.push(from);
+        if self.back.len() > MAX_JUMPS {
+            self.back.remove(0);
+        }
+    }
+
+    pub fn back(&mut self, current: (usize, usize)) -> Option<(usize, usize)> {
+        let to = self.back.pop()?;
+        self.forward.push(current);
+        Some(to)
+    }
+
+    pub fn forward(&mut self, current: (usize, usize)) -> Option<(usize, usize)> {
+        let to = self.forward.pop()?;
+        self.back.push(current);
+        Some(to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::panels::jumps::{JumpList, MAX_JUMPS};
+
+    #[test]
+    fn back_then_forward() {
+        let mut jumps = JumpList::default();
+        jumps.push((1, 0));
+        jumps.push((5, 2));
+
+        assert_eq!(jumps.back((9, 0)), Some((5, 2)));
+        assert_eq!(jumps.back((5, 2)), Some((1, 0)));
+        assert_eq!(jumps.back((1, 0)), None);
+        assert_eq!(jumps.forward((1, 0)), Some((5, 2)));
+        assert_eq!(jumps.forward((5, 2)), Some((9, 0)));
+        assert_eq!(jumps.forward((9, 0)), None);
+    }
+
+    #[test]
+    fn new_jump_drops_forward() {
+        let mut jumps = JumpList::default();
+        jumps.push((1, 0));
+        jumps.back((3, 0));
+        jumps.push((1, 0));
+
+        assert_eq!(jumps.forward((7, 0)), None);
+        assert_eq!(jumps.back((7, 0)), Some((1, 0)));
+    }
+
+    #[test]
+    fn oldest_dropped() {
+        let mut jumps = JumpList::default();
+        for line in 0..MAX_JUMPS + 1 {
+            jumps.push((line, 0));
+        }
+
+        let mut current = (MAX_JUMPS + 1, 0);
+        while let Some(to) = jumps.back(current) {
+            current = to;
+        }
+
+        assert_eq!(current, (1, 0));
+    }
+}
@@ -0,0 +1,71 @@
+// line ending used by most lines of a file
+// lines are kept without it in buffer, written back on save
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    // ties go to LF
+    pub fn detect(text: &str) -> Self {
+        let crlf = text.matches("\r\n").count();
+        let lf = text.matches('\n').count() - crlf;
+
+        match crlf > lf {
+            true => LineEnding::CrLf,
+            false => LineEnding::Lf,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "LF",
+            LineEnding::CrLf => "CRLF",
+        }
+    }
+
+    pub fn other(&self) -> Self {
+        match self {
+            LineEnding::Lf => LineEnding::CrLf,
+            LineEnding::CrLf => LineEnding::Lf,
+        }
+    }
+}
+
+// text with only LF line endings, and the ending it mostly had
+// mixed files end up with one kind on save
+pub fn normalize(text: &str) -> (String, LineEnding) {
+    (text.replace("\r\n", "\n"), LineEnding::detect(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::panels::line_ending::{normalize, LineEnding};
+
+    #[test]
+    fn detects_dominant_ending() {
+        assert_eq!(LineEnding::detect("one\r\ntwo\r\nthree\n"), LineEnding::CrLf);
+        assert_eq!(LineEnding::detect("one\r\ntwo\nthree\n"), LineEnding::Lf);
+        assert_eq!(LineEnding::detect("one\r\ntwo\n"), LineEnding::Lf);
+        assert_eq!(LineEnding::detect("no endings"), LineEnding::Lf);
+    }
+
+    #[test]
+    fn normalize_strips_carriage_returns() {
+        assert_eq!(
+            normalize("one\r\ntwo\r\n"),
+            ("one\ntwo\n".to_string(), LineEnding::CrLf)
+        );
+        // lone carriage return isn't a line ending
+        assert_eq!(normalize("a\rb\n"), ("a\rb\n".to_string(), LineEnding::Lf));
+    }
+}
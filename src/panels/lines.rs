@@ -0,0 +1,324 @@
+use std::fmt;
+use std::mem;
+use std::ops::{Bound, Index, IndexMut, Range, RangeBounds};
+
+// gap left by a growing buffer, lines inserted after that reuse it until it fills
+const MIN_GAP: usize = 64;
+
+// lines of a buffer with a gap kept where they were last inserted or removed
+// edits near the cursor only move the lines between there and the gap, not every line after it
+#[derive(Clone, Default)]
+pub struct Lines {
+    buffer: Vec<String>,
+    gap_start: usize,
+    gap_end: usize,
+}
+
+impl Lines {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffer.len() - (self.gap_end - self.gap_start)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<&String> {
+        match index < self.len() {
+            true => self.buffer.get(self.position(index)),
+            false => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut String> {
+        match index < self.len() {
+            true => {
+                let position = self.position(index);
+                self.buffer.get_mut(position)
+            }
+            false => None,
+        }
+    }
+
+    pub fn last(&self) -> Option<&String> {
+        self.len().checked_sub(1).and_then(|index| self.get(index))
+    }
+
+    pub fn push(&mut self, line: String) {
+        self.insert(self.len(), line);
+    }
+
+    pub fn pop(&mut self) -> Option<String> {
+        self.len().checked_sub(1).map(|index| self.remove(index))
+    }
+
+    // panics like Vec::insert when index is past the end
+    pub fn insert(&mut self, index: usize, line: String) {
+        assert!(index <= self.len(), "insert index {} past end of {} lines", index, self.len());
+
+        self.move_gap(index);
+
+        if self.gap_start == self.gap_end {
+            let grow = self.buffer.len().max(MIN_GAP);
+            self.buffer.splice(self.gap_end..self.gap_end, (0..grow).map(|_| String::new()));
+            self.gap_end += grow;
+        }
+
+        self.buffer[self.gap_start] = line;
+        self.gap_start += 1;
+    }
+
+    // panics like Vec::remove when index is past the end
+    pub fn remove(&mut self, index: usize) -> String {
+        assert!(index < self.len(), "remove index {} past end of {} lines", index, self.len());
+
+        self.move_gap(index);
+        let line = mem::take(&mut self.buffer[self.gap_end]);
+        self.gap_end += 1;
+
+        line
+    }
+
+    pub fn swap(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.position(a), self.position(b));
+        self.buffer.swap(a, b);
+    }
+
+    // removes range and puts lines in its place with a single move of the gap
+    pub fn splice<R: RangeBounds<usize>, I: IntoIterator<Item = String>>(&mut self, range: R, lines: I) {
+        let range = self.bounds(range);
+        assert!(range.start <= range.end && range.end <= self.len(), "splice range {:?} past end of {} lines", range, self.len());
+
+        self.move_gap(range.start);
+        for line in &mut self.buffer[self.gap_end..self.gap_end + range.len()] {
+            mem::take(line);
+        }
+        self.gap_end += range.len();
+
+        for (index, line) in (range.start..).zip(lines) {
+            self.insert(index, line);
+        }
+    }
+
+    pub fn extend<I: IntoIterator<Item = String>>(&mut self, lines: I) {
+        for line in lines {
+            self.push(line);
+        }
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &String> {
+        self.buffer[..self.gap_start].iter().chain(self.buffer[self.gap_end..].iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut String> {
+        let (before, after) = self.buffer.split_at_mut(self.gap_start);
+        before.iter_mut().chain(after[self.gap_end - self.gap_start..].iter_mut())
+    }
+
+    pub fn range<R: RangeBounds<usize>>(&self, range: R) -> impl DoubleEndedIterator<Item = &String> {
+        let range = self.bounds(range);
+        assert!(range.start <= range.end && range.end <= self.len(), "range {:?} past end of {} lines", range, self.len());
+
+        let gap = self.gap_end - self.gap_start;
+        let before = &self.buffer[range.start.min(self.gap_start)..range.end.min(self.gap_start)];
+        let after = &self.buffer[range.start.max(self.gap_start) + gap..range.end.max(self.gap_start) + gap];
+
+        before.iter().chain(after.iter())
+    }
+
+    pub fn join(&self, separator: &str) -> String {
+        self.iter().map(|line| line.as_str()).collect::<Vec<&str>>().join(separator)
+    }
+
+    pub fn to_vec(&self) -> Vec<String> {
+        self.iter().cloned().collect()
+    }
+
+    fn bounds<R: RangeBounds<usize>>(&self, range: R) -> Range<usize> {
+        let start = match range.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => end + 1,
+            Bound::Excluded(end) => *end,
+            Bound::Unbounded => self.len(),
+        };
+
+        start..end
+    }
+
+    fn position(&self, index: usize) -> usize {
+        match index < self.gap_start {
+            true => index,
+            false => index + (self.gap_end - self.gap_start),
+        }
+    }
+
+    // swaps lines across the gap one at a time, gap slots are always empty strings
+    fn move_gap(&mut self, index: usize) {
+        while self.gap_start > index {
+            self.gap_start -= 1;
+            self.gap_end -= 1;
+            self.buffer.swap(self.gap_start, self.gap_end);
+        }
+
+        while self.gap_start < index {
+            self.buffer.swap(self.gap_start, self.gap_end);
+            self.gap_start += 1;
+            self.gap_end += 1;
+        }
+    }
+}
+
+impl Index<usize> for Lines {
+    type Output = String;
+
+    fn index(&self, index: usize) -> &String {
+        match self.get(index) {
+            Some(line) => line,
+            None => panic!("line {} past end of {} lines", index, self.len()),
+        }
+    }
+}
+
+impl IndexMut<usize> for Lines {
+    fn index_mut(&mut self, index: usize) -> &mut String {
+        let len = self.len();
+        match self.get_mut(index) {
+            Some(line) => line,
+            None => panic!("line {} past end of {} lines", index, len),
+        }
+    }
+}
+
+impl From<Vec<String>> for Lines {
+    fn from(buffer: Vec<String>) -> Self {
+        let gap_start = buffer.len();
+        Self { buffer, gap_start, gap_end: gap_start }
+    }
+}
+
+impl FromIterator<String> for Lines {
+    fn from_iter<I: IntoIterator<Item = String>>(lines: I) -> Self {
+        Lines::from(lines.into_iter().collect::<Vec<String>>())
+    }
+}
+
+impl<'a> IntoIterator for &'a Lines {
+    type Item = &'a String;
+    type IntoIter = Box<dyn DoubleEndedIterator<Item = &'a String> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter())
+    }
+}
+
+impl PartialEq for Lines {
+    fn eq(&self, other: &Lines) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for Lines {}
+
+impl PartialEq<Vec<String>> for Lines {
+    fn eq(&self, other: &Vec<String>) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+// gap isn't part of contents
+impl fmt::Debug for Lines {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::panels::lines::Lines;
+
+    fn lines(text: &[&str]) -> Lines {
+        text.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn insert_and_remove_around_gap() {
+        let mut lines = lines(&["one", "two", "three"]);
+
+        lines.insert(1, "a".to_string());
+        lines.insert(2, "b".to_string());
+        assert_eq!(lines.to_vec(), vec!["one", "a", "b", "two", "three"]);
+
+        assert_eq!(lines.remove(0), "one");
+        lines.insert(4, "end".to_string());
+        assert_eq!(lines.to_vec(), vec!["a", "b", "two", "three", "end"]);
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[3], "three");
+        assert_eq!(lines.last(), Some(&"end".to_string()));
+    }
+
+    #[test]
+    fn grows_past_first_gap() {
+        let mut lines = Lines::new();
+        let mut expected = vec![];
+
+        for index in 0..500 {
+            lines.insert(index / 2, index.to_string());
+            expected.insert(index / 2, index.to_string());
+        }
+
+        assert_eq!(lines, expected);
+    }
+
+    #[test]
+    fn splice_replaces_range() {
+        let mut lines = lines(&["one", "two", "three", "four"]);
+
+        lines.splice(1..3, vec!["a".to_string(), "b".to_string(), "c".to_string()]);
+        assert_eq!(lines.to_vec(), vec!["one", "a", "b", "c", "four"]);
+
+        lines.splice(0..5, vec![]);
+        assert!(lines.is_empty());
+    }
+
+    #[test]
+    fn iterates_in_order_after_edits() {
+        let mut lines = lines(&["one", "two", "three"]);
+        lines.remove(1);
+        lines.swap(0, 1);
+
+        for line in lines.iter_mut() {
+            line.push('!');
+        }
+
+        assert_eq!(lines.iter().rev().cloned().collect::<Vec<String>>(), vec!["one!", "three!"]);
+        assert_eq!(lines.range(1..=1).collect::<Vec<&String>>(), vec!["one!"]);
+        assert_eq!(lines.join("\n"), "three!\none!");
+    }
+
+    #[test]
+    fn equal_regardless_of_gap() {
+        let mut edited = lines(&["one", "three"]);
+        edited.insert(1, "two".to_string());
+
+        assert_eq!(edited, lines(&["one", "two", "three"]));
+        assert_eq!(edited, vec!["one".to_string(), "two".to_string(), "three".to_string()]);
+        assert_eq!(format!("{:?}", edited), r#"["one", "two", "three"]"#);
+    }
+
+    #[test]
+    fn out_of_range() {
+        let mut lines = lines(&["one"]);
+
+        assert_eq!(lines.get(1), None);
+        assert_eq!(lines.pop(), Some("one".to_string()));
+        assert_eq!(lines.pop(), None);
+    }
+}
@@ -0,0 +1,145 @@
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader};
+
+use crate::panels::line_ending::LineEnding;
+
+// files larger than this are read a chunk at a time during updates
+pub const LAZY_LOAD_THRESHOLD: u64 = 1024 * 1024;
+pub const LINES_PER_CHUNK: usize = 5000;
+
+pub struct FileLoader {
+    reader: BufReader<File>,
+    loaded_bytes: u64,
+    total_bytes: u64,
+    ended_with_newline: bool,
+    crlf_lines: usize,
+    lf_lines: usize,
+}
+
+impl FileLoader {
+    pub fn new(file: File, total_bytes: u64) -> Self {
+        Self {
+            reader: BufReader::new(file),
+            loaded_bytes: 0,
+            total_bytes,
+            // empty file still has one empty line
+            ended_with_newline: true,
+            crlf_lines: 0,
+            lf_lines: 0,
+        }
+    }
+
+    pub fn progress(&self) -> (u64, u64) {
+        (self.loaded_bytes, self.total_bytes)
+    }
+
+    // most common ending in lines read so far
+    pub fn line_ending(&self) -> LineEnding {
+        match self.crlf_lines > self.lf_lines {
+            true => LineEnding::CrLf,
+            false => LineEnding::Lf,
+        }
+    }
+
+    // lines split the same as TextPanel::set_text, with ending removed like line_ending::normalize
+    // second value is true once end of file is reached
+    pub fn read_chunk(&mut self, max_lines: usize) -> io::Result<(Vec<String>, bool)> {
+        let mut lines = vec![];
+
+        while lines.len() < max_lines {
+            let mut line = String::new();
+            let read = self.reader.read_line(&mut line)?;
+
+            if read == 0 {
+                if self.ended_with_newline {
+                    lines.push(String::new());
+                }
+
+                return Ok((lines, true));
+            }
+
+            self.loaded_bytes += read as u64;
+            self.ended_with_newline = line.ends_with('\n');
+
+            if self.ended_with_newline {
+                line.pop();
+
+                match line.ends_with('\r') {
+                    true => {
+                        line.pop();
+                        self.crlf_lines += 1;
+                    }
+                    false => self.lf_lines += 1,
+                }
+            }
+
+            lines.push(line);
+        }
+
+        Ok((lines, false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use crate::panels::line_ending::LineEnding;
+    use crate::panels::loader::FileLoader;
+
+    fn loader_for(name: &str, contents: &str) -> (FileLoader, std::path::PathBuf) {
+        let path = std::env::temp_dir().join(format!("edish_loader_{}_{}", name, std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+
+        (FileLoader::new(File::open(&path).unwrap(), contents.len() as u64), path)
+    }
+
+    #[test]
+    fn reads_in_chunks() {
+        let (mut loader, path) = loader_for("chunks", "one\ntwo\nthree");
+
+        assert_eq!(
+            loader.read_chunk(2).unwrap(),
+            (vec!["one".to_string(), "two".to_string()], false)
+        );
+        assert_eq!(loader.progress(), (8, 13));
+        assert_eq!(loader.read_chunk(2).unwrap(), (vec!["three".to_string()], true));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn trailing_newline_adds_empty_line() {
+        let (mut loader, path) = loader_for("trailing", "one\n");
+
+        assert_eq!(
+            loader.read_chunk(10).unwrap(),
+            (vec!["one".to_string(), "".to_string()], true)
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn crlf_removed_from_lines() {
+        let (mut loader, path) = loader_for("crlf", "one\r\ntwo\r\nthree");
+
+        assert_eq!(
+            loader.read_chunk(10).unwrap(),
+            (vec!["one".to_string(), "two".to_string(), "three".to_string()], true)
+        );
+        assert_eq!(loader.line_ending(), LineEnding::CrLf);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn empty_file() {
+        let (mut loader, path) = loader_for("empty", "");
+
+        assert_eq!(loader.read_chunk(10).unwrap(), (vec!["".to_string()], true));
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
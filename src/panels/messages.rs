@@ -1,36 +1,273 @@
-use tui::layout::Rect;
-use tui::style::{Color, Style};
+use crossterm::event::KeyCode;
+use tui::layout::{Direction, Rect};
 use tui::text::{Span, Text};
 use tui::widgets::{List, ListItem};
 
-use crate::app::MessageChannel;
+use crate::app::{Message, MessageChannel, MessagesPlacement, StateChangeRequest};
 use crate::{AppState, CURSOR_MAX, EditorFrame, TextPanel};
 use crate::commands::Manager;
+use crate::locale;
 use crate::panels::text::RenderDetails;
 
 pub struct MessagesPanel {}
 
+// borders plus three messages
+const STRIP_LENGTH: u16 = 5;
+
 impl MessagesPanel {
-    pub fn render_handler(_: &TextPanel, state: &AppState, _: &Manager, frame: &mut EditorFrame, rect: Rect) -> RenderDetails {
-        let spans: Vec<ListItem> = state
+    pub fn length_handler(_: &TextPanel, _: u16, _: u16, direction: Direction, state: &AppState) -> u16 {
+        match (state.messages_placement(), direction) {
+            // strip only makes sense when stacked
+            (MessagesPlacement::Strip, Direction::Vertical) => STRIP_LENGTH,
+            _ => 0,
+        }
+    }
+
+    // newest first, scroll_y is how many of the newest are skipped
+    fn shown_messages<'a>(panel: &TextPanel, state: &'a AppState) -> Vec<&'a Message> {
+        state
             .get_messages()
             .iter()
             .rev()
+            .filter(|m| match panel.message_filter() {
+                Some(channel) => m.channel() == channel,
+                None => true,
+            })
+            .filter(|m| panel.matches_search(&state.text(m.text())))
+            .collect()
+    }
+
+    pub fn render_handler(panel: &TextPanel, state: &AppState, _: &Manager, frame: &mut EditorFrame, rect: Rect) -> RenderDetails {
+        let spans: Vec<ListItem> = MessagesPanel::shown_messages(panel, state)
+            .into_iter()
+            .skip(panel.scroll_y() as usize)
             .map(|m| {
-                let color = match m.channel() {
-                    MessageChannel::INFO => Color::White,
-                    MessageChannel::WARNING => Color::Yellow,
-                    MessageChannel::ERROR => Color::Red,
+                let style = match m.channel() {
+                    MessageChannel::INFO => state.theme().info,
+                    MessageChannel::WARNING => state.theme().warning,
+                    MessageChannel::ERROR => state.theme().error,
                 };
 
-                ListItem::new(Text::styled(m.text().as_str(), Style::default().fg(color)))
+                ListItem::new(Text::styled(
+                    format!("{}{}", state.text(&m.channel().indicator()), state.text(m.text())),
+                    style,
+                ))
             })
             .collect();
 
-        let list = List::new(spans).style(Style::default().fg(Color::White).bg(Color::Black));
+        let list = List::new(spans).style(state.theme().text);
 
         frame.render_widget(list, rect);
 
-        RenderDetails::new("Messages".to_string(), CURSOR_MAX)
+        let title = match panel.message_filter() {
+            None => locale::Text::new("messages.title", "Messages"),
+            Some(MessageChannel::ERROR) => locale::Text::new("messages.title_errors", "Messages (Errors)"),
+            Some(MessageChannel::WARNING) => locale::Text::new("messages.title_warnings", "Messages (Warnings)"),
+            Some(MessageChannel::INFO) => locale::Text::new("messages.title_info", "Messages (Info)"),
+        };
+
+        let title = match panel.search_label() {
+            None => state.text(&title),
+            Some(label) => format!("{} {}", state.text(&title), label),
+        };
+
+        RenderDetails::new(title, CURSOR_MAX)
+    }
+
+    pub fn scroll_back(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let count = MessagesPanel::shown_messages(panel, state).len() as u16;
+        if panel.scroll_y() + 1 < count {
+            panel.set_scroll_y(panel.scroll_y() + 1);
+        }
+
+        (true, vec![])
+    }
+
+    pub fn scroll_forward(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        panel.set_scroll_y(panel.scroll_y().saturating_sub(1));
+
+        (true, vec![])
+    }
+
+    pub fn scroll_to_newest(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        panel.set_scroll_y(0);
+
+        (true, vec![])
+    }
+
+    fn set_filter(panel: &mut TextPanel, filter: Option<MessageChannel>) -> (bool, Vec<StateChangeRequest>) {
+        panel.set_message_filter(filter);
+        // old offset is meaningless for a different list
+        panel.set_scroll_y(0);
+
+        (true, vec![])
+    }
+
+    pub fn show_errors(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        MessagesPanel::set_filter(panel, Some(MessageChannel::ERROR))
+    }
+
+    pub fn show_warnings(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        MessagesPanel::set_filter(panel, Some(MessageChannel::WARNING))
+    }
+
+    pub fn show_info(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        MessagesPanel::set_filter(panel, Some(MessageChannel::INFO))
+    }
+
+    pub fn show_all(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        MessagesPanel::set_filter(panel, None)
+    }
+
+    pub fn clear(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        state.clear_messages();
+        panel.set_scroll_y(0);
+
+        (true, vec![])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyCode;
+
+    use crate::app::MessageChannel;
+    use crate::commands::Manager;
+    use crate::panels::MessagesPanel;
+    use crate::{AppState, TextPanel};
+
+    fn setup() -> (TextPanel, AppState, Manager) {
+        let mut state = AppState::new();
+        state.add_info("One");
+        state.add_error("Two");
+        state.add_info("Three");
+
+        (TextPanel::messages_panel(), state, Manager::default())
+    }
+
+    #[test]
+    fn scroll_back_stops_at_oldest() {
+        let (mut panel, mut state, mut commands) = setup();
+
+        for _ in 0..5 {
+            MessagesPanel::scroll_back(&mut panel, KeyCode::Null, &mut state, &mut commands);
+        }
+
+        assert_eq!(panel.scroll_y(), 2);
+
+        MessagesPanel::scroll_forward(&mut panel, KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(panel.scroll_y(), 1);
+
+        MessagesPanel::scroll_to_newest(&mut panel, KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(panel.scroll_y(), 0);
+    }
+
+    #[test]
+    fn filter_errors() {
+        let (mut panel, mut state, mut commands) = setup();
+        panel.set_scroll_y(1);
+
+        MessagesPanel::show_errors(&mut panel, KeyCode::Null, &mut state, &mut commands);
+
+        assert_eq!(panel.message_filter(), Some(MessageChannel::ERROR));
+        assert_eq!(panel.scroll_y(), 0);
+
+        let shown = MessagesPanel::shown_messages(&panel, &state);
+        assert_eq!(shown.len(), 1);
+        assert_eq!(shown[0].text(), "Two");
+
+        // only one error so can't scroll
+        MessagesPanel::scroll_back(&mut panel, KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(panel.scroll_y(), 0);
+    }
+
+    #[test]
+    fn filter_info_then_all() {
+        let (mut panel, mut state, mut commands) = setup();
+
+        MessagesPanel::show_info(&mut panel, KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(MessagesPanel::shown_messages(&panel, &state).len(), 2);
+
+        MessagesPanel::show_all(&mut panel, KeyCode::Null, &mut state, &mut commands);
+        assert_eq!(panel.message_filter(), None);
+        assert_eq!(MessagesPanel::shown_messages(&panel, &state).len(), 3);
+    }
+
+    #[test]
+    fn search_narrows_while_typing() {
+        let (mut panel, mut state, mut commands) = setup();
+        panel.set_scroll_y(1);
+
+        panel.start_search(KeyCode::Null, &mut state, &mut commands);
+        panel.search_key(KeyCode::Char('T'));
+
+        // case is ignored
+        assert_eq!(panel.scroll_y(), 0);
+        assert_eq!(MessagesPanel::shown_messages(&panel, &state).len(), 2);
+
+        panel.search_key(KeyCode::Char('w'));
+
+        let shown = MessagesPanel::shown_messages(&panel, &state);
+        assert_eq!(shown.len(), 1);
+        assert_eq!(shown[0].text(), "Two");
+
+        // stops typing but keeps filtering
+        assert!(panel.search_key(KeyCode::Enter));
+        assert!(!panel.search_key(KeyCode::Char('x')));
+        assert_eq!(panel.search_label(), Some("/Tw".to_string()));
+
+        assert!(panel.cancel_search());
+        assert_eq!(MessagesPanel::shown_messages(&panel, &state).len(), 3);
+    }
+
+    #[test]
+    fn clear_log() {
+        let (mut panel, mut state, mut commands) = setup();
+        panel.set_scroll_y(2);
+
+        MessagesPanel::clear(&mut panel, KeyCode::Null, &mut state, &mut commands);
+
+        assert!(state.get_messages().is_empty());
+        assert_eq!(panel.scroll_y(), 0);
     }
 }
\ No newline at end of file
@@ -2,19 +2,47 @@ use crossterm::event::KeyEvent;
 use tui::layout::{Direction, Rect};
 use tui::text::Span;
 
+pub use diff::{DiffPanel, DiffView};
 pub use factory::*;
+pub use help::HelpPanel;
+pub use hex::HexPanel;
 pub use input::InputPanel;
+pub use lines::Lines;
 pub use messages::MessagesPanel;
-pub use text::{TextPanel};
+pub use output::{CommandOutput, OutputPanel};
+pub use terminal::TerminalPanel;
+pub use welcome::WelcomePanel;
+pub use text::{
+    AnswersHandler, EditMode, InputHandler, LengthHandler, PanelState, RenderHandler, ShutdownHandler,
+    TextPanel, UpdateHandler,
+};
+pub(crate) use bom::decode;
+pub(crate) use buffer::buffer_name;
+pub(crate) use line_ending::{normalize, LineEnding};
 
 use crate::app::StateChangeRequest;
 use crate::{AppState, EditorFrame};
 
+mod bom;
+mod brackets;
+mod buffer;
+mod diff;
 mod edit;
 mod factory;
+mod help;
+mod hex;
+mod image;
 mod input;
+mod jumps;
+mod line_ending;
+mod lines;
+mod loader;
 mod messages;
+mod output;
+mod terminal;
 mod text;
+mod todo;
+mod welcome;
 pub mod commands;
 
 pub type PanelTypeID = &'static str;
@@ -24,14 +52,21 @@ pub const INPUT_PANEL_TYPE_ID: &str = "Input";
 pub const COMMANDS_PANEL_TYPE_ID: &str = "Commands";
 pub const MESSAGE_PANEL_TYPE_ID: &str = "Messages";
 pub const NULL_PANEL_TYPE_ID: &str = "Null";
+pub const TERMINAL_PANEL_TYPE_ID: &str = "Terminal";
+pub const OUTPUT_PANEL_TYPE_ID: &str = "Output";
+pub const DIFF_PANEL_TYPE_ID: &str = "Diff";
+pub const HEX_PANEL_TYPE_ID: &str = "Hex";
+pub const HELP_PANEL_TYPE_ID: &str = "Help";
+pub const WELCOME_PANEL_TYPE_ID: &str = "Welcome";
 
 pub struct Panels {
     panels: Vec<TextPanel>,
+    next_view_id: usize,
 }
 
 impl Panels {
     pub fn new() -> Self {
-        Self { panels: vec![] }
+        Self { panels: vec![], next_view_id: 0 }
     }
 
     #[allow(dead_code)]
@@ -66,10 +101,97 @@ impl Panels {
     pub fn get_mut(&mut self, index: usize) -> Option<&mut TextPanel> {
         self.panels.get_mut(index)
     }
+
+    // panels editing the same file or view share its content
+    // scroll stays per panel, cursor only follows if source has sync on
+    pub fn propagate_edits(&mut self, index: usize) {
+        let (source_path, source_view) = match self.panels.get(index) {
+            Some(panel) if panel.panel_type() == EDIT_PANEL_TYPE_ID => {
+                (panel.file_path().cloned(), panel.view_id())
+            }
+            _ => return,
+        };
+
+        if source_path.is_none() && source_view.is_none() {
+            return;
+        }
+
+        let (before, rest) = self.panels.split_at_mut(index);
+        let (source_panel, after) = match rest.split_first_mut() {
+            None => return,
+            Some(split) => split,
+        };
+
+        for panel in before.iter_mut().chain(after.iter_mut()) {
+            if panel.panel_type() != EDIT_PANEL_TYPE_ID {
+                continue;
+            }
+
+            let same_file = source_path.is_some() && panel.file_path() == source_path.as_ref();
+            let same_view = source_view.is_some() && panel.view_id() == source_view;
+
+            if same_file || same_view {
+                panel.take_edits_from(source_panel);
+            }
+        }
+    }
+
+    // id a buffer can be found by after panel indexes are reused, made on first use
+    // None if there's no edit panel at index
+    pub fn view_id_of(&mut self, index: usize) -> Option<usize> {
+        let panel = match self.panels.get_mut(index) {
+            Some(panel) if panel.panel_type() == EDIT_PANEL_TYPE_ID => panel,
+            _ => return None,
+        };
+
+        match panel.view_id() {
+            Some(id) => Some(id),
+            None => {
+                let id = self.next_view_id;
+                self.next_view_id += 1;
+                panel.set_view_id(Some(id));
+                Some(id)
+            }
+        }
+    }
+
+    // first edit panel showing view
+    pub fn find_view(&self, view_id: usize) -> Option<usize> {
+        self.panels
+            .iter()
+            .position(|panel| panel.panel_type() == EDIT_PANEL_TYPE_ID && panel.view_id() == Some(view_id))
+    }
+
+    // make target show the same buffer as source, with its own cursor and scroll
+    pub fn share_view(&mut self, source: usize, target: usize) -> Result<(), String> {
+        if source == target {
+            return Err("Cannot share a panel's view with itself.".to_string());
+        }
+
+        let view_id = match (self.panels.get(source).is_some(), self.view_id_of(source)) {
+            (_, Some(id)) => id,
+            (true, None) => return Err("Only edit panels can be viewed in a split.".to_string()),
+            (false, None) => return Err(format!("No panel at index {}.", source)),
+        };
+
+        let mut view = PanelFactory::edit();
+        view.set_view_id(Some(view_id));
+
+        match self.panels.get_mut(target) {
+            Some(panel) => *panel = view,
+            None => return Err(format!("No panel at index {}.", target)),
+        }
+
+        self.propagate_edits(source);
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::path::PathBuf;
+
     use crate::panels::{PanelFactory, Panels, NULL_PANEL_TYPE_ID};
 
     #[test]
@@ -100,4 +222,160 @@ mod tests {
 
         assert_eq!(index, 0);
     }
+
+    #[test]
+    fn propagate_edits_to_same_file() {
+        let mut panels = Panels::new();
+        let mut first = PanelFactory::edit();
+        first.set_file_path(PathBuf::from("file.garnish"));
+        first.set_text("one\ntwo");
+        let mut second = PanelFactory::edit();
+        second.set_file_path(PathBuf::from("file.garnish"));
+        second.set_text("one\ntwo");
+        second.set_current_line(1);
+        second.set_cursor_index(3);
+        let mut third = PanelFactory::edit();
+        third.set_file_path(PathBuf::from("other.garnish"));
+        third.set_text("other");
+
+        panels.push(first);
+        panels.push(second);
+        panels.push(third);
+
+        panels.get_mut(0).unwrap().set_text("o");
+        panels.propagate_edits(0);
+
+        assert_eq!(panels.get(1).unwrap().lines(), &vec!["o".to_string()]);
+        assert_eq!(panels.get(1).unwrap().current_line(), 0);
+        assert_eq!(panels.get(1).unwrap().cursor_index_in_line(), 1);
+        assert_eq!(panels.get(2).unwrap().lines(), &vec!["other".to_string()]);
+    }
+
+    #[test]
+    fn propagate_edits_skips_unchanged_source() {
+        let mut panels = Panels::new();
+        let mut first = PanelFactory::edit();
+        first.set_file_path(PathBuf::from("file.garnish"));
+        first.set_text("one\ntwo");
+        let mut second = PanelFactory::edit();
+        second.set_file_path(PathBuf::from("file.garnish"));
+
+        panels.push(first);
+        panels.push(second);
+        panels.propagate_edits(0);
+
+        let copied = panels.get(1).unwrap().lines()[0].as_ptr();
+
+        // cursor movement alone isn't an edit, lines aren't copied again
+        panels.get_mut(0).unwrap().set_current_line(1);
+        panels.propagate_edits(0);
+
+        assert_eq!(panels.get(1).unwrap().lines()[0].as_ptr(), copied);
+
+        panels.get_mut(0).unwrap().set_text("three");
+        panels.propagate_edits(0);
+
+        assert_eq!(panels.get(1).unwrap().lines(), &vec!["three".to_string()]);
+    }
+
+    #[test]
+    fn propagate_edits_with_synced_cursor() {
+        let mut panels = Panels::new();
+        let mut first = PanelFactory::edit();
+        first.set_file_path(PathBuf::from("file.garnish"));
+        first.set_text("one\ntwo");
+        first.set_current_line(1);
+        first.set_cursor_index(2);
+        first.set_sync_cursors(true);
+        first.set_scroll_y(1);
+        let mut second = PanelFactory::edit();
+        second.set_file_path(PathBuf::from("file.garnish"));
+
+        panels.push(first);
+        panels.push(second);
+
+        panels.propagate_edits(0);
+
+        let second = panels.get(1).unwrap();
+        assert_eq!(second.current_line(), 1);
+        assert_eq!(second.cursor_index_in_line(), 2);
+        assert_eq!(second.scroll_y(), 0);
+    }
+
+    #[test]
+    fn save_through_one_view_isnt_change_on_disk_for_other() {
+        let path = std::env::temp_dir().join(format!("edish_two_views_{}.txt", std::process::id()));
+        std::fs::write(&path, "one").unwrap();
+
+        let mut panels = Panels::new();
+        for _ in 0..2 {
+            let mut panel = PanelFactory::edit();
+            panel.set_file_path(path.clone());
+            panel.record_disk_time();
+            panels.push(panel);
+        }
+
+        let first = panels.get_mut(0).unwrap();
+        first.set_text("two");
+        first.set_modified(true);
+        // dated ahead so it differs even on coarse clocks
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(10))
+            .unwrap();
+        first.record_disk_time();
+        panels.propagate_edits(0);
+
+        assert!(!panels.get(1).unwrap().changed_on_disk());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn share_view_of_unsaved_buffer() {
+        let mut panels = Panels::new();
+        let mut first = PanelFactory::edit();
+        first.set_text("one\ntwo");
+        panels.push(first);
+        panels.push(PanelFactory::edit());
+
+        panels.share_view(0, 1).unwrap();
+
+        assert_eq!(panels.get(1).unwrap().lines(), panels.get(0).unwrap().lines());
+        assert!(panels.get(0).unwrap().view_id().is_some());
+
+        panels.get_mut(1).unwrap().set_text("three");
+        panels.propagate_edits(1);
+
+        assert_eq!(panels.get(0).unwrap().lines(), &vec!["three".to_string()]);
+    }
+
+    #[test]
+    fn share_view_with_non_edit_panel_is_error() {
+        let mut panels = Panels::new();
+        panels.push(PanelFactory::messages());
+        panels.push(PanelFactory::edit());
+
+        assert!(panels.share_view(0, 1).is_err());
+    }
+
+    #[test]
+    fn propagate_edits_keeps_cursor_on_char_boundary() {
+        let mut panels = Panels::new();
+        let mut first = PanelFactory::edit();
+        first.set_file_path(PathBuf::from("file.garnish"));
+        let mut second = PanelFactory::edit();
+        second.set_file_path(PathBuf::from("file.garnish"));
+        second.set_text("abcd");
+        second.set_cursor_index(2);
+        panels.push(first);
+        panels.push(second);
+
+        panels.get_mut(0).unwrap().set_text("aé");
+        panels.propagate_edits(0);
+
+        assert_eq!(panels.get(1).unwrap().cursor_index_in_line(), 1);
+    }
 }
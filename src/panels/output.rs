@@ -0,0 +1,277 @@
+use crossterm::event::KeyCode;
+use tui::layout::Rect;
+use tui::text::Text;
+use tui::widgets::{List, ListItem};
+
+use crate::app::StateChangeRequest;
+use crate::commands::Manager;
+use crate::locale;
+use crate::panels::text::RenderDetails;
+use crate::process::{append_output, Process};
+use crate::{AppState, EditorFrame, TextPanel, CURSOR_MAX};
+
+// single external command and what it printed, stdout and stderr mixed in order read
+pub struct CommandOutput {
+    command: String,
+    process: Option<Process>,
+    lines: Vec<String>,
+    // exit status once finished
+    status: Option<String>,
+    success: bool,
+}
+
+impl CommandOutput {
+    pub fn start(command: String) -> Result<Self, String> {
+        let process = match cfg!(windows) {
+            true => Process::spawn("cmd", &["/C", &command]),
+            false => Process::spawn("sh", &["-c", &command]),
+        };
+
+        match process {
+            Err(err) => Err(format!("Could not run {:?}. {}", command, err)),
+            Ok(mut process) => {
+                // nothing is sent, command sees end of input right away
+                process.close_input();
+
+                Ok(Self {
+                    command,
+                    process: Some(process),
+                    lines: vec![String::new()],
+                    status: None,
+                    success: false,
+                })
+            }
+        }
+    }
+
+    pub fn command(&self) -> &String {
+        &self.command
+    }
+
+    pub fn lines(&self) -> &Vec<String> {
+        &self.lines
+    }
+
+    pub fn running(&self) -> bool {
+        self.process.is_some()
+    }
+
+    pub fn status(&self) -> Option<&String> {
+        self.status.as_ref()
+    }
+
+    pub fn poll(&mut self) -> Vec<StateChangeRequest> {
+        let (output, exited) = match self.process.as_mut() {
+            None => return vec![],
+            Some(process) => {
+                let output = process.take_output();
+                let exited = match process.try_wait() {
+                    Err(err) => Some((err.to_string(), false)),
+                    Ok(Some(status)) if process.output_finished() => Some((status.to_string(), status.success())),
+                    Ok(_) => None,
+                };
+
+                (output, exited)
+            }
+        };
+
+        append_output(&mut self.lines, &output);
+
+        match exited {
+            None => vec![],
+            Some((status, success)) => {
+                self.process = None;
+                self.success = success;
+
+                let message = locale::Text::new("output.finished", "{0} finished with {1}.")
+                    .arg(format!("{:?}", self.command))
+                    .arg(&status);
+                self.status = Some(status);
+
+                match success {
+                    true => vec![StateChangeRequest::info(message)],
+                    false => vec![StateChangeRequest::error(message)],
+                }
+            }
+        }
+    }
+
+    fn stop(&mut self) -> Result<(), String> {
+        match self.process.as_mut() {
+            None => Ok(()),
+            Some(process) => process.kill().map_err(|err| err.to_string()),
+        }
+    }
+}
+
+// shows output of last command run with Run Shell Command
+pub struct OutputPanel {}
+
+impl OutputPanel {
+    pub fn update_handler(panel: &mut TextPanel) -> Vec<StateChangeRequest> {
+        match panel.command_output_mut() {
+            None => vec![],
+            Some(output) => output.poll(),
+        }
+    }
+
+    // same list as messages panel, but oldest at top and scroll_y counted up from bottom
+    pub fn render_handler(panel: &TextPanel, state: &AppState, _: &Manager, frame: &mut EditorFrame, rect: Rect) -> RenderDetails {
+        let output = match panel.command_output() {
+            None => return RenderDetails::new(state.text(&locale::Text::new("output.title", "Output")), CURSOR_MAX),
+            Some(output) => output,
+        };
+
+        let mut items: Vec<ListItem> = output
+            .lines()
+            .iter()
+            .map(|line| ListItem::new(Text::styled(line.clone(), state.theme().text)))
+            .collect();
+
+        match output.status() {
+            None => (),
+            Some(status) => items.push(ListItem::new(Text::styled(
+                format!("[{}]", status),
+                match output.success {
+                    true => state.theme().info,
+                    false => state.theme().error,
+                },
+            ))),
+        }
+
+        let end = items.len().saturating_sub(panel.scroll_y() as usize);
+        let start = end.saturating_sub(rect.height as usize);
+        let items: Vec<ListItem> = items.into_iter().skip(start).take(end - start).collect();
+
+        frame.render_widget(List::new(items).style(state.theme().text), rect);
+
+        let title = match output.running() {
+            true => locale::Text::new("output.title_running", "Output {0} (running)"),
+            false => locale::Text::new("output.title_command", "Output {0}"),
+        };
+
+        RenderDetails::new(state.text(&title.arg(output.command())), CURSOR_MAX)
+    }
+
+    pub fn scroll_back(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let count = panel.command_output().map(|output| output.lines().len()).unwrap_or(0) as u16;
+        if panel.scroll_y() + 1 < count {
+            panel.set_scroll_y(panel.scroll_y() + 1);
+        }
+
+        (true, vec![])
+    }
+
+    pub fn scroll_forward(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        panel.set_scroll_y(panel.scroll_y().saturating_sub(1));
+
+        (true, vec![])
+    }
+
+    pub fn scroll_to_newest(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        panel.set_scroll_y(0);
+
+        (true, vec![])
+    }
+
+    pub fn stop(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        // exit is reported by next update
+        match panel.command_output_mut().map(|output| output.stop()) {
+            None | Some(Ok(())) => (true, vec![]),
+            Some(Err(err)) => (false, vec![StateChangeRequest::error(
+                locale::Text::new("output.stop_failed", "Could not stop command. {0}").arg(err),
+            )]),
+        }
+    }
+
+    pub fn rerun(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let command = match panel.command_output() {
+            None => return (false, vec![]),
+            Some(output) => output.command().clone(),
+        };
+
+        match CommandOutput::start(command) {
+            Err(err) => (false, vec![StateChangeRequest::error(err)]),
+            Ok(output) => {
+                panel.set_command_output(Some(output));
+                panel.set_scroll_y(0);
+                (true, vec![])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crossterm::event::KeyCode;
+
+    use crate::commands::Manager;
+    use crate::panels::output::{CommandOutput, OutputPanel};
+    use crate::{AppState, TextPanel};
+
+    fn run_to_end(panel: &mut TextPanel) {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(5) && panel.command_output().unwrap().running() {
+            OutputPanel::update_handler(panel);
+        }
+    }
+
+    #[test]
+    fn output_and_status_collected() {
+        let mut panel = TextPanel::output_panel();
+        panel.set_command_output(Some(CommandOutput::start("echo one; echo two 1>&2; exit 3".to_string()).unwrap()));
+
+        run_to_end(&mut panel);
+
+        let output = panel.command_output().unwrap();
+        assert!(output.lines().contains(&"one".to_string()));
+        assert!(output.lines().contains(&"two".to_string()));
+        assert_eq!(output.status(), Some(&"exit status: 3".to_string()));
+    }
+
+    #[test]
+    fn rerun_starts_again() {
+        let mut panel = TextPanel::output_panel();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        panel.set_command_output(Some(CommandOutput::start("echo one".to_string()).unwrap()));
+
+        run_to_end(&mut panel);
+        assert!(panel.command_output().unwrap().status().is_some());
+
+        OutputPanel::rerun(&mut panel, KeyCode::Null, &mut state, &mut commands);
+
+        assert!(panel.command_output().unwrap().status().is_none());
+        assert_eq!(panel.command_output().unwrap().command(), "echo one");
+
+        run_to_end(&mut panel);
+        assert!(panel.command_output().unwrap().lines().contains(&"one".to_string()));
+    }
+}
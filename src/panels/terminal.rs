@@ -0,0 +1,325 @@
+use std::env;
+use std::mem;
+
+use crossterm::event::KeyCode;
+use tui::layout::Rect;
+use tui::text::Text;
+use tui::widgets::Paragraph;
+
+use crate::app::StateChangeRequest;
+use crate::commands::Manager;
+use crate::locale;
+use crate::panels::text::RenderDetails;
+use crate::process::{append_output, Process};
+use crate::{AppState, EditorFrame, TextPanel, CURSOR_MAX};
+
+// shell running in a panel
+// without a tty the shell doesn't prompt or echo, typed line is kept here and echoed on Enter
+pub struct Terminal {
+    process: Option<Process>,
+    started: bool,
+    output: Vec<String>,
+    input: String,
+}
+
+impl Terminal {
+    pub fn new() -> Self {
+        Self {
+            process: None,
+            started: false,
+            output: vec![String::new()],
+            input: String::new(),
+        }
+    }
+
+    pub fn output(&self) -> &Vec<String> {
+        &self.output
+    }
+
+    pub fn running(&self) -> bool {
+        self.process.is_some()
+    }
+
+    // last line of output with typed input after it
+    pub fn prompt_line(&self) -> String {
+        let last = self.output.last().map(|line| line.as_str()).unwrap_or("");
+
+        match (self.running(), last.is_empty()) {
+            (true, true) => format!("$ {}", self.input),
+            _ => format!("{}{}", last, self.input),
+        }
+    }
+
+    fn start(&mut self) -> Vec<StateChangeRequest> {
+        self.started = true;
+
+        let shell = shell_program();
+        match Process::spawn(&shell, &[]) {
+            Err(err) => vec![StateChangeRequest::error(
+                locale::Text::new("terminal.start_failed", "Could not start {0}. {1}").arg(shell).arg(err),
+            )],
+            Ok(process) => {
+                self.process = Some(process);
+                vec![]
+            }
+        }
+    }
+
+    // reads new output, process is dropped once it's exited and everything it wrote has been read
+    pub fn poll(&mut self) -> Vec<StateChangeRequest> {
+        if !self.started {
+            return self.start();
+        }
+
+        let (output, exited) = match self.process.as_mut() {
+            None => return vec![],
+            Some(process) => {
+                let output = process.take_output();
+                let exited = match process.try_wait() {
+                    Err(err) => Some(err.to_string()),
+                    Ok(Some(status)) if process.output_finished() => Some(status.to_string()),
+                    Ok(_) => None,
+                };
+
+                (output, exited)
+            }
+        };
+
+        append_output(&mut self.output, &output);
+
+        match exited {
+            None => vec![],
+            Some(status) => {
+                self.process = None;
+                self.input.clear();
+                append_output(&mut self.output, &format!("\n[{}]\n", status));
+
+                vec![StateChangeRequest::info(locale::Text::new("terminal.shell_exited", "Shell exited."))]
+            }
+        }
+    }
+
+    fn send_input(&mut self) -> Vec<StateChangeRequest> {
+        let line = mem::take(&mut self.input);
+
+        let echo = match self.output.last().map(|last| last.is_empty()).unwrap_or(true) {
+            true => format!("$ {}\n", line),
+            false => format!("{}\n", line),
+        };
+
+        let result = match self.process.as_mut() {
+            None => return vec![StateChangeRequest::error(
+                locale::Text::new("terminal.shell_not_running", "Shell isn't running. Ctrl+R starts a new one."),
+            )],
+            Some(process) => process.write(format!("{}\n", line).as_bytes()),
+        };
+
+        append_output(&mut self.output, &echo);
+
+        match result {
+            Err(err) => vec![StateChangeRequest::error(
+                locale::Text::new("terminal.send_failed", "Could not send input. {0}").arg(err),
+            )],
+            Ok(()) => vec![],
+        }
+    }
+
+    fn restart(&mut self) {
+        self.process = None;
+        self.started = false;
+        self.input.clear();
+    }
+}
+
+// SHELL is set on most unix systems, sh is the fallback it would point to anyway
+fn shell_program() -> String {
+    match env::var("SHELL") {
+        Ok(shell) if !shell.is_empty() => shell,
+        _ => match cfg!(windows) {
+            true => "cmd".to_string(),
+            false => "sh".to_string(),
+        },
+    }
+}
+
+pub struct TerminalPanel {}
+
+impl TerminalPanel {
+    pub fn update_handler(panel: &mut TextPanel) -> Vec<StateChangeRequest> {
+        match panel.terminal_mut() {
+            None => vec![],
+            Some(terminal) => terminal.poll(),
+        }
+    }
+
+    pub fn render_handler(panel: &TextPanel, state: &AppState, _: &Manager, frame: &mut EditorFrame, rect: Rect) -> RenderDetails {
+        let terminal = match panel.terminal() {
+            None => return RenderDetails::new(state.text(&locale::Text::new("terminal.title", "Terminal")), CURSOR_MAX),
+            Some(terminal) => terminal,
+        };
+
+        // newest output at bottom, older scrolled off the top
+        let output = terminal.output();
+        let start = output.len().saturating_sub((rect.height as usize).max(1));
+        let mut lines = output[start..output.len().saturating_sub(1)].to_vec();
+        let prompt = terminal.prompt_line();
+        let cursor = (
+            rect.x + prompt.chars().count() as u16,
+            rect.y + lines.len() as u16,
+        );
+        lines.push(prompt);
+
+        frame.render_widget(Paragraph::new(Text::raw(lines.join("\n"))).style(state.theme().text), rect);
+
+        let title = match terminal.process.as_ref() {
+            None => locale::Text::new("terminal.title_exited", "Terminal (exited)"),
+            Some(process) if process.input_closed() => {
+                locale::Text::new("terminal.title_input_closed", "Terminal (input closed)")
+            }
+            Some(_) => locale::Text::new("terminal.title", "Terminal"),
+        };
+
+        RenderDetails::new(state.text(&title), cursor)
+    }
+
+    pub fn handle_key_stroke(
+        panel: &mut TextPanel,
+        code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let terminal = match panel.terminal_mut() {
+            None => return (false, vec![]),
+            Some(terminal) => terminal,
+        };
+
+        match code {
+            KeyCode::Char(c) => terminal.input.push(c),
+            KeyCode::Tab => terminal.input.push('\t'),
+            KeyCode::Backspace => {
+                terminal.input.pop();
+            }
+            KeyCode::Enter => return (true, terminal.send_input()),
+            _ => return (false, vec![]),
+        }
+
+        (true, vec![])
+    }
+
+    pub fn end_input(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        match panel.terminal_mut().and_then(|terminal| terminal.process.as_mut()) {
+            None => (false, vec![]),
+            Some(process) => {
+                process.close_input();
+                (true, vec![])
+            }
+        }
+    }
+
+    pub fn stop(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        // exit is reported by next update
+        match panel.terminal_mut().and_then(|terminal| terminal.process.as_mut()) {
+            None => (false, vec![]),
+            Some(process) => match process.kill() {
+                Ok(()) => (true, vec![]),
+                Err(err) => (false, vec![StateChangeRequest::error(
+                    locale::Text::new("terminal.stop_failed", "Could not stop shell. {0}").arg(err),
+                )]),
+            },
+        }
+    }
+
+    pub fn restart(
+        panel: &mut TextPanel,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        match panel.terminal_mut() {
+            None => (false, vec![]),
+            Some(terminal) => {
+                terminal.restart();
+                (true, vec![])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crossterm::event::KeyCode;
+
+    use crate::commands::Manager;
+    use crate::panels::TerminalPanel;
+    use crate::{AppState, TextPanel};
+
+    fn type_line(panel: &mut TextPanel, state: &mut AppState, commands: &mut Manager, line: &str) {
+        for c in line.chars() {
+            TerminalPanel::handle_key_stroke(panel, KeyCode::Char(c), state, commands);
+        }
+        TerminalPanel::handle_key_stroke(panel, KeyCode::Enter, state, commands);
+    }
+
+    fn update_until<F: Fn(&TextPanel) -> bool>(panel: &mut TextPanel, done: F) {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(5) && !done(panel) {
+            TerminalPanel::update_handler(panel);
+        }
+    }
+
+    #[test]
+    fn typed_lines_run_in_shell() {
+        let mut panel = TextPanel::terminal_panel();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        TerminalPanel::update_handler(&mut panel);
+        assert!(panel.terminal().unwrap().running());
+
+        TerminalPanel::handle_key_stroke(&mut panel, KeyCode::Char('x'), &mut state, &mut commands);
+        TerminalPanel::handle_key_stroke(&mut panel, KeyCode::Backspace, &mut state, &mut commands);
+        type_line(&mut panel, &mut state, &mut commands, "echo hello");
+
+        update_until(&mut panel, |panel| panel.terminal().unwrap().output().contains(&"hello".to_string()));
+
+        let output = panel.terminal().unwrap().output();
+        assert_eq!(output[0], "$ echo hello");
+        assert!(output.contains(&"hello".to_string()));
+        assert_eq!(panel.terminal().unwrap().prompt_line(), "$ ");
+
+        TerminalPanel::end_input(&mut panel, KeyCode::Null, &mut state, &mut commands);
+        update_until(&mut panel, |panel| !panel.terminal().unwrap().running());
+
+        assert!(!panel.terminal().unwrap().running());
+    }
+
+    #[test]
+    fn restart_after_stop() {
+        let mut panel = TextPanel::terminal_panel();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+
+        TerminalPanel::update_handler(&mut panel);
+        TerminalPanel::stop(&mut panel, KeyCode::Null, &mut state, &mut commands);
+        update_until(&mut panel, |panel| !panel.terminal().unwrap().running());
+
+        assert!(!panel.terminal().unwrap().running());
+
+        TerminalPanel::restart(&mut panel, KeyCode::Null, &mut state, &mut commands);
+        TerminalPanel::update_handler(&mut panel);
+
+        assert!(panel.terminal().unwrap().running());
+    }
+}
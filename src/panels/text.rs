@@ -1,24 +1,86 @@
-use std::{fs, iter};
-use std::fs::File;
+use std::{env, fs, io, iter, mem};
+use std::cell::Cell;
+use std::fs::{File, OpenOptions};
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
 use crossterm::event::{KeyCode, KeyEvent};
 use tui::layout::{Direction, Rect};
+use tui::style::{Modifier, Style};
 use tui::text::{Span, Spans, Text};
 use crate::{AppState, catch_all, CommandDetails, Commands, ctrl_key, CURSOR_MAX, EditorFrame};
-use crate::app::{Message, StateChangeRequest};
-use crate::autocomplete::FileAutoCompleter;
+use crate::app::{Message, MessageChannel, StateChangeRequest};
+use crate::autocomplete::{BufferAutoCompleter, FileAutoCompleter, ProjectFileAutoCompleter, RecentFilesAutoCompleter};
 use crate::commands::{alt_key, Manager, shift_alt_key, shift_catch_all};
-use crate::panels::{commands, COMMANDS_PANEL_TYPE_ID, EDIT_PANEL_TYPE_ID, INPUT_PANEL_TYPE_ID, InputPanel, MESSAGE_PANEL_TYPE_ID, MessagesPanel, NULL_PANEL_TYPE_ID, PanelFactory, PanelTypeID};
+use crate::commands::MAX_COUNT;
+use crate::locale;
+use crate::panels::{commands, COMMANDS_PANEL_TYPE_ID, EDIT_PANEL_TYPE_ID, INPUT_PANEL_TYPE_ID, InputPanel, MESSAGE_PANEL_TYPE_ID, MessagesPanel, NULL_PANEL_TYPE_ID, PanelTypeID, DIFF_PANEL_TYPE_ID, HEX_PANEL_TYPE_ID, OUTPUT_PANEL_TYPE_ID, TERMINAL_PANEL_TYPE_ID, HexPanel, HelpPanel, HELP_PANEL_TYPE_ID, WelcomePanel, WELCOME_PANEL_TYPE_ID};
+use crate::panels::brackets::matching_bracket;
+use crate::panels::buffer::{buffer_name, Buffer, Checkpoint};
 use crate::panels::edit::TextEditPanel;
+use crate::panels::bom::{encode, Bom};
+use crate::panels::line_ending::LineEnding;
+use crate::panels::lines::Lines;
+use crate::panels::loader::{FileLoader, LINES_PER_CHUNK};
+use crate::panels::diff::{DiffPanel, DiffView};
+use crate::panels::hex::HexView;
+use crate::panels::jumps::JumpList;
+use crate::panels::todo::todo_lines;
+use crate::panels::output::{CommandOutput, OutputPanel};
+use crate::panels::terminal::{Terminal, TerminalPanel};
+use crate::diff::CLIPBOARD_TARGET;
+use crate::remote::{RemotePath, UploadRemote};
+use crate::share::HostSession;
+use crate::swap::remove_swap;
 
 #[derive(Debug, Copy, Clone, PartialOrd, PartialEq)]
 pub enum PanelState {
     Normal,
     WaitingToOpen,
     WaitingToSave,
+    WaitingToRecover,
+    WaitingToSetRuler,
+    WaitingToDuplicate,
+    WaitingToCopyLines,
+    WaitingToReplace,
+    WaitingToReplaceInSelection,
+    WaitingToReplaceInLines,
+    WaitingToConfirmOverwrite,
+    WaitingToCreateDirectories,
+    WaitingToSwitchBuffer,
+    WaitingToJoinSession,
+    WaitingToExport,
+    WaitingToCompare,
+    WaitingToCheckpoint,
+    WaitingToRestoreCheckpoint,
+    WaitingToCompareCheckpoint,
+    WaitingToResolveChange,
+    WaitingToResolveChangeBeforeSave,
+    WaitingToGoToLine,
 }
 
+// modeless types text directly, normal and insert work like vim
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum EditMode {
+    Modeless,
+    Normal,
+    Insert,
+}
+
+impl EditMode {
+    pub fn name(&self) -> locale::Text {
+        match self {
+            EditMode::Modeless => locale::Text::default(),
+            EditMode::Normal => locale::Text::new("mode.normal", "NORMAL"),
+            EditMode::Insert => locale::Text::new("mode.insert", "INSERT"),
+        }
+    }
+}
+
+pub const DEFAULT_WRAP_WIDTH: usize = 80;
+pub const DEFAULT_PAGE_HEIGHT: u16 = 10;
+
 pub struct RenderDetails {
     title: String,
     cursor: (u16, u16),
@@ -40,13 +102,23 @@ impl RenderDetails {
     }
 }
 
+// functions that make one kind of panel behave differently from another
+pub type LengthHandler = fn(&TextPanel, u16, u16, Direction, &AppState) -> u16;
+pub type InputHandler = fn(&mut TextPanel, String) -> Vec<StateChangeRequest>;
+pub type AnswersHandler = fn(&mut TextPanel, Vec<String>) -> Vec<StateChangeRequest>;
+pub type UpdateHandler = fn(&mut TextPanel) -> Vec<StateChangeRequest>;
+pub type RenderHandler = fn(&TextPanel, &AppState, &Manager, &mut EditorFrame, Rect) -> RenderDetails;
+pub type ShutdownHandler = fn(&mut TextPanel);
+
 pub struct TextPanel {
     current_line: usize,
     cursor_index_in_line: usize,
     title: String,
     file_path: Option<PathBuf>,
     scroll_y: u16,
-    lines: Vec<String>,
+    scroll_x: u16,
+    wrap: bool,
+    lines: Lines,
     gutter_size: u16,
     visible: bool,
     panel_type: PanelTypeID,
@@ -54,9 +126,69 @@ pub struct TextPanel {
     continuation_marker: String,
     selection: usize,
     command_index: usize,
-    pub(crate) length_handler: fn(&TextPanel, u16, u16, Direction, &AppState) -> u16,
-    pub(crate) receive_input_handler: fn(&mut TextPanel, String) -> Vec<StateChangeRequest>,
-    pub(crate) render_handler: fn(&TextPanel, &AppState, &Manager, &mut EditorFrame, Rect) -> RenderDetails,
+    mark: Option<(usize, usize)>,
+    // typing edits every line of marked block at the same column
+    column_select: bool,
+    modified: bool,
+    ruler: Option<usize>,
+    // text past ruler is highlighted
+    flag_long_lines: bool,
+    // spaces and tabs drawn as glyphs
+    show_whitespace: bool,
+    sub_word_motions: bool,
+    // typing an opening bracket or quote adds its closing one
+    auto_close_pairs: bool,
+    // applied to lines on explicit save
+    trim_trailing_whitespace: bool,
+    final_newline: bool,
+    indent_with_tabs: bool,
+    tab_width: usize,
+    sync_cursors: bool,
+    edit_mode: EditMode,
+    // shared by panels showing the same buffer, lets unsaved buffers be split too
+    view_id: Option<usize>,
+    // names recovery file of unsaved buffer
+    buffer_id: u64,
+    // changes with lines or file details, views only copy from a panel whose revision differs
+    revision: u64,
+    // file is a local copy of this, uploaded back on save
+    remote: Option<RemotePath>,
+    // mark file was opened with, hidden from lines
+    bom: Option<Bom>,
+    keep_bom: bool,
+    line_ending: LineEnding,
+    loader: Option<FileLoader>,
+    terminal: Option<Terminal>,
+    command_output: Option<CommandOutput>,
+    diff: Option<DiffView>,
+    hex: Option<HexView>,
+    message_filter: Option<MessageChannel>,
+    // listing panels only show lines containing this
+    search: Option<String>,
+    // keys go to search text until Enter
+    searching: bool,
+    // area given to render handler last frame
+    viewport: Cell<Rect>,
+    // save as target waiting on overwrite confirmation
+    pending_save_path: Option<PathBuf>,
+    // other buffers in panel, in switching order after current one
+    buffers: Vec<Buffer>,
+    // snapshots of current buffer, most recent last
+    checkpoints: Vec<Checkpoint>,
+    // found while idle, may be behind latest edits
+    todo_lines: Vec<usize>,
+    // modification time of file when last read or written
+    disk_time: Option<SystemTime>,
+    // newer time already told about, so it's only asked once
+    reported_disk_time: Option<SystemTime>,
+    // positions left by go to line, bracket and TODO jumps in current buffer
+    jumps: JumpList,
+    pub(crate) length_handler: LengthHandler,
+    pub(crate) receive_input_handler: InputHandler,
+    pub(crate) receive_answers_handler: AnswersHandler,
+    pub(crate) update_handler: UpdateHandler,
+    pub(crate) render_handler: RenderHandler,
+    pub(crate) shutdown_handler: ShutdownHandler,
 }
 
 impl Default for TextPanel {
@@ -64,10 +196,14 @@ impl Default for TextPanel {
         Self {
             current_line: 0,
             cursor_index_in_line: 0,
+            revision: 0,
+            buffer_id: next_buffer_id(),
             title: String::new(),
             file_path: None,
             scroll_y: 0,
-            lines: vec![],
+            scroll_x: 0,
+            wrap: true,
+            lines: Lines::new(),
             gutter_size: 5,
             visible: true,
             panel_type: NULL_PANEL_TYPE_ID,
@@ -75,15 +211,61 @@ impl Default for TextPanel {
             continuation_marker: "... ".to_string(),
             selection: 0,
             command_index: 0,
+            mark: None,
+            column_select: false,
+            modified: false,
+            ruler: None,
+            flag_long_lines: false,
+            show_whitespace: false,
+            sub_word_motions: false,
+            auto_close_pairs: false,
+            trim_trailing_whitespace: false,
+            final_newline: false,
+            indent_with_tabs: false,
+            tab_width: 4,
+            sync_cursors: false,
+            edit_mode: EditMode::Modeless,
+            view_id: None,
+            remote: None,
+            bom: None,
+            keep_bom: true,
+            line_ending: LineEnding::Lf,
+            loader: None,
+            terminal: None,
+            command_output: None,
+            diff: None,
+            hex: None,
+            message_filter: None,
+            search: None,
+            searching: false,
+            viewport: Cell::new(Rect::default()),
+            pending_save_path: None,
+            buffers: vec![],
+            checkpoints: vec![],
+            todo_lines: vec![],
+            disk_time: None,
+            reported_disk_time: None,
+            jumps: JumpList::default(),
             length_handler: TextPanel::empty_length_handler,
             receive_input_handler: TextPanel::empty_input_handler,
+            receive_answers_handler: TextPanel::empty_answers_handler,
+            update_handler: TextPanel::empty_update_handler,
             render_handler: TextPanel::empty_render_handler,
+            shutdown_handler: TextPanel::empty_shutdown_handler,
         }
     }
 }
 
 impl TextPanel {
 
+    // no behavior beyond type, handlers are set by PanelBuilder
+    pub(crate) fn custom_panel(type_id: PanelTypeID) -> Self {
+        TextPanel {
+            panel_type: type_id,
+            ..TextPanel::default()
+        }
+    }
+
     fn empty_length_handler(_: &TextPanel, _: u16, _: u16, _: Direction, _: &AppState) -> u16 {
         0
     }
@@ -92,16 +274,28 @@ impl TextPanel {
         vec![]
     }
 
+    fn empty_answers_handler(_: &mut TextPanel, _: Vec<String>) -> Vec<StateChangeRequest> {
+        vec![]
+    }
+
+    fn empty_update_handler(_: &mut TextPanel) -> Vec<StateChangeRequest> {
+        vec![]
+    }
+
     fn empty_render_handler(_: &TextPanel, _: &AppState, _: &Manager, _: &mut EditorFrame, _: Rect) -> RenderDetails {
         RenderDetails::new(String::new(), CURSOR_MAX)
     }
 
+    fn empty_shutdown_handler(_: &mut TextPanel) {}
+
     pub fn edit_panel() -> Self {
         let mut defaults = TextPanel::default();
         defaults.panel_type = EDIT_PANEL_TYPE_ID;
 
         defaults.render_handler = TextEditPanel::render_handler;
         defaults.receive_input_handler = TextEditPanel::input_handler;
+        defaults.receive_answers_handler = TextEditPanel::answers_handler;
+        defaults.shutdown_handler = TextEditPanel::shutdown_handler;
 
         defaults
     }
@@ -121,6 +315,7 @@ impl TextPanel {
         let mut defaults = TextPanel::default();
         defaults.panel_type = MESSAGE_PANEL_TYPE_ID;
 
+        defaults.length_handler = MessagesPanel::length_handler;
         defaults.render_handler = MessagesPanel::render_handler;
 
         defaults
@@ -135,6 +330,58 @@ impl TextPanel {
         defaults
     }
 
+    pub fn terminal_panel() -> Self {
+        TextPanel {
+            panel_type: TERMINAL_PANEL_TYPE_ID,
+            // shell starts on first update, creating the panel to check its type shouldn't spawn anything
+            terminal: Some(Terminal::new()),
+            render_handler: TerminalPanel::render_handler,
+            update_handler: TerminalPanel::update_handler,
+            ..TextPanel::default()
+        }
+    }
+
+    pub fn output_panel() -> Self {
+        TextPanel {
+            panel_type: OUTPUT_PANEL_TYPE_ID,
+            render_handler: OutputPanel::render_handler,
+            update_handler: OutputPanel::update_handler,
+            ..TextPanel::default()
+        }
+    }
+
+    pub fn diff_panel() -> Self {
+        TextPanel {
+            panel_type: DIFF_PANEL_TYPE_ID,
+            render_handler: DiffPanel::render_handler,
+            ..TextPanel::default()
+        }
+    }
+
+    pub fn hex_panel() -> Self {
+        TextPanel {
+            panel_type: HEX_PANEL_TYPE_ID,
+            render_handler: HexPanel::render_handler,
+            ..TextPanel::default()
+        }
+    }
+
+    pub fn help_panel() -> Self {
+        TextPanel {
+            panel_type: HELP_PANEL_TYPE_ID,
+            render_handler: HelpPanel::render_handler,
+            ..TextPanel::default()
+        }
+    }
+
+    pub fn welcome_panel() -> Self {
+        TextPanel {
+            panel_type: WELCOME_PANEL_TYPE_ID,
+            render_handler: WelcomePanel::render_handler,
+            ..TextPanel::default()
+        }
+    }
+
     fn init(&mut self, _state: &mut AppState) {
 
     }
@@ -146,9 +393,12 @@ impl TextPanel {
 
     pub fn set_text<T: ToString>(&mut self, text: T) {
         self.lines = text.to_string().split('\n').map(|s| s.to_string()).collect();
+        self.touch();
     }
 
     pub fn append_text<T: ToString>(&mut self, text: T) {
+        self.touch();
+
         let new_lines = text.to_string();
         let mut spliterator = new_lines.split('\n');
 
@@ -172,7 +422,7 @@ impl TextPanel {
         }
     }
 
-    pub fn lines(&self) -> &Vec<String> {
+    pub fn lines(&self) -> &Lines {
         &self.lines
     }
 
@@ -184,12 +434,31 @@ impl TextPanel {
         self.selection = selection;
     }
 
+    pub fn mark(&self) -> Option<(usize, usize)> {
+        self.mark
+    }
+
+    pub fn set_mark(&mut self, mark: Option<(usize, usize)>) {
+        self.mark = mark;
+    }
+
+    // commands that clear the mark end column select too
+    pub fn column_select(&self) -> bool {
+        self.column_select && self.mark.is_some()
+    }
+
+    // block corners for highlighting, lines inclusive and columns exclusive
+    pub fn block(&self) -> Option<(usize, usize, usize, usize)> {
+        self.marked_block()
+    }
+
     pub fn title(&self) -> &String {
         &self.title
     }
 
     pub fn set_title(&mut self, title: String) {
         self.title = title;
+        self.touch();
     }
 
     pub fn current_line(&self) -> usize {
@@ -200,6 +469,14 @@ impl TextPanel {
         self.current_line = current_line;
     }
 
+    // file may have changed since position was saved
+    pub fn restore_cursor(&mut self, line: usize, column: usize) {
+        self.current_line = line.min(self.lines.len().saturating_sub(1));
+        self.cursor_index_in_line = column.min(self.lines.get(self.current_line).map(|l| l.len()).unwrap_or(0));
+        // place line in middle of view
+        self.scroll_y = (self.current_line as u16).saturating_sub(self.page_height() / 2);
+    }
+
     pub fn cursor_index_in_line(&self) -> usize {
         self.cursor_index_in_line
     }
@@ -216,16 +493,337 @@ impl TextPanel {
         self.scroll_y = y;
     }
 
+    pub fn message_filter(&self) -> Option<MessageChannel> {
+        self.message_filter
+    }
+
+    pub fn set_message_filter(&mut self, filter: Option<MessageChannel>) {
+        self.message_filter = filter;
+    }
+
+    pub fn search(&self) -> Option<&String> {
+        self.search.as_ref()
+    }
+
+    pub fn searching(&self) -> bool {
+        self.searching
+    }
+
+    // case is ignored, everything matches when there's no search
+    pub fn matches_search(&self, text: &str) -> bool {
+        match self.search.as_ref() {
+            None => true,
+            Some(search) => text.to_lowercase().contains(&search.to_lowercase()),
+        }
+    }
+
+    // for panel titles, trailing underscore while still typing
+    pub fn search_label(&self) -> Option<String> {
+        self.search
+            .as_ref()
+            .map(|search| format!("/{}{}", search, if self.searching { "_" } else { "" }))
+    }
+
+    // shared by listing panels, typed keys narrow what's shown as they come in
+    pub(crate) fn start_search(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.search = Some(String::new());
+        self.searching = true;
+        self.jump_to_first_match();
+
+        (true, vec![])
+    }
+
+    // true when key went to search text
+    pub fn search_key(&mut self, code: KeyCode) -> bool {
+        let search = match (self.searching, self.search.as_mut()) {
+            (true, Some(search)) => search,
+            _ => return false,
+        };
+
+        match code {
+            KeyCode::Char(c) => search.push(c),
+            KeyCode::Backspace => {
+                search.pop();
+            }
+            KeyCode::Enter => self.searching = false,
+            _ => return false,
+        }
+
+        self.jump_to_first_match();
+
+        true
+    }
+
+    // true when there was a search to clear
+    pub fn cancel_search(&mut self) -> bool {
+        self.searching = false;
+
+        match self.search.take() {
+            None => false,
+            Some(_) => {
+                self.jump_to_first_match();
+                true
+            }
+        }
+    }
+
+    // listings are rebuilt from matches, so first match is at the start
+    fn jump_to_first_match(&mut self) {
+        self.scroll_y = 0;
+        self.selection = match self.search.as_ref() {
+            Some(search) if !search.is_empty() => 1,
+            _ => 0,
+        };
+    }
+
+    pub fn scroll_x(&self) -> u16 {
+        self.scroll_x
+    }
+
+    pub fn set_scroll_x(&mut self, x: u16) {
+        self.scroll_x = x;
+    }
+
+    pub fn wrap(&self) -> bool {
+        self.wrap
+    }
+
+    pub fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+    }
+
     pub fn state(&self) -> PanelState {
         self.state
     }
 
+    pub fn set_state(&mut self, state: PanelState) {
+        self.state = state;
+    }
+
+    pub fn sub_word_motions(&self) -> bool {
+        self.sub_word_motions
+    }
+
+    pub fn set_sub_word_motions(&mut self, sub_word_motions: bool) {
+        self.sub_word_motions = sub_word_motions;
+    }
+
+    pub fn auto_close_pairs(&self) -> bool {
+        self.auto_close_pairs
+    }
+
+    pub fn set_auto_close_pairs(&mut self, auto_close_pairs: bool) {
+        self.auto_close_pairs = auto_close_pairs;
+    }
+
+    pub fn trim_trailing_whitespace(&self) -> bool {
+        self.trim_trailing_whitespace
+    }
+
+    pub fn set_trim_trailing_whitespace(&mut self, trim_trailing_whitespace: bool) {
+        self.trim_trailing_whitespace = trim_trailing_whitespace;
+    }
+
+    pub fn final_newline(&self) -> bool {
+        self.final_newline
+    }
+
+    pub fn set_final_newline(&mut self, final_newline: bool) {
+        self.final_newline = final_newline;
+    }
+
+    pub fn indent_with_tabs(&self) -> bool {
+        self.indent_with_tabs
+    }
+
+    pub fn set_indent_with_tabs(&mut self, indent_with_tabs: bool) {
+        self.indent_with_tabs = indent_with_tabs;
+    }
+
+    pub fn tab_width(&self) -> usize {
+        self.tab_width
+    }
+
+    pub fn set_tab_width(&mut self, tab_width: usize) {
+        self.tab_width = tab_width.max(1);
+    }
+
+    pub fn sync_cursors(&self) -> bool {
+        self.sync_cursors
+    }
+
+    pub fn set_sync_cursors(&mut self, sync_cursors: bool) {
+        self.sync_cursors = sync_cursors;
+    }
+
+    pub fn edit_mode(&self) -> EditMode {
+        self.edit_mode
+    }
+
+    pub fn set_edit_mode(&mut self, edit_mode: EditMode) {
+        self.edit_mode = edit_mode;
+    }
+
+    pub fn buffer_id(&self) -> u64 {
+        self.buffer_id
+    }
+
+    pub fn view_id(&self) -> Option<usize> {
+        self.view_id
+    }
+
+    pub fn set_view_id(&mut self, view_id: Option<usize>) {
+        self.view_id = view_id;
+    }
+
+    pub fn remote(&self) -> Option<&RemotePath> {
+        self.remote.as_ref()
+    }
+
+    pub fn set_remote(&mut self, remote: Option<RemotePath>) {
+        self.remote = remote;
+    }
+
+    pub fn bom(&self) -> Option<Bom> {
+        self.bom
+    }
+
+    pub fn set_bom(&mut self, bom: Option<Bom>) {
+        self.bom = bom;
+    }
+
+    #[allow(dead_code)]
+    pub fn line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    pub fn set_line_ending(&mut self, line_ending: LineEnding) {
+        self.line_ending = line_ending;
+    }
+
+    pub fn keep_bom(&self) -> bool {
+        self.keep_bom
+    }
+
+    pub fn set_keep_bom(&mut self, keep_bom: bool) {
+        self.keep_bom = keep_bom;
+    }
+
+    pub fn ruler(&self) -> Option<usize> {
+        self.ruler
+    }
+
+    pub fn set_ruler(&mut self, ruler: Option<usize>) {
+        self.ruler = ruler;
+    }
+
+    pub fn flag_long_lines(&self) -> bool {
+        self.flag_long_lines
+    }
+
+    pub fn set_flag_long_lines(&mut self, flag_long_lines: bool) {
+        self.flag_long_lines = flag_long_lines;
+    }
+
+    pub fn show_whitespace(&self) -> bool {
+        self.show_whitespace
+    }
+
+    pub fn set_show_whitespace(&mut self, show_whitespace: bool) {
+        self.show_whitespace = show_whitespace;
+    }
+
+    pub fn modified(&self) -> bool {
+        self.modified
+    }
+
+    pub fn set_modified(&mut self, modified: bool) {
+        self.modified = modified;
+        self.touch();
+    }
+
+    // every edit goes through here so views of this buffer know to update
+    fn mark_modified(&mut self) {
+        self.modified = true;
+        self.touch();
+    }
+
+    fn touch(&mut self) {
+        self.revision = next_revision();
+    }
+
     pub fn file_path(&self) -> Option<&PathBuf> {
         self.file_path.as_ref()
     }
 
+    // time of a previous file doesn't apply to new one
     pub fn set_file_path(&mut self, path: PathBuf) {
         self.file_path = Some(path);
+        self.disk_time = None;
+        self.reported_disk_time = None;
+        self.touch();
+    }
+
+    // call after file is read or written, later changes by other programs are measured from here
+    pub fn record_disk_time(&mut self) {
+        self.disk_time = self.current_disk_time();
+        self.reported_disk_time = None;
+        self.touch();
+    }
+
+    fn current_disk_time(&self) -> Option<SystemTime> {
+        self.file_path
+            .as_ref()
+            .and_then(|path| fs::metadata(path).ok())
+            .and_then(|metadata| metadata.modified().ok())
+    }
+
+    // file was written by something else since it was opened or saved here
+    pub fn changed_on_disk(&self) -> bool {
+        match (self.disk_time, self.current_disk_time()) {
+            (Some(recorded), Some(current)) => recorded != current,
+            _ => false,
+        }
+    }
+
+    // true once for each new change, for watcher to only ask once
+    pub fn take_disk_change(&mut self) -> bool {
+        let current = self.current_disk_time();
+        if !self.changed_on_disk() || self.reported_disk_time == current {
+            return false;
+        }
+
+        self.reported_disk_time = current;
+        self.touch();
+        true
+    }
+
+    pub fn ask_about_disk_change(&mut self, before_save: bool) -> Vec<StateChangeRequest> {
+        self.state = match before_save {
+            true => PanelState::WaitingToResolveChangeBeforeSave,
+            false => PanelState::WaitingToResolveChange,
+        };
+
+        let name = buffer_name(&self.title, self.file_path.as_ref());
+        vec![
+            StateChangeRequest::Message(Message::warning(
+                locale::Text::new("text.changed_on_disk", "{0} changed on disk.").arg(name),
+            )),
+            StateChangeRequest::validated_input(
+                locale::Text::new("prompt.changed_on_disk", "Changed On Disk (reload, keep, diff)"),
+                validate_disk_change_choice,
+            ),
+        ]
+    }
+
+    pub fn clear_file_path(&mut self) {
+        self.file_path = None;
+        self.touch();
     }
 
     pub fn gutter_size(&self) -> u16 {
@@ -259,9 +857,152 @@ impl TextPanel {
         frame: &mut EditorFrame,
         rect: Rect
     ) -> RenderDetails {
+        self.set_viewport(rect);
         (self.render_handler)(self, state, commands, frame, rect)
     }
 
+    pub fn viewport(&self) -> Rect {
+        self.viewport.get()
+    }
+
+    // current buffer included
+    pub fn buffer_count(&self) -> usize {
+        self.buffers.len() + 1
+    }
+
+    // unsaved changes in current or any background buffer
+    pub fn any_modified(&self) -> bool {
+        self.modified || self.buffers.iter().any(|buffer| buffer.modified)
+    }
+
+    // current buffer first
+    pub fn file_paths(&self) -> Vec<&PathBuf> {
+        self.file_path
+            .iter()
+            .chain(self.buffers.iter().filter_map(|buffer| buffer.file_path.as_ref()))
+            .collect()
+    }
+
+    // numbered from current buffer, in switching order
+    pub fn buffer_names(&self) -> Vec<String> {
+        iter::once(buffer_name(&self.title, self.file_path.as_ref()))
+            .chain(self.buffers.iter().map(|buffer| buffer.name()))
+            .enumerate()
+            .map(|(i, name)| format!("{} {}", i + 1, name))
+            .collect()
+    }
+
+    // current contents traded for given buffer
+    fn swap_buffer(&mut self, buffer: Buffer) -> Buffer {
+        self.touch();
+
+        Buffer {
+            buffer_id: mem::replace(&mut self.buffer_id, buffer.buffer_id),
+            lines: mem::replace(&mut self.lines, buffer.lines),
+            title: mem::replace(&mut self.title, buffer.title),
+            file_path: mem::replace(&mut self.file_path, buffer.file_path),
+            current_line: mem::replace(&mut self.current_line, buffer.current_line),
+            cursor_index_in_line: mem::replace(&mut self.cursor_index_in_line, buffer.cursor_index_in_line),
+            scroll_x: mem::replace(&mut self.scroll_x, buffer.scroll_x),
+            scroll_y: mem::replace(&mut self.scroll_y, buffer.scroll_y),
+            mark: mem::replace(&mut self.mark, buffer.mark),
+            modified: mem::replace(&mut self.modified, buffer.modified),
+            loader: mem::replace(&mut self.loader, buffer.loader),
+            view_id: mem::replace(&mut self.view_id, buffer.view_id),
+            remote: mem::replace(&mut self.remote, buffer.remote),
+            bom: mem::replace(&mut self.bom, buffer.bom),
+            line_ending: mem::replace(&mut self.line_ending, buffer.line_ending),
+            checkpoints: mem::replace(&mut self.checkpoints, buffer.checkpoints),
+            disk_time: mem::replace(&mut self.disk_time, buffer.disk_time),
+            reported_disk_time: mem::replace(&mut self.reported_disk_time, buffer.reported_disk_time),
+            jumps: mem::replace(&mut self.jumps, buffer.jumps),
+        }
+    }
+
+    // index counts from current buffer, same as buffer names
+    pub fn switch_to_buffer(&mut self, index: usize) -> bool {
+        if index >= self.buffer_count() {
+            return false;
+        }
+
+        for _ in 0..index {
+            let next = self.buffers.remove(0);
+            let previous = self.swap_buffer(next);
+            self.buffers.push(previous);
+        }
+
+        true
+    }
+
+    pub fn take_pending_save_path(&mut self) -> Option<PathBuf> {
+        self.pending_save_path.take()
+    }
+
+    pub fn set_pending_save_path(&mut self, path: Option<PathBuf>) {
+        self.pending_save_path = path;
+    }
+
+    pub fn set_viewport(&self, rect: Rect) {
+        self.viewport.set(rect);
+    }
+
+    // line numbers and gutter left of text
+    pub fn gutter_width(&self) -> u16 {
+        self.lines.len().to_string().len().min(u16::MAX as usize) as u16 + self.gutter_size
+    }
+
+    // buffer line shown at screen row of last render, wrapped rows belong to line they continue
+    pub fn line_at_row(&self, row: u16) -> Option<usize> {
+        let viewport = self.viewport.get();
+
+        if row < viewport.y || row >= viewport.bottom() || viewport.width <= self.gutter_width() {
+            return None;
+        }
+
+        let text_box = Rect::new(
+            viewport.x + self.gutter_width(),
+            viewport.y,
+            viewport.width - self.gutter_width(),
+            viewport.height,
+        );
+        let (_, _, gutter) = self.make_text_content(text_box);
+
+        // numbered row at or above, continuation rows are marked with '.'
+        gutter
+            .get(..=(row - viewport.y) as usize)?
+            .iter()
+            .rev()
+            .find_map(|spans| spans.0.first().and_then(|span| span.content.parse::<usize>().ok()))
+            .map(|number| number - 1)
+    }
+
+    // mark and cursor cover every column of lines between anchor and line
+    pub fn select_lines(&mut self, anchor: usize, line: usize) {
+        if self.lines.is_empty() {
+            return;
+        }
+
+        let last = self.lines.len() - 1;
+        let (anchor, line) = (anchor.min(last), line.min(last));
+        let width = self.lines
+            .range(anchor.min(line)..=anchor.max(line))
+            .map(String::len)
+            .max()
+            .unwrap_or(0);
+
+        self.mark = Some((anchor, width));
+        self.current_line = line;
+        self.cursor_index_in_line = 0;
+    }
+
+    // before first render there is no height to go by
+    pub(crate) fn page_height(&self) -> u16 {
+        match self.viewport.get().height {
+            0 => DEFAULT_PAGE_HEIGHT,
+            height => height,
+        }
+    }
+
     pub fn get_length(
         &self,
         fixed_length: u16,
@@ -276,26 +1017,37 @@ impl TextPanel {
         (self.receive_input_handler)(self, input)
     }
 
-    fn remove_character(&mut self, index_adjustment: usize, movement: usize, state: &mut AppState) {
+    pub fn receive_answers(&mut self, answers: Vec<String>) -> Vec<StateChangeRequest> {
+        (self.receive_answers_handler)(self, answers)
+    }
+
+    fn remove_character(&mut self, before_cursor: bool, state: &mut AppState) {
         match self.lines.get_mut(self.current_line) {
             None => (), // no text, do nothing
             Some(line) => {
-                if self.cursor_index_in_line - index_adjustment < line.len() {
-                    line.remove(self.cursor_index_in_line - index_adjustment);
-                    self.cursor_index_in_line -= movement;
+                let index = match before_cursor {
+                    true => previous_char_index(line, self.cursor_index_in_line),
+                    false => self.cursor_index_in_line,
+                };
+
+                if index < line.len() && line.is_char_boundary(index) {
+                    line.remove(index);
+                    self.cursor_index_in_line = index;
                 } else {
                     // cursor isn't in line
                     // implementation error
                     // log message and reset cursor to start of line
                     self.cursor_index_in_line = 0;
-                    state.add_error("Cursor outside of current line. Resetting to start of line.");
+                    state.add_error(
+                        locale::Text::new("edit.cursor_outside_line", "Cursor outside of current line. Resetting to start of line."),
+                    );
                 }
             }
         }
     }
 
     fn remove_line(&mut self) {
-        if self.current_line != 0 {
+        if self.current_line != 0 && self.current_line < self.lines.len() {
             let remaining = self.lines.remove(self.current_line);
             self.current_line -= 1;
             self.cursor_index_in_line = match self.lines.get_mut(self.current_line) {
@@ -319,7 +1071,35 @@ impl TextPanel {
         state: &mut AppState,
         commands: &mut Manager,
     ) -> (bool, Vec<StateChangeRequest>) {
-        self.handle_key_stroke_internal(code, state, TextPanel::enter_newline)
+        if self.column_select() {
+            return self.column_key_stroke(code);
+        }
+
+        let (handled, changes) = self.handle_key_stroke_internal(code, state, TextPanel::enter_newline);
+        if handled {
+            self.mark_modified();
+        }
+
+        (handled, changes)
+    }
+
+    // other alt keys fall through to global commands
+    pub(crate) fn count_digit(
+        &mut self,
+        code: KeyCode,
+        _state: &mut AppState,
+        commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        match code {
+            KeyCode::Char(c) => match c.to_digit(10) {
+                Some(digit) => {
+                    commands.push_count_digit(digit as usize);
+                    (true, vec![])
+                }
+                None => (false, vec![]),
+            },
+            _ => (false, vec![]),
+        }
     }
 
     pub(crate) fn handle_key_stroke_internal<Enter>(
@@ -336,17 +1116,20 @@ impl TextPanel {
                 if self.cursor_index_in_line == 0 {
                     self.remove_line();
                 } else {
-                    self.remove_character(1, 1, state);
+                    self.remove_character(true, state);
                 }
             }
             KeyCode::Delete => match self.lines.get(self.current_line) {
                 None => (),
                 Some(line) => {
-                    if self.cursor_index_in_line == line.len() {
-                        self.current_line += 1;
-                        self.remove_line();
+                    if self.cursor_index_in_line >= line.len() {
+                        // nothing after last line to join
+                        if self.current_line + 1 < self.lines.len() {
+                            self.current_line += 1;
+                            self.remove_line();
+                        }
                     } else {
-                        self.remove_character(0, 0, state);
+                        self.remove_character(false, state);
                     }
                 }
             },
@@ -354,17 +1137,35 @@ impl TextPanel {
                 enter_func(self, &mut changes)
             }
             KeyCode::Char(c) => {
+                let pair = match (self.auto_close_pairs, self.lines.get(self.current_line)) {
+                    (false, _) => AutoClose::Insert,
+                    (true, None) => auto_close("", 0, c),
+                    (true, Some(line)) => auto_close(line, self.cursor_index_in_line, c),
+                };
+
+                let closing = match pair {
+                    AutoClose::Insert => String::new(),
+                    AutoClose::Pair(closing) => closing.to_string(),
+                    AutoClose::StepOver => {
+                        let line = &self.lines[self.current_line];
+                        self.cursor_index_in_line = floor_char_index(line, self.cursor_index_in_line) + c.len_utf8();
+                        return (true, changes);
+                    }
+                };
+
                 match self.lines.get_mut(self.current_line) {
                     None => {
                         // start new
-                        self.lines.push(c.to_string());
+                        self.lines.push(format!("{}{}", c, closing));
+                        self.cursor_index_in_line = c.len_utf8();
                     }
                     Some(s) => {
                         // add to existing
-                        s.insert(self.cursor_index_in_line, c);
+                        let index = floor_char_index(s, self.cursor_index_in_line);
+                        s.insert_str(index, &format!("{}{}", c, closing));
+                        self.cursor_index_in_line = index + c.len_utf8();
                     }
                 }
-                self.cursor_index_in_line += 1;
             }
             _ => return (false, vec![]),
         }
@@ -372,24 +1173,284 @@ impl TextPanel {
         (true, changes)
     }
 
+    // split line at cursor, new line keeps leading whitespace of current line
     pub fn enter_newline(&mut self, _: &mut Vec<StateChangeRequest>) {
-        self.lines.push(String::new());
+        while self.lines.len() <= self.current_line {
+            self.lines.push(String::new());
+        }
+
+        let line = &mut self.lines[self.current_line];
+        let split_index = floor_char_index(line, self.cursor_index_in_line);
+        let remaining = line.split_off(split_index);
+
+        let indent: String = line.chars().take_while(|c| *c == ' ' || *c == '\t').collect();
+        let indent_length = indent.len();
+
+        self.lines.insert(self.current_line + 1, indent + remaining.trim_start_matches([' ', '\t']));
         self.current_line += 1;
-        self.cursor_index_in_line = 0;
+        self.cursor_index_in_line = indent_length;
     }
 
-    pub(crate) fn open_file(
+    pub(crate) fn indent(
         &mut self,
         _code: KeyCode,
         _state: &mut AppState,
-        commands: &mut Manager,
+        _commands: &mut Manager,
     ) -> (bool, Vec<StateChangeRequest>) {
-        self.state = PanelState::WaitingToOpen;
-        (
+        while self.lines.len() <= self.current_line {
+            self.lines.push(String::new());
+        }
+
+        // spaces go to next tab stop
+        let indent = match self.indent_with_tabs {
+            true => "\t".to_string(),
+            false => " ".repeat(self.tab_width - self.cursor_index_in_line % self.tab_width),
+        };
+
+        let line = &mut self.lines[self.current_line];
+        let index = floor_char_index(line, self.cursor_index_in_line);
+        line.insert_str(index, indent.as_str());
+
+        self.cursor_index_in_line = index + indent.len();
+        self.mark_modified();
+
+        (true, vec![])
+    }
+
+    // remove one level of indentation from start of line
+    pub(crate) fn dedent(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        match self.lines.get_mut(self.current_line) {
+            None => (),
+            Some(line) => {
+                let removed = if line.starts_with('\t') {
+                    1
+                } else {
+                    line.chars().take(self.tab_width).take_while(|c| *c == ' ').count()
+                };
+
+                if removed > 0 {
+                    line.replace_range(..removed, "");
+                    self.cursor_index_in_line = self.cursor_index_in_line.saturating_sub(removed);
+                    self.mark_modified();
+                }
+            }
+        }
+
+        (true, vec![])
+    }
+
+    // whole buffer is written with the other ending on next save
+    pub(crate) fn convert_line_endings(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.line_ending = self.line_ending.other();
+        self.mark_modified();
+
+        (
+            true,
+            vec![StateChangeRequest::info(
+                locale::Text::new("text.line_endings_set", "Line endings set to {0}.").arg(self.line_ending.name()),
+            )],
+        )
+    }
+
+    pub(crate) fn toggle_auto_close_pairs(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.auto_close_pairs = !self.auto_close_pairs;
+
+        (
+            true,
+            vec![StateChangeRequest::info(match self.auto_close_pairs {
+                true => "Closing brackets and quotes will be added.",
+                false => "Closing brackets and quotes won't be added.",
+            })],
+        )
+    }
+
+    pub(crate) fn toggle_flag_long_lines(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.flag_long_lines = !self.flag_long_lines;
+
+        let message = match (self.flag_long_lines, self.ruler) {
+            (false, _) => locale::Text::new("text.long_lines_unflagged", "Long lines won't be flagged."),
+            (true, None) => locale::Text::new("text.long_lines_need_ruler", "Long lines will be flagged once a ruler is set."),
+            (true, Some(column)) => locale::Text::new("text.long_lines_flagged", "Lines longer than {0} will be flagged.").arg(column),
+        };
+
+        (true, vec![StateChangeRequest::info(message)])
+    }
+
+    pub(crate) fn toggle_show_whitespace(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.show_whitespace = !self.show_whitespace;
+
+        (true, vec![])
+    }
+
+    pub(crate) fn toggle_indent_with_tabs(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.indent_with_tabs = !self.indent_with_tabs;
+
+        (
+            true,
+            vec![StateChangeRequest::info(match self.indent_with_tabs {
+                true => "Indenting with tab characters.".to_string(),
+                false => format!("Indenting with {} spaces.", self.tab_width),
+            })],
+        )
+    }
+
+    pub(crate) fn open_file(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.state = PanelState::WaitingToOpen;
+        (
             true,
             vec![StateChangeRequest::input_request_with_completer(
                 "File Name".to_string(),
-                Box::new(FileAutoCompleter::new()),
+                Box::new(FileAutoCompleter::fuzzy()),
+            )],
+        )
+    }
+
+    pub(crate) fn new_buffer(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let previous = self.swap_buffer(Buffer::new());
+        self.buffers.push(previous);
+
+        (true, vec![])
+    }
+
+    pub(crate) fn next_buffer(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.switch_to_buffer(1);
+
+        (true, vec![])
+    }
+
+    pub(crate) fn previous_buffer(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        if let Some(last) = self.buffers.pop() {
+            let previous = self.swap_buffer(last);
+            self.buffers.insert(0, previous);
+        }
+
+        (true, vec![])
+    }
+
+    // next buffer takes its place, last one is emptied instead
+    pub(crate) fn close_buffer(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        if self.modified {
+            return (
+                true,
+                vec![StateChangeRequest::error(
+                    locale::Text::new("edit.close_unsaved_buffer", "Buffer has unsaved changes. Save before closing."),
+                )],
+            );
+        }
+
+        let next = match self.buffers.is_empty() {
+            true => Buffer::new(),
+            false => self.buffers.remove(0),
+        };
+        self.swap_buffer(next);
+
+        (true, vec![])
+    }
+
+    pub(crate) fn list_buffers(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.state = PanelState::WaitingToSwitchBuffer;
+        (
+            true,
+            vec![StateChangeRequest::input_request_with_completer(
+                "Buffer".to_string(),
+                Box::new(BufferAutoCompleter::new(self.buffer_names())),
+            )],
+        )
+    }
+
+    pub(crate) fn open_project_file(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.state = PanelState::WaitingToOpen;
+        (
+            true,
+            vec![StateChangeRequest::input_request_with_completer(
+                "Project File".to_string(),
+                Box::new(ProjectFileAutoCompleter::new()),
+            )],
+        )
+    }
+
+    pub(crate) fn quick_open(
+        &mut self,
+        _code: KeyCode,
+        state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let recent = match env::current_dir() {
+            Ok(dir) => state.recent_files().names(&dir),
+            Err(_) => vec![],
+        };
+
+        self.state = PanelState::WaitingToOpen;
+        (
+            true,
+            vec![StateChangeRequest::input_request_with_completer(
+                "Quick Open".to_string(),
+                Box::new(RecentFilesAutoCompleter::new(recent)),
             )],
         )
     }
@@ -422,7 +1483,7 @@ impl TextPanel {
                     self.cursor_index_in_line = 0;
                     self.current_line += 1;
                 } else {
-                    self.cursor_index_in_line += 1;
+                    self.cursor_index_in_line = next_char_index(line, self.cursor_index_in_line);
                 }
             }
         }
@@ -437,7 +1498,10 @@ impl TextPanel {
         commands: &mut Manager,
     ) -> (bool, Vec<StateChangeRequest>) {
         if self.cursor_index_in_line > 0 {
-            self.cursor_index_in_line -= 1;
+            self.cursor_index_in_line = match self.lines.get(self.current_line) {
+                None => 0,
+                Some(line) => previous_char_index(line, self.cursor_index_in_line),
+            };
         } else if self.current_line > 0 {
             self.current_line -= 1;
             self.cursor_index_in_line = match self.lines.get(self.current_line) {
@@ -460,11 +1524,7 @@ impl TextPanel {
 
             match self.lines.get(self.current_line) {
                 None => self.cursor_index_in_line = 0,
-                Some(line) => {
-                    if self.cursor_index_in_line > line.len() {
-                        self.cursor_index_in_line = line.len();
-                    }
-                }
+                Some(line) => self.cursor_index_in_line = floor_char_index(line, self.cursor_index_in_line),
             }
         }
 
@@ -482,10 +1542,30 @@ impl TextPanel {
 
             match self.lines.get(self.current_line) {
                 None => self.cursor_index_in_line = 0,
-                Some(line) => {
-                    if self.cursor_index_in_line > line.len() {
-                        self.cursor_index_in_line = line.len();
+                Some(line) => self.cursor_index_in_line = floor_char_index(line, self.cursor_index_in_line),
+            }
+        }
+
+        (true, vec![])
+    }
+
+    pub(crate) fn move_to_next_word(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        match self.lines.get(self.current_line) {
+            None => self.cursor_index_in_line = 0,
+            Some(line) => {
+                if self.cursor_index_in_line >= line.len() {
+                    if self.current_line + 1 < self.lines.len() {
+                        self.current_line += 1;
+                        self.cursor_index_in_line = 0;
                     }
+                } else {
+                    self.cursor_index_in_line =
+                        next_word_index(line, self.cursor_index_in_line, self.sub_word_motions);
                 }
             }
         }
@@ -493,86 +1573,1468 @@ impl TextPanel {
         (true, vec![])
     }
 
-    fn scroll_down(&mut self, amount: u16) {
-        if self.scroll_y < u16::MAX - amount {
-            self.scroll_y += amount;
+    pub(crate) fn move_to_previous_word(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        if self.cursor_index_in_line == 0 {
+            if self.current_line > 0 {
+                self.current_line -= 1;
+                self.cursor_index_in_line = match self.lines.get(self.current_line) {
+                    None => 0,
+                    Some(line) => line.len(),
+                };
+            }
         } else {
-            self.scroll_y = u16::MAX;
+            match self.lines.get(self.current_line) {
+                None => self.cursor_index_in_line = 0,
+                Some(line) => {
+                    self.cursor_index_in_line = previous_word_index(
+                        line,
+                        self.cursor_index_in_line,
+                        self.sub_word_motions,
+                    )
+                }
+            }
         }
+
+        (true, vec![])
     }
 
-    fn scroll_up(&mut self, amount: u16) {
-        if self.scroll_y >= amount {
-            self.scroll_y -= amount;
-        } else {
-            self.scroll_y = 0;
+    pub(crate) fn delete_next_word(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let sub_word = self.sub_word_motions;
+        match self.lines.get_mut(self.current_line) {
+            None => (),
+            Some(line) => {
+                if self.cursor_index_in_line >= line.len() {
+                    // join next line like delete key
+                    if self.current_line + 1 < self.lines.len() {
+                        self.current_line += 1;
+                        self.remove_line();
+                    }
+                } else {
+                    let end = next_word_index(line, self.cursor_index_in_line, sub_word);
+                    line.replace_range(self.cursor_index_in_line..end, "");
+                }
+                self.mark_modified();
+            }
         }
+
+        (true, vec![])
     }
 
-    pub(crate) fn scroll_down_one(
+    pub(crate) fn delete_previous_word(
         &mut self,
         _code: KeyCode,
         _state: &mut AppState,
-        commands: &mut Manager,
+        _commands: &mut Manager,
     ) -> (bool, Vec<StateChangeRequest>) {
-        self.scroll_down(1);
+        if self.cursor_index_in_line == 0 {
+            // join with previous line like backspace key
+            self.remove_line();
+        } else {
+            let sub_word = self.sub_word_motions;
+            match self.lines.get_mut(self.current_line) {
+                None => (),
+                Some(line) => {
+                    let start = previous_word_index(line, self.cursor_index_in_line, sub_word);
+                    line.replace_range(start..self.cursor_index_in_line, "");
+                    self.cursor_index_in_line = start;
+                }
+            }
+        }
+        self.mark_modified();
+
         (true, vec![])
     }
 
-    pub(crate) fn scroll_up_one(
+    // swap characters around cursor and move forward
+    // at end of line, the two characters before cursor are swapped
+    pub(crate) fn transpose_characters(
         &mut self,
         _code: KeyCode,
         _state: &mut AppState,
-        commands: &mut Manager,
+        _commands: &mut Manager,
     ) -> (bool, Vec<StateChangeRequest>) {
-        self.scroll_up(1);
+        match self.lines.get_mut(self.current_line) {
+            None => (),
+            Some(line) => {
+                let mut chars: Vec<char> = line.chars().collect();
+                let cursor = self.cursor_index_in_line.min(line.len());
+                let position = line[..cursor].chars().count().min(chars.len());
+
+                if position > 0 && chars.len() > 1 {
+                    let right = if position == chars.len() { position - 1 } else { position };
+                    chars.swap(right - 1, right);
+                    *line = chars.iter().collect();
+
+                    self.cursor_index_in_line = chars.iter().take(right + 1).map(|c| c.len_utf8()).sum();
+                    self.mark_modified();
+                }
+            }
+        }
+
         (true, vec![])
     }
 
-    pub(crate) fn scroll_down_ten(
+    // swap current line with previous and move to next line
+    pub(crate) fn transpose_lines(
         &mut self,
         _code: KeyCode,
         _state: &mut AppState,
-        commands: &mut Manager,
+        _commands: &mut Manager,
     ) -> (bool, Vec<StateChangeRequest>) {
-        let limit = self.lines.len() as u16;
-        self.scroll_down(10);
+        if self.current_line > 0 && self.current_line < self.lines.len() {
+            self.lines.swap(self.current_line - 1, self.current_line);
 
-        if self.scroll_y > limit {
-            self.scroll_y = limit;
+            if self.current_line + 1 < self.lines.len() {
+                self.current_line += 1;
+            }
+
+            self.cursor_index_in_line = floor_char_index(&self.lines[self.current_line], self.cursor_index_in_line);
+            self.mark_modified();
         }
 
         (true, vec![])
     }
 
-    pub(crate) fn scroll_up_ten(
+    pub(crate) fn duplicate_line(
         &mut self,
         _code: KeyCode,
         _state: &mut AppState,
-        commands: &mut Manager,
+        _commands: &mut Manager,
     ) -> (bool, Vec<StateChangeRequest>) {
-        self.scroll_up(10);
+        match self.lines.get(self.current_line) {
+            None => (),
+            Some(line) => {
+                let copy = line.clone();
+                self.lines.insert(self.current_line + 1, copy);
+                self.current_line += 1;
+                self.mark_modified();
+            }
+        }
+
         (true, vec![])
     }
 
-    pub fn make_text_content(&self, text_content_box: Rect) -> (Vec<Spans>, (u16, u16), Vec<Spans>) {
-        let max_text_length = text_content_box.width as usize;
+    pub(crate) fn delete_line(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        if self.current_line < self.lines.len() {
+            self.lines.remove(self.current_line);
 
-        let (mut cursor_x, mut cursor_y) = CURSOR_MAX;
+            // always leave one line to edit
+            if self.lines.is_empty() {
+                self.lines.push(String::new());
+            }
 
-        let mut lines = vec![];
-        let mut gutter = vec![];
-        let mut real_line_count = self.scroll_y;
+            if self.current_line >= self.lines.len() {
+                self.current_line = self.lines.len() - 1;
+            }
 
-        for i in 0..(text_content_box.height) {
+            self.cursor_index_in_line = floor_char_index(&self.lines[self.current_line], self.cursor_index_in_line);
+            self.mark_modified();
+        }
+
+        (true, vec![])
+    }
+
+    pub(crate) fn move_line_up(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        if self.current_line > 0 && self.current_line < self.lines.len() {
+            self.lines.swap(self.current_line - 1, self.current_line);
+            self.current_line -= 1;
+            self.mark_modified();
+        }
+
+        (true, vec![])
+    }
+
+    pub(crate) fn move_line_down(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        if self.current_line + 1 < self.lines.len() {
+            self.lines.swap(self.current_line, self.current_line + 1);
+            self.current_line += 1;
+            self.mark_modified();
+        }
+
+        (true, vec![])
+    }
+
+    pub(crate) fn start_duplicate_selection(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.state = PanelState::WaitingToDuplicate;
+        (true, vec![StateChangeRequest::validated_input(
+            locale::Text::new("prompt.duplicate_count", "Duplicate Count")
+        , validate_count)])
+    }
+
+    pub(crate) fn start_copy_lines(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.state = PanelState::WaitingToCopyLines;
+        (
+            true,
+            vec![StateChangeRequest::validated_input(
+                locale::Text::new("prompt.copy_lines", "Copy Lines (start,end t destination)"),
+                validate_copy_lines,
+            )],
+        )
+    }
+
+    pub(crate) fn start_replace_all(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.state = PanelState::WaitingToReplace;
+        (
+            true,
+            vec![StateChangeRequest::InputSequence(vec![
+                locale::Text::new("prompt.find", "Find"),
+                locale::Text::new("prompt.replace_with", "Replace With"),
+            ])],
+        )
+    }
+
+    // marked block if there is one, otherwise asks for lines first
+    pub(crate) fn start_replace_in_range(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let mut prompts = vec![
+            locale::Text::new("prompt.find", "Find"),
+            locale::Text::new("prompt.replace_with", "Replace With"),
+        ];
+
+        self.state = match self.mark {
+            Some(_) => PanelState::WaitingToReplaceInSelection,
+            None => {
+                prompts.insert(0, locale::Text::new("prompt.replace_lines", "Lines (start,end)"));
+                PanelState::WaitingToReplaceInLines
+            }
+        };
+
+        (true, vec![StateChangeRequest::InputSequence(prompts)])
+    }
+
+    // returns number of replacements made
+    pub fn replace_all(&mut self, find: &str, with: &str) -> usize {
+        match self.lines.len() {
+            0 => 0,
+            length => self.replace_in(find, with, 0, length - 1, None),
+        }
+    }
+
+    // only inside columns of marked block, a block with no width covers its whole lines
+    pub fn replace_in_selection(&mut self, find: &str, with: &str) -> Result<usize, String> {
+        let (start_line, end_line, start_column, end_column) = match self.marked_block() {
+            None => return Err("No selection to replace in.".to_string()),
+            Some(block) => block,
+        };
+
+        let columns = match start_column == end_column {
+            true => None,
+            false => Some((start_column, end_column)),
+        };
+
+        let count = self.replace_in(find, with, start_line, end_line.min(self.lines.len().saturating_sub(1)), columns);
+        self.mark = None;
+
+        Ok(count)
+    }
+
+    // line numbers start at 1, same as copy_lines
+    pub fn replace_in_lines(&mut self, find: &str, with: &str, start: usize, end: usize) -> Result<usize, String> {
+        if start == 0 || start > end || end > self.lines.len() {
+            return Err(format!("Invalid line range {},{}.", start, end));
+        }
+
+        Ok(self.replace_in(find, with, start - 1, end - 1, None))
+    }
+
+    // every line is changed before anything else happens, so the whole replace is one edit
+    fn replace_in(
+        &mut self,
+        find: &str,
+        with: &str,
+        start_line: usize,
+        end_line: usize,
+        columns: Option<(usize, usize)>,
+    ) -> usize {
+        if find.is_empty() {
+            return 0;
+        }
+
+        let mut count = 0;
+        for line in self.lines.iter_mut().skip(start_line).take(end_line + 1 - start_line) {
+            let (start, end) = match columns {
+                None => (0, line.len()),
+                Some((start, end)) => (floor_char_index(line, start), floor_char_index(line, end)),
+            };
+
+            let found = line[start..end].matches(find).count();
+            if found > 0 {
+                let replaced = line[start..end].replace(find, with);
+                line.replace_range(start..end, &replaced);
+                count += found;
+            }
+        }
+
+        if count > 0 {
+            self.mark_modified();
+            self.clamp_cursor();
+        }
+
+        count
+    }
+
+    // insert marked lines, or current line, count times after themselves
+    // count is capped like a count prefix, so a huge one can't exhaust memory
+    pub fn duplicate_selection(&mut self, count: usize) {
+        let count = count.min(MAX_COUNT);
+        if self.lines.is_empty() {
+            return;
+        }
+
+        let (start, end) = match self.marked_block() {
+            Some((start_line, end_line, ..)) => (start_line, end_line.min(self.lines.len() - 1)),
+            None => (self.current_line, self.current_line),
+        };
+
+        if start >= self.lines.len() {
+            return;
+        }
+
+        let block: Vec<String> = self.lines.range(start..=end).cloned().collect();
+        let copies: Vec<String> = iter::repeat_n(block, count).flatten().collect();
+
+        if copies.is_empty() {
+            return;
+        }
+
+        self.lines.splice(end + 1..end + 1, copies);
+        self.current_line = end + 1;
+        self.cursor_index_in_line = self.cursor_index_in_line.min(self.lines[self.current_line].len());
+        self.mark = None;
+        self.mark_modified();
+    }
+
+    // copy range of lines below destination line, line numbers start at 1
+    // destination 0 copies to top of buffer
+    pub fn copy_lines(&mut self, start: usize, end: usize, destination: usize) -> Result<(), String> {
+        if start == 0 || start > end || end > self.lines.len() {
+            return Err(format!("Invalid line range {},{}.", start, end));
+        }
+
+        if destination > self.lines.len() {
+            return Err(format!("Invalid destination line {}.", destination));
+        }
+
+        let copied: Vec<String> = self.lines.range(start - 1..end).cloned().collect();
+        self.lines.splice(destination..destination, copied);
+
+        self.current_line = destination;
+        self.cursor_index_in_line = 0;
+        self.mark_modified();
+
+        Ok(())
+    }
+
+    pub(crate) fn toggle_sync_cursors(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.sync_cursors = !self.sync_cursors;
+        let message = match self.sync_cursors {
+            true => locale::Text::new("text.sync_cursors_on", "Sync cursors on"),
+            false => locale::Text::new("text.sync_cursors_off", "Sync cursors off"),
+        };
+
+        (true, vec![StateChangeRequest::info(message)])
+    }
+
+    pub(crate) fn toggle_modal_editing(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.edit_mode = match self.edit_mode {
+            EditMode::Modeless => EditMode::Normal,
+            EditMode::Normal | EditMode::Insert => EditMode::Modeless,
+        };
+
+        (
+            true,
+            vec![StateChangeRequest::info(match self.edit_mode {
+                EditMode::Modeless => "Modal editing off.",
+                _ => "Modal editing on. Press i to insert text and Escape to return to normal mode.",
+            })],
+        )
+    }
+
+    pub(crate) fn enter_insert_mode(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.edit_mode = EditMode::Insert;
+        (true, vec![])
+    }
+
+    // true when panel was inserting, Escape is used up returning to normal mode
+    pub fn leave_column_select(&mut self) -> bool {
+        let active = self.column_select();
+        self.column_select = false;
+        if active {
+            self.mark = None;
+        }
+
+        active
+    }
+
+    pub fn leave_insert_mode(&mut self) -> bool {
+        match self.edit_mode {
+            EditMode::Insert => {
+                self.edit_mode = EditMode::Normal;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub(crate) fn append(
+        &mut self,
+        code: KeyCode,
+        state: &mut AppState,
+        commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        if let Some(line) = self.lines.get(self.current_line) {
+            self.cursor_index_in_line = next_char_index(line, self.cursor_index_in_line);
+        }
+
+        self.enter_insert_mode(code, state, commands)
+    }
+
+    pub(crate) fn insert_at_line_start(
+        &mut self,
+        code: KeyCode,
+        state: &mut AppState,
+        commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        // after indentation
+        self.cursor_index_in_line = match self.lines.get(self.current_line) {
+            None => 0,
+            Some(line) => line.len() - line.trim_start_matches([' ', '\t']).len(),
+        };
+
+        self.enter_insert_mode(code, state, commands)
+    }
+
+    pub(crate) fn append_at_line_end(
+        &mut self,
+        code: KeyCode,
+        state: &mut AppState,
+        commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.move_to_line_end(code, state, commands);
+        self.enter_insert_mode(code, state, commands)
+    }
+
+    pub(crate) fn open_line_below(
+        &mut self,
+        code: KeyCode,
+        state: &mut AppState,
+        commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.move_to_line_end(code, state, commands);
+        self.enter_newline(&mut vec![]);
+        self.mark_modified();
+
+        self.enter_insert_mode(code, state, commands)
+    }
+
+    pub(crate) fn open_line_above(
+        &mut self,
+        code: KeyCode,
+        state: &mut AppState,
+        commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let indent: String = match self.lines.get(self.current_line) {
+            None => String::new(),
+            Some(line) => line.chars().take_while(|c| *c == ' ' || *c == '\t').collect(),
+        };
+
+        self.cursor_index_in_line = indent.len();
+        self.lines.insert(self.current_line.min(self.lines.len()), indent);
+        self.mark_modified();
+
+        self.enter_insert_mode(code, state, commands)
+    }
+
+    // zero is also a digit, only moves when no count is being typed
+    pub(crate) fn move_to_line_start_or_count(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        match commands.current_count() {
+            Some(_) => commands.push_count_digit(0),
+            None => self.cursor_index_in_line = 0,
+        }
+
+        (true, vec![])
+    }
+
+    pub(crate) fn move_to_line_end(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.cursor_index_in_line = self.lines.get(self.current_line).map(|line| line.len()).unwrap_or(0);
+        (true, vec![])
+    }
+
+    // bracket at cursor, otherwise one just before it, since cursor usually sits after a closing bracket that was typed
+    pub fn matching_bracket(&self) -> Option<(usize, usize)> {
+        let line = self.lines.get(self.current_line)?;
+
+        matching_bracket(&self.lines, self.current_line, self.cursor_index_in_line).or_else(|| {
+            match self.cursor_index_in_line {
+                0 => None,
+                index => matching_bracket(&self.lines, self.current_line, previous_char_index(line, index)),
+            }
+        })
+    }
+
+    pub(crate) fn jump_to_matching_bracket(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let (line, index) = match self.matching_bracket() {
+            None => return (true, vec![StateChangeRequest::info(
+                locale::Text::new("edit.no_matching_bracket", "No matching bracket."),
+            )]),
+            Some(position) => position,
+        };
+
+        self.record_jump();
+        self.current_line = line;
+        self.cursor_index_in_line = index;
+        self.show_line(line);
+
+        (true, vec![])
+    }
+
+    // viewport and scroll were for previous terminal size, until next render uses new one
+    pub fn fit_to_screen(&mut self, screen: Rect) {
+        self.viewport.set(self.viewport.get().intersection(screen));
+
+        self.clamp_cursor();
+        self.scroll_y = self.scroll_y.min(self.lines.len() as u16);
+        self.show_line(self.current_line);
+
+        // wrapped lines have no horizontal scroll
+        let width = self.viewport.get().width.saturating_sub(self.gutter_width()) as usize;
+        if !self.wrap && width > 0 {
+            let cursor_column = self.cursor_column();
+            let scroll_x = (self.scroll_x as usize)
+                .min(cursor_column)
+                .max(cursor_column.saturating_sub(width));
+            self.scroll_x = scroll_x.min(u16::MAX as usize) as u16;
+        }
+    }
+
+    // place line in middle of view when it's off screen
+    fn show_line(&mut self, line: usize) {
+        let scroll_y = self.scroll_y as usize;
+        if line < scroll_y || line >= scroll_y + self.page_height() as usize {
+            self.scroll_y = (line as u16).saturating_sub(self.page_height() / 2);
+        }
+    }
+
+    // call before moving cursor somewhere far, so jump back returns here
+    pub fn record_jump(&mut self) {
+        self.jumps.push((self.current_line, self.cursor_index_in_line));
+    }
+
+    // positions refer to old text once a different file is opened
+    pub fn clear_jumps(&mut self) {
+        self.jumps = JumpList::default();
+    }
+
+    // text may have shrunk since position was recorded
+    fn move_to_jump(&mut self, (line, column): (usize, usize)) {
+        self.current_line = line;
+        self.cursor_index_in_line = column;
+        self.clamp_cursor();
+        self.show_line(self.current_line);
+    }
+
+    pub(crate) fn jump_back(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        match self.jumps.back((self.current_line, self.cursor_index_in_line)) {
+            None => (true, vec![StateChangeRequest::info(locale::Text::new("edit.no_earlier_position", "No earlier position."))]),
+            Some(position) => {
+                self.move_to_jump(position);
+                (true, vec![])
+            }
+        }
+    }
+
+    pub(crate) fn jump_forward(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        match self.jumps.forward((self.current_line, self.cursor_index_in_line)) {
+            None => (true, vec![StateChangeRequest::info(locale::Text::new("edit.no_later_position", "No later position."))]),
+            Some(position) => {
+                self.move_to_jump(position);
+                (true, vec![])
+            }
+        }
+    }
+
+    pub(crate) fn go_to_line(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.state = PanelState::WaitingToGoToLine;
+        (true, vec![StateChangeRequest::validated_input(
+            locale::Text::new("prompt.go_to_line", "Go To Line")
+        , validate_line_number)])
+    }
+
+    // line numbers start at 1 like the gutter, past the end goes to last line
+    pub fn go_to_line_number(&mut self, number: usize) {
+        self.record_jump();
+        self.move_to_jump((number.saturating_sub(1), 0));
+    }
+
+    // unlike Delete key, doesn't join next line at end of line
+    pub(crate) fn delete_character(
+        &mut self,
+        _code: KeyCode,
+        state: &mut AppState,
+        commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        match self.lines.get(self.current_line) {
+            Some(line) if self.cursor_index_in_line < line.len() => self.handle_key_stroke(KeyCode::Delete, state, commands),
+            _ => (true, vec![]),
+        }
+    }
+
+    // bring lines in from another view of the same file
+    // cursor is kept within new bounds unless synced
+    // nothing is copied when other hasn't changed since last time, cursor movement alone only syncs cursor
+    pub(crate) fn take_edits_from(&mut self, other: &TextPanel) {
+        if self.revision != other.revision {
+            self.lines = other.lines.clone();
+            self.modified = other.modified;
+            self.title = other.title.clone();
+            self.file_path = other.file_path.clone();
+            // a save through other panel isn't a change by another program
+            self.disk_time = other.disk_time;
+            self.reported_disk_time = other.reported_disk_time;
+            self.revision = other.revision;
+        }
+
+        if other.sync_cursors {
+            self.current_line = other.current_line;
+            self.cursor_index_in_line = other.cursor_index_in_line;
+        } else {
+            self.clamp_cursor();
+        }
+    }
+
+    // keeps cursor inside text after it was replaced
+    pub(crate) fn clamp_cursor(&mut self) {
+        self.current_line = self.current_line.min(self.lines.len().saturating_sub(1));
+        self.cursor_index_in_line = match self.lines.get(self.current_line) {
+            Some(line) => floor_char_index(line, self.cursor_index_in_line),
+            None => 0,
+        };
+    }
+
+    pub(crate) fn host_session(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        (true, vec![StateChangeRequest::custom(HostSession)])
+    }
+
+    pub(crate) fn join_session(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.state = PanelState::WaitingToJoinSession;
+
+        (true, vec![StateChangeRequest::Input(locale::Text::new("prompt.session_address", "Session Address"), None)])
+    }
+
+    pub(crate) fn export_buffer(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.state = PanelState::WaitingToExport;
+
+        (
+            true,
+            vec![StateChangeRequest::input_request_with_completer(
+                "Export To (.html for HTML, otherwise ANSI text)".to_string(),
+                Box::new(FileAutoCompleter::new()),
+            )],
+        )
+    }
+
+    pub(crate) fn compare(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.state = PanelState::WaitingToCompare;
+
+        (
+            true,
+            vec![StateChangeRequest::Input(
+                locale::Text::new("prompt.compare_with", "Compare With ('{0}', panel ID or file)").arg(CLIPBOARD_TARGET),
+                None,
+            )],
+        )
+    }
+
+    pub(crate) fn start_checkpoint(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.state = PanelState::WaitingToCheckpoint;
+
+        (true, vec![StateChangeRequest::Input(locale::Text::new("prompt.checkpoint_label", "Checkpoint Label"), None)])
+    }
+
+    pub(crate) fn start_restore_checkpoint(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.request_checkpoint(PanelState::WaitingToRestoreCheckpoint, "Restore Checkpoint")
+    }
+
+    pub(crate) fn start_compare_checkpoint(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.request_checkpoint(PanelState::WaitingToCompareCheckpoint, "Compare With Checkpoint")
+    }
+
+    fn request_checkpoint(&mut self, state: PanelState, prompt: &str) -> (bool, Vec<StateChangeRequest>) {
+        if self.checkpoints.is_empty() {
+            return (true, vec![StateChangeRequest::error(
+                locale::Text::new("edit.no_checkpoints", "No checkpoints for this buffer."),
+            )]);
+        }
+
+        self.state = state;
+
+        (
+            true,
+            vec![StateChangeRequest::input_request_with_completer(
+                prompt.to_string(),
+                Box::new(BufferAutoCompleter::new(self.checkpoint_labels())),
+            )],
+        )
+    }
+
+    pub fn checkpoint_labels(&self) -> Vec<String> {
+        self.checkpoints.iter().map(|checkpoint| checkpoint.label.clone()).collect()
+    }
+
+    pub fn checkpoint(&self, label: &str) -> Option<&Lines> {
+        self.checkpoints
+            .iter()
+            .find(|checkpoint| checkpoint.label == label)
+            .map(|checkpoint| &checkpoint.lines)
+    }
+
+    // reusing a label replaces its snapshot
+    pub fn add_checkpoint(&mut self, label: &str) {
+        self.checkpoints.retain(|checkpoint| checkpoint.label != label);
+        self.checkpoints.push(Checkpoint {
+            label: label.to_string(),
+            lines: self.lines.clone(),
+        });
+    }
+
+    // checkpoint is kept, so it can be restored again
+    pub fn restore_checkpoint(&mut self, label: &str) -> Result<(), String> {
+        let lines = match self.checkpoint(label) {
+            None => return Err(format!("No checkpoint named {:?}.", label)),
+            Some(lines) => lines.clone(),
+        };
+
+        if lines != self.lines {
+            self.lines = lines;
+            self.mark_modified();
+        }
+
+        self.mark = None;
+        self.clamp_cursor();
+
+        Ok(())
+    }
+
+    pub fn todo_lines(&self) -> &Vec<usize> {
+        &self.todo_lines
+    }
+
+    // work too slow to do on every key, run by AppState once input stops
+    pub fn refresh_analysis(&mut self) {
+        self.todo_lines = todo_lines(&self.lines);
+    }
+
+    // next marked line after cursor, wrapping to first
+    pub(crate) fn next_todo(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let next = self
+            .todo_lines
+            .iter()
+            .find(|line| **line > self.current_line)
+            .or_else(|| self.todo_lines.first())
+            .copied();
+
+        match next {
+            None => (true, vec![StateChangeRequest::info(locale::Text::new("edit.no_todos", "No TODOs found."))]),
+            Some(line) => {
+                self.record_jump();
+                self.current_line = line;
+                self.cursor_index_in_line = 0;
+                self.clamp_cursor();
+                self.show_line(self.current_line);
+                (true, vec![])
+            }
+        }
+    }
+
+    pub(crate) fn leave_session(
+        &mut self,
+        _code: KeyCode,
+        state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        match state.share_session().is_some() {
+            true => {
+                state.set_share_session(None);
+                (true, vec![StateChangeRequest::info(locale::Text::new("edit.left_shared_session", "Left shared session."))])
+            }
+            false => (true, vec![StateChangeRequest::info(
+                locale::Text::new("edit.no_shared_session", "No shared session to leave."),
+            )]),
+        }
+    }
+
+    pub(crate) fn toggle_sub_word_motions(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.sub_word_motions = !self.sub_word_motions;
+
+        (
+            true,
+            vec![StateChangeRequest::info(match self.sub_word_motions {
+                true => "Word motions will stop at camel case humps and underscores.",
+                false => "Word motions will stop at whole words.",
+            })],
+        )
+    }
+
+    fn scroll_down(&mut self, amount: u16) {
+        if self.scroll_y < u16::MAX - amount {
+            self.scroll_y += amount;
+        } else {
+            self.scroll_y = u16::MAX;
+        }
+    }
+
+    fn scroll_up(&mut self, amount: u16) {
+        if self.scroll_y >= amount {
+            self.scroll_y -= amount;
+        } else {
+            self.scroll_y = 0;
+        }
+    }
+
+    pub(crate) fn scroll_down_one(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.scroll_down(1);
+        (true, vec![])
+    }
+
+    pub(crate) fn scroll_up_one(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.scroll_up(1);
+        (true, vec![])
+    }
+
+    pub(crate) fn scroll_down_ten(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.scroll_down_limited(10);
+        (true, vec![])
+    }
+
+    pub(crate) fn scroll_up_ten(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.scroll_up(10);
+        (true, vec![])
+    }
+
+    fn scroll_down_limited(&mut self, amount: u16) {
+        let limit = self.lines.len() as u16;
+        self.scroll_down(amount);
+
+        if self.scroll_y > limit {
+            self.scroll_y = limit;
+        }
+    }
+
+    pub(crate) fn scroll_down_page(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.scroll_down_limited(self.page_height());
+        (true, vec![])
+    }
+
+    pub(crate) fn scroll_up_page(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.scroll_up(self.page_height());
+        (true, vec![])
+    }
+
+    pub(crate) fn scroll_down_half_page(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.scroll_down_limited((self.page_height() / 2).max(1));
+        (true, vec![])
+    }
+
+    pub(crate) fn scroll_up_half_page(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.scroll_up((self.page_height() / 2).max(1));
+        (true, vec![])
+    }
+
+    // lines and columns of the rectangle between mark and cursor
+    // lines are inclusive, end column is exclusive
+    fn marked_block(&self) -> Option<(usize, usize, usize, usize)> {
+        self.mark.map(|(mark_line, mark_index)| {
+            (
+                mark_line.min(self.current_line),
+                mark_line.max(self.current_line),
+                mark_index.min(self.cursor_index_in_line),
+                mark_index.max(self.cursor_index_in_line),
+            )
+        })
+    }
+
+    // column counts characters, so multi byte characters on one line don't shift text on others
+    fn insert_at_column(&mut self, line_index: usize, column: usize, text: &str) {
+        while self.lines.len() <= line_index {
+            self.lines.push(String::new());
+        }
+
+        let line = &mut self.lines[line_index];
+
+        // pad short lines so text lands in the same column on every line
+        let length = line.chars().count();
+        if length < column {
+            line.extend(iter::repeat_n(' ', column - length));
+        }
+
+        let index = line.char_indices().nth(column).map_or(line.len(), |(index, _)| index);
+        line.insert_str(index, text);
+    }
+
+    // block and scroll columns count characters, cursor index counts bytes
+    fn cursor_column(&self) -> usize {
+        self.lines
+            .get(self.current_line)
+            .map_or(0, |line| char_column(line, self.cursor_index_in_line))
+    }
+
+    pub(crate) fn toggle_mark(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.column_select = false;
+        self.mark = match self.mark {
+            Some(_) => None,
+            None => Some((self.current_line, self.cursor_index_in_line)),
+        };
+
+        (true, vec![])
+    }
+
+    pub(crate) fn copy_block(
+        &mut self,
+        _code: KeyCode,
+        state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let copied = match self.marked_block() {
+            // no mark, copy entire line
+            None => match self.lines.get(self.current_line) {
+                None => vec![],
+                Some(line) => vec![line.clone()],
+            },
+            Some((start_line, end_line, start_column, end_column)) => self
+                .lines
+                .iter()
+                .skip(start_line)
+                .take(end_line - start_line + 1)
+                .map(|line| {
+                    let start = floor_char_index(line, start_column);
+                    let end = floor_char_index(line, end_column);
+                    line[start..end].to_string()
+                })
+                .collect(),
+        };
+
+        state.set_clipboard(copied);
+        self.mark = None;
+
+        (true, vec![])
+    }
+
+    pub(crate) fn paste_block(
+        &mut self,
+        _code: KeyCode,
+        state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let column = self.cursor_column();
+        for (i, line) in state.clipboard().iter().enumerate() {
+            self.insert_at_column(self.current_line + i, column, line);
+        }
+
+        // cursor ends after text inserted into current line
+        if let Some(first) = state.clipboard().first() {
+            self.cursor_index_in_line += first.len();
+            self.mark_modified();
+        }
+
+        (true, vec![])
+    }
+
+    pub(crate) fn insert_on_marked_lines(
+        &mut self,
+        _code: KeyCode,
+        state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let (start_line, end_line) = match self.marked_block() {
+            None => return (true, vec![StateChangeRequest::info(locale::Text::new("edit.no_lines_marked", "No lines marked."))]),
+            Some((start_line, end_line, ..)) => (start_line, end_line),
+        };
+
+        let text = match state.clipboard().first() {
+            None => return (true, vec![]),
+            Some(text) => text.clone(),
+        };
+
+        let column = self.cursor_column();
+        for line_index in start_line..=end_line {
+            self.insert_at_column(line_index, column, text.as_str());
+        }
+
+        self.cursor_index_in_line += text.len();
+        self.mark = None;
+        self.mark_modified();
+
+        (true, vec![])
+    }
+
+    // marks cursor when nothing is marked, moving cursor then grows the block
+    pub(crate) fn toggle_column_select(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        if !self.leave_column_select() {
+            self.column_select = true;
+            if self.mark.is_none() {
+                self.mark = Some((self.current_line, self.cursor_index_in_line));
+            }
+        }
+
+        (true, vec![])
+    }
+
+    pub(crate) fn delete_block(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        match self.remove_block() {
+            false => (true, vec![StateChangeRequest::info(locale::Text::new("edit.no_block_marked", "No block marked."))]),
+            true => {
+                self.mark = None;
+                self.column_select = false;
+                (true, vec![])
+            }
+        }
+    }
+
+    // removes columns of marked block from each of its lines
+    // leaves cursor and mark on the block's start column, so block has no width after
+    fn remove_block(&mut self) -> bool {
+        let (start_line, end_line, start_column, end_column) = match self.marked_block() {
+            None => return false,
+            Some(block) => block,
+        };
+
+        for line in self.lines.iter_mut().take(end_line + 1).skip(start_line) {
+            let start = floor_char_index(line, start_column);
+            let end = floor_char_index(line, end_column);
+            line.replace_range(start..end, "");
+        }
+
+        if end_column > start_column {
+            self.mark_modified();
+        }
+
+        self.set_block_column(start_column);
+        true
+    }
+
+    fn set_block_column(&mut self, column: usize) {
+        self.cursor_index_in_line = column;
+        if let Some((_, mark_column)) = self.mark.as_mut() {
+            *mark_column = column;
+        }
+    }
+
+    // same key applied at block's column on every marked line
+    // a block with width is removed first, like typing over a selection
+    fn column_key_stroke(&mut self, code: KeyCode) -> (bool, Vec<StateChangeRequest>) {
+        let (start_line, end_line, start_column, end_column) = match self.marked_block() {
+            None => return (false, vec![]),
+            Some(block) => block,
+        };
+
+        match code {
+            KeyCode::Char(c) => {
+                self.remove_block();
+                let column = self.cursor_column();
+                for line_index in start_line..=end_line {
+                    self.insert_at_column(line_index, column, &c.to_string());
+                }
+
+                let line = &self.lines[self.current_line];
+                self.set_block_column(line.char_indices().nth(column + 1).map_or(line.len(), |(index, _)| index));
+            }
+            KeyCode::Backspace | KeyCode::Delete if end_column > start_column => {
+                self.remove_block();
+            }
+            KeyCode::Backspace => {
+                if start_column == 0 {
+                    return (true, vec![]);
+                }
+
+                // short lines end before the column and are left alone
+                let mut column = start_column;
+                for line in self.lines.iter_mut().take(end_line + 1).skip(start_line) {
+                    if line.len() >= start_column && line.is_char_boundary(start_column) {
+                        let previous = floor_char_index(line, start_column - 1);
+                        line.replace_range(previous..start_column, "");
+                        column = column.min(previous);
+                    }
+                }
+                self.set_block_column(column);
+            }
+            KeyCode::Delete => {
+                for line in self.lines.iter_mut().take(end_line + 1).skip(start_line) {
+                    if line.len() > start_column && line.is_char_boundary(start_column) {
+                        let next = next_char_index(line, start_column);
+                        line.replace_range(start_column..next, "");
+                    }
+                }
+            }
+            // finishes column edit instead of splitting only cursor line
+            KeyCode::Enter => {
+                self.leave_column_select();
+                return (true, vec![]);
+            }
+            _ => return (false, vec![]),
+        }
+
+        self.mark_modified();
+        (true, vec![])
+    }
+
+    pub(crate) fn set_ruler_column(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.state = PanelState::WaitingToSetRuler;
+        (
+            true,
+            vec![StateChangeRequest::validated_input(
+                locale::Text::new("prompt.ruler_column", "Ruler Column (0 to hide)")
+            , validate_count)],
+        )
+    }
+
+    // rewrap marked lines, or paragraph around cursor, to ruler width
+    pub(crate) fn reflow(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        let (start, end) = match self.marked_block() {
+            Some((start_line, end_line, ..)) => (start_line, end_line.min(self.lines.len().saturating_sub(1))),
+            None => {
+                let is_blank = |line: &String| line.trim().is_empty();
+                match self.lines.get(self.current_line) {
+                    None => return (true, vec![]),
+                    Some(line) if is_blank(line) => {
+                        return (true, vec![StateChangeRequest::info(
+                            locale::Text::new("edit.no_paragraph_at_cursor", "No paragraph at cursor."),
+                        )])
+                    }
+                    Some(_) => (),
+                }
+
+                let mut start = self.current_line;
+                while start > 0 && !is_blank(&self.lines[start - 1]) {
+                    start -= 1;
+                }
+
+                let mut end = self.current_line;
+                while end + 1 < self.lines.len() && !is_blank(&self.lines[end + 1]) {
+                    end += 1;
+                }
+
+                (start, end)
+            }
+        };
+
+        if start >= self.lines.len() {
+            return (true, vec![]);
+        }
+
+        let indent: String = self.lines[start]
+            .chars()
+            .take_while(|c| c.is_whitespace())
+            .collect();
+
+        let words: Vec<String> = self.lines
+            .range(start..=end)
+            .flat_map(|line| line.split_whitespace().map(|w| w.to_string()))
+            .collect();
+
+        let wrapped = wrap_words(&indent, &words, self.ruler.unwrap_or(DEFAULT_WRAP_WIDTH));
+        let wrapped_len = wrapped.len();
+
+        self.lines.splice(start..=end, wrapped);
+
+        self.current_line = start + wrapped_len - 1;
+        self.cursor_index_in_line = self.lines[self.current_line].len();
+        self.mark = None;
+        self.mark_modified();
+
+        (true, vec![])
+    }
+
+    pub(crate) fn scroll_left_one(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.scroll_x = self.scroll_x.saturating_sub(1);
+        (true, vec![])
+    }
+
+    pub(crate) fn scroll_right_one(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.scroll_x = self.scroll_x.saturating_add(1);
+        (true, vec![])
+    }
+
+    pub(crate) fn scroll_left_ten(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.scroll_x = self.scroll_x.saturating_sub(10);
+        (true, vec![])
+    }
+
+    pub(crate) fn scroll_right_ten(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.scroll_x = self.scroll_x.saturating_add(10);
+        (true, vec![])
+    }
+
+    pub(crate) fn toggle_wrap(
+        &mut self,
+        _code: KeyCode,
+        _state: &mut AppState,
+        _commands: &mut Manager,
+    ) -> (bool, Vec<StateChangeRequest>) {
+        self.wrap = !self.wrap;
+        self.scroll_x = 0;
+        (true, vec![])
+    }
+
+    pub fn make_text_content(&self, text_content_box: Rect) -> (Vec<Spans>, (u16, u16), Vec<Spans>) {
+        let max_text_length = text_content_box.width as usize;
+
+        let (mut cursor_x, mut cursor_y) = CURSOR_MAX;
+
+        let mut lines = vec![];
+        let mut gutter = vec![];
+        let mut real_line_count = self.scroll_y;
+
+        for i in 0..(text_content_box.height) {
             let true_index = (i + self.scroll_y) as usize;
             real_line_count += 1;
 
             match self.lines.get(true_index) {
                 None => (), // empty
+                Some(line) if !self.wrap => {
+                    // clip to window starting at horizontal scroll
+                    let visible: String = line
+                        .chars()
+                        .skip(self.scroll_x as usize)
+                        .take(max_text_length)
+                        .collect();
+
+                    lines.push(match self.show_whitespace {
+                        true => Spans::from(whitespace_spans(&visible)),
+                        false => Spans::from(visible),
+                    });
+                    gutter.push(Spans::from(Span::from(real_line_count.to_string())));
+
+                    if true_index == self.current_line {
+                        let scroll_x = self.scroll_x as usize;
+                        let cursor_column = self.cursor_column();
+                        if cursor_column >= scroll_x && cursor_column - scroll_x <= max_text_length {
+                            cursor_y = text_content_box.y + lines.len() as u16 - 1;
+                            cursor_x = text_content_box.x + (cursor_column - scroll_x) as u16;
+                        }
+                    }
+                }
                 Some(line) => {
                     if line.len() < max_text_length {
-                        lines.push(Spans::from(line.as_str()));
+                        lines.push(Spans::from(self.text_spans(line)));
                         gutter.push(Spans::from(Span::from(real_line_count.to_string())));
 
                         if true_index == self.current_line {
@@ -584,23 +3046,25 @@ impl TextPanel {
                         let (mut current, mut next) = line.split_at(max_text_length);
                         let continuation_length = max_text_length - self.continuation_marker.len();
 
-                        lines.push(Spans::from(Span::from(current)));
+                        lines.push(Spans::from(self.text_spans(current)));
                         gutter.push(Spans::from(Span::from(real_line_count.to_string())));
 
                         while next.len() >= continuation_length {
                             (current, next) = next.split_at(continuation_length);
 
-                            lines.push(Spans::from(vec![
-                                Span::from(self.continuation_marker.as_str()),
-                                Span::from(current),
-                            ]));
+                            lines.push(Spans::from(
+                                iter::once(Span::from(self.continuation_marker.as_str()))
+                                    .chain(self.text_spans(current))
+                                    .collect::<Vec<Span>>(),
+                            ));
                             gutter.push(Spans::from(Span::from(".")));
                         }
 
-                        lines.push(Spans::from(vec![
-                            Span::from(self.continuation_marker.as_str()),
-                            Span::from(next),
-                        ]));
+                        lines.push(Spans::from(
+                            iter::once(Span::from(self.continuation_marker.as_str()))
+                                .chain(self.text_spans(next))
+                                .collect::<Vec<Span>>(),
+                        ));
                         gutter.push(Spans::from(Span::from(".")));
 
                         if true_index == self.current_line {
@@ -629,6 +3093,54 @@ impl TextPanel {
         (lines, (cursor_x, cursor_y), gutter)
     }
 
+    fn text_spans<'a>(&self, text: &'a str) -> Vec<Span<'a>> {
+        match self.show_whitespace {
+            true => whitespace_spans(text),
+            false => vec![Span::from(text)],
+        }
+    }
+
+    // where make_text_content draws character at line and byte index, None when it's out of view
+    pub fn screen_position(&self, line_index: usize, index: usize, text_content_box: Rect) -> Option<(u16, u16)> {
+        let scroll_y = self.scroll_y as usize;
+        if line_index >= self.lines.len() || line_index < scroll_y {
+            return None;
+        }
+
+        let max_text_length = text_content_box.width as usize;
+        let (row, column) = match self.wrap {
+            // middle of a multi byte character has no cell of its own
+            false if !self.lines[line_index].is_char_boundary(index.min(self.lines[line_index].len())) => return None,
+            false => match char_column(&self.lines[line_index], index).checked_sub(self.scroll_x as usize) {
+                Some(column) if column < max_text_length => (line_index - scroll_y, column),
+                _ => return None,
+            },
+            true => {
+                let continuation_length = max_text_length.checked_sub(self.continuation_marker.len()).filter(|l| *l > 0)?;
+                let rows_before: usize = self.lines
+                    .range(scroll_y..line_index)
+                    .map(|line| match line.len() < max_text_length {
+                        true => 1,
+                        false => 2 + (line.len() - max_text_length) / continuation_length,
+                    })
+                    .sum();
+
+                match index.checked_sub(max_text_length) {
+                    None => (rows_before, index),
+                    Some(past) => (
+                        rows_before + 1 + past / continuation_length,
+                        self.continuation_marker.len() + past % continuation_length,
+                    ),
+                }
+            }
+        };
+
+        match row < text_content_box.height as usize {
+            true => Some((text_content_box.x + column as u16, text_content_box.y + row as u16)),
+            false => None,
+        }
+    }
+
     pub(crate) fn save_buffer(
         &mut self,
         _code: KeyCode,
@@ -638,9 +3150,253 @@ impl TextPanel {
         (true, self.save())
     }
 
+    // trailing newline in file is kept as an empty last line, so joining gives back same text
+    pub fn contents(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    // save time clean up, buffer is changed too so it matches what was written
+    fn prepare_for_save(&mut self) {
+        if self.loading() {
+            return;
+        }
+
+        if self.trim_trailing_whitespace {
+            let mut trimmed_any = false;
+            for line in self.lines.iter_mut() {
+                let trimmed = line.trim_end().len();
+                if trimmed < line.len() {
+                    line.truncate(trimmed);
+                    trimmed_any = true;
+                }
+            }
+
+            if trimmed_any {
+                self.mark_modified();
+            }
+        }
+
+        // exactly one, extra blank lines at end are dropped
+        if self.final_newline {
+            let before = self.lines.len();
+            while self.lines.len() > 1 && self.lines.last().is_some_and(|line| line.is_empty()) {
+                self.lines.pop();
+            }
+
+            if self.lines.last().is_some_and(|line| !line.is_empty()) {
+                self.lines.push(String::new());
+            }
+
+            if self.lines.len() != before {
+                self.mark_modified();
+            }
+        }
+
+        self.clamp_cursor();
+    }
+
+    pub fn terminal(&self) -> Option<&Terminal> {
+        self.terminal.as_ref()
+    }
+
+    pub fn terminal_mut(&mut self) -> Option<&mut Terminal> {
+        self.terminal.as_mut()
+    }
+
+    pub fn command_output(&self) -> Option<&CommandOutput> {
+        self.command_output.as_ref()
+    }
+
+    pub fn command_output_mut(&mut self) -> Option<&mut CommandOutput> {
+        self.command_output.as_mut()
+    }
+
+    // previous command is stopped when its output is replaced
+    pub fn set_command_output(&mut self, command_output: Option<CommandOutput>) {
+        self.command_output = command_output;
+    }
+
+    pub fn diff(&self) -> Option<&DiffView> {
+        self.diff.as_ref()
+    }
+
+    pub fn diff_mut(&mut self) -> Option<&mut DiffView> {
+        self.diff.as_mut()
+    }
+
+    pub fn set_diff(&mut self, diff: Option<DiffView>) {
+        self.diff = diff;
+    }
+
+    pub fn hex(&self) -> Option<&HexView> {
+        self.hex.as_ref()
+    }
+
+    pub fn set_hex(&mut self, hex: Option<HexView>) {
+        self.hex = hex;
+    }
+
+    pub fn loading(&self) -> bool {
+        self.loader.is_some()
+    }
+
+    // bytes loaded and total bytes
+    pub fn loading_progress(&self) -> Option<(u64, u64)> {
+        self.loader.as_ref().map(|loader| loader.progress())
+    }
+
+    // replaces contents with what the loader reads over following updates
+    pub fn start_loading(&mut self, loader: FileLoader) {
+        self.lines = Lines::new();
+        self.current_line = 0;
+        self.cursor_index_in_line = 0;
+        self.loader = Some(loader);
+        self.touch();
+    }
+
+    pub fn stop_loading(&mut self) {
+        self.loader = None;
+    }
+
+    // loading continues while hidden, panel's own update only runs when visible
+    pub fn update(&mut self) -> Vec<StateChangeRequest> {
+        let mut changes = self.load_next_chunk();
+        if self.visible {
+            changes.extend((self.update_handler)(self));
+        }
+
+        changes
+    }
+
+    // editor is closing, quit was already confirmed
+    pub fn shutdown(&mut self) {
+        (self.shutdown_handler)(self)
+    }
+
+    fn load_next_chunk(&mut self) -> Vec<StateChangeRequest> {
+        let loader = match self.loader.as_mut() {
+            None => return vec![],
+            Some(loader) => loader,
+        };
+
+        match loader.read_chunk(LINES_PER_CHUNK) {
+            Err(err) => {
+                self.loader = None;
+                vec![StateChangeRequest::error(locale::Text::new("text.loading_stopped", "Stopped loading file. {0}").arg(err))]
+            }
+            Ok((lines, done)) => {
+                self.lines.extend(lines);
+                self.line_ending = loader.line_ending();
+                self.revision = next_revision();
+
+                if done {
+                    self.loader = None;
+                    vec![StateChangeRequest::info(
+                        locale::Text::new("text.loading_finished", "Finished loading {0} lines.").arg(self.lines.len()),
+                    )]
+                } else {
+                    vec![]
+                }
+            }
+        }
+    }
+
+    // write to current file path without prompting
+    pub fn write_file(&mut self) -> Result<(), String> {
+        let file_path = match &self.file_path {
+            None => return Err("No file path to write to.".to_string()),
+            Some(path) => path.clone(),
+        };
+
+        // would cut off the part that isn't loaded yet
+        if self.loading() {
+            return Err("File is still loading.".to_string());
+        }
+
+        if let Some(dir) = missing_directory(&file_path) {
+            return Err(missing_directory_message(&dir));
+        }
+
+        // only an explicit save can decide which version wins
+        if self.changed_on_disk() {
+            return Err(format!("{:?} changed on disk, save it to choose which version to keep.", file_path));
+        }
+
+        let text = self.lines.join(self.line_ending.as_str());
+        write_atomic(&file_path, &encode(&text, self.bom, self.keep_bom))?;
+        self.record_disk_time();
+        self.set_modified(false);
+        remove_swap(&file_path);
+
+        Ok(())
+    }
+
+    fn read_only(&self) -> bool {
+        self.file_path
+            .as_ref()
+            .and_then(|path| fs::metadata(path).ok())
+            .map(|metadata| metadata.permissions().readonly())
+            .unwrap_or(false)
+    }
+
+    // only explicit saves go over the network, autosave keeps to the local copy
+    fn upload_saved(&self) -> Option<StateChangeRequest> {
+        match (&self.remote, &self.file_path, self.modified) {
+            (Some(remote), Some(local), false) => Some(StateChangeRequest::custom(
+                UploadRemote::new(remote.clone(), local.clone()),
+            )),
+            _ => None,
+        }
+    }
+
+    // every modified buffer in panel that can be written without asking anything
+    // returns count saved and names of those left, e.g. untitled, read-only or changed on disk
+    pub fn save_all_buffers(&mut self) -> (usize, Vec<String>, Vec<StateChangeRequest>) {
+        let mut saved = 0;
+        let mut skipped = vec![];
+        let mut changes = vec![];
+
+        // rotating through every buffer ends back on current one
+        for _ in 0..self.buffer_count() {
+            if self.modified {
+                match self.file_path.is_some() && !self.read_only() && !self.changed_on_disk() {
+                    false => skipped.push(buffer_name(&self.title, self.file_path.as_ref())),
+                    true => {
+                        self.prepare_for_save();
+
+                        match self.write_file() {
+                            Err(err) => changes.push(StateChangeRequest::error(err)),
+                            Ok(()) => saved += 1,
+                        }
+
+                        changes.extend(self.upload_saved());
+                    }
+                }
+            }
+
+            self.switch_to_buffer(1);
+        }
+
+        (saved, skipped, changes)
+    }
+
     pub fn save(&mut self) -> Vec<StateChangeRequest> {
         let mut changes = vec![];
 
+        if self.read_only() {
+            self.state = PanelState::WaitingToSave;
+            return vec![
+                StateChangeRequest::error(
+                    locale::Text::new("text.read_only", "{0} is read-only. Save to a different file or make it writable (e.g. chmod u+w).")
+                        .arg(format!("{:?}", self.file_path.as_ref().unwrap())),
+                ),
+                StateChangeRequest::input_request_with_completer(
+                    "Save As".to_string(),
+                    Box::new(FileAutoCompleter::new()),
+                ),
+            ];
+        }
+
         match &self.file_path {
             None => {
                 self.state = PanelState::WaitingToSave;
@@ -649,48 +3405,415 @@ impl TextPanel {
                     Box::new(FileAutoCompleter::new()),
                 )];
             }
+            Some(_) if self.changed_on_disk() => {
+                changes.extend(self.ask_about_disk_change(true));
+            }
             Some(file_path) => {
-                changes.push(StateChangeRequest::info(format!(
-                    "Saving file to {:?}",
-                    file_path
-                )));
-
-                match File::options()
-                    .write(true)
-                    .create(true)
-                    .truncate(true)
-                    .open(file_path)
-                {
-                    Err(err) => {
-                        changes.push(StateChangeRequest::error(format!(
-                            "Could not open file to save. {}",
-                            err.to_string()
-                        )));
-                    }
-                    Ok(mut file) => {
-                        self.lines.iter().for_each(|line| {
-                            match file.write(line.as_bytes()) {
-                                Err(err) => changes.push(StateChangeRequest::error(format!(
-                                    "Could not write to file. {}",
-                                    err.to_string()
-                                ))),
-                                Ok(_) => (),
-                            }
-                            match file.write("\n".as_bytes()) {
-                                Err(err) => changes.push(StateChangeRequest::error(format!(
-                                    "Could not write to file. {}",
-                                    err.to_string()
-                                ))),
-                                Ok(_) => (),
-                            }
-                        });
+                changes.push(StateChangeRequest::info(
+                    locale::Text::new("text.saving", "Saving file to {0}").arg(format!("{:?}", file_path)),
+                ));
 
-                        changes.push(StateChangeRequest::info("Save complete."));
-                    }
+                self.prepare_for_save();
+
+                match self.write_file() {
+                    Err(err) => changes.push(StateChangeRequest::error(err)),
+                    Ok(()) => changes.push(StateChangeRequest::info(locale::Text::new("edit.save_complete", "Save complete."))),
                 }
+
+                changes.extend(self.upload_saved());
             }
         }
 
         changes
     }
-}
\ No newline at end of file
+}
+
+// first directory leading to path that doesn't exist or isn't a directory
+pub(crate) fn missing_directory(path: &Path) -> Option<PathBuf> {
+    let parent = path.parent()?;
+    let mut ancestors = parent.ancestors().collect::<Vec<&Path>>();
+    ancestors.reverse();
+
+    ancestors
+        .into_iter()
+        .find(|dir| !dir.as_os_str().is_empty() && !dir.is_dir())
+        .map(Path::to_path_buf)
+}
+
+pub(crate) fn missing_directory_message(dir: &Path) -> String {
+    match dir.exists() {
+        true => format!("Could not save, {:?} is a file not a directory.", dir),
+        false => format!("Could not save, directory {:?} doesn't exist.", dir),
+    }
+}
+
+// one at a time so an error names the directory that couldn't be made
+pub(crate) fn create_parent_directories(path: &Path) -> Result<(), String> {
+    let parent = match path.parent() {
+        None => return Ok(()),
+        Some(parent) => parent,
+    };
+
+    let mut ancestors = parent.ancestors().collect::<Vec<&Path>>();
+    ancestors.reverse();
+
+    for dir in ancestors.into_iter().filter(|dir| !dir.as_os_str().is_empty() && !dir.is_dir()) {
+        fs::create_dir(dir).map_err(|err| io_error_message(&format!("Could not create directory {:?}.", dir), err))?;
+    }
+
+    Ok(())
+}
+
+// write to a sibling temp file then rename over target
+// so a failure part way through leaves the original untouched
+fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    // replace what a symlink points to, renaming over the link itself would turn it into a regular file
+    let path = &fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let existing = fs::metadata(path).ok();
+
+    let (temp_path, mut file) = create_temp_file(path)?;
+    let written = file.write_all(contents).and_then(|_| file.sync_all());
+    drop(file);
+
+    let result = written
+        .map_err(|err| io_error_message("Could not write to file.", err))
+        .and_then(|_| match &existing {
+            None => Ok(()),
+            Some(metadata) => copy_metadata(metadata, &temp_path),
+        })
+        .and_then(|_| {
+            fs::rename(&temp_path, path)
+                .map_err(|err| io_error_message("Could not replace file.", err))
+        });
+
+    if result.is_err() {
+        // best effort, original is still intact
+        fs::remove_file(&temp_path).unwrap_or_default();
+    }
+
+    result
+}
+
+// new file next to path, never one that's already there, so a planted file or link isn't written through
+fn create_temp_file(path: &Path) -> Result<(PathBuf, File), String> {
+    static NEXT_TEMP: AtomicU64 = AtomicU64::new(0);
+
+    let file_name = match path.file_name() {
+        None => return Err(format!("Invalid file path {:?}", path)),
+        Some(name) => name.to_string_lossy().to_string(),
+    };
+
+    loop {
+        let temp_path = path.with_file_name(format!(
+            ".{}.{}-{}.edish-save",
+            file_name,
+            std::process::id(),
+            NEXT_TEMP.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        match OpenOptions::new().write(true).create_new(true).open(&temp_path) {
+            Ok(file) => return Ok((temp_path, file)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => (),
+            Err(err) => return Err(io_error_message("Could not write to file.", err)),
+        }
+    }
+}
+
+fn io_error_message(context: &str, err: io::Error) -> String {
+    match err.kind() {
+        io::ErrorKind::PermissionDenied => format!(
+            "{} {} Check permissions of the file and its directory, or save to a different file.",
+            context,
+            err
+        ),
+        _ => format!("{} {}", context, err),
+    }
+}
+
+fn copy_metadata(metadata: &fs::Metadata, to: &Path) -> Result<(), String> {
+    fs::set_permissions(to, metadata.permissions())
+        .map_err(|err| format!("Could not set file permissions. {}", err))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        // only succeeds for owner or privileged user, keeping ours is fine otherwise
+        std::os::unix::fs::chown(to, Some(metadata.uid()), Some(metadata.gid())).unwrap_or_default();
+    }
+
+    Ok(())
+}
+
+// unique across panels, a view's revision only matches the panel it copied from
+fn next_revision() -> u64 {
+    static NEXT_REVISION: AtomicU64 = AtomicU64::new(1);
+
+    NEXT_REVISION.fetch_add(1, Ordering::Relaxed)
+}
+
+// unique for editor session, follows buffer when it's switched in and out of a panel
+pub(crate) fn next_buffer_id() -> u64 {
+    static NEXT_BUFFER_ID: AtomicU64 = AtomicU64::new(1);
+
+    NEXT_BUFFER_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+// characters before byte index, past end of line each byte is a column
+fn char_column(line: &str, index: usize) -> usize {
+    line.char_indices().take_while(|(i, _)| *i < index).count() + index.saturating_sub(line.len())
+}
+
+// greedy wrap, words longer than width are placed on their own line
+fn wrap_words(indent: &str, words: &Vec<String>, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut current = indent.to_string();
+
+    for word in words {
+        if current.len() > indent.len() && current.len() + 1 + word.len() > width {
+            lines.push(current);
+            current = indent.to_string();
+        }
+
+        if current.len() > indent.len() {
+            current.push(' ');
+        }
+
+        current.push_str(word);
+    }
+
+    lines.push(current);
+    lines
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum CharClass {
+    Space,
+    Word,
+    Punctuation,
+}
+
+fn char_class(c: char, sub_word: bool) -> CharClass {
+    if c.is_whitespace() || (sub_word && c == '_') {
+        CharClass::Space
+    } else if c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punctuation
+    }
+}
+
+// whether a word starts at 'next'
+fn is_word_boundary(previous: char, next: char, after: Option<char>, sub_word: bool) -> bool {
+    if char_class(previous, sub_word) != char_class(next, sub_word) {
+        return true;
+    }
+
+    if !sub_word || char_class(next, sub_word) != CharClass::Word {
+        return false;
+    }
+
+    // fooBar -> foo|Bar, HTTPServer -> HTTP|Server
+    (!previous.is_uppercase() && next.is_uppercase())
+        || (previous.is_uppercase()
+            && next.is_uppercase()
+            && after.map(|c| c.is_lowercase()).unwrap_or(false))
+}
+
+// cursor is a byte index, these keep it on character boundaries
+const CLOSING_CHARACTERS: [char; 5] = [')', ']', '}', '"', '\''];
+
+enum AutoClose {
+    Insert,
+    Pair(char),
+    // closing character typed right before the same one moves past it
+    StepOver,
+}
+
+// quotes aren't paired after a letter or number, so apostrophes in words stay single
+fn auto_close(line: &str, index: usize, c: char) -> AutoClose {
+    let index = floor_char_index(line, index);
+    let next = line[index..].chars().next();
+    let previous = line[..index].chars().next_back();
+
+    if next == Some(c) && CLOSING_CHARACTERS.contains(&c) {
+        return AutoClose::StepOver;
+    }
+
+    match c {
+        '(' => AutoClose::Pair(')'),
+        '[' => AutoClose::Pair(']'),
+        '{' => AutoClose::Pair('}'),
+        '"' | '\'' if !previous.map(|p| p.is_alphanumeric()).unwrap_or(false) => AutoClose::Pair(c),
+        _ => AutoClose::Insert,
+    }
+}
+
+const SPACE_GLYPH: char = '·';
+const TAB_GLYPH: char = '→';
+
+// one glyph per space or tab, so wrapping and cursor columns stay where they were
+fn whitespace_spans(text: &str) -> Vec<Span<'static>> {
+    let mut spans = vec![];
+    let mut run = String::new();
+    let mut in_whitespace = false;
+
+    for c in text.chars() {
+        let glyph = match c {
+            ' ' => Some(SPACE_GLYPH),
+            '\t' => Some(TAB_GLYPH),
+            _ => None,
+        };
+
+        if glyph.is_some() != in_whitespace && !run.is_empty() {
+            spans.push(whitespace_span(mem::take(&mut run), in_whitespace));
+        }
+
+        in_whitespace = glyph.is_some();
+        run.push(glyph.unwrap_or(c));
+    }
+
+    if !run.is_empty() || spans.is_empty() {
+        spans.push(whitespace_span(run, in_whitespace));
+    }
+
+    spans
+}
+
+fn whitespace_span(text: String, whitespace: bool) -> Span<'static> {
+    match whitespace {
+        true => Span::styled(text, Style::default().add_modifier(Modifier::DIM)),
+        false => Span::from(text),
+    }
+}
+
+fn floor_char_index(line: &str, index: usize) -> usize {
+    let mut index = index.min(line.len());
+    while !line.is_char_boundary(index) {
+        index -= 1;
+    }
+
+    index
+}
+
+fn next_char_index(line: &str, index: usize) -> usize {
+    let index = floor_char_index(line, index);
+    line[index..].chars().next().map(|c| index + c.len_utf8()).unwrap_or(line.len())
+}
+
+fn previous_char_index(line: &str, index: usize) -> usize {
+    let index = floor_char_index(line, index);
+    line[..index].chars().next_back().map(|c| index - c.len_utf8()).unwrap_or(0)
+}
+
+// index of start of next word, or end of line
+fn next_word_index(line: &str, index: usize, sub_word: bool) -> usize {
+    let chars: Vec<(usize, char)> = line.char_indices().collect();
+    let mut i = match chars.iter().position(|(byte, _)| *byte >= index) {
+        None => return line.len(),
+        Some(i) => i + 1,
+    };
+
+    while i < chars.len() {
+        let c = chars[i].1;
+        if char_class(c, sub_word) != CharClass::Space
+            && is_word_boundary(chars[i - 1].1, c, chars.get(i + 1).map(|(_, c)| *c), sub_word)
+        {
+            return chars[i].0;
+        }
+        i += 1;
+    }
+
+    line.len()
+}
+
+// index of start of current word, or previous word if already at start
+fn previous_word_index(line: &str, index: usize, sub_word: bool) -> usize {
+    let chars: Vec<(usize, char)> = line.char_indices().collect();
+    let mut i = match chars.iter().position(|(byte, _)| *byte >= index) {
+        None => chars.len(),
+        Some(i) => i,
+    };
+
+    if i == 0 {
+        return 0;
+    }
+
+    i -= 1;
+    while i > 0 && char_class(chars[i].1, sub_word) == CharClass::Space {
+        i -= 1;
+    }
+
+    while i > 0
+        && !is_word_boundary(
+            chars[i - 1].1,
+            chars[i].1,
+            chars.get(i + 1).map(|(_, c)| *c),
+            sub_word,
+        )
+    {
+        i -= 1;
+    }
+
+    chars.get(i).map(|(byte, _)| *byte).unwrap_or(0)
+}
+
+// first letter is enough
+pub fn parse_disk_change_choice(input: &str) -> Option<char> {
+    match input.trim().to_lowercase().as_str() {
+        "r" | "reload" => Some('r'),
+        "k" | "keep" => Some('k'),
+        "d" | "diff" => Some('d'),
+        _ => None,
+    }
+}
+
+fn validate_disk_change_choice(input: &str) -> Result<(), String> {
+    match parse_disk_change_choice(input) {
+        Some(_) => Ok(()),
+        None => Err(format!("Expected reload, keep or diff, got {:?}.", input)),
+    }
+}
+
+fn validate_count(input: &str) -> Result<(), String> {
+    match input.trim().parse::<usize>() {
+        Ok(count) if count <= MAX_COUNT => Ok(()),
+        Ok(_) => Err(format!("Expected at most {}, got {:?}.", MAX_COUNT, input)),
+        Err(_) => Err(format!("Expected a whole number, got {:?}.", input)),
+    }
+}
+
+fn validate_line_number(input: &str) -> Result<(), String> {
+    match input.trim().parse::<usize>() {
+        Ok(number) if number > 0 => Ok(()),
+        _ => Err(format!("Expected a line number, got {:?}.", input)),
+    }
+}
+
+fn validate_copy_lines(input: &str) -> Result<(), String> {
+    match parse_copy_lines(input) {
+        Some(_) => Ok(()),
+        None => Err(format!("Invalid copy command: {:?}. Expected 'start,end t destination'.", input)),
+    }
+}
+
+// parses line copy commands in the form of 'start,end t destination'
+// end is optional, 'start t destination' copies a single line
+pub fn parse_copy_lines(input: &str) -> Option<(usize, usize, usize)> {
+    let (range, destination) = input.split_once('t')?;
+    let destination = destination.trim().parse::<usize>().ok()?;
+    let (start, end) = parse_line_range(range)?;
+
+    Some((start, end, destination))
+}
+
+// 'start,end' or a single line number
+pub fn parse_line_range(input: &str) -> Option<(usize, usize)> {
+    match input.split_once(',') {
+        Some((start, end)) => Some((start.trim().parse::<usize>().ok()?, end.trim().parse::<usize>().ok()?)),
+        None => {
+            let line = input.trim().parse::<usize>().ok()?;
+            Some((line, line))
+        }
+    }
+}
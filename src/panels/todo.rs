@@ -0,0 +1,40 @@
+use crate::panels::lines::Lines;
+
+const MARKERS: [&str; 2] = ["TODO", "FIXME"];
+
+// indexes of lines with a marker as a whole word, e.g. 'TODO:' but not 'TODOS'
+pub fn todo_lines(lines: &Lines) -> Vec<usize> {
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| has_marker(line))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+fn has_marker(line: &str) -> bool {
+    line.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .any(|word| MARKERS.contains(&word))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::panels::lines::Lines;
+    use crate::panels::todo::todo_lines;
+
+    #[test]
+    fn finds_marked_lines() {
+        let lines: Lines = vec![
+            "// TODO: one",
+            "nothing here",
+            "x = 1 # FIXME later",
+            "TODOS and todo don't count",
+            "MY_TODO neither",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+        assert_eq!(todo_lines(&lines), vec![0, 2]);
+    }
+}
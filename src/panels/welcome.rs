@@ -0,0 +1,144 @@
+use std::env;
+
+use tui::layout::Rect;
+use tui::text::{Span, Spans};
+use tui::widgets::Paragraph;
+
+use crate::commands::{Manager, GLOBAL_KEYS_SECTION};
+use crate::locale::{Catalog, Text};
+use crate::panels::help::format_keys;
+use crate::panels::text::RenderDetails;
+use crate::panels::EDIT_PANEL_TYPE_ID;
+use crate::{AppState, EditorFrame, TextPanel, CURSOR_MAX};
+
+// commands worth knowing first, looked up by name so rebound keys are shown
+const QUICK_START: [(&str, &str); 9] = [
+    (EDIT_PANEL_TYPE_ID, "Open File"),
+    (EDIT_PANEL_TYPE_ID, "Quick Open"),
+    (EDIT_PANEL_TYPE_ID, "Save"),
+    (EDIT_PANEL_TYPE_ID, "Go To Line"),
+    (GLOBAL_KEYS_SECTION, "Command Palette"),
+    (GLOBAL_KEYS_SECTION, "Activate Panel"),
+    (GLOBAL_KEYS_SECTION, "Split Horizontal"),
+    (GLOBAL_KEYS_SECTION, "Show Help"),
+    (GLOBAL_KEYS_SECTION, "Quit"),
+];
+
+const RECENT_SHOWN: usize = 10;
+
+const INDENT: &str = "  ";
+
+fn keys_for(commands: &Manager, section: &str, name: &str) -> Option<String> {
+    let bindings = match section {
+        GLOBAL_KEYS_SECTION => commands.global_key_bindings(),
+        _ => commands
+            .panel_key_bindings()
+            .into_iter()
+            .find(|(id, _)| *id == section)
+            .map(|(_, bindings)| bindings)
+            .unwrap_or_default(),
+    };
+
+    bindings
+        .iter()
+        .find(|(_, details)| details.name().as_str() == name)
+        .map(|(keys, _)| format_keys(keys))
+}
+
+// quick start keys then most recent files, commands that were unbound are left out
+pub fn welcome_lines(commands: &Manager, catalog: &Catalog, recent: &[String]) -> Vec<String> {
+    let hints: Vec<(String, &str)> = QUICK_START
+        .iter()
+        .filter_map(|(section, name)| keys_for(commands, section, name).map(|keys| (keys, catalog.command_name(name))))
+        .collect();
+
+    let width = hints.iter().map(|(keys, _)| keys.chars().count()).max().unwrap_or(0);
+
+    let mut lines = vec![catalog.text(&Text::new("welcome.quick_start", "Quick Start"))];
+    lines.extend(
+        hints
+            .iter()
+            .map(|(keys, name)| format!("{}{:<width$}  {}", INDENT, keys, name, width = width)),
+    );
+
+    lines.push(String::new());
+    lines.push(catalog.text(&Text::new("welcome.recent_files", "Recent Files")));
+    match recent.is_empty() {
+        true => lines.push(format!("{}{}", INDENT, catalog.text(&Text::new("welcome.no_recent_files", "None yet")))),
+        false => lines.extend(recent.iter().take(RECENT_SHOWN).map(|name| format!("{}{}", INDENT, name))),
+    }
+
+    lines.push(String::new());
+    lines.push(catalog.text(&Text::new("welcome.start", "Press any key to start editing.")));
+
+    lines
+}
+
+pub struct WelcomePanel {}
+
+impl WelcomePanel {
+    pub fn render_handler(_panel: &TextPanel, state: &AppState, commands: &Manager, frame: &mut EditorFrame, rect: Rect) -> RenderDetails {
+        let recent = match env::current_dir() {
+            Ok(dir) => state.recent_files().names(&dir),
+            Err(_) => vec![],
+        };
+
+        let rows: Vec<Spans> = welcome_lines(commands, state.catalog(), &recent)
+            .into_iter()
+            .take(rect.height as usize)
+            .map(|line| match line.starts_with(INDENT) || line.is_empty() {
+                true => Spans::from(Span::raw(line)),
+                false => Spans::from(Span::styled(line, state.theme().panel_id)),
+            })
+            .collect();
+
+        frame.render_widget(Paragraph::new(rows).style(state.theme().text), rect);
+
+        RenderDetails::new(state.text(&Text::new("welcome.title", "Welcome")), CURSOR_MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    use crate::commands::{CommandKeyId, Manager, GLOBAL_KEYS_SECTION};
+    use crate::locale::Catalog;
+    use crate::panels::welcome::{welcome_lines, RECENT_SHOWN};
+
+    #[test]
+    fn quick_start_hints() {
+        let lines = welcome_lines(&Manager::default(), &Catalog::default(), &[]);
+
+        assert_eq!(lines[0], "Quick Start");
+        assert!(lines.iter().any(|line| line.starts_with("  C + o") && line.ends_with("  Open File")));
+        assert!(lines.contains(&"  None yet".to_string()));
+    }
+
+    #[test]
+    fn unbound_hints_left_out() {
+        let mut commands = Manager::default();
+        let before = welcome_lines(&commands, &Catalog::default(), &[]).len();
+
+        // replaces quit's only binding
+        commands
+            .bind(GLOBAL_KEYS_SECTION, &vec![CommandKeyId::new(KeyCode::Char('q'), KeyModifiers::CONTROL)], "Save All")
+            .unwrap();
+
+        let lines = welcome_lines(&commands, &Catalog::default(), &[]);
+        assert_eq!(lines.len(), before - 1);
+        assert!(!lines.iter().any(|line| line.ends_with("  Quit")));
+    }
+
+    #[test]
+    fn recent_files_limited() {
+        let commands = Manager::default();
+        let recent: Vec<String> = (0..RECENT_SHOWN + 5).map(|i| format!("file_{}.txt", i)).collect();
+
+        let lines = welcome_lines(&commands, &Catalog::default(), &recent);
+
+        assert!(lines.contains(&"  file_0.txt".to_string()));
+        assert!(lines.contains(&format!("  file_{}.txt", RECENT_SHOWN - 1)));
+        assert!(!lines.contains(&format!("  file_{}.txt", RECENT_SHOWN)));
+    }
+}
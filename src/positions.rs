@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::panels::EDIT_PANEL_TYPE_ID;
+use crate::{Panels, TextPanel};
+
+pub const STATE_DIR_VAR: &str = "EDISH_STATE_DIR";
+
+// where editor keeps data between sessions that isn't config
+pub fn state_dir() -> Option<PathBuf> {
+    match env::var(STATE_DIR_VAR) {
+        Ok(dir) => Some(PathBuf::from(dir)),
+        Err(_) => match env::var("XDG_STATE_HOME") {
+            Ok(dir) => Some(PathBuf::from(dir).join("edish")),
+            Err(_) => env::var("HOME")
+                .ok()
+                .map(|home| PathBuf::from(home).join(".local").join("state").join("edish")),
+        },
+    }
+}
+
+// temp directory only current user can use, for copies of file contents
+pub fn private_dir(name: &str) -> Result<PathBuf, String> {
+    let dir = private_path(name);
+
+    make_private(&dir)?;
+
+    Ok(dir)
+}
+
+// runtime dir is private already, otherwise one is made per user under temp dir
+pub fn private_path(name: &str) -> PathBuf {
+    match env::var("XDG_RUNTIME_DIR") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir).join("edish").join(name),
+        _ => env::temp_dir().join(format!("edish-{}-{}", env::var("USER").unwrap_or_default(), name)),
+    }
+}
+
+// existing directory may have been made by another user, or be a link to somewhere else
+pub fn make_private(dir: &Path) -> Result<(), String> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+
+    builder
+        .create(dir)
+        .map_err(|err| format!("Could not make directory {:?}. {}", dir, err))?;
+
+    let metadata = fs::symlink_metadata(dir).map_err(|err| format!("Could not read directory {:?}. {}", dir, err))?;
+    if !metadata.is_dir() {
+        return Err(format!("{:?} isn't a directory.", dir));
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if metadata.permissions().mode() & 0o077 != 0 {
+            return Err(format!("{:?} can be used by other users.", dir));
+        }
+    }
+
+    Ok(())
+}
+
+// last cursor line and column for each file, stored as 'line col path' lines
+pub struct CursorPositions {
+    file: PathBuf,
+    positions: HashMap<PathBuf, (usize, usize)>,
+}
+
+impl CursorPositions {
+    pub fn default_file() -> Option<PathBuf> {
+        state_dir().map(|dir| dir.join("positions"))
+    }
+
+    // missing file is a fresh start
+    pub fn load(file: PathBuf) -> Result<Self, String> {
+        let mut positions = HashMap::new();
+
+        if file.exists() {
+            let text = fs::read_to_string(&file)
+                .map_err(|err| format!("Could not read cursor positions {:?}. {}", file, err))?;
+
+            for line in text.lines() {
+                let mut parts = line.splitn(3, ' ');
+                // skip anything damaged rather than lose the rest
+                if let (Some(line), Some(column), Some(path)) = (
+                    parts.next().and_then(|l| l.parse::<usize>().ok()),
+                    parts.next().and_then(|c| c.parse::<usize>().ok()),
+                    parts.next(),
+                ) {
+                    positions.insert(PathBuf::from(path), (line, column));
+                }
+            }
+        }
+
+        Ok(Self { file, positions })
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let text = self
+            .positions
+            .iter()
+            .map(|(path, (line, column))| format!("{} {} {}\n", line, column, path.to_string_lossy()))
+            .collect::<String>();
+
+        match self.file.parent() {
+            None => Ok(()),
+            Some(dir) => fs::create_dir_all(dir),
+        }
+        .and_then(|_| fs::write(&self.file, text))
+        .map_err(|err| format!("Could not save cursor positions {:?}. {}", self.file, err))
+    }
+
+    pub fn get(&self, path: &Path) -> Option<(usize, usize)> {
+        self.positions.get(&key(path)).cloned()
+    }
+
+    pub fn set(&mut self, path: &Path, line: usize, column: usize) {
+        self.positions.insert(key(path), (line, column));
+    }
+
+    pub fn record(&mut self, panel: &TextPanel) {
+        match panel.file_path() {
+            Some(path) if panel.panel_type() == EDIT_PANEL_TYPE_ID => {
+                self.set(path, panel.current_line(), panel.cursor_index_in_line())
+            }
+            _ => (),
+        }
+    }
+
+    pub fn record_all(&mut self, panels: &Panels) {
+        for index in 0..panels.len() {
+            match panels.get(index) {
+                None => (),
+                Some(panel) => self.record(panel),
+            }
+        }
+    }
+
+    pub fn restore(&self, panel: &mut TextPanel) {
+        match panel.file_path().and_then(|path| self.get(path)) {
+            None => (),
+            Some((line, column)) => {
+                panel.record_jump();
+                panel.restore_cursor(line, column);
+            }
+        }
+    }
+}
+
+// same file opened through different relative paths shares a position
+fn key(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use crate::positions::{make_private, CursorPositions};
+
+    fn temp_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn missing_file_is_empty() {
+        let file = temp_file("edish-positions-missing");
+        let positions = CursorPositions::load(file).unwrap();
+
+        assert_eq!(positions.get(&PathBuf::from("/some/file.txt")), None);
+    }
+
+    #[test]
+    fn save_and_load() {
+        let file = temp_file("edish-positions-save");
+        let mut positions = CursorPositions::load(file.clone()).unwrap();
+        positions.set(&PathBuf::from("/some/file with spaces.txt"), 10, 4);
+        positions.set(&PathBuf::from("/other.txt"), 2, 0);
+        positions.save().unwrap();
+
+        let loaded = CursorPositions::load(file.clone()).unwrap();
+
+        assert_eq!(loaded.get(&PathBuf::from("/some/file with spaces.txt")), Some((10, 4)));
+        assert_eq!(loaded.get(&PathBuf::from("/other.txt")), Some((2, 0)));
+
+        fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn damaged_lines_skipped() {
+        let file = temp_file("edish-positions-damaged");
+        fs::write(&file, "abc\n3 4 /good.txt\n5 /bad.txt\n").unwrap();
+
+        let loaded = CursorPositions::load(file.clone()).unwrap();
+
+        assert_eq!(loaded.get(&PathBuf::from("/good.txt")), Some((3, 4)));
+        assert_eq!(loaded.get(&PathBuf::from("/bad.txt")), None);
+
+        fs::remove_file(&file).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn private_dir_only_for_owner() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("edish_private_{}", std::process::id()));
+        make_private(&dir).unwrap();
+        assert_eq!(fs::metadata(&dir).unwrap().permissions().mode() & 0o777, 0o700);
+
+        // opened up afterwards, e.g. made by someone else
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o777)).unwrap();
+        assert!(make_private(&dir).is_err());
+
+        let link = std::env::temp_dir().join(format!("edish_private_link_{}", std::process::id()));
+        let _ = fs::remove_file(&link);
+        std::os::unix::fs::symlink(&dir, &link).unwrap();
+        assert!(make_private(&link).is_err());
+
+        fs::remove_file(&link).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
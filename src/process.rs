@@ -0,0 +1,223 @@
+use std::io;
+use std::io::{Read, Write};
+use std::process::{Child, ChildStdin, Command, ExitStatus, Stdio};
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::thread;
+
+const READ_BUFFER_SIZE: usize = 4096;
+// oldest output is dropped past this
+pub const MAX_OUTPUT_LINES: usize = 10_000;
+
+// child process with piped stdio
+// stdout and stderr are read on their own threads so a chatty process never blocks on a full pipe
+pub struct Process {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    output: Receiver<Vec<u8>>,
+    // end of last read that wasn't a whole character yet
+    partial: Vec<u8>,
+    // both reading threads have stopped
+    output_finished: bool,
+}
+
+impl Process {
+    pub fn spawn(program: &str, args: &[&str]) -> io::Result<Self> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let (sender, output) = channel();
+
+        match child.stdout.take() {
+            None => (),
+            Some(stdout) => read_into(stdout, sender.clone()),
+        }
+
+        match child.stderr.take() {
+            None => (),
+            Some(stderr) => read_into(stderr, sender),
+        }
+
+        Ok(Self {
+            stdin: child.stdin.take(),
+            child,
+            output,
+            partial: vec![],
+            output_finished: false,
+        })
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        match self.stdin.as_mut() {
+            None => Err(io::Error::new(io::ErrorKind::BrokenPipe, "Input already closed.")),
+            Some(stdin) => {
+                stdin.write_all(bytes)?;
+                stdin.flush()
+            }
+        }
+    }
+
+    // process sees end of file on its input
+    pub fn close_input(&mut self) {
+        self.stdin = None;
+    }
+
+    pub fn input_closed(&self) -> bool {
+        self.stdin.is_none()
+    }
+
+    // everything read since last call, characters split across reads are held until complete
+    pub fn take_output(&mut self) -> String {
+        loop {
+            match self.output.try_recv() {
+                Ok(bytes) => self.partial.extend(bytes),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.output_finished = true;
+                    break;
+                }
+            }
+        }
+
+        let valid = match std::str::from_utf8(&self.partial) {
+            Ok(_) => self.partial.len(),
+            // error_len of None means bytes at end could still become a character
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(_) => self.partial.len(),
+        };
+
+        let rest = self.partial.split_off(valid);
+        let text = String::from_utf8_lossy(&self.partial).to_string();
+        self.partial = rest;
+
+        text
+    }
+
+    // process may exit before everything it wrote has been read
+    pub fn output_finished(&self) -> bool {
+        self.output_finished
+    }
+
+    pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        self.child.try_wait()
+    }
+
+    pub fn kill(&mut self) -> io::Result<()> {
+        self.child.kill()
+    }
+}
+
+impl Drop for Process {
+    fn drop(&mut self) {
+        // already exited is the only expected failure, nothing to do about either
+        self.child.kill().unwrap_or_default();
+        self.child.wait().map(|_| ()).unwrap_or_default();
+    }
+}
+
+// carriage returns are dropped, first line of text continues last line
+pub fn append_output(lines: &mut Vec<String>, text: &str) {
+    let text = text.replace('\r', "");
+    let mut new_lines = text.split('\n');
+
+    match (new_lines.next(), lines.last_mut()) {
+        (Some(first), Some(last)) => last.push_str(first),
+        (Some(first), None) => lines.push(first.to_string()),
+        (None, _) => (),
+    }
+
+    lines.extend(new_lines.map(|line| line.to_string()));
+
+    if lines.len() > MAX_OUTPUT_LINES {
+        let extra = lines.len() - MAX_OUTPUT_LINES;
+        lines.drain(..extra);
+    }
+}
+
+fn read_into<R: Read + Send + 'static>(mut reader: R, sender: Sender<Vec<u8>>) {
+    thread::spawn(move || {
+        let mut buffer = [0u8; READ_BUFFER_SIZE];
+
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => {
+                    // receiver gone means process was dropped
+                    if sender.send(buffer[..read].to_vec()).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::process::{append_output, Process};
+
+    // output is read on other threads, so it can still be arriving after exit
+    fn read_until(process: &mut Process, expected: &[&str]) -> String {
+        let mut output = String::new();
+        let start = Instant::now();
+
+        while start.elapsed() < Duration::from_secs(5) && !expected.iter().all(|e| output.contains(e)) {
+            output.push_str(&process.take_output());
+        }
+
+        output
+    }
+
+    #[test]
+    fn input_is_sent_and_output_read() {
+        let mut process = Process::spawn("sh", &[]).unwrap();
+
+        process.write(b"echo one\necho two 1>&2\n").unwrap();
+        process.close_input();
+
+        let output = read_until(&mut process, &["one\n", "two\n"]);
+
+        assert!(output.contains("one\n"));
+        assert!(output.contains("two\n"));
+
+        // input closing ends shell
+        let start = Instant::now();
+        let mut exited = false;
+        while start.elapsed() < Duration::from_secs(5) && !(exited && process.output_finished()) {
+            process.take_output();
+            exited = process.try_wait().unwrap().is_some();
+        }
+
+        assert!(exited);
+        assert!(process.output_finished());
+    }
+
+    #[test]
+    fn write_after_close() {
+        let mut process = Process::spawn("sh", &[]).unwrap();
+        process.close_input();
+
+        assert!(process.input_closed());
+        assert!(process.write(b"echo one\n").is_err());
+    }
+
+    #[test]
+    fn output_continues_last_line() {
+        let mut lines = vec!["$ ".to_string()];
+
+        append_output(&mut lines, "one\r\ntw");
+        append_output(&mut lines, "o\n");
+
+        assert_eq!(lines, vec!["$ one".to_string(), "two".to_string(), "".to_string()]);
+    }
+
+    #[test]
+    fn missing_program() {
+        assert!(Process::spawn("edish_no_such_program", &[]).is_err());
+    }
+}
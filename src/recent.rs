@@ -0,0 +1,116 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::positions::state_dir;
+
+pub const RECENT_LIMIT: usize = 50;
+
+// most recently opened files first, one path per line
+#[derive(Default)]
+pub struct RecentFiles {
+    // nothing is written without a file
+    file: Option<PathBuf>,
+    entries: Vec<PathBuf>,
+}
+
+impl RecentFiles {
+    pub fn default_file() -> Option<PathBuf> {
+        state_dir().map(|dir| dir.join("recent"))
+    }
+
+    // missing file is a fresh start
+    pub fn load(file: PathBuf) -> Result<Self, String> {
+        let entries = match file.exists() {
+            false => vec![],
+            true => fs::read_to_string(&file)
+                .map_err(|err| format!("Could not read recent files {:?}. {}", file, err))?
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(PathBuf::from)
+                .collect(),
+        };
+
+        Ok(Self {
+            file: Some(file),
+            entries,
+        })
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let file = match self.file.as_ref() {
+            None => return Ok(()),
+            Some(file) => file,
+        };
+
+        let text = self
+            .entries
+            .iter()
+            .map(|path| format!("{}\n", path.to_string_lossy()))
+            .collect::<String>();
+
+        match file.parent() {
+            None => Ok(()),
+            Some(dir) => fs::create_dir_all(dir),
+        }
+        .and_then(|_| fs::write(file, text))
+        .map_err(|err| format!("Could not save recent files {:?}. {}", file, err))
+    }
+
+    pub fn add(&mut self, path: &Path) {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        self.entries.retain(|entry| *entry != path);
+        self.entries.insert(0, path);
+        self.entries.truncate(RECENT_LIMIT);
+    }
+
+    // relative to directory when inside it, for shorter names
+    pub fn names(&self, dir: &Path) -> Vec<String> {
+        self.entries
+            .iter()
+            .map(|path| path.strip_prefix(dir).unwrap_or(path).to_string_lossy().to_string())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use crate::recent::{RecentFiles, RECENT_LIMIT};
+
+    #[test]
+    fn most_recent_first_without_duplicates() {
+        let mut recent = RecentFiles::default();
+        recent.add(Path::new("/one.txt"));
+        recent.add(Path::new("/two.txt"));
+        recent.add(Path::new("/one.txt"));
+
+        assert_eq!(recent.names(Path::new("/")), vec!["one.txt", "two.txt"]);
+    }
+
+    #[test]
+    fn capped() {
+        let mut recent = RecentFiles::default();
+        for i in 0..RECENT_LIMIT + 5 {
+            recent.add(&PathBuf::from(format!("/{}.txt", i)));
+        }
+
+        assert_eq!(recent.names(Path::new("/")).len(), RECENT_LIMIT);
+    }
+
+    #[test]
+    fn save_and_load() {
+        let file = std::env::temp_dir().join(format!("edish_recent_{}", std::process::id()));
+        let mut recent = RecentFiles::load(file.clone()).unwrap();
+        recent.add(Path::new("/one.txt"));
+        recent.add(Path::new("/two.txt"));
+        recent.save().unwrap();
+
+        let loaded = RecentFiles::load(file.clone()).unwrap();
+
+        assert_eq!(loaded.names(Path::new("/other")), vec!["/two.txt", "/one.txt"]);
+
+        fs::remove_file(&file).unwrap();
+    }
+}
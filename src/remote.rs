@@ -0,0 +1,317 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::app::{StateChange, StateChangeRequest};
+use crate::commands::Manager;
+use crate::locale::Text;
+use crate::panels::{decode, normalize, EDIT_PANEL_TYPE_ID};
+use crate::positions::private_dir;
+use crate::tasks::TaskCompletion;
+use crate::{AppState, Panels};
+
+pub const SFTP_SCHEME: &str = "sftp://";
+
+// file on another machine, edited through a local copy
+// transfers go through the system scp so existing ssh config and keys are used
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RemotePath {
+    // may include a user, e.g. user@host
+    host: String,
+    port: Option<u16>,
+    path: String,
+}
+
+impl RemotePath {
+    // sftp://[user@]host[:port]/path
+    // host can't look like an scp option and path can't climb out of local copy directory
+    pub fn parse(text: &str) -> Option<Self> {
+        let rest = text.trim().strip_prefix(SFTP_SCHEME)?;
+        let (authority, path) = rest.split_once('/')?;
+
+        if path.is_empty() {
+            return None;
+        }
+
+        let (host, port) = match authority.rsplit_once(':') {
+            None => (authority, None),
+            Some((host, port)) => (host, Some(port.parse::<u16>().ok()?)),
+        };
+
+        if host.is_empty() || host.ends_with('@') || host.split('@').any(|part| part.starts_with('-')) {
+            return None;
+        }
+
+        if path.split('/').any(|component| component == "..") {
+            return None;
+        }
+
+        Some(Self {
+            host: host.to_string(),
+            port,
+            path: format!("/{}", path),
+        })
+    }
+
+    pub fn url(&self) -> String {
+        match self.port {
+            None => format!("{}{}{}", SFTP_SCHEME, self.host, self.path),
+            Some(port) => format!("{}{}:{}{}", SFTP_SCHEME, self.host, port, self.path),
+        }
+    }
+
+    // copies live in a directory only current user can read
+    pub fn local_copy(&self) -> Result<PathBuf, String> {
+        private_dir("remote").map(|dir| self.local_copy_in(&dir))
+    }
+
+    // mirrors remote layout so two hosts can't share a copy
+    fn local_copy_in(&self, dir: &Path) -> PathBuf {
+        let mut path = dir.join(self.host.replace('@', "_"));
+
+        if let Some(port) = self.port {
+            path = path.join(port.to_string());
+        }
+
+        path.join(self.path.trim_start_matches('/'))
+    }
+
+    // batch mode fails instead of prompting for a password over the editor
+    fn scp_args(&self, from: String, to: String) -> Vec<String> {
+        let mut args = vec!["-q".to_string(), "-o".to_string(), "BatchMode=yes".to_string()];
+
+        if let Some(port) = self.port {
+            args.push("-P".to_string());
+            args.push(port.to_string());
+        }
+
+        // nothing after is read as an option
+        args.push("--".to_string());
+        args.push(from);
+        args.push(to);
+
+        args
+    }
+
+    fn scp_target(&self) -> String {
+        format!("{}:{}", self.host, self.path)
+    }
+
+    pub fn download_args(&self, local: &Path) -> Vec<String> {
+        self.scp_args(self.scp_target(), local.to_string_lossy().to_string())
+    }
+
+    pub fn upload_args(&self, local: &Path) -> Vec<String> {
+        self.scp_args(local.to_string_lossy().to_string(), self.scp_target())
+    }
+
+    pub fn download(&self, local: &Path) -> Result<(), String> {
+        match local.parent() {
+            None => (),
+            Some(dir) => fs::create_dir_all(dir)
+                .map_err(|err| format!("Could not make local copy of {}. {}", self.url(), err))?,
+        }
+
+        run_scp(self.download_args(local))
+    }
+
+    pub fn upload(&self, local: &Path) -> Result<(), String> {
+        run_scp(self.upload_args(local))
+    }
+}
+
+fn run_scp(args: Vec<String>) -> Result<(), String> {
+    match Command::new("scp").args(&args).output() {
+        Err(err) => Err(format!("Could not run scp. {}", err)),
+        Ok(output) if output.status.success() => Ok(()),
+        Ok(output) => Err(format!(
+            "scp failed. {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+    }
+}
+
+// edit panels waiting on or showing the remote file
+fn remote_panels(panels: &Panels, remote: &RemotePath) -> Vec<usize> {
+    (0..panels.len())
+        .filter(|index| match panels.get(*index) {
+            Some(panel) => panel.panel_type() == EDIT_PANEL_TYPE_ID && panel.remote() == Some(remote),
+            None => false,
+        })
+        .collect()
+}
+
+pub struct DownloadRemote {
+    remote: RemotePath,
+}
+
+impl DownloadRemote {
+    pub fn new(remote: RemotePath) -> Self {
+        Self { remote }
+    }
+}
+
+impl StateChange for DownloadRemote {
+    fn apply(self: Box<Self>, state: &mut AppState, _panels: &mut Panels, _commands: &mut Manager) -> Vec<StateChangeRequest> {
+        let remote = self.remote;
+        let message = Text::new("remote.downloading", "Downloading {0}.").arg(remote.url());
+
+        state.tasks_mut().spawn(move || -> TaskCompletion {
+            let result = remote.local_copy().and_then(|local| {
+                remote
+                    .download(&local)
+                    .and_then(|_| fs::read(&local).map_err(|err| err.to_string()))
+                    .and_then(|bytes| decode(&bytes))
+                    .map(|decoded| (local, decoded))
+            });
+
+            Box::new(move |state: &mut AppState, panels: &mut Panels| match result {
+                Err(err) => state.add_error(
+                    Text::new("remote.open_failed", "Could not open {0}. {1}").arg(remote.url()).arg(err),
+                ),
+                Ok((local, (text, bom))) => {
+                    let (lines, line_ending) = normalize(&text);
+                    for index in remote_panels(panels, &remote) {
+                        match panels.get_mut(index) {
+                            None => (),
+                            Some(panel) => {
+                                panel.set_text(&lines);
+                                panel.set_bom(bom);
+                                panel.set_line_ending(line_ending);
+                                panel.set_file_path(local.clone());
+                                panel.record_disk_time();
+                                panel.set_modified(false);
+                            }
+                        }
+                    }
+
+                    state.add_info(
+                        Text::new("remote.downloaded", "Downloaded {0} bytes from {1}.").arg(text.len()).arg(remote.url()),
+                    );
+                }
+            })
+        });
+
+        vec![StateChangeRequest::info(message)]
+    }
+}
+
+pub struct UploadRemote {
+    remote: RemotePath,
+    local: PathBuf,
+}
+
+impl UploadRemote {
+    pub fn new(remote: RemotePath, local: PathBuf) -> Self {
+        Self { remote, local }
+    }
+}
+
+impl StateChange for UploadRemote {
+    fn apply(self: Box<Self>, state: &mut AppState, _panels: &mut Panels, _commands: &mut Manager) -> Vec<StateChangeRequest> {
+        let UploadRemote { remote, local } = *self;
+        let message = Text::new("remote.uploading", "Uploading to {0}.").arg(remote.url());
+
+        state.tasks_mut().spawn(move || -> TaskCompletion {
+            let result = remote.upload(&local);
+
+            Box::new(move |state: &mut AppState, panels: &mut Panels| match result {
+                Ok(()) => state.add_info(Text::new("remote.uploaded", "Uploaded to {0}.").arg(remote.url())),
+                Err(err) => {
+                    // only local copy is saved, keep buffer marked so it isn't closed by mistake
+                    for index in remote_panels(panels, &remote) {
+                        match panels.get_mut(index) {
+                            None => (),
+                            Some(panel) => panel.set_modified(true),
+                        }
+                    }
+
+                    state.add_error(Text::new("remote.upload_failed", "Could not upload to {0}. {1}").arg(remote.url()).arg(err));
+                }
+            })
+        });
+
+        vec![StateChangeRequest::info(message)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::remote::RemotePath;
+
+    #[test]
+    fn parse_url() {
+        let remote = RemotePath::parse("sftp://user@example.com/home/user/notes.txt").unwrap();
+
+        assert_eq!(remote.url(), "sftp://user@example.com/home/user/notes.txt");
+    }
+
+    #[test]
+    fn parse_url_with_port() {
+        let remote = RemotePath::parse("sftp://example.com:2222/notes.txt").unwrap();
+
+        assert_eq!(remote.url(), "sftp://example.com:2222/notes.txt");
+        assert_eq!(
+            remote.download_args(&PathBuf::from("/tmp/notes.txt")),
+            vec!["-q", "-o", "BatchMode=yes", "-P", "2222", "--", "example.com:/notes.txt", "/tmp/notes.txt"]
+        );
+    }
+
+    #[test]
+    fn parse_rejects_other_input() {
+        assert_eq!(RemotePath::parse("notes.txt"), None);
+        assert_eq!(RemotePath::parse("sftp://example.com"), None);
+        assert_eq!(RemotePath::parse("sftp://example.com/"), None);
+        assert_eq!(RemotePath::parse("sftp:///notes.txt"), None);
+        assert_eq!(RemotePath::parse("sftp://example.com:port/notes.txt"), None);
+    }
+
+    #[test]
+    fn upload_args_reverse_download() {
+        let remote = RemotePath::parse("sftp://example.com/notes.txt").unwrap();
+
+        assert_eq!(
+            remote.upload_args(&PathBuf::from("/tmp/notes.txt")),
+            vec!["-q", "-o", "BatchMode=yes", "--", "/tmp/notes.txt", "example.com:/notes.txt"]
+        );
+    }
+
+    #[test]
+    fn local_copy_per_host() {
+        let first = RemotePath::parse("sftp://one/notes.txt").unwrap();
+        let second = RemotePath::parse("sftp://two/notes.txt").unwrap();
+
+        let dir = PathBuf::from("/private");
+
+        assert_ne!(first.local_copy_in(&dir), second.local_copy_in(&dir));
+        assert_eq!(first.local_copy_in(&dir), PathBuf::from("/private/one/notes.txt"));
+    }
+
+    #[test]
+    fn parse_rejects_option_like_host() {
+        assert_eq!(RemotePath::parse("sftp://-oProxyCommand=touch%20pwned/x"), None);
+        assert_eq!(RemotePath::parse("sftp://user@-oProxyCommand=x/notes.txt"), None);
+        assert_eq!(RemotePath::parse("sftp://-user@example.com/notes.txt"), None);
+    }
+
+    #[test]
+    fn parse_rejects_parent_components() {
+        assert_eq!(RemotePath::parse("sftp://h/../../../home/u/.bashrc"), None);
+        assert_eq!(RemotePath::parse("sftp://h/home/../u/.bashrc"), None);
+        assert!(RemotePath::parse("sftp://h/home/u/..notes").is_some());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn local_copy_in_private_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let local = RemotePath::parse("sftp://example.com/notes.txt").unwrap().local_copy().unwrap();
+        let dir = local.parent().unwrap().parent().unwrap();
+
+        assert!(local.ends_with("example.com/notes.txt"));
+        assert_eq!(std::fs::metadata(dir).unwrap().permissions().mode() & 0o777, 0o700);
+    }
+}
@@ -1,9 +1,13 @@
-use tui::layout::{Constraint, Direction, Layout, Rect};
-use tui::style::{Color, Modifier, Style};
+use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use tui::style::{Modifier, Style};
 use tui::text::{Span, Spans};
-use tui::widgets::{Block, Borders};
+use tui::widgets::{Block, Borders, Clear, Paragraph};
 
-use crate::panels::NULL_PANEL_TYPE_ID;
+use crate::panels::commands::format_key;
+use crate::app::MessagesPlacement;
+use crate::locale::Text;
+use crate::lock::{LockState, CONFIRM_TEXT, LOCKED_TEXT};
+use crate::panels::{EditMode, LineEnding, EDIT_PANEL_TYPE_ID, MESSAGE_PANEL_TYPE_ID, NULL_PANEL_TYPE_ID};
 use crate::splits::UserSplits;
 use crate::{AppState, EditorFrame, Panels};
 use crate::commands::Manager;
@@ -20,6 +24,288 @@ impl HasPoint for Rect {
     }
 }
 
+pub fn render_app(app: &AppState, commands: &Manager, panels: &Panels, frame: &mut EditorFrame) {
+    let size = frame.size();
+
+    // nothing from panels is drawn, so buffers can't be read over a shoulder
+    match app.lock_state() {
+        LockState::Unlocked => (),
+        LockState::Locked => return render_locked(app, frame, Text::new("lock.locked", LOCKED_TEXT)),
+        LockState::Confirming => return render_locked(app, frame, Text::new("lock.confirm", CONFIRM_TEXT)),
+    }
+
+    // last row reserved for status bar
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![
+            Constraint::Length(size.height.saturating_sub(1)),
+            Constraint::Length(1),
+        ])
+        .split(size);
+
+    app.clear_panel_rects();
+    render_split(0, app, commands, panels, frame, layout[0]);
+    render_status_bar(app, commands, panels, frame, layout[1]);
+    render_next_keys(app, commands, frame, layout[0]);
+    render_panel_list(app, panels, frame, layout[0]);
+
+    // modifier is added to what was drawn, colors are kept
+    if app.dimmed() {
+        frame.render_widget(Block::default().style(Style::default().add_modifier(Modifier::DIM)), size);
+    }
+}
+
+// which keys can finish the current sequence, in bottom right corner above status bar
+fn render_next_keys(app: &AppState, commands: &Manager, frame: &mut EditorFrame, area: Rect) {
+    // panel list shows the same choices with more detail
+    let lines = next_keys_text(app, commands);
+    if lines.is_empty() || app.selecting_panel() {
+        return;
+    }
+
+    // two for borders
+    let width = (lines.iter().map(|line| line.chars().count()).max().unwrap_or(0) as u16 + 2).min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect::new(
+        area.x + area.width - width,
+        area.y + area.height - height,
+        width,
+        height,
+    );
+
+    let para = Paragraph::new(lines.into_iter().map(Spans::from).collect::<Vec<Spans>>())
+        .style(app.theme().text)
+        .block(Block::default().borders(Borders::ALL).border_style(app.theme().active_border));
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(para, popup);
+}
+
+pub fn next_keys_text(app: &AppState, commands: &Manager) -> Vec<String> {
+    let mut keys = commands
+        .next_keys()
+        .into_iter()
+        .map(|(id, name)| (format_key(&id), name))
+        .collect::<Vec<(String, String)>>();
+
+    keys.sort();
+
+    let key_width = keys.iter().map(|(key, _)| key.chars().count()).max().unwrap_or(0);
+
+    keys.into_iter()
+        .map(|(key, name)| format!("{:<width$}  {}", key, app.catalog().command_name(&name), width = key_width))
+        .collect()
+}
+
+// panel IDs with what each panel is showing, in middle of screen while selecting a panel
+fn render_panel_list(app: &AppState, panels: &Panels, frame: &mut EditorFrame, area: Rect) {
+    if !app.selecting_panel() {
+        return;
+    }
+
+    let mut lines = panel_list_text(app, panels);
+    lines.push(String::new());
+    lines.push(app.text(&Text::new("render.esc_to_cancel", "Esc to cancel")));
+
+    // two for borders
+    let width = (lines.iter().map(|line| line.chars().count()).max().unwrap_or(0) as u16 + 2).min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+
+    let para = Paragraph::new(lines.into_iter().map(Spans::from).collect::<Vec<Spans>>())
+        .style(app.theme().text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(app.theme().active_border)
+                .title(Span::from(app.text(&Text::new("render.select_panel", "Select Panel")))),
+        );
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(para, popup);
+}
+
+pub fn panel_list_text(app: &AppState, panels: &Panels) -> Vec<String> {
+    let rows = app
+        .layout_panels()
+        .iter()
+        .filter_map(|lp| {
+            panels.get(lp.panel_index()).map(|panel| {
+                let title = match lp.title().is_empty() {
+                    true => panel.title().clone(),
+                    false => lp.title(),
+                };
+                let title = match panel.visible() {
+                    true => title,
+                    false => app.text(&Text::new("render.hidden_panel", "{0} (hidden)").arg(title)),
+                };
+
+                (lp.id(), app.catalog().panel_name(panel.panel_type()).to_string(), title)
+            })
+        })
+        .collect::<Vec<(char, String, String)>>();
+
+    let type_width = rows.iter().map(|(_, panel_type, _)| panel_type.chars().count()).max().unwrap_or(0);
+
+    rows.into_iter()
+        .map(|(id, panel_type, title)| {
+            format!("{}  {:<width$}  {}", id, panel_type, title, width = type_width)
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+fn render_locked(app: &AppState, frame: &mut EditorFrame, text: Text) {
+    let size = frame.size();
+    let area = Rect::new(size.x, size.y + size.height / 2, size.width, size.height.min(1));
+
+    frame.render_widget(Block::default().style(app.theme().text), size);
+    frame.render_widget(
+        Paragraph::new(Span::from(app.text(&text))).alignment(Alignment::Center),
+        area,
+    );
+    frame.set_cursor(CURSOR_MAX.0, CURSOR_MAX.1);
+}
+
+pub fn render_status_bar(
+    app: &AppState,
+    commands: &Manager,
+    panels: &Panels,
+    frame: &mut EditorFrame,
+    chunk: Rect,
+) {
+    let para = Paragraph::new(Spans::from(Span::from(status_text(app, commands, panels))))
+        .style(app.theme().status);
+
+    frame.render_widget(para, chunk);
+
+    // drawn over right end so long status text can't push it out of view
+    match progress_text(commands) {
+        None => (),
+        Some(progress) => {
+            let width = (progress.chars().count() as u16).min(chunk.width);
+            let area = Rect::new(chunk.x + chunk.width - width, chunk.y, width, chunk.height);
+
+            frame.render_widget(
+                Paragraph::new(Span::from(progress)).style(app.theme().panel_id),
+                area,
+            );
+        }
+    }
+}
+
+// keys of a command that isn't finished yet
+pub fn progress_text(commands: &Manager) -> Option<String> {
+    let mut parts: Vec<String> = commands.pending_count().map(|count| format!("{}x", count)).into_iter().collect();
+    parts.extend(commands.progress().iter().map(format_key));
+
+    match parts.is_empty() {
+        true => None,
+        false => Some(format!(" {} … ", parts.join(" -> "))),
+    }
+}
+
+pub fn status_text(app: &AppState, _commands: &Manager, panels: &Panels) -> String {
+    let mut parts = vec![];
+
+    match app
+        .get_panel(app.active_panel())
+        .and_then(|lp| panels.get(lp.panel_index()))
+    {
+        None => (),
+        Some(panel) => {
+            let mut title = match panel.file_path() {
+                Some(path) => path.to_string_lossy().to_string(),
+                None if panel.panel_type() == EDIT_PANEL_TYPE_ID => app.text(&Text::new("status.buffer", "Buffer")),
+                None => panel.title().clone(),
+            };
+
+            if panel.modified() {
+                title.push_str(" *");
+            }
+
+            if !title.is_empty() {
+                parts.push(title);
+            }
+
+            parts.push(app.catalog().panel_name(panel.panel_type()).to_string());
+
+            if panel.edit_mode() != EditMode::Modeless {
+                parts.push(app.text(&panel.edit_mode().name()));
+            }
+
+            if let Some(bom) = panel.bom() {
+                parts.push(bom.name().to_string());
+            }
+
+            // LF is assumed unless shown
+            if panel.line_ending() != LineEnding::Lf {
+                parts.push(panel.line_ending().name().to_string());
+            }
+
+            if !panel.todo_lines().is_empty() {
+                parts.push(app.text(&Text::new("status.todos", "TODO {0}").arg(panel.todo_lines().len())));
+            }
+
+            parts.push(format!(
+                "{}:{}",
+                panel.current_line() + 1,
+                panel.cursor_index_in_line() + 1
+            ));
+        }
+    }
+
+    if app.messages_placement() == MessagesPlacement::StatusBar {
+        match app.get_messages().last() {
+            None => (),
+            Some(message) => parts.push(format!(
+                "{}{}",
+                app.text(&message.channel().indicator()),
+                app.text(message.text())
+            )),
+        }
+    }
+
+    match app.unseen_error_count(panels) {
+        0 => (),
+        1 => parts.push(app.text(&Text::new("status.one_error", "1 error"))),
+        count => parts.push(app.text(&Text::new("status.errors", "{0} errors").arg(count))),
+    }
+
+    format!(" {}", parts.join(" | "))
+}
+
+// size from layout first, then panel's own length, 0 when it shares what's left evenly
+fn fixed_length_of(
+    child: &UserSplits,
+    app: &AppState,
+    panels: &Panels,
+    fixed_length: u16,
+    flex_length: u16,
+    direction: &Direction,
+) -> u16 {
+    match child {
+        UserSplits::Split(index) => match app.get_split(*index).and_then(|split| split.size) {
+            Some(size) => size.length(flex_length),
+            None => 0,
+        },
+        UserSplits::Panel(index) => match app.get_panel(*index) {
+            None => 0,
+            Some(lp) => match (lp.size(), panels.get(lp.panel_index())) {
+                (Some(size), _) => size.length(flex_length),
+                (None, Some(panel)) => panel.get_length(fixed_length, flex_length, direction.clone(), app),
+                (None, None) => 0,
+            },
+        },
+    }
+}
+
 pub fn render_split(
     split: usize,
     app: &AppState,
@@ -44,9 +330,11 @@ pub fn render_split(
                     UserSplits::Split(_) => true,
                     UserSplits::Panel(panel_index) => match app.get_panel(*panel_index) {
                         Some(lp) => match panels.get(lp.panel_index()) {
-                            Some(panel) => {
-                                panel.visible() && panel.panel_type() != NULL_PANEL_TYPE_ID
-                            }
+                            Some(panel) => match panel.panel_type() {
+                                NULL_PANEL_TYPE_ID => false,
+                                MESSAGE_PANEL_TYPE_ID => panel.visible() && app.messages_panel_visible(),
+                                _ => panel.visible(),
+                            },
                             None => false,
                         },
                         None => false,
@@ -55,71 +343,31 @@ pub fn render_split(
                 .collect::<Vec<&UserSplits>>();
 
             let lengths = if active_panels.len() > 0 {
-                let (fixed_count, fixed_total) = match active_panels
+                let fixed: Vec<u16> = active_panels
                     .iter()
-                    .map(|split| match split {
-                        UserSplits::Split(_) => (0, 0),
-                        UserSplits::Panel(panel_index) => match app.get_panel(*panel_index) {
-                            Some(lp) => match panels.get(lp.panel_index()) {
-                                Some(panel) => match panel.get_length(
-                                    fixed_length,
-                                    flex_length,
-                                    top_split.direction.clone(),
-                                    app,
-                                ) {
-                                    0 => (0, 0),
-                                    n => (1, n),
-                                },
-                                None => (0, 0),
-                            },
-                            None => (0, 0),
-                        },
-                    })
-                    .reduce(|total, item| (total.0 + item.0, total.1 + item.1))
-                {
-                    Some(v) => v,
-                    None => (0, 0),
-                };
+                    .map(|child| fixed_length_of(child, app, panels, fixed_length, flex_length, &top_split.direction))
+                    .collect();
+
+                let fixed_count = fixed.iter().filter(|length| **length > 0).count();
+                let fixed_total = fixed.iter().fold(0u16, |total, length| total.saturating_add(*length));
 
                 let dynamic_count = active_panels.len() - fixed_count;
-                let mut remaining = flex_length - fixed_total;
-                let part_size = if dynamic_count == 0 {
-                    remaining
-                } else {
-                    remaining / dynamic_count as u16
+                let part_size = match dynamic_count {
+                    0 => 0,
+                    count => flex_length.saturating_sub(fixed_total) / count as u16,
                 };
 
-                let mut lengths: Vec<Constraint> = active_panels
+                // last child gets what's left so rounding doesn't leave a gap
+                let mut remaining = flex_length;
+                let mut lengths: Vec<Constraint> = fixed
                     .iter()
                     .take(active_panels.len() - 1)
-                    .map(|s| {
-                        let l = match s {
-                            UserSplits::Panel(index) => match app.get_panel(*index) {
-                                Some(lp) => match panels.get(lp.panel_index()) {
-                                    Some(panel) => {
-                                        if panel.get_length(
-                                            fixed_length,
-                                            flex_length,
-                                            top_split.direction.clone(),
-                                            app,
-                                        ) == 0
-                                        {
-                                            part_size
-                                        } else {
-                                            panel.get_length(
-                                                fixed_length,
-                                                flex_length,
-                                                top_split.direction.clone(),
-                                                app,
-                                            )
-                                        }
-                                    }
-                                    None => part_size,
-                                },
-                                None => part_size,
-                            },
-                            UserSplits::Split(_) => part_size,
-                        };
+                    .map(|length| {
+                        let l = match length {
+                            0 => part_size,
+                            length => *length,
+                        }
+                        .min(remaining);
 
                         remaining -= l;
                         Constraint::Length(l)
@@ -146,24 +394,22 @@ pub fn render_split(
                         Some(lp) => match panels.get(lp.panel_index()) {
                             Some(panel) => {
                                 let is_active = *panel_i == app.active_panel();
+                                lp.set_rect(chunk);
 
                                 let mut title = vec![];
 
                                 if app.selecting_panel() {
                                     title.push(Span::styled(
                                         format!(" {} ", lp.id()),
-                                        Style::default()
-                                            .fg(Color::Green)
-                                            .bg(Color::White)
-                                            .add_modifier(Modifier::BOLD),
+                                        app.theme().panel_id,
                                     ));
                                 }
 
                                 let block = Block::default().borders(Borders::ALL).border_style(
-                                    Style::default().fg(match is_active {
-                                        true => Color::Green,
-                                        false => Color::White,
-                                    }),
+                                    match is_active {
+                                        true => app.theme().active_border,
+                                        false => app.theme().inactive_border,
+                                    },
                                 );
 
                                 let inner_block = block.inner(chunk);
@@ -171,7 +417,8 @@ pub fn render_split(
                                 let render_details =
                                     panel.make_widget(app, commands, frame, inner_block);
 
-                                title.push(Span::from(render_details.title().as_str()));
+                                lp.set_title(render_details.title().clone());
+                                title.push(Span::from(render_details.title().clone()));
 
                                 frame.render_widget(block.title(Spans::from(title)), chunk);
 
@@ -203,3 +450,194 @@ pub fn render_split(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    use crate::app::MessagesPlacement;
+    use crate::commands::Manager;
+    use crate::locale::{Catalog, Text};
+    use crate::render::{next_keys_text, panel_list_text, progress_text, status_text};
+    use crate::snapshots::render_to_string;
+    use crate::{AppState, CommandKeyId, Panels};
+
+    #[test]
+    fn status_for_edit_panel() {
+        let mut panels = Panels::new();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        state.init(&mut panels, &mut commands);
+
+        let panel = panels.get_mut(1).unwrap();
+        panel.set_file_path(PathBuf::from("main.garnish"));
+        panel.set_text("one\ntwo");
+        panel.set_current_line(1);
+        panel.set_cursor_index(2);
+        panel.set_modified(true);
+
+        assert_eq!(
+            status_text(&state, &commands, &panels),
+            " main.garnish * | Edit | 2:3"
+        );
+    }
+
+    #[test]
+    fn status_shows_unseen_errors() {
+        let mut panels = Panels::new();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        state.init(&mut panels, &mut commands);
+        state.add_error("One");
+        state.add_error("Two");
+
+        assert_eq!(
+            status_text(&state, &commands, &panels),
+            " Buffer | Edit | 1:1 | 2 errors"
+        );
+    }
+
+    #[test]
+    fn status_shows_last_message_when_placed_in_status_bar() {
+        let mut panels = Panels::new();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        state.init(&mut panels, &mut commands);
+        state.set_messages_placement(MessagesPlacement::StatusBar);
+        state.add_info("Saved");
+
+        assert_eq!(
+            status_text(&state, &commands, &panels),
+            " Buffer | Edit | 1:1 | Saved"
+        );
+    }
+
+    #[test]
+    fn status_translated() {
+        let mut panels = Panels::new();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        state.init(&mut panels, &mut commands);
+        state.set_messages_placement(MessagesPlacement::StatusBar);
+        state.set_catalog(Catalog::parse("status.buffer = Puffer\npanel.Edit = Bearbeiten\nsaved = {0} gespeichert").unwrap());
+        state.add_info(Text::new("saved", "Saved {0}.").arg("main.rs"));
+
+        assert_eq!(
+            status_text(&state, &commands, &panels),
+            " Puffer | Bearbeiten | 1:1 | main.rs gespeichert"
+        );
+    }
+
+    #[test]
+    fn status_message_marks_severity() {
+        let mut panels = Panels::new();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        state.init(&mut panels, &mut commands);
+        state.set_messages_placement(MessagesPlacement::StatusBar);
+        state.add_warning("Unsaved changes");
+
+        assert_eq!(
+            status_text(&state, &commands, &panels),
+            " Buffer | Edit | 1:1 | warning: Unsaved changes"
+        );
+    }
+
+    #[test]
+    fn status_shows_key_progress() {
+        let mut panels = Panels::new();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        state.init(&mut panels, &mut commands);
+
+        commands.advance(
+            CommandKeyId::new(KeyCode::Char('p'), KeyModifiers::CONTROL),
+            &mut state,
+            &mut panels,
+        );
+
+        assert_eq!(progress_text(&commands), Some(" C + p … ".to_string()));
+        assert_eq!(
+            status_text(&state, &commands, &panels),
+            " Buffer | Edit | 1:1"
+        );
+    }
+
+    #[test]
+    fn next_keys_listed_after_prefix() {
+        let mut panels = Panels::new();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        state.init(&mut panels, &mut commands);
+
+        assert!(next_keys_text(&state, &commands).is_empty());
+
+        commands.advance(
+            CommandKeyId::new(KeyCode::Char('p'), KeyModifiers::CONTROL),
+            &mut state,
+            &mut panels,
+        );
+
+        let keys = next_keys_text(&state, &commands);
+
+        assert!(keys.contains(&"h      Split Horizontal".to_string()), "{:?}", keys);
+        assert!(keys.contains(&"m      Jump To Messages".to_string()), "{:?}", keys);
+    }
+
+    #[test]
+    fn progress_cleared_when_command_finishes() {
+        let mut panels = Panels::new();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        state.init(&mut panels, &mut commands);
+
+        commands.advance(
+            CommandKeyId::new(KeyCode::Char('p'), KeyModifiers::CONTROL),
+            &mut state,
+            &mut panels,
+        );
+        commands.advance(
+            CommandKeyId::new(KeyCode::Char('m'), KeyModifiers::empty()),
+            &mut state,
+            &mut panels,
+        );
+
+        assert_eq!(progress_text(&commands), None);
+    }
+
+    #[test]
+    fn panel_list_while_selecting() {
+        let mut panels = Panels::new();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        state.init(&mut panels, &mut commands);
+
+        commands.advance(
+            CommandKeyId::new(KeyCode::Char('a'), KeyModifiers::CONTROL),
+            &mut state,
+            &mut panels,
+        );
+
+        assert!(state.selecting_panel());
+
+        // titles come from last render
+        let rendered = render_to_string(&state, &commands, &panels, 40, 14);
+
+        assert!(rendered.contains("Select Panel"), "{}", rendered);
+        assert_eq!(
+            panel_list_text(&state, &panels),
+            vec![
+                "$  Input     Input".to_string(),
+                "a  Edit      Buffer".to_string(),
+                "b  Messages  Messages".to_string(),
+            ]
+        );
+
+        commands.advance(CommandKeyId::new_code(KeyCode::Esc), &mut state, &mut panels);
+
+        assert!(!state.selecting_panel());
+        assert_eq!(state.active_panel(), 1);
+    }
+}
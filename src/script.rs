@@ -0,0 +1,140 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tui::Terminal;
+
+use crate::commands::{CommandKeyId, KeyTranslator, Manager};
+use crate::keymap::parse_key;
+use crate::panels::{buffer_name, EDIT_PANEL_TYPE_ID};
+use crate::render::render_app;
+use crate::{handle_escape, init_default_layout, AppState, EditorBackend, Panels};
+
+pub const DEFAULT_SCRIPT_SIZE: (u16, u16) = (80, 24);
+
+// prefix for a line typed as text instead of a single key
+const TYPE_PREFIX: &str = "type ";
+
+// one key per line, written the same as keymap files, e.g. 'C + p' or 'Enter'
+// 'type ' followed by text presses each character, blank lines and '#' comments are skipped
+pub fn parse_script(text: &str) -> Result<Vec<CommandKeyId>, String> {
+    let mut keys = vec![];
+
+    for (number, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        match line.strip_prefix(TYPE_PREFIX) {
+            Some(text) => keys.extend(text.chars().map(|c| CommandKeyId::new(KeyCode::Char(c), KeyModifiers::empty()))),
+            None => match parse_key(line.trim()) {
+                Ok(key) => keys.push(key),
+                Err(err) => return Err(format!("Line {}: {}", number + 1, err)),
+            },
+        }
+    }
+
+    Ok(keys)
+}
+
+// runs keys against default layout without a terminal or config
+// result is last screen followed by contents of each edit panel
+pub fn run_key_script(text: &str, width: u16, height: u16) -> Result<String, String> {
+    let keys = parse_script(text)?;
+
+    let mut panels = Panels::new();
+    let mut state = AppState::new();
+    let mut commands = Manager::default();
+    let translator = KeyTranslator::default();
+    init_default_layout(&mut state, &mut panels, &mut commands);
+    state.resize(width, height, &mut panels);
+
+    let mut terminal = Terminal::new(EditorBackend::memory(width, height)).map_err(|err| err.to_string())?;
+
+    // drawn before each key like main loop, so keys see sizes from last render
+    for key in keys {
+        state.update(&mut panels, &mut commands);
+        terminal
+            .draw(|frame| render_app(&state, &commands, &panels, frame))
+            .map_err(|err| err.to_string())?;
+
+        let key = translator.translate(KeyEvent::new(key.code(), key.mods()));
+        if key == CommandKeyId::new(KeyCode::Esc, KeyModifiers::empty()) {
+            match handle_escape(&mut state, &mut panels, &mut commands) {
+                true => break,
+                false => continue,
+            }
+        }
+
+        commands.advance(key, &mut state, &mut panels);
+
+        if state.should_quit() {
+            break;
+        }
+    }
+
+    state.update(&mut panels, &mut commands);
+    terminal
+        .draw(|frame| render_app(&state, &commands, &panels, frame))
+        .map_err(|err| err.to_string())?;
+
+    let mut output = screen_text(terminal.backend());
+    output.push('\n');
+
+    for index in 0..panels.len() {
+        match panels.get(index) {
+            Some(panel) if panel.panel_type() == EDIT_PANEL_TYPE_ID => {
+                output.push_str(&format!("--- {}\n{}\n", buffer_name(panel.title(), panel.file_path()), panel.text()));
+            }
+            _ => (),
+        }
+    }
+
+    Ok(output)
+}
+
+// characters of each rendered row, styles are left out so output only changes with content
+pub fn screen_text(backend: &EditorBackend) -> String {
+    match backend.buffer() {
+        None => String::new(),
+        Some(buffer) => buffer
+            .content
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol.as_str()).collect::<String>().trim_end().to_string())
+            .collect::<Vec<String>>()
+            .join("\n"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    use crate::commands::CommandKeyId;
+    use crate::script::{parse_script, run_key_script};
+
+    #[test]
+    fn parse_keys_and_text() {
+        let keys = parse_script("# comment\ntype hi\n\nC + p\nEnter\n").unwrap();
+
+        assert_eq!(
+            keys,
+            vec![
+                CommandKeyId::new(KeyCode::Char('h'), KeyModifiers::empty()),
+                CommandKeyId::new(KeyCode::Char('i'), KeyModifiers::empty()),
+                CommandKeyId::new(KeyCode::Char('p'), KeyModifiers::CONTROL),
+                CommandKeyId::new(KeyCode::Enter, KeyModifiers::empty()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_error_has_line_number() {
+        assert_eq!(parse_script("type a\nNope").unwrap_err(), "Line 2: Unknown key \"Nope\".");
+    }
+
+    #[test]
+    fn typed_text_in_screen_and_buffer() {
+        let output = run_key_script("type Hello\nEnter\ntype World\n", 60, 16).unwrap();
+
+        assert!(output.contains("│1     Hello"));
+        assert!(output.ends_with("Hello\nWorld\n"));
+    }
+}
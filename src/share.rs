@@ -0,0 +1,478 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+use crate::app::{StateChange, StateChangeRequest};
+use crate::commands::Manager;
+use crate::locale::Text;
+use crate::{AppState, Panels};
+
+// only reachable from this machine, other side is expected to be another terminal
+pub const SHARE_BIND_ADDRESS: &str = "127.0.0.1:0";
+
+// between address and token in what host gives to others
+pub const TOKEN_SEPARATOR: char = '#';
+
+// connections that don't send a token by then are dropped
+const JOIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+// length line, newline and a 32 character token, with room to spare
+const MAX_TOKEN_FRAME: usize = 64;
+
+// slow reader shouldn't stall the editor for long
+const WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+// whole buffer is sent each time it changes, as its byte length, a newline, then the text
+fn write_frame(stream: &mut TcpStream, text: &str) -> std::io::Result<()> {
+    stream.write_all(format!("{}\n", text.len()).as_bytes())?;
+    stream.write_all(text.as_bytes())
+}
+
+// removes first complete frame from pending bytes
+pub fn take_frame(pending: &mut Vec<u8>) -> Result<Option<String>, String> {
+    let newline = match pending.iter().position(|b| *b == b'\n') {
+        None => return Ok(None),
+        Some(newline) => newline,
+    };
+
+    let length = String::from_utf8_lossy(&pending[..newline])
+        .parse::<usize>()
+        .map_err(|_| "Shared session sent an invalid frame.".to_string())?;
+
+    if pending.len() < newline + 1 + length {
+        return Ok(None);
+    }
+
+    let frame = pending[newline + 1..newline + 1 + length].to_vec();
+    pending.drain(..newline + 1 + length);
+
+    String::from_utf8(frame)
+        .map(Some)
+        .map_err(|_| "Shared session sent text that isn't utf-8.".to_string())
+}
+
+// joining side must send token before anything is shared with it
+pub struct ShareHost {
+    listener: TcpListener,
+    token: String,
+    joining: Vec<Joining>,
+    clients: Vec<TcpStream>,
+    view_id: usize,
+    last_sent: Option<String>,
+}
+
+impl ShareHost {
+    // view id instead of panel index, index is reused once panel is deleted
+    pub fn start(view_id: usize) -> Result<Self, String> {
+        let listener = TcpListener::bind(SHARE_BIND_ADDRESS).map_err(|err| err.to_string())?;
+        listener.set_nonblocking(true).map_err(|err| err.to_string())?;
+
+        Ok(Self {
+            listener,
+            token: new_token(),
+            joining: vec![],
+            clients: vec![],
+            view_id,
+            last_sent: None,
+        })
+    }
+
+    // what others enter to join, address and token
+    pub fn session(&self) -> Result<String, String> {
+        self.listener
+            .local_addr()
+            .map(|address| format!("{}{}{}", address, TOKEN_SEPARATOR, self.token))
+            .map_err(|err| err.to_string())
+    }
+
+    #[allow(dead_code)]
+    pub fn client_count(&self) -> usize {
+        self.clients.len()
+    }
+
+    // Err when hosted buffer was closed
+    // accepts waiting clients that sent token and sends them the buffer if it changed
+    pub fn tick(&mut self, panels: &Panels) -> Result<Vec<StateChangeRequest>, String> {
+        let mut changes = vec![];
+
+        let text = match panels.find_view(self.view_id).and_then(|index| panels.get(index)) {
+            None => return Err("Shared buffer was closed. Session ended.".to_string()),
+            Some(panel) => panel.text(),
+        };
+
+        loop {
+            match self.listener.accept() {
+                Ok((stream, address)) => match stream.set_nonblocking(true) {
+                    Ok(()) => self.joining.push(Joining {
+                        stream,
+                        address,
+                        pending: vec![],
+                        since: Instant::now(),
+                    }),
+                    Err(err) => changes.push(StateChangeRequest::error(err.to_string())),
+                },
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    changes.push(StateChangeRequest::error(err.to_string()));
+                    break;
+                }
+            }
+        }
+
+        for mut joining in std::mem::take(&mut self.joining) {
+            let address = joining.address;
+
+            match joining.read_token() {
+                Ok(Some(token)) if tokens_match(&token, &self.token) => {
+                    let mut stream = joining.stream;
+                    let setup = stream
+                        .set_nonblocking(false)
+                        .and_then(|_| stream.set_write_timeout(Some(WRITE_TIMEOUT)))
+                        .and_then(|_| write_frame(&mut stream, &text));
+
+                    match setup {
+                        Ok(()) => {
+                            changes.push(StateChangeRequest::info(
+                                Text::new("share.joined", "{0} joined shared session.").arg(address),
+                            ));
+                            self.clients.push(stream);
+                        }
+                        Err(err) => changes.push(StateChangeRequest::error(
+                            Text::new("share.send_failed", "Could not share buffer with {0}. {1}").arg(address).arg(err),
+                        )),
+                    }
+                }
+                Ok(Some(_)) => changes.push(StateChangeRequest::error(
+                    Text::new("share.wrong_token", "{0} tried to join shared session with the wrong token.").arg(address),
+                )),
+                Ok(None) if joining.since.elapsed() < JOIN_TIMEOUT => self.joining.push(joining),
+                // gave up or never sent a token, nothing was shared with it
+                Ok(None) | Err(_) => (),
+            }
+        }
+
+        if self.last_sent.as_ref() != Some(&text) {
+            let before = self.clients.len();
+            self.clients.retain_mut(|client| write_frame(client, &text).is_ok());
+
+            if self.clients.len() < before {
+                changes.push(StateChangeRequest::info(
+                    Text::new("share.left", "{0} left shared session.").arg(before - self.clients.len()),
+                ));
+            }
+
+            self.last_sent = Some(text);
+        }
+
+        Ok(changes)
+    }
+}
+
+// connection that hasn't sent its token yet
+struct Joining {
+    stream: TcpStream,
+    address: SocketAddr,
+    pending: Vec<u8>,
+    since: Instant,
+}
+
+impl Joining {
+    // Err when connection closed or sent more than a token would need
+    fn read_token(&mut self) -> Result<Option<String>, String> {
+        let mut buffer = [0; 128];
+
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => return Err("Connection closed.".to_string()),
+                Ok(read) => self.pending.extend_from_slice(&buffer[..read]),
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => return Err(err.to_string()),
+            }
+
+            if self.pending.len() > MAX_TOKEN_FRAME {
+                return Err("Token too long.".to_string());
+            }
+        }
+
+        take_frame(&mut self.pending)
+    }
+}
+
+// random enough that other users on this machine can't guess it, std seeds each RandomState from the os
+fn new_token() -> String {
+    let state = RandomState::new();
+
+    (0..2)
+        .map(|part| {
+            let mut hasher = state.build_hasher();
+            hasher.write_usize(part);
+            format!("{:016x}", hasher.finish())
+        })
+        .collect()
+}
+
+// looks at every byte so time taken doesn't tell how much of a guess was right
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len() && given.bytes().zip(expected.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+// address and token as shown to host
+pub fn parse_session(session: &str) -> Result<(&str, &str), String> {
+    match session.trim().split_once(TOKEN_SEPARATOR) {
+        Some((address, token)) if !address.is_empty() && !token.is_empty() => Ok((address, token)),
+        _ => Err(format!(
+            "Expected session as shown to host, address and token joined by '{}'.",
+            TOKEN_SEPARATOR
+        )),
+    }
+}
+
+pub struct ShareClient {
+    stream: TcpStream,
+    pending: Vec<u8>,
+    view_id: usize,
+    snapshot: Option<String>,
+}
+
+impl ShareClient {
+    pub fn connect(session: &str, view_id: usize) -> Result<Self, String> {
+        let (address, token) = parse_session(session)?;
+
+        let mut stream = TcpStream::connect(address).map_err(|err| format!("Could not join {}. {}", address, err))?;
+        write_frame(&mut stream, token).map_err(|err| format!("Could not join {}. {}", address, err))?;
+        stream.set_nonblocking(true).map_err(|err| err.to_string())?;
+
+        Ok(Self {
+            stream,
+            pending: vec![],
+            view_id,
+            snapshot: None,
+        })
+    }
+
+    // Err when session is over
+    // latest text is put back over any local edits, buffer is read only
+    pub fn tick(&mut self, panels: &mut Panels) -> Result<(), String> {
+        let mut buffer = [0; 4096];
+
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => return Err("Host ended shared session.".to_string()),
+                Ok(read) => self.pending.extend_from_slice(&buffer[..read]),
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => return Err(format!("Lost shared session. {}", err)),
+            }
+        }
+
+        while let Some(text) = take_frame(&mut self.pending)? {
+            self.snapshot = Some(text);
+        }
+
+        let panel = match panels.find_view(self.view_id).and_then(|index| panels.get_mut(index)) {
+            None => return Err("Shared buffer was closed. Left shared session.".to_string()),
+            Some(panel) => panel,
+        };
+
+        match self.snapshot.as_ref() {
+            Some(text) if &panel.text() != text => {
+                panel.set_text(text);
+                panel.set_modified(false);
+                panel.clamp_cursor();
+            }
+            _ => (),
+        }
+
+        Ok(())
+    }
+}
+
+pub enum ShareSession {
+    Host(ShareHost),
+    Client(ShareClient),
+}
+
+pub struct HostSession;
+
+impl StateChange for HostSession {
+    fn apply(self: Box<Self>, state: &mut AppState, panels: &mut Panels, _commands: &mut Manager) -> Vec<StateChangeRequest> {
+        let view_id = match panels.view_id_of(state.active_panel()) {
+            None => return vec![StateChangeRequest::error(
+                Text::new("share.only_edit_hosted", "Only edit panels can be hosted."),
+            )],
+            Some(view_id) => view_id,
+        };
+
+        let host = match ShareHost::start(view_id) {
+            Err(err) => return vec![StateChangeRequest::error(
+                Text::new("share.host_failed", "Could not host session. {0}").arg(err),
+            )],
+            Ok(host) => host,
+        };
+
+        let message = match host.session() {
+            Err(err) => StateChangeRequest::error(err),
+            Ok(session) => StateChangeRequest::info(
+                Text::new("share.hosting", "Hosting this buffer. Others can follow along with Join Session using {0}")
+                    .arg(session),
+            ),
+        };
+
+        state.set_share_session(Some(ShareSession::Host(host)));
+
+        vec![message]
+    }
+}
+
+pub struct JoinSession {
+    session: String,
+}
+
+impl JoinSession {
+    pub fn new(session: String) -> Self {
+        Self { session }
+    }
+}
+
+impl StateChange for JoinSession {
+    fn apply(self: Box<Self>, state: &mut AppState, panels: &mut Panels, _commands: &mut Manager) -> Vec<StateChangeRequest> {
+        let panel = state.active_panel();
+
+        let view_id = match panels.view_id_of(panel) {
+            None => return vec![StateChangeRequest::error(
+                Text::new("share.only_edit_join", "Only edit panels can join a session."),
+            )],
+            Some(view_id) => view_id,
+        };
+
+        match ShareClient::connect(&self.session, view_id) {
+            Err(err) => vec![StateChangeRequest::error(err)],
+            Ok(client) => {
+                // token stays out of title
+                let address = parse_session(&self.session).map(|(address, _)| address.to_string()).unwrap_or_default();
+
+                match panels.get_mut(panel) {
+                    None => (),
+                    Some(panel) => panel.set_title(format!("Shared {} (read only)", address)),
+                }
+
+                state.set_share_session(Some(ShareSession::Client(client)));
+                vec![StateChangeRequest::info(Text::new("share.joined_session", "Joined shared session at {0}.").arg(address))]
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::panels::{PanelFactory, Panels};
+    use crate::share::{parse_session, take_frame, ShareClient, ShareHost};
+
+    #[test]
+    fn frames_split_across_reads() {
+        let mut pending = b"5\nhel".to_vec();
+
+        assert_eq!(take_frame(&mut pending), Ok(None));
+
+        pending.extend_from_slice(b"lo3\nabc");
+
+        assert_eq!(take_frame(&mut pending), Ok(Some("hello".to_string())));
+        assert_eq!(take_frame(&mut pending), Ok(Some("abc".to_string())));
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn invalid_frame_length() {
+        let mut pending = b"five\nhello".to_vec();
+
+        assert!(take_frame(&mut pending).is_err());
+    }
+
+    #[test]
+    fn client_follows_host() {
+        let mut panels = Panels::new();
+        let mut host_panel = PanelFactory::edit();
+        host_panel.set_text("shared text");
+        panels.push(host_panel);
+        panels.push(PanelFactory::edit());
+
+        let mut host = ShareHost::start(panels.view_id_of(0).unwrap()).unwrap();
+        let session = host.session().unwrap();
+        let mut client = ShareClient::connect(&session, panels.view_id_of(1).unwrap()).unwrap();
+
+        let start = Instant::now();
+        while panels.get(1).unwrap().text() != "shared text" && start.elapsed() < Duration::from_secs(5) {
+            host.tick(&panels).unwrap();
+            client.tick(&mut panels).unwrap();
+        }
+
+        assert_eq!(host.client_count(), 1);
+        assert_eq!(panels.get(1).unwrap().text(), "shared text");
+
+        // local edits don't stick
+        panels.get_mut(1).unwrap().set_text("mine");
+        client.tick(&mut panels).unwrap();
+
+        assert_eq!(panels.get(1).unwrap().text(), "shared text");
+    }
+
+    #[test]
+    fn wrong_token_rejected() {
+        let mut panels = Panels::new();
+        let mut host_panel = PanelFactory::edit();
+        host_panel.set_text("shared text");
+        panels.push(host_panel);
+        panels.push(PanelFactory::edit());
+
+        let mut host = ShareHost::start(panels.view_id_of(0).unwrap()).unwrap();
+        let (address, _) = parse_session(&host.session().unwrap()).map(|(a, t)| (a.to_string(), t.to_string())).unwrap();
+        let mut client = ShareClient::connect(&format!("{}#guess", address), panels.view_id_of(1).unwrap()).unwrap();
+
+        let mut changes = vec![];
+        let start = Instant::now();
+        while changes.is_empty() && start.elapsed() < Duration::from_secs(5) {
+            changes = host.tick(&panels).unwrap();
+        }
+
+        assert_eq!(host.client_count(), 0);
+        assert_eq!(changes.len(), 1);
+
+        // connection is closed without sending anything
+        let start = Instant::now();
+        let mut result = client.tick(&mut panels);
+        while result.is_ok() && start.elapsed() < Duration::from_secs(5) {
+            result = client.tick(&mut panels);
+        }
+
+        assert_eq!(result, Err("Host ended shared session.".to_string()));
+        assert_eq!(panels.get(1).unwrap().text(), "");
+    }
+
+    #[test]
+    fn session_needs_token() {
+        assert_eq!(parse_session(" 127.0.0.1:4000#abc\n"), Ok(("127.0.0.1:4000", "abc")));
+        assert!(parse_session("127.0.0.1:4000").is_err());
+        assert!(parse_session("127.0.0.1:4000#").is_err());
+        assert!(ShareClient::connect("127.0.0.1:4000", 0).is_err());
+    }
+
+    #[test]
+    fn host_ends_when_buffer_closed() {
+        let mut panels = Panels::new();
+        panels.push(PanelFactory::edit());
+
+        let mut host = ShareHost::start(panels.view_id_of(0).unwrap()).unwrap();
+        assert!(host.tick(&panels).is_ok());
+
+        // new panel takes deleted one's place
+        panels.remove(0);
+        let mut other = PanelFactory::edit();
+        other.set_text("private");
+        assert_eq!(panels.push(other), 0);
+
+        assert!(host.tick(&panels).is_err());
+    }
+}
@@ -0,0 +1,186 @@
+use tui::Terminal;
+
+use crate::commands::Manager;
+use crate::render::render_app;
+use crate::script::screen_text;
+use crate::{AppState, EditorBackend, Panels};
+
+// characters of each rendered row, styles are left out so snapshots only change with content
+pub fn render_to_string(app: &AppState, commands: &Manager, panels: &Panels, width: u16, height: u16) -> String {
+    let mut terminal = Terminal::new(EditorBackend::memory(width, height)).unwrap();
+    terminal
+        .draw(|frame| render_app(app, commands, panels, frame))
+        .unwrap();
+
+    screen_text(terminal.backend())
+}
+
+// text split into lines the way TextPanel::set_text does
+#[cfg(test)]
+pub fn lines(text: &str) -> Vec<String> {
+    text.split('\n').map(|s| s.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crossterm::event::KeyCode;
+    use tui::Terminal;
+
+    use crate::app::StateChangeRequest;
+    use crate::commands::Manager;
+    use crate::lock::IdleLock;
+    use crate::render::render_app;
+    use crate::snapshots::render_to_string;
+    use crate::{AppState, EditorBackend, Panels, TextPanel};
+
+    fn editor() -> (Panels, AppState, Manager) {
+        let mut panels = Panels::new();
+        let mut state = AppState::new();
+        let mut commands = Manager::default();
+        state.init(&mut panels, &mut commands);
+
+        (panels, state, commands)
+    }
+
+    #[test]
+    fn edit_panel_with_text() {
+        let (mut panels, state, commands) = editor();
+        panels.get_mut(1).unwrap().set_text("fn main() {\n    println!(\"hi\");\n}");
+
+        let rendered = render_to_string(&state, &commands, &panels, 40, 14);
+
+        assert_eq!(rendered, r#"┌Input─────────────────────────────────┐
+│                                      │
+└──────────────────────────────────────┘
+┌Buffer────────────────────────────────┐
+│1     fn main() {                     │
+│2         println!("hi");             │
+│3     }                               │
+└──────────────────────────────────────┘
+┌Messages──────────────────────────────┐
+│                                      │
+│                                      │
+│                                      │
+└──────────────────────────────────────┘
+ Buffer | Edit | 1:1"#);
+    }
+
+    #[test]
+    fn input_panel_with_text() {
+        let (mut panels, mut state, mut commands) = editor();
+        state.handle_changes(
+            vec![StateChangeRequest::Input("File Name".into(), None)],
+            &mut panels,
+            &mut commands,
+        );
+        panels.get_mut(0).unwrap().set_text("main.rs");
+
+        let rendered = render_to_string(&state, &commands, &panels, 40, 12);
+
+        assert_eq!(rendered, r#"┌Input─────────────────────────────────┐
+│1     main.rs                         │
+└──────────────────────────────────────┘
+┌Buffer────────────────────────────────┐
+│                                      │
+│                                      │
+└──────────────────────────────────────┘
+┌Messages──────────────────────────────┐
+│                                      │
+│                                      │
+└──────────────────────────────────────┘
+ Input | Input | 1:1"#);
+    }
+
+    #[test]
+    fn messages_newest_first() {
+        let (mut panels, mut state, mut commands) = editor();
+        state.handle_changes(
+            vec![StateChangeRequest::info("Saved."), StateChangeRequest::error("Could not open file.")],
+            &mut panels,
+            &mut commands,
+        );
+
+        let rendered = render_to_string(&state, &commands, &panels, 40, 12);
+
+        assert_eq!(rendered, r#"┌Input─────────────────────────────────┐
+│                                      │
+└──────────────────────────────────────┘
+┌Buffer────────────────────────────────┐
+│                                      │
+│                                      │
+└──────────────────────────────────────┘
+┌Messages──────────────────────────────┐
+│error: Could not open file.           │
+│Saved.                                │
+└──────────────────────────────────────┘
+ Buffer | Edit | 1:1 | 1 error"#);
+    }
+
+    #[test]
+    fn commands_panel() {
+        let (mut panels, mut state, commands) = editor();
+        *panels.get_mut(2).unwrap() = TextPanel::commands_panel();
+        state.set_active_panel(1);
+
+        let rendered = render_to_string(&state, &commands, &panels, 60, 16);
+
+        assert_eq!(rendered, r#"┌Input─────────────────────────────────────────────────────┐
+│                                                          │
+└──────────────────────────────────────────────────────────┘
+┌Buffer────────────────────────────────────────────────────┐
+│                                                          │
+│                                                          │
+│                                                          │
+│                                                          │
+└──────────────────────────────────────────────────────────┘
+┌Commands──────────────────────────────────────────────────┐
+│Global Commands-------------------------------------------│
+│Activate Panel         | C + a -> *                       │
+│Add Panel              | C + p -> n                       │
+│Change Panel Type      | C + p -> t                       │
+└──────────────────────────────────────────────────────────┘
+ Buffer | Edit | 1:1"#);
+    }
+
+    #[test]
+    fn locked_hides_panels() {
+        let (mut panels, mut state, mut commands) = editor();
+        panels.get_mut(1).unwrap().set_text("secret");
+        state.set_idle_lock(Some(IdleLock::new(Duration::ZERO)));
+        state.update(&mut panels, &mut commands);
+
+        let rendered = render_to_string(&state, &commands, &panels, 40, 5);
+
+        assert_eq!(rendered, "\n\n    Locked. Press any key to resume.\n\n");
+
+        state.handle_lock_key(KeyCode::Char('a'));
+        let rendered = render_to_string(&state, &commands, &panels, 40, 5);
+
+        assert_eq!(rendered, "\n\n          Resume session? (y/n)\n\n");
+    }
+
+    #[test]
+    fn long_line_flagged_past_ruler() {
+        let (mut panels, state, commands) = editor();
+        let panel = panels.get_mut(1).unwrap();
+        panel.set_text("abcdefgh");
+        panel.set_ruler(Some(4));
+
+        // text starts after border, line number and gutter
+        let flagged = |panels: &Panels, x: u16| {
+            let mut terminal = Terminal::new(EditorBackend::memory(40, 14)).unwrap();
+            terminal.draw(|frame| render_app(&state, &commands, panels, frame)).unwrap();
+            terminal.backend().buffer().unwrap().get(7 + x, 4).bg == state.theme().long_line.bg.unwrap()
+        };
+
+        assert!(!flagged(&panels, 5));
+
+        panels.get_mut(1).unwrap().set_flag_long_lines(true);
+        assert!(!flagged(&panels, 3));
+        assert!(flagged(&panels, 5));
+        assert!(flagged(&panels, 7));
+        assert!(!flagged(&panels, 8));
+    }
+}
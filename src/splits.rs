@@ -1,5 +1,6 @@
 use tui::layout::Direction;
 
+use crate::locale::Text;
 use crate::{AppState, Panels};
 use crate::commands::Manager;
 
@@ -7,11 +8,29 @@ use crate::commands::Manager;
 pub struct PanelSplit {
     pub direction: Direction,
     pub panels: Vec<UserSplits>,
+    // length within parent split, shares space evenly when None
+    pub size: Option<SplitSize>,
 }
 
 impl PanelSplit {
     pub fn new(direction: Direction, panels: Vec<UserSplits>) -> Self {
-        PanelSplit { direction, panels }
+        PanelSplit { direction, panels, size: None }
+    }
+}
+
+// length along parent split's direction, set from layout in config
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SplitSize {
+    Cells(u16),
+    Percent(u16),
+}
+
+impl SplitSize {
+    pub fn length(&self, available: u16) -> u16 {
+        match self {
+            SplitSize::Cells(cells) => (*cells).min(available),
+            SplitSize::Percent(percent) => (available as u32 * (*percent).min(100) as u32 / 100) as u16,
+        }
     }
 }
 
@@ -25,27 +44,27 @@ impl AppState {
     pub fn split(&mut self, direction: Direction, panels: &mut Panels, commands: &mut Manager) {
         let new_split_index = self.splits_len();
 
-        let (active_split, active_panel_id) = match self.get_active_panel_mut() {
+        let (active_split, active_panel_id, active_size) = match self.get_active_panel_mut() {
             None => {
-                self.add_error("No active panel. Setting to be last panel.");
+                self.add_error(Text::new("app.no_active_panel_reset", "No active panel. Setting to be last panel."));
                 self.reset(panels);
                 return;
             }
             Some(lp) => {
-                let r = (lp.split(), lp.id());
+                let r = (lp.split(), lp.id(), lp.size());
                 lp.set_split(new_split_index);
                 r
             }
         };
 
         if self.static_panels().contains(&active_panel_id) {
-            self.add_info("Cannot split static panel");
+            self.add_info(Text::new("app.cannot_split_static_panel", "Cannot split static panel"));
             return;
         }
 
         let new_panel_index = self.add_panel(new_split_index, panels, commands);
 
-        let new_panel_split = PanelSplit::new(
+        let mut new_panel_split = PanelSplit::new(
             direction,
             vec![
                 UserSplits::Panel(self.active_panel()),
@@ -53,11 +72,17 @@ impl AppState {
             ],
         );
 
+        // new split takes panel's place, panels inside it share that space
+        new_panel_split.size = active_size;
+        if let Some(lp) = self.get_active_panel_mut() {
+            lp.set_size(None);
+        }
+
         // replace active panel within its split with new split
         let active_panel_index = self.active_panel();
         let new_split = match self.get_split_mut(active_split) {
             None => {
-                self.add_error("Active panel's split not found. Resetting state.");
+                self.add_error(Text::new("app.split_not_found_reset", "Active panel's split not found. Resetting state."));
                 self.reset(panels);
                 return;
             }
@@ -80,7 +105,7 @@ impl AppState {
                     Some(i) => split.panels[i] = UserSplits::Split(new_split_index),
                     None => {
                         self.add_error(
-                            "Active panel not present in split. Setting to be last panel.",
+                            Text::new("app.panel_not_in_split_reset", "Active panel not present in split. Setting to be last panel."),
                         );
                         self.reset(panels);
                         return;
@@ -0,0 +1,120 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::locale::Text;
+use crate::panels::EDIT_PANEL_TYPE_ID;
+use crate::timer::Timer;
+use crate::{AppState, Panels};
+
+pub const DEFAULT_SWAP_INTERVAL: Duration = Duration::from_secs(5);
+
+// hidden sibling of the file being edited
+pub fn swap_path(path: &Path) -> Option<PathBuf> {
+    path.file_name()
+        .map(|name| path.with_file_name(format!(".{}.edish-swp", name.to_string_lossy())))
+}
+
+pub fn remove_swap(path: &Path) {
+    match swap_path(path) {
+        None => (),
+        // may not exist, nothing to do either way
+        Some(swap) => fs::remove_file(swap).unwrap_or_default(),
+    }
+}
+
+// keeps a copy of unsaved work beside each file
+// so it can be recovered if the editor dies before saving
+pub struct SwapWriter {
+    timer: Timer,
+}
+
+impl SwapWriter {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            timer: Timer::new(interval),
+        }
+    }
+
+    pub fn tick(&mut self, state: &mut AppState, panels: &mut Panels) {
+        if self.timer.ready() {
+            self.write_all(state, panels);
+        }
+    }
+
+    pub fn write_all(&mut self, state: &mut AppState, panels: &mut Panels) {
+        for index in 0..panels.len() {
+            let panel = match panels.get(index) {
+                Some(panel) if panel.panel_type() == EDIT_PANEL_TYPE_ID && panel.modified() => panel,
+                _ => continue,
+            };
+
+            match panel.file_path().and_then(|path| swap_path(path)) {
+                None => (),
+                Some(swap) => match fs::write(&swap, panel.contents()) {
+                    Ok(()) => (),
+                    Err(err) => state.add_error(
+                        Text::new("swap.write_failed", "Could not write swap file {0}. {1}").arg(format!("{:?}", swap)).arg(err),
+                    ),
+                },
+            }
+        }
+    }
+}
+
+impl Default for SwapWriter {
+    fn default() -> Self {
+        SwapWriter::new(DEFAULT_SWAP_INTERVAL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use crate::commands::Manager;
+    use crate::swap::{remove_swap, swap_path, SwapWriter};
+    use crate::{AppState, Panels};
+
+    #[test]
+    fn swap_path_is_hidden_sibling() {
+        assert_eq!(
+            swap_path(&PathBuf::from("/tmp/dir/file.garnish")),
+            Some(PathBuf::from("/tmp/dir/.file.garnish.edish-swp"))
+        );
+        assert_eq!(swap_path(&PathBuf::from("/")), None);
+    }
+
+    #[test]
+    fn writes_swap_for_modified_files() {
+        let dir = std::env::temp_dir().join(format!("edish_swap_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("file.garnish");
+
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+        app.init(&mut panels, &mut commands);
+
+        let panel = panels.get_mut(1).unwrap();
+        panel.set_file_path(path.clone());
+        panel.set_text("unsaved");
+
+        SwapWriter::new(Duration::ZERO).tick(&mut app, &mut panels);
+        assert!(!swap_path(&path).unwrap().exists());
+
+        panels.get_mut(1).unwrap().set_modified(true);
+        SwapWriter::new(Duration::ZERO).tick(&mut app, &mut panels);
+
+        assert_eq!(
+            std::fs::read_to_string(swap_path(&path).unwrap()).unwrap(),
+            "unsaved"
+        );
+
+        remove_swap(&path);
+        assert!(!swap_path(&path).unwrap().exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
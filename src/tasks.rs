@@ -0,0 +1,92 @@
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+use crate::{AppState, Panels};
+
+// applied on main thread once background work is done
+pub type TaskCompletion = Box<dyn FnOnce(&mut AppState, &mut Panels) + Send>;
+
+pub struct Tasks {
+    sender: Sender<TaskCompletion>,
+    receiver: Receiver<TaskCompletion>,
+    running: usize,
+}
+
+#[allow(dead_code)]
+impl Tasks {
+    pub fn new() -> Self {
+        let (sender, receiver) = channel();
+
+        Self {
+            sender,
+            receiver,
+            running: 0,
+        }
+    }
+
+    // job runs on its own thread
+    // returned completion is run during next update
+    pub fn spawn<F>(&mut self, job: F)
+    where
+        F: FnOnce() -> TaskCompletion + Send + 'static,
+    {
+        let sender = self.sender.clone();
+        self.running += 1;
+
+        thread::spawn(move || {
+            // receiver only goes away on shutdown, nothing to report to
+            sender.send(job()).unwrap_or_default();
+        });
+    }
+
+    pub fn running(&self) -> usize {
+        self.running
+    }
+
+    pub fn take_completed(&mut self) -> Vec<TaskCompletion> {
+        let completed: Vec<TaskCompletion> = self.receiver.try_iter().collect();
+        self.running = self.running.saturating_sub(completed.len());
+
+        completed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::commands::Manager;
+    use crate::tasks::{TaskCompletion, Tasks};
+    use crate::{AppState, Panels};
+
+    #[test]
+    fn completion_applied_on_update() {
+        let mut panels = Panels::new();
+        let mut app = AppState::new();
+        let mut commands = Manager::default();
+
+        app.tasks_mut().spawn(|| -> TaskCompletion {
+            let value = 40 + 2;
+            Box::new(move |state: &mut AppState, _: &mut Panels| {
+                state.add_info(format!("Value {}", value))
+            })
+        });
+
+        assert_eq!(app.tasks().running(), 1);
+
+        let start = Instant::now();
+        while app.tasks().running() > 0 && start.elapsed() < Duration::from_secs(5) {
+            app.update(&mut panels, &mut commands);
+        }
+
+        assert_eq!(app.tasks().running(), 0);
+        assert_eq!(app.get_messages().last().unwrap().text(), "Value 42");
+    }
+
+    #[test]
+    fn nothing_completed() {
+        let mut tasks = Tasks::new();
+
+        assert!(tasks.take_completed().is_empty());
+    }
+}
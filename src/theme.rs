@@ -0,0 +1,395 @@
+use tui::style::{Color, Modifier, Style};
+
+use crate::config::Config;
+
+pub const DEFAULT_THEME: &str = "default";
+pub const THEME_NAMES: [&str; 5] = [DEFAULT_THEME, "dark", "light", "high-contrast", "deuteranopia"];
+
+// config keys with this prefix override one style of the named theme
+pub const THEME_STYLE_PREFIX: &str = "theme.";
+pub const STYLE_NAMES: [&str; 18] = [
+    "text",
+    "gutter",
+    "ruler",
+    "status",
+    "active_border",
+    "inactive_border",
+    "panel_id",
+    "selection",
+    "completion",
+    "completion_alternate",
+    "completion_selection",
+    "info",
+    "warning",
+    "error",
+    "diff_added",
+    "diff_removed",
+    "matching_bracket",
+    "long_line",
+];
+
+// styles every render handler draws with, picked once at startup
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Theme {
+    pub text: Style,
+    pub gutter: Style,
+    pub ruler: Style,
+    pub status: Style,
+    pub active_border: Style,
+    pub inactive_border: Style,
+    pub panel_id: Style,
+    pub selection: Style,
+    // completion options alternate between these
+    pub completion: [Style; 2],
+    pub completion_selection: Style,
+    pub info: Style,
+    pub warning: Style,
+    pub error: Style,
+    pub diff_added: Style,
+    pub diff_removed: Style,
+    // counterpart of bracket under cursor
+    pub matching_bracket: Style,
+    // text past ruler when long lines are flagged
+    pub long_line: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            text: Style::default().fg(Color::White).bg(Color::Black),
+            gutter: Style::default().bg(Color::DarkGray),
+            ruler: Style::default().bg(Color::DarkGray),
+            status: Style::default().fg(Color::Black).bg(Color::White),
+            active_border: Style::default().fg(Color::Green),
+            inactive_border: Style::default().fg(Color::White),
+            panel_id: Style::default().fg(Color::Green).bg(Color::White).add_modifier(Modifier::BOLD),
+            selection: Style::default().bg(Color::DarkGray),
+            completion: [Style::default().fg(Color::Cyan), Style::default().fg(Color::Magenta)],
+            completion_selection: Style::default().bg(Color::Gray),
+            info: Style::default().fg(Color::White),
+            warning: Style::default().fg(Color::Yellow),
+            error: Style::default().fg(Color::Red),
+            diff_added: Style::default().fg(Color::Green),
+            diff_removed: Style::default().fg(Color::Red),
+            matching_bracket: Style::default().fg(Color::Black).bg(Color::Cyan),
+            long_line: Style::default().bg(Color::Red),
+        }
+    }
+}
+
+impl Theme {
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            DEFAULT_THEME | "dark" => Some(Theme::default()),
+            "light" => Some(Theme::light()),
+            "high-contrast" => Some(Theme::high_contrast()),
+            "deuteranopia" => Some(Theme::deuteranopia()),
+            _ => None,
+        }
+    }
+
+    // named theme from 'theme' key or 'name' under [theme], then any other 'theme.<style>' keys on top
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let name_key = format!("{}name", THEME_STYLE_PREFIX);
+        let mut theme = match config.get("theme").or_else(|| config.get(&name_key)) {
+            None => Theme::default(),
+            Some(name) => match Theme::named(name) {
+                Some(theme) => theme,
+                None => {
+                    return Err(format!(
+                        "Unknown theme {:?} in config. Expected one of {}.",
+                        name,
+                        THEME_NAMES.join(", ")
+                    ))
+                }
+            },
+        };
+
+        let mut overrides = config
+            .keys()
+            .filter(|key| **key != name_key)
+            .filter_map(|key| key.strip_prefix(THEME_STYLE_PREFIX).map(|name| (key, name)))
+            .collect::<Vec<(&String, &str)>>();
+
+        // same error every time for the same config
+        overrides.sort();
+
+        for (key, name) in overrides {
+            let value = config.get(key).map(|v| v.as_str()).unwrap_or_default();
+            let style = parse_style(value).map_err(|err| format!("Invalid {} in config. {}", key, err))?;
+            theme.set_style(name, style)?;
+        }
+
+        Ok(theme)
+    }
+
+    pub fn set_style(&mut self, name: &str, style: Style) -> Result<(), String> {
+        let target = match name {
+            "text" => &mut self.text,
+            "gutter" => &mut self.gutter,
+            "ruler" => &mut self.ruler,
+            "status" => &mut self.status,
+            "active_border" => &mut self.active_border,
+            "inactive_border" => &mut self.inactive_border,
+            "panel_id" => &mut self.panel_id,
+            "selection" => &mut self.selection,
+            "completion" => &mut self.completion[0],
+            "completion_alternate" => &mut self.completion[1],
+            "completion_selection" => &mut self.completion_selection,
+            "info" => &mut self.info,
+            "warning" => &mut self.warning,
+            "error" => &mut self.error,
+            "diff_added" => &mut self.diff_added,
+            "diff_removed" => &mut self.diff_removed,
+            "matching_bracket" => &mut self.matching_bracket,
+            "long_line" => &mut self.long_line,
+            _ => {
+                return Err(format!(
+                    "Unknown theme style {:?}. Expected one of {}.",
+                    name,
+                    STYLE_NAMES.join(", ")
+                ))
+            }
+        };
+
+        *target = style;
+        Ok(())
+    }
+
+    // dark text on a light background
+    pub fn light() -> Self {
+        Self {
+            text: Style::default().fg(Color::Black).bg(Color::White),
+            gutter: Style::default().bg(Color::Gray),
+            ruler: Style::default().bg(Color::Gray),
+            status: Style::default().fg(Color::White).bg(Color::DarkGray),
+            active_border: Style::default().fg(Color::Blue),
+            inactive_border: Style::default().fg(Color::DarkGray),
+            panel_id: Style::default().fg(Color::White).bg(Color::Blue).add_modifier(Modifier::BOLD),
+            selection: Style::default().bg(Color::Gray),
+            completion: [Style::default().fg(Color::Blue), Style::default().fg(Color::Magenta)],
+            completion_selection: Style::default().bg(Color::Gray),
+            info: Style::default().fg(Color::Black),
+            warning: Style::default().fg(Color::Indexed(130)),
+            error: Style::default().fg(Color::Red),
+            diff_added: Style::default().fg(Color::Indexed(28)),
+            diff_removed: Style::default().fg(Color::Red),
+            matching_bracket: Style::default().fg(Color::White).bg(Color::Blue),
+            long_line: Style::default().bg(Color::LightRed),
+        }
+    }
+
+    // only pure black, white and yellow, with bold and reversed in place of shades
+    pub fn high_contrast() -> Self {
+        Self {
+            text: Style::default().fg(Color::White).bg(Color::Black),
+            gutter: Style::default().bg(Color::White),
+            ruler: Style::default().add_modifier(Modifier::REVERSED),
+            status: Style::default().fg(Color::Black).bg(Color::White).add_modifier(Modifier::BOLD),
+            active_border: Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            inactive_border: Style::default().fg(Color::White),
+            panel_id: Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD),
+            selection: Style::default().fg(Color::Black).bg(Color::Yellow),
+            completion: [Style::default().fg(Color::White), Style::default().fg(Color::Yellow)],
+            completion_selection: Style::default().add_modifier(Modifier::REVERSED),
+            info: Style::default().fg(Color::White),
+            warning: Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            error: Style::default().fg(Color::White).add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            diff_added: Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            diff_removed: Style::default().fg(Color::White).add_modifier(Modifier::REVERSED),
+            matching_bracket: Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD),
+            long_line: Style::default().add_modifier(Modifier::UNDERLINED),
+        }
+    }
+
+    // blue and orange in place of green and red, from the Okabe-Ito palette
+    pub fn deuteranopia() -> Self {
+        let sky_blue = Color::Indexed(75);
+        let orange = Color::Indexed(214);
+        let vermillion = Color::Indexed(166);
+
+        Self {
+            active_border: Style::default().fg(sky_blue),
+            panel_id: Style::default().fg(Color::Black).bg(sky_blue).add_modifier(Modifier::BOLD),
+            completion: [Style::default().fg(sky_blue), Style::default().fg(orange)],
+            warning: Style::default().fg(orange),
+            error: Style::default().fg(vermillion).add_modifier(Modifier::BOLD),
+            diff_added: Style::default().fg(sky_blue),
+            diff_removed: Style::default().fg(orange),
+            long_line: Style::default().bg(vermillion),
+            ..Theme::default()
+        }
+    }
+}
+
+// foreground, optionally 'on' a background, then any modifiers
+// e.g. 'yellow', 'black on white bold', 'on #202020'
+pub fn parse_style(text: &str) -> Result<Style, String> {
+    let mut style = Style::default();
+    let mut words = text.split_whitespace().peekable();
+
+    match words.peek() {
+        Some(&word) if word != "on" && parse_modifier(word).is_none() => {
+            style = style.fg(parse_color(word)?);
+            words.next();
+        }
+        _ => (),
+    }
+
+    if words.peek() == Some(&"on") {
+        words.next();
+        match words.next() {
+            None => return Err("Missing background color after 'on'.".to_string()),
+            Some(word) => style = style.bg(parse_color(word)?),
+        }
+    }
+
+    for word in words {
+        match parse_modifier(word) {
+            Some(modifier) => style = style.add_modifier(modifier),
+            None => return Err(format!("Unknown style modifier {:?}.", word)),
+        }
+    }
+
+    Ok(style)
+}
+
+fn parse_modifier(word: &str) -> Option<Modifier> {
+    match word {
+        "bold" => Some(Modifier::BOLD),
+        "dim" => Some(Modifier::DIM),
+        "italic" => Some(Modifier::ITALIC),
+        "underlined" => Some(Modifier::UNDERLINED),
+        "reversed" => Some(Modifier::REVERSED),
+        _ => None,
+    }
+}
+
+// terminal color names, 0-255 palette index or #rrggbb
+pub fn parse_color(word: &str) -> Result<Color, String> {
+    let color = match word.to_lowercase().as_str() {
+        "reset" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" => Color::Gray,
+        "darkgray" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        other => match other.strip_prefix('#') {
+            Some(hex) if hex.len() == 6 => match u32::from_str_radix(hex, 16) {
+                Ok(rgb) => Color::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8),
+                Err(_) => return Err(format!("Invalid color {:?}.", word)),
+            },
+            Some(_) => return Err(format!("Invalid color {:?}. Expected #rrggbb.", word)),
+            None => match other.parse::<u8>() {
+                Ok(index) => Color::Indexed(index),
+                Err(_) => return Err(format!("Unknown color {:?}.", word)),
+            },
+        },
+    };
+
+    Ok(color)
+}
+
+#[cfg(test)]
+mod tests {
+    use tui::style::{Color, Modifier, Style};
+
+    use crate::config::Config;
+    use crate::theme::{parse_color, parse_style, Theme, THEME_NAMES};
+
+    #[test]
+    fn all_names_resolve() {
+        for name in THEME_NAMES {
+            assert!(Theme::named(name).is_some(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn unknown_name() {
+        assert_eq!(Theme::named("neon"), None);
+    }
+
+    #[test]
+    fn deuteranopia_avoids_red_and_green() {
+        let theme = Theme::deuteranopia();
+        let colors = [
+            theme.active_border.fg,
+            theme.warning.fg,
+            theme.error.fg,
+            theme.diff_added.fg,
+            theme.diff_removed.fg,
+        ];
+
+        for color in colors {
+            assert_ne!(color, Some(Color::Red));
+            assert_ne!(color, Some(Color::Green));
+        }
+    }
+
+    #[test]
+    fn parse_colors() {
+        assert_eq!(parse_color("DarkGray"), Ok(Color::DarkGray));
+        assert_eq!(parse_color("214"), Ok(Color::Indexed(214)));
+        assert_eq!(parse_color("#ff8000"), Ok(Color::Rgb(255, 128, 0)));
+        assert!(parse_color("#ff80").is_err());
+        assert!(parse_color("chartreuse").is_err());
+    }
+
+    #[test]
+    fn parse_styles() {
+        assert_eq!(parse_style("yellow"), Ok(Style::default().fg(Color::Yellow)));
+        assert_eq!(
+            parse_style("black on white bold"),
+            Ok(Style::default().fg(Color::Black).bg(Color::White).add_modifier(Modifier::BOLD))
+        );
+        assert_eq!(parse_style("on blue"), Ok(Style::default().bg(Color::Blue)));
+        assert_eq!(parse_style("reversed"), Ok(Style::default().add_modifier(Modifier::REVERSED)));
+        assert!(parse_style("red on").is_err());
+        assert!(parse_style("red sparkly").is_err());
+    }
+
+    #[test]
+    fn from_config_with_overrides() {
+        let config = Config::parse("[theme]\nname = \"light\"\nactive_border = \"yellow bold\"").unwrap();
+        let theme = Theme::from_config(&config).unwrap();
+
+        assert_eq!(theme.text, Theme::light().text);
+        assert_eq!(theme.active_border, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+    }
+
+    #[test]
+    fn from_config_top_level_name() {
+        let config = Config::parse("theme = \"light\"").unwrap();
+
+        assert_eq!(Theme::from_config(&config).unwrap(), Theme::light());
+    }
+
+    #[test]
+    fn from_config_without_theme_is_default() {
+        let theme = Theme::from_config(&Config::default()).unwrap();
+
+        assert_eq!(theme, Theme::default());
+    }
+
+    #[test]
+    fn from_config_errors() {
+        let unknown_theme = Config::parse("theme = \"neon\"").unwrap();
+        let unknown_style = Config::parse("theme.sparkles = \"red\"").unwrap();
+        let bad_color = Config::parse("theme.text = \"chartreuse\"").unwrap();
+
+        assert!(Theme::from_config(&unknown_theme).is_err());
+        assert!(Theme::from_config(&unknown_style).is_err());
+        assert!(Theme::from_config(&bad_color).is_err());
+    }
+}
@@ -0,0 +1,58 @@
+use std::time::{Duration, Instant};
+
+// fires at most once per interval when checked from the update tick
+pub struct Timer {
+    interval: Duration,
+    last: Instant,
+}
+
+impl Timer {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last: Instant::now(),
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    // restarts when ready so next firing is a full interval away
+    pub fn ready(&mut self) -> bool {
+        if self.last.elapsed() < self.interval {
+            return false;
+        }
+
+        self.last = Instant::now();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::timer::Timer;
+
+    #[test]
+    fn not_ready_before_interval() {
+        let mut timer = Timer::new(Duration::from_secs(60));
+
+        assert!(!timer.ready());
+    }
+
+    #[test]
+    fn ready_once_per_interval() {
+        let mut timer = Timer::new(Duration::ZERO);
+
+        assert!(timer.ready());
+        assert!(timer.ready());
+
+        let mut timer = Timer::new(Duration::from_millis(50));
+        std::thread::sleep(Duration::from_millis(60));
+
+        assert!(timer.ready());
+        assert!(!timer.ready());
+    }
+}
@@ -0,0 +1,92 @@
+use std::time::{Duration, Instant};
+
+// a frame is anything over this from key press to finished draw
+pub const DEFAULT_FRAME_BUDGET: Duration = Duration::from_millis(50);
+
+pub struct SlowFrame {
+    elapsed: Duration,
+    cause: String,
+}
+
+impl SlowFrame {
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn cause(&self) -> &String {
+        &self.cause
+    }
+}
+
+pub struct FrameTimer {
+    budget: Duration,
+    pending: Option<(Instant, String)>,
+}
+
+impl FrameTimer {
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            budget,
+            pending: None,
+        }
+    }
+
+    // call when an event is received
+    // cause is what will be reported if resulting frame is slow
+    pub fn start<T: ToString>(&mut self, cause: T) {
+        self.pending = Some((Instant::now(), cause.to_string()));
+    }
+
+    // call after draw completes
+    pub fn finish(&mut self) -> Option<SlowFrame> {
+        let (started, cause) = self.pending.take()?;
+        let elapsed = started.elapsed();
+
+        if elapsed > self.budget {
+            Some(SlowFrame { elapsed, cause })
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for FrameTimer {
+    fn default() -> Self {
+        FrameTimer::new(DEFAULT_FRAME_BUDGET)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::timing::FrameTimer;
+
+    #[test]
+    fn finish_without_start() {
+        let mut timer = FrameTimer::default();
+
+        assert!(timer.finish().is_none());
+    }
+
+    #[test]
+    fn fast_frame_not_reported() {
+        let mut timer = FrameTimer::new(Duration::from_secs(60));
+        timer.start("C + s");
+
+        assert!(timer.finish().is_none());
+    }
+
+    #[test]
+    fn slow_frame_reported_once() {
+        let mut timer = FrameTimer::new(Duration::ZERO);
+        timer.start("C + s");
+        std::thread::sleep(Duration::from_millis(1));
+
+        let frame = timer.finish().unwrap();
+
+        assert_eq!(frame.cause(), "C + s");
+        assert!(frame.elapsed() > Duration::ZERO);
+        assert!(timer.finish().is_none());
+    }
+}
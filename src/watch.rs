@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+use crate::app::StateChangeRequest;
+use crate::locale::Text;
+use crate::panels::{buffer_name, PanelState, EDIT_PANEL_TYPE_ID};
+use crate::timer::Timer;
+use crate::{AppState, Panels};
+
+pub const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+// polls modification times of open files so changes by other programs aren't lost on save
+pub struct FileWatcher {
+    timer: Timer,
+}
+
+impl FileWatcher {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            timer: Timer::new(interval),
+        }
+    }
+
+    pub fn tick(&mut self, state: &mut AppState, panels: &mut Panels) -> Vec<StateChangeRequest> {
+        match self.timer.ready() {
+            true => check_files(state, panels),
+            false => vec![],
+        }
+    }
+}
+
+impl Default for FileWatcher {
+    fn default() -> Self {
+        FileWatcher::new(DEFAULT_WATCH_INTERVAL)
+    }
+}
+
+// active panel asks what to do, unless busy with another prompt
+// others only get a warning, saving them asks later
+pub fn check_files(state: &mut AppState, panels: &mut Panels) -> Vec<StateChangeRequest> {
+    let mut changes = vec![];
+
+    for index in 0..panels.len() {
+        let active = index == state.active_panel();
+        let busy = state.input_request().is_some();
+
+        let panel = match panels.get_mut(index) {
+            Some(panel) if panel.panel_type() == EDIT_PANEL_TYPE_ID && !panel.loading() => panel,
+            _ => continue,
+        };
+
+        // leave it to be asked once prompt is done
+        if active && (busy || panel.state() != PanelState::Normal) {
+            continue;
+        }
+
+        if !panel.take_disk_change() {
+            continue;
+        }
+
+        match active {
+            true => changes.extend(panel.ask_about_disk_change(false)),
+            false => state.add_warning(
+                Text::new("watch.changed_on_disk", "{0} changed on disk.").arg(buffer_name(panel.title(), panel.file_path())),
+            ),
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+    use std::time::{Duration, SystemTime};
+
+    use crate::app::StateChangeRequest;
+    use crate::panels::{PanelFactory, PanelState};
+    use crate::watch::check_files;
+    use crate::{AppState, Panels};
+
+    fn write_later(path: &Path, text: &str) {
+        fs::write(path, text).unwrap();
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(10)).unwrap();
+    }
+
+    #[test]
+    fn active_panel_asks_once() {
+        let path = std::env::temp_dir().join(format!("edish_watched_{}.txt", std::process::id()));
+        fs::write(&path, "one").unwrap();
+
+        let mut state = AppState::new();
+        let mut panels = Panels::new();
+        let mut panel = PanelFactory::edit();
+        panel.set_file_path(path.clone());
+        panel.record_disk_time();
+        panels.push(panel);
+
+        assert!(check_files(&mut state, &mut panels).is_empty());
+
+        write_later(&path, "two");
+
+        let changes = check_files(&mut state, &mut panels);
+        assert!(changes.iter().any(|change| matches!(change, StateChangeRequest::ValidatedInput(..))));
+        assert_eq!(panels.get(0).unwrap().state(), PanelState::WaitingToResolveChange);
+
+        panels.get_mut(0).unwrap().set_state(PanelState::Normal);
+        assert!(check_files(&mut state, &mut panels).is_empty());
+
+        fs::remove_file(path).unwrap();
+    }
+}
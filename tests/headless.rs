@@ -0,0 +1,19 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use edish::{AppState, CommandKeyId, Manager, Panels};
+
+// editor driven through the library without a terminal
+#[test]
+fn type_into_active_panel() {
+    let mut panels = Panels::new();
+    let mut app = AppState::new();
+    let mut commands = Manager::default();
+    app.init(&mut panels, &mut commands);
+
+    for c in "hello".chars() {
+        commands.advance(CommandKeyId::new(KeyCode::Char(c), KeyModifiers::empty()), &mut app, &mut panels);
+    }
+    app.update(&mut panels, &mut commands);
+
+    assert_eq!(panels.get(app.active_panel()).unwrap().text(), "hello");
+}